@@ -0,0 +1,100 @@
+use std::{fs::{read_to_string, remove_file}, path::Path, process::Command};
+
+/// Location of the size report, relative to the project directory.
+const REPORT_PATH: &str = "target/skylite-size-report.txt";
+
+/// Location of the asset schema, relative to the project directory.
+const SCHEMA_PATH: &str = "target/skylite-schema.json";
+
+/// Extracts the package name from the content of a `Cargo.toml`.
+fn package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "name" {
+                    return Some(value.trim().trim_matches('"').to_owned());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Runs `cargo check` on the project in the current directory, with the environment variables `env` set.
+///
+/// The assets are parsed by `skylite_project!` while the crate is compiled, so the crate
+/// is cleaned first to make sure the macro runs again, even if no Rust code has changed.
+pub(crate) fn run_check(env: &[(&str, &str)]) -> Result<(), String> {
+    let manifest = read_to_string("Cargo.toml").map_err(|e| format!("Error reading Cargo.toml: {}", e))?;
+    let name = package_name(&manifest).ok_or("Cargo.toml does not contain a package name")?;
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+
+    let status = Command::new(&cargo)
+        .args(["clean", "-p", &name])
+        .status()
+        .map_err(|e| format!("Error running cargo clean: {}", e))?;
+    if !status.success() {
+        return Err("cargo clean failed".to_owned());
+    }
+
+    let status = Command::new(&cargo)
+        .arg("check")
+        .envs(env.iter().copied())
+        .status().map_err(|e| format!("Error running cargo check: {}", e))?;
+    if !status.success() {
+        return Err("The project contains errors, see above.".to_owned());
+    }
+    Ok(())
+}
+
+/// Checks that all assets of the project can be parsed and encoded.
+pub(crate) fn check_assets() -> Result<(), String> {
+    run_check(&[])?;
+    println!("All assets are valid.");
+    Ok(())
+}
+
+/// Prints the size report for the project, see `SKYLITE_SIZE_REPORT`.
+pub(crate) fn report() -> Result<(), String> {
+    if Path::new(REPORT_PATH).exists() {
+        remove_file(REPORT_PATH).map_err(|e| format!("Error removing old report: {}", e))?;
+    }
+    run_check(&[("SKYLITE_SIZE_REPORT", REPORT_PATH)])?;
+    let report = read_to_string(REPORT_PATH)
+        .map_err(|e| format!("Error reading size report {}: {}", REPORT_PATH, e))?;
+    print!("{}", report);
+    Ok(())
+}
+
+/// Prints the JSON schema of the project's assets, see `SKYLITE_SCHEMA`.
+pub(crate) fn schema() -> Result<(), String> {
+    if Path::new(SCHEMA_PATH).exists() {
+        remove_file(SCHEMA_PATH).map_err(|e| format!("Error removing old schema: {}", e))?;
+    }
+    run_check(&[("SKYLITE_SCHEMA", SCHEMA_PATH)])?;
+    let schema = read_to_string(SCHEMA_PATH)
+        .map_err(|e| format!("Error reading schema {}: {}", SCHEMA_PATH, e))?;
+    print!("{}", schema);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::package_name;
+
+    #[test]
+    fn test_package_name() {
+        let manifest = "\
+            [package]\n\
+            version = \"0.1.0\"\n\
+            name = \"my-game\"\n\
+            \n\
+            [dependencies]\n\
+            name = \"not-this\"\n";
+        assert_eq!(package_name(manifest), Some("my-game".to_owned()));
+        assert_eq!(package_name("[dependencies]\nname = \"x\"\n"), None);
+    }
+}
@@ -0,0 +1,599 @@
+use std::{fs::{read_dir, read_to_string, write}, path::{Path, PathBuf}};
+
+/// Forms are broken into multiple lines when they do not fit into this many columns.
+const MAX_WIDTH: usize = 80;
+
+/// A node of a Scheme file, as far as it is needed for formatting.
+#[derive(Debug, PartialEq)]
+enum Node {
+    /// An atom or a string, including its prefix, e.g. `'none`.
+    Atom(String),
+    /// A list with the prefix before the opening paren, e.g. `'`, `#` or `#vu8` for vectors,
+    /// or `#;` for a commented out datum.
+    List { prefix: String, children: Vec<Node> },
+    /// A comment. Trailing comments follow another node on the same line.
+    Comment { text: String, trailing: bool },
+    /// One or more empty lines between two nodes.
+    Blank
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize
+}
+
+impl Parser {
+    fn error(&self, msg: &str) -> String {
+        format!("line {}: {}", self.line, msg)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    /// Skips whitespace and returns the number of newlines skipped.
+    fn skip_whitespace(&mut self) -> usize {
+        let mut newlines = 0;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            if c == '\n' {
+                newlines += 1;
+            }
+            self.next();
+        }
+        newlines
+    }
+
+    /// Parses nodes until the end of the input, or until the closing paren if `in_list` is `true`.
+    fn parse_nodes(&mut self, in_list: bool) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        loop {
+            let newlines = self.skip_whitespace();
+            let after_node = matches!(nodes.last(), Some(Node::Atom(_) | Node::List { .. }));
+            if newlines >= 2 && !nodes.is_empty() && nodes.last() != Some(&Node::Blank) {
+                nodes.push(Node::Blank);
+            }
+
+            match self.peek() {
+                None if in_list => return Err(self.error("Unclosed parenthesis at end of file")),
+                Some(')') if in_list => {
+                    self.next();
+                    if nodes.last() == Some(&Node::Blank) {
+                        nodes.pop();
+                    }
+                    return Ok(nodes);
+                },
+                None => {
+                    if nodes.last() == Some(&Node::Blank) {
+                        nodes.pop();
+                    }
+                    return Ok(nodes);
+                },
+                Some(')') => return Err(self.error("Unexpected ')'")),
+                Some(';') => {
+                    let mut text = String::new();
+                    while let Some(c) = self.peek().filter(|c| *c != '\n') {
+                        text.push(c);
+                        self.next();
+                    }
+                    nodes.push(Node::Comment { text: text.trim_end().to_owned(), trailing: newlines == 0 && after_node });
+                },
+                Some('#') if self.starts_with("#|") => {
+                    let mut text = String::new();
+                    while !self.starts_with("|#") {
+                        text.push(self.next().ok_or_else(|| self.error("Unclosed block comment"))?);
+                    }
+                    self.pos += 2;
+                    text.push_str("|#");
+                    nodes.push(Node::Comment { text, trailing: newlines == 0 && after_node });
+                },
+                Some(_) => nodes.push(self.parse_datum()?)
+            }
+        }
+    }
+
+    /// Returns the length of the vector prefix at the current position, e.g. `#` for `#(`
+    /// or `#vu8` for `#vu8(`, or `None` if there is no vector.
+    fn vector_prefix_len(&self) -> Option<usize> {
+        if self.peek() != Some('#') {
+            return None;
+        }
+        let len = 1 + self.chars[self.pos + 1..].iter().take_while(|c| c.is_ascii_alphanumeric()).count();
+        (self.chars.get(self.pos + len) == Some(&'(')).then_some(len)
+    }
+
+    fn parse_datum(&mut self) -> Result<Node, String> {
+        // Whitespace after a prefix is dropped, so `' (a)` is written as `'(a)`.
+        let mut prefix = String::new();
+        loop {
+            let len = if self.starts_with(",@") || self.starts_with("#;") {
+                2
+            } else if let Some(len) = self.vector_prefix_len() {
+                len
+            } else if let Some('\'' | '`' | ',') = self.peek() {
+                1
+            } else {
+                break;
+            };
+            for _ in 0..len {
+                prefix.push(self.next().unwrap());
+            }
+            self.skip_whitespace();
+        }
+
+        match self.peek() {
+            Some('(') => {
+                self.next();
+                let children = self.parse_nodes(true)?;
+                Ok(Node::List { prefix, children })
+            },
+            Some('"') => {
+                let mut text = prefix;
+                text.push(self.next().unwrap());
+                loop {
+                    let c = self.next().ok_or_else(|| self.error("Unterminated string"))?;
+                    text.push(c);
+                    match c {
+                        '"' => return Ok(Node::Atom(text)),
+                        '\\' => text.push(self.next().ok_or_else(|| self.error("Unterminated string"))?),
+                        _ => {}
+                    }
+                }
+            },
+            _ => {
+                let mut text = String::new();
+                if self.starts_with("#\\") {
+                    // Character literals like #\( may contain any character.
+                    text.push(self.next().unwrap());
+                    text.push(self.next().unwrap());
+                    text.extend(self.next());
+                }
+                while let Some(c) = self.peek().filter(|c| !c.is_whitespace() && !"()\";".contains(*c)) {
+                    text.push(c);
+                    self.next();
+                }
+                if text.is_empty() {
+                    return Err(self.error(&format!("Expected datum after '{}'", prefix)));
+                }
+                Ok(Node::Atom(prefix + &normalize_number(&text)))
+            }
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Node>, String> {
+    Parser { chars: input.chars().collect(), pos: 0, line: 1 }.parse_nodes(false)
+}
+
+/// Writes decimal numbers in a canonical form, e.g. `+1.50E+2` as `1.5e2`. Other atoms are returned unchanged.
+///
+/// Exact and inexact numbers are kept apart, so `2.` becomes `2.0` instead of `2`.
+fn normalize_number(atom: &str) -> String {
+    let (sign, rest) = match atom.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", atom.strip_prefix('+').unwrap_or(atom))
+    };
+    let (mantissa, exponent) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+        None => (rest, None)
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None)
+    };
+
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let exponent_valid = exponent.is_none_or(|e| {
+        let digits = e.strip_prefix(['+', '-']).unwrap_or(e);
+        !digits.is_empty() && is_digits(digits)
+    });
+    if !is_digits(int) || !frac.is_none_or(is_digits) || int.len() + frac.map_or(0, str::len) == 0 || !exponent_valid
+        || (frac.is_none() && exponent.is_some()) {
+        return atom.to_owned();
+    }
+
+    let mut out = sign.to_owned();
+    let int = int.trim_start_matches('0');
+    out.push_str(if int.is_empty() { "0" } else { int });
+    if let Some(frac) = frac {
+        let frac = frac.trim_end_matches('0');
+        out.push('.');
+        out.push_str(if frac.is_empty() { "0" } else { frac });
+    }
+    if let Some(exponent) = exponent {
+        let (exp_sign, digits) = match exponent.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", exponent.strip_prefix('+').unwrap_or(exponent))
+        };
+        let digits = digits.trim_start_matches('0');
+        out.push('e');
+        out.push_str(exp_sign);
+        out.push_str(if digits.is_empty() { "0" } else { digits });
+    }
+    out
+}
+
+/// Canonical order of the top-level keys of actor assets, see `docs/actor_assets.md`.
+const ACTOR_KEYS: &[&str] = &["format", "actions", "parameters", "initial-action", "extends", "description"];
+
+/// Canonical order of the top-level keys of scene assets, see `docs/scene_assets.md`.
+const SCENE_KEYS: &[&str] = &["format", "actors", "extras", "placements", "parameters", "compression", "description"];
+
+/// Returns the key of a `(key . value)` node.
+fn entry_key(node: &Node) -> Option<&str> {
+    match node {
+        Node::List { prefix, children } if prefix.is_empty() => match &children[..] {
+            [Node::Atom(key), Node::Atom(dot), ..] if dot == "." => Some(key),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// Sorts the entries of a quoted actor or scene alist into the order of `ACTOR_KEYS` or `SCENE_KEYS`.
+///
+/// Comments and empty lines before an entry, as well as a trailing comment after it, are moved
+/// together with the entry. Unknown keys are kept in their original order after the known keys.
+/// Other forms are returned unchanged.
+fn sort_keys(node: &mut Node) {
+    let Node::List { prefix, children } = node else {
+        return;
+    };
+    let entries = children.iter().filter(|c| matches!(c, Node::Atom(_) | Node::List { .. }));
+    let keys = entries.map(entry_key).collect::<Option<Vec<&str>>>();
+    let order = match keys {
+        Some(keys) if prefix == "'" && keys.contains(&"actions") => ACTOR_KEYS,
+        Some(keys) if prefix == "'" && keys.contains(&"actors") => SCENE_KEYS,
+        _ => return
+    };
+
+    // Split the children into groups of leading comments, an entry and its trailing comment.
+    let mut groups: Vec<Vec<Node>> = Vec::new();
+    let mut pending = Vec::new();
+    for child in children.drain(..) {
+        match child {
+            Node::Comment { trailing: true, .. } if pending.is_empty() && !groups.is_empty() => groups.last_mut().unwrap().push(child),
+            Node::Comment { .. } | Node::Blank => pending.push(child),
+            _ => {
+                pending.push(child);
+                groups.push(std::mem::take(&mut pending));
+            }
+        }
+    }
+
+    let rank = |group: &Vec<Node>| {
+        let key = group.iter().find_map(entry_key).unwrap();
+        order.iter().position(|k| *k == key).unwrap_or(order.len())
+    };
+    groups.sort_by_key(rank);
+    *children = groups.into_iter().flatten().chain(pending).collect();
+    if children.first() == Some(&Node::Blank) {
+        children.remove(0);
+    }
+}
+
+/// Returns the node on a single line, or `None` if it contains comments or empty lines.
+fn flat(node: &Node) -> Option<String> {
+    match node {
+        Node::Atom(text) => Some(text.clone()),
+        Node::List { prefix, children } => {
+            let children = children.iter().map(flat).collect::<Option<Vec<String>>>()?;
+            Some(format!("{}({})", prefix, children.join(" ")))
+        },
+        Node::Comment { .. } | Node::Blank => None
+    }
+}
+
+fn newline(out: &mut String, col: usize) {
+    out.push('\n');
+    out.extend(std::iter::repeat_n(' ', col));
+}
+
+/// Writes `nodes` on separate lines at column `col`, except for trailing comments.
+///
+/// If `at_line_start` is `true`, `out` must already be positioned at `col`. Otherwise,
+/// the first node is written on a new line, unless it is a trailing comment.
+fn format_sequence(nodes: &[Node], col: usize, out: &mut String, mut at_line_start: bool) {
+    for node in nodes {
+        match node {
+            Node::Blank => out.push('\n'),
+            Node::Comment { text, trailing: true } if !at_line_start => {
+                out.push(' ');
+                out.push_str(text);
+            },
+            _ => {
+                if !at_line_start {
+                    newline(out, col);
+                }
+                format_node(node, col, out);
+            }
+        }
+        at_line_start = false;
+    }
+}
+
+/// Writes `node` starting at column `col`.
+///
+/// A list which does not fit on the line is broken up depending on its content:
+/// - `(key . value)` pairs keep the key on the first line, and indent the value by two.
+/// - Lists of atoms are filled up to the maximum width.
+/// - Lists starting with an atom, like `(move (params...) "description")`, keep the first
+///   two elements on the first line and align the others with the second one.
+/// - All other lists align their elements with the first one.
+fn format_node(node: &Node, col: usize, out: &mut String) {
+    let (prefix, children) = match node {
+        Node::List { prefix, children } => (prefix, children),
+        Node::Atom(text) | Node::Comment { text, .. } => {
+            out.push_str(text);
+            return;
+        },
+        Node::Blank => return
+    };
+    if let Some(text) = flat(node).filter(|text| col + text.len() <= MAX_WIDTH) {
+        out.push_str(&text);
+        return;
+    }
+
+    out.push_str(prefix);
+    out.push('(');
+    let inner = col + prefix.len() + 1;
+    let leading_atoms: Vec<&str> = children.iter()
+        .map_while(|child| if let Node::Atom(text) = child { Some(text.as_str()) } else { None })
+        .collect();
+    let inline = children.iter().take_while(|child| matches!(child, Node::Atom(_) | Node::List { .. })).count();
+
+    let (inline_count, child_col) = if leading_atoms.len() == children.len() {
+        // Fill the lines with atoms.
+        let mut line_col = inner;
+        for (i, atom) in leading_atoms.iter().enumerate() {
+            if i > 0 {
+                if line_col + 1 + atom.len() > MAX_WIDTH {
+                    newline(out, inner);
+                    line_col = inner;
+                } else {
+                    out.push(' ');
+                    line_col += 1;
+                }
+            }
+            out.push_str(atom);
+            line_col += atom.len();
+        }
+        out.push(')');
+        return;
+    } else if leading_atoms.len() >= 2 && leading_atoms[1] == "." {
+        (2, inner + 1)
+    } else if !leading_atoms.is_empty() && inline >= 2 && inner + leading_atoms[0].len() < MAX_WIDTH / 2 {
+        (2, inner + leading_atoms[0].len() + 1)
+    } else if inline >= 1 {
+        (1, inner)
+    } else {
+        (0, inner)
+    };
+
+    let mut inline_col = inner;
+    for (i, child) in children[..inline_count].iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+            inline_col += 1;
+        }
+        format_node(child, inline_col, out);
+        inline_col += flat(child).map_or(0, |text| text.len());
+    }
+    format_sequence(&children[inline_count..], child_col, out, false);
+    if matches!(children.last(), Some(Node::Comment { .. })) {
+        newline(out, child_col);
+    }
+    out.push(')');
+}
+
+/// Formats the content of a Scheme asset file.
+fn format_source(input: &str) -> Result<String, String> {
+    let mut nodes = parse(input)?;
+    nodes.iter_mut().for_each(sort_keys);
+    let mut out = String::new();
+    format_sequence(&nodes, 0, &mut out, true);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Collects the `.scm` files in `dir` and its subdirectories, except for `target` and hidden directories.
+fn collect_scheme_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = read_dir(dir).map_err(|e| format!("Error reading {}: {}", dir.display(), e))?;
+    let mut paths = entries.map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .map_err(|e| format!("Error reading {}: {}", dir.display(), e))?;
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') {
+                collect_scheme_files(&path, out)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "scm") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Formats the Scheme files given in `args`, or all Scheme files of the project in the current
+/// directory. With `--check`, the files are not changed, and an error is returned if any of
+/// them is not formatted.
+pub(crate) fn fmt(args: &[String]) -> Result<(), String> {
+    let check = args.iter().any(|a| a == "--check");
+    let mut files = Vec::new();
+    for arg in args.iter().filter(|a| *a != "--check") {
+        let path = PathBuf::from(arg);
+        if path.is_dir() {
+            collect_scheme_files(&path, &mut files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    if args.iter().all(|a| a == "--check") {
+        collect_scheme_files(Path::new("."), &mut files)?;
+    }
+
+    let mut unformatted = 0;
+    for path in &files {
+        let input = read_to_string(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        let output = format_source(&input).map_err(|e| format!("Error formatting {}: {}", path.display(), e))?;
+        if output == input {
+            continue;
+        }
+        unformatted += 1;
+        if check {
+            println!("{} is not formatted", path.display());
+        } else {
+            write(path, output).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+            println!("Formatted {}", path.display());
+        }
+    }
+
+    if check && unformatted > 0 {
+        return Err(format!("{} of {} files are not formatted.", unformatted, files.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{collect_scheme_files, format_source, normalize_number, parse, Node};
+
+    #[test]
+    fn test_parse() {
+        let nodes = parse("'(a \"b)\\\"\" #\\( ; c\n\n\n  #(1 2))").unwrap();
+        assert_eq!(nodes, vec![Node::List {
+            prefix: "'".to_owned(),
+            children: vec![
+                Node::Atom("a".to_owned()),
+                Node::Atom("\"b)\\\"\"".to_owned()),
+                Node::Atom("#\\(".to_owned()),
+                Node::Comment { text: "; c".to_owned(), trailing: true },
+                Node::Blank,
+                Node::List { prefix: "#".to_owned(), children: vec![Node::Atom("1".to_owned()), Node::Atom("2".to_owned())] }
+            ]
+        }]);
+        assert_eq!(parse("#vu8(1 2) #u8(3) #;(skipped) ' (a)\n\n)").unwrap_err(), "line 3: Unexpected ')'");
+        assert_eq!(parse("(#vu8(1 2) #; skipped ' (a)\n\n)").unwrap(), vec![Node::List {
+            prefix: String::new(),
+            children: vec![
+                Node::List { prefix: "#vu8".to_owned(), children: vec![Node::Atom("1".to_owned()), Node::Atom("2".to_owned())] },
+                Node::Atom("#;skipped".to_owned()),
+                Node::List { prefix: "'".to_owned(), children: vec![Node::Atom("a".to_owned())] }
+            ]
+        }]);
+        assert!(parse("(a (b)").is_err());
+        assert!(parse("(a))").is_err());
+        assert!(parse("(\"a)").is_err());
+    }
+
+    #[test]
+    fn test_normalize_number() {
+        assert_eq!(normalize_number("+007"), "7");
+        assert_eq!(normalize_number("-1.50"), "-1.5");
+        assert_eq!(normalize_number("2."), "2.0");
+        assert_eq!(normalize_number(".5E+02"), "0.5e2");
+        assert_eq!(normalize_number("1e5"), "1e5");
+        assert_eq!(normalize_number("x1.0"), "x1.0");
+        assert_eq!(normalize_number("-"), "-");
+        assert_eq!(normalize_number("1.2.3"), "1.2.3");
+    }
+
+    #[test]
+    fn test_format_source() {
+        let input = "\
+'((actions .   ((move ((dx i8 \"change in x-coordinate\") (dy i8 \"change in y-coordinate\")) \"Moves the actor by the given amount each update.\")
+(idle)))
+
+
+
+  (parameters . ((x i16) (y +16.50))) ; position
+  ; The first action
+  (initial-action . (idle)))";
+        let expected = "\
+'((actions .
+    ((move ((dx i8 \"change in x-coordinate\") (dy i8 \"change in y-coordinate\"))
+           \"Moves the actor by the given amount each update.\")
+     (idle)))
+
+  (parameters . ((x i16) (y 16.5))) ; position
+  ; The first action
+  (initial-action . (idle)))
+";
+        let output = format_source(input).unwrap();
+        assert_eq!(output, expected);
+        assert_eq!(format_source(&output).unwrap(), output);
+
+        let input = "'(\n; Comment first\n(data . (1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30)))";
+        let expected = "\
+'(
+  ; Comment first
+  (data .
+    (1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28
+     29 30)))
+";
+        assert_eq!(format_source(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let input = "\
+'((initial-action . (idle))
+
+  ; Parameters
+  (parameters . ((x i16))) ; position
+  (custom . 1)
+  (actions . ((idle))))
+";
+        let expected = "\
+'((actions . ((idle)))
+
+  ; Parameters
+  (parameters . ((x i16))) ; position
+  (initial-action . (idle))
+  (custom . 1))
+";
+        assert_eq!(format_source(input).unwrap(), expected);
+
+        let input = "'((parameters . ()) (actors . ()) (name . test))\n";
+        assert_eq!(format_source(input).unwrap(), "'((actors . ()) (parameters . ()) (name . test))\n");
+
+        // Project definitions and code are not reordered.
+        let input = "'((name . test) (compression . none))\n";
+        assert_eq!(format_source(input).unwrap(), input);
+    }
+
+    #[test]
+    fn test_format_idempotent() {
+        let mut files = Vec::new();
+        collect_scheme_files(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../.."), &mut files).unwrap();
+        assert!(!files.is_empty());
+        for path in files {
+            let input = std::fs::read_to_string(&path).unwrap();
+            let once = format_source(&input).unwrap_or_else(|e| panic!("Error formatting {}: {}", path.display(), e));
+            assert_eq!(format_source(&once).unwrap(), once, "{} is not formatted idempotently", path.display());
+        }
+
+        let once = format_source("'(a\n\n  ; b\n\n)\n\n\n").unwrap();
+        assert_eq!(once, "'(a\n\n  ; b\n  )\n");
+        assert_eq!(format_source(&once).unwrap(), once);
+    }
+}
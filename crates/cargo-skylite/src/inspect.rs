@@ -0,0 +1,56 @@
+use std::{io::{self, stdin, BufRead, BufReader, Write}, net::TcpStream};
+
+/// Reads a response of the debug server, which is terminated by a line containing only `.`.
+fn read_response<R: BufRead>(reader: &mut R) -> io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let line = line.trim_end();
+        if line == "." {
+            return Ok(lines);
+        }
+        lines.push(line.to_owned());
+    }
+}
+
+/// Connects to the debug server of a running project and forwards requests from stdin.
+///
+/// The server is provided by `skylite_core::debug_server`, which also documents the requests.
+pub(crate) fn inspect(args: &[String]) -> Result<(), String> {
+    let addr = args.first().ok_or("Missing address of the debug server")?;
+    let stream = TcpStream::connect(addr).map_err(|e| format!("Error connecting to {}: {}", addr, e))?;
+    let mut writer = stream.try_clone().map_err(|e| format!("Error connecting to {}: {}", addr, e))?;
+    let mut reader = BufReader::new(stream);
+    println!("Connected to {}. Requests: actors, props <index>, set <index> <name> <value>, timings, draws", addr);
+
+    let connection_error = |e: io::Error| format!("Connection lost: {}", e);
+    for request in stdin().lock().lines() {
+        let request = request.map_err(|e| format!("Error reading stdin: {}", e))?;
+        if request.trim().is_empty() {
+            continue;
+        }
+        writeln!(writer, "{}", request.trim()).map_err(connection_error)?;
+        for line in read_response(&mut reader).map_err(connection_error)? {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::read_response;
+
+    #[test]
+    fn test_read_response() {
+        let mut input = Cursor::new("0 player 3\n1 - 7\n.\nerror: Unknown request x\n.\n0 player");
+        assert_eq!(read_response(&mut input).unwrap(), vec!["0 player 3", "1 - 7"]);
+        assert_eq!(read_response(&mut input).unwrap(), vec!["error: Unknown request x"]);
+        assert!(read_response(&mut input).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use new::{create_project, ProjectOptions, Target};
+
+mod check;
+mod fmt;
+mod inspect;
+mod new;
+mod watch;
+
+const USAGE: &str = "\
+Usage: cargo skylite <command> [options]
+
+Commands:
+    new <path> [--target mock|wasm4] [--skylite-path <path>]
+                    Creates a new Skylite project in <path>
+    check-assets    Checks the assets of the project in the current directory
+    fmt [--check] [paths]
+                    Formats the Scheme files in [paths], or all Scheme files of the project
+                    in the current directory. With --check, lists unformatted files instead
+    report          Prints the size report for the project in the current directory
+    schema          Prints a JSON description of the actors and scenes of the project
+                    in the current directory, for use by external editors
+    inspect <addr>  Connects to the debug server of a running project at <addr>,
+                    see skylite_core::debug_server
+    watch [args]    Rebuilds the project in the current directory whenever a file changes.
+                    The assets are validated before each build, and [args] are passed
+                    on to cargo build";
+
+fn parse_new_args(args: &[String]) -> Result<ProjectOptions, String> {
+    let mut path = None;
+    let mut target = Target::Mock;
+    let mut skylite_path = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--target" => {
+                let value = iter.next().ok_or("Missing value for --target")?;
+                target = Target::from_name(value).ok_or_else(|| format!("Unknown target {}, expected mock or wasm4", value))?;
+            },
+            "--skylite-path" => {
+                let value = iter.next().ok_or("Missing value for --skylite-path")?;
+                skylite_path = Some(PathBuf::from(value));
+            },
+            other if path.is_none() && !other.starts_with("--") => path = Some(PathBuf::from(other)),
+            other => return Err(format!("Unexpected argument {}", other))
+        }
+    }
+
+    let path = path.ok_or("Missing project path")?;
+    let name = path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or("Invalid project path")?;
+    Ok(ProjectOptions { path, name, target, skylite_path })
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args.first().map(|a| a.as_str()) {
+        Some("new") => {
+            let options = parse_new_args(&args[1..])?;
+            create_project(&options).map_err(|e| format!("Error creating project: {}", e))?;
+            println!("Created project {} in {}", options.name, options.path.display());
+            Ok(())
+        },
+        Some("check-assets") => check::check_assets(),
+        Some("fmt") => fmt::fmt(&args[1..]),
+        Some("report") => check::report(),
+        Some("schema") => check::schema(),
+        Some("watch") => watch::watch(&args[1..]),
+        Some("inspect") => inspect::inspect(&args[1..]),
+        Some(other) => Err(format!("Unknown command {}\n\n{}", other, USAGE)),
+        None => Err(USAGE.to_owned())
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // When invoked as `cargo skylite`, cargo passes the subcommand name as the first argument.
+    if args.first().map(|a| a.as_str()) == Some("skylite") {
+        args.remove(0);
+    }
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::new::Target;
+
+    use super::parse_new_args;
+
+    #[test]
+    fn test_parse_new_args() {
+        let args: Vec<String> = ["games/my-game", "--target", "wasm4"].iter().map(|s| s.to_string()).collect();
+        let options = parse_new_args(&args).unwrap();
+        assert_eq!(options.path, PathBuf::from("games/my-game"));
+        assert_eq!(options.name, "my-game");
+        assert_eq!(options.target, Target::Wasm4);
+        assert_eq!(options.skylite_path, None);
+
+        let args: Vec<String> = ["my-game", "--target", "snes"].iter().map(|s| s.to_string()).collect();
+        assert!(parse_new_args(&args).is_err());
+        assert!(parse_new_args(&[]).is_err());
+    }
+}
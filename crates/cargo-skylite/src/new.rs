@@ -0,0 +1,242 @@
+use std::{fs::{create_dir, create_dir_all, write}, io, path::{Path, PathBuf}};
+
+/// The targets for which a project skeleton can be created.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Target {
+    /// `skylite_mock::MockTarget`, which does not depend on any platform.
+    Mock,
+    /// `wasm4_target::Wasm4Target`, for the WASM-4 fantasy console.
+    Wasm4
+}
+
+impl Target {
+    pub(crate) fn from_name(name: &str) -> Option<Target> {
+        match name {
+            "mock" => Some(Target::Mock),
+            "wasm4" => Some(Target::Wasm4),
+            _ => None
+        }
+    }
+}
+
+pub(crate) struct ProjectOptions {
+    pub path: PathBuf,
+    pub name: String,
+    pub target: Target,
+    /// Path to a checkout of the Skylite repository. If this is `None`,
+    /// the dependencies are taken from the registry.
+    pub skylite_path: Option<PathBuf>
+}
+
+/// Converts a package name like `my-game` to a type name like `MyGame`.
+fn project_type_name(name: &str) -> String {
+    name.split(|c: char| c == '-' || c == '_' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().unwrap().to_uppercase().chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+/// Returns the dependency entry for the manifest. `skylite_path` must already be absolute
+/// or relative to the new project, since Cargo resolves paths relative to the manifest.
+fn dependency(name: &str, repo_dir: &str, skylite_path: Option<&Path>) -> String {
+    match skylite_path {
+        Some(path) => format!("{} = {{ path = \"{}\" }}\n", name, path.join(repo_dir).join(name).display()),
+        None => format!("{} = \"0.1.0\"\n", name)
+    }
+}
+
+fn cargo_toml(options: &ProjectOptions, skylite_path: Option<&Path>) -> String {
+    let mut out = format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n", options.name);
+    if options.target == Target::Wasm4 {
+        out += "[lib]\ncrate-type = [\"cdylib\"]\n\n";
+    }
+    out += "[dependencies]\n";
+    out += &dependency("skylite-core", "crates", skylite_path);
+    out += &dependency("skylite-proc", "crates", skylite_path);
+    match options.target {
+        Target::Mock => out += &dependency("skylite-mock", "crates", skylite_path),
+        Target::Wasm4 => out += &dependency("wasm4-target", "support", skylite_path)
+    }
+    if options.target == Target::Wasm4 {
+        out += "\n[profile.release]\nlto = true\nopt-level = \"z\"\nstrip = \"symbols\"\n";
+    }
+    out
+}
+
+fn project_scm(type_name: &str) -> String {
+    format!("\
+'((name . {})
+  (tile-types . (solid non-solid))
+  (initial-scene . (main)))
+", type_name)
+}
+
+const PLAYER_SCM: &str = "\
+'((actions .
+    ((idle)))
+
+  (parameters .
+    ((x i16 \"initial x-coordinate\")
+     (y i16 \"initial y-coordinate\")))
+
+  (initial-action . (idle))
+
+  (description . \"The player character.\"))
+";
+
+const MAIN_SCENE_SCM: &str = "\
+'((actors .
+    ((player . (player 80 80))))
+
+  (description . \"The first scene of the project.\"))
+";
+
+fn lib_rs(type_name: &str, target: Target) -> String {
+    let (target_use, target_type) = match target {
+        Target::Mock => ("use skylite_mock::MockTarget;", "MockTarget"),
+        Target::Wasm4 => ("use wasm4_target::Wasm4Target;", "Wasm4Target")
+    };
+
+    let mut out = format!(r#"use skylite_core::prelude::*;
+use skylite_proc::skylite_project;
+{target_use}
+
+skylite_proc::actor_definition! {{
+    use skylite_core::prelude::*;
+
+    skylite_proc::asset_file!("./project.scm", "player");
+
+    skylite_proc::properties! {{
+        pub x: i16,
+        pub y: i16
+    }}
+
+    #[skylite_proc::create_properties]
+    fn create_properties(x: i16, y: i16) -> PlayerProperties {{
+        PlayerProperties {{ x, y }}
+    }}
+
+    #[skylite_proc::action("idle")]
+    fn idle(_actor: &mut Player, _scene: &mut dyn Scene<P={type_name}>, _controls: &mut ProjectControls<{type_name}>) {{}}
+
+    #[skylite_proc::render]
+    fn render(_actor: &Player, _ctx: &mut DrawContext<{type_name}>) {{}}
+}}
+
+skylite_proc::scene_definition! {{
+    skylite_proc::asset_file!("./project.scm", "main");
+
+    skylite_proc::properties! {{}}
+
+    #[skylite_proc::create_properties]
+    fn create_properties() -> MainProperties {{
+        MainProperties {{}}
+    }}
+}}
+
+skylite_project! {{
+    skylite_proc::project_file!("./project.scm");
+
+    skylite_proc::target_type!({target_type});
+}}
+"#);
+
+    if target == Target::Wasm4 {
+        out += &format!(r#"
+static mut PROJECT: Option<{type_name}> = None;
+
+#[no_mangle]
+fn start() {{
+    unsafe {{ PROJECT = Some({type_name}::new(Wasm4Target::new())); }}
+}}
+
+#[no_mangle]
+fn update() {{
+    #[allow(static_mut_refs)]
+    let project = unsafe {{ PROJECT.as_mut().unwrap() }};
+    project.update();
+    project.render();
+}}
+"#);
+    }
+    out
+}
+
+/// Creates the skeleton of a new project, consisting of the crate manifest,
+/// a project definition, and a single actor and scene.
+pub(crate) fn create_project(options: &ProjectOptions) -> io::Result<()> {
+    let type_name = project_type_name(&options.name);
+    let root: &Path = &options.path;
+    // The path is given relative to the current directory, not to the new project.
+    let skylite_path = options.skylite_path.as_deref().map(Path::canonicalize).transpose()?;
+
+    create_dir_all(root.parent().unwrap_or(Path::new(".")))?;
+    // Fails if the directory already exists, so that no existing files are overwritten.
+    create_dir(root)?;
+    create_dir(root.join("src"))?;
+    create_dir(root.join("actors"))?;
+    create_dir(root.join("scenes"))?;
+
+    write(root.join("Cargo.toml"), cargo_toml(options, skylite_path.as_deref()))?;
+    write(root.join("project.scm"), project_scm(&type_name))?;
+    write(root.join("actors/player.scm"), PLAYER_SCM)?;
+    write(root.join("scenes/main.scm"), MAIN_SCENE_SCM)?;
+    write(root.join("src/lib.rs"), lib_rs(&type_name, options.target))?;
+    write(root.join(".gitignore"), "/target\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::{read_to_string, remove_dir_all}, path::PathBuf};
+
+    use super::{cargo_toml, create_project, project_type_name, ProjectOptions, Target};
+
+    #[test]
+    fn test_project_type_name() {
+        assert_eq!(project_type_name("my-game"), "MyGame");
+        assert_eq!(project_type_name("game_2"), "Game2");
+    }
+
+    #[test]
+    fn test_cargo_toml() {
+        let options = ProjectOptions {
+            path: PathBuf::from("my-game"),
+            name: "my-game".to_owned(),
+            target: Target::Wasm4,
+            skylite_path: Some(PathBuf::from("../skylite"))
+        };
+        let manifest = cargo_toml(&options, options.skylite_path.as_deref());
+        assert!(manifest.contains("crate-type = [\"cdylib\"]"));
+        assert!(manifest.contains("skylite-core = { path = \"../skylite/crates/skylite-core\" }"));
+        assert!(manifest.contains("wasm4-target = { path = \"../skylite/support/wasm4-target\" }"));
+        assert!(!manifest.contains("skylite-mock"));
+    }
+
+    #[test]
+    fn test_create_project() {
+        let path = std::env::temp_dir().join(format!("cargo_skylite_new_{}", std::process::id()));
+        let options = ProjectOptions {
+            path: path.clone(),
+            name: "my-game".to_owned(),
+            target: Target::Mock,
+            skylite_path: Some(PathBuf::from("."))
+        };
+        create_project(&options).unwrap();
+
+        let skylite_core = std::env::current_dir().unwrap().canonicalize().unwrap().join("crates/skylite-core");
+        assert!(read_to_string(path.join("Cargo.toml")).unwrap().contains(&format!("skylite-core = {{ path = \"{}\" }}", skylite_core.display())));
+        assert!(read_to_string(path.join("project.scm")).unwrap().contains("(name . MyGame)"));
+        assert!(read_to_string(path.join("src/lib.rs")).unwrap().contains("skylite_proc::target_type!(MockTarget);"));
+        assert!(path.join("actors/player.scm").exists());
+        assert!(path.join("scenes/main.scm").exists());
+
+        // Existing projects are not overwritten.
+        assert!(create_project(&options).is_err());
+
+        remove_dir_all(path).unwrap();
+    }
+}
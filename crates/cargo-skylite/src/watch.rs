@@ -0,0 +1,103 @@
+use std::{collections::HashMap, fs::read_dir, path::{Path, PathBuf}, process::Command, thread::sleep, time::{Duration, SystemTime}};
+
+use crate::check::run_check;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The modification times of all files in a project.
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+/// Collects the modification times of all files below `dir`.
+///
+/// The `target` directory and hidden files and directories are skipped, since they
+/// are changed by the build itself.
+fn snapshot(dir: &Path, out: &mut Snapshot) {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == "target" {
+            continue;
+        }
+        match entry.metadata() {
+            Ok(meta) if meta.is_dir() => snapshot(&path, out),
+            Ok(meta) => {
+                if let Ok(modified) = meta.modified() {
+                    out.insert(path, modified);
+                }
+            },
+            Err(_) => {}
+        }
+    }
+}
+
+fn take_snapshot(dir: &Path) -> Snapshot {
+    let mut out = Snapshot::new();
+    snapshot(dir, &mut out);
+    out
+}
+
+/// Validates the assets and, if they are valid, builds the project with `build_args`.
+fn rebuild(build_args: &[String]) {
+    if let Err(msg) = run_check(&[]) {
+        eprintln!("{}", msg);
+        return;
+    }
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    match Command::new(cargo).arg("build").args(build_args).status() {
+        Ok(status) if status.success() => println!("Build finished."),
+        Ok(_) => eprintln!("Build failed, see above."),
+        Err(e) => eprintln!("Error running cargo build: {}", e)
+    }
+}
+
+/// Watches the project in the current directory, and rebuilds it whenever
+/// an asset or source file changes. This function does not return.
+///
+/// `build_args` are passed on to `cargo build`.
+pub(crate) fn watch(build_args: &[String]) -> Result<(), String> {
+    let root = Path::new(".");
+    let mut last = take_snapshot(root);
+    rebuild(build_args);
+    println!("Watching for changes...");
+    loop {
+        sleep(POLL_INTERVAL);
+        let current = take_snapshot(root);
+        if current != last {
+            last = current;
+            rebuild(build_args);
+            println!("Watching for changes...");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    use super::take_snapshot;
+
+    #[test]
+    fn test_snapshot() {
+        let dir = std::env::temp_dir().join(format!("cargo_skylite_watch_{}", std::process::id()));
+        create_dir_all(dir.join("actors")).unwrap();
+        create_dir_all(dir.join("target")).unwrap();
+        write(dir.join("project.scm"), "'()").unwrap();
+        write(dir.join("actors/player.scm"), "'()").unwrap();
+        write(dir.join("target/output"), "").unwrap();
+
+        let snapshot = take_snapshot(&dir);
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot.contains_key(&dir.join("actors/player.scm")));
+        assert!(!snapshot.contains_key(&dir.join("target/output")));
+
+        write(dir.join("scenes.scm"), "'()").unwrap();
+        assert_ne!(take_snapshot(&dir), snapshot);
+
+        remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 use std::env;
 
 use proc_macro2::{Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
-use skylite_compress::{compress, CompressionMethods, CompressionReport};
+use skylite_compress::{compress, CompressionLevel, CompressionMethods, CompressionReport};
 
 extern crate proc_macro;
 
@@ -43,7 +43,10 @@ fn print_compression_report(data_name: &str, initial_size: usize, reports: &[Com
                     CompressionMethods::Raw => "Raw data",
                     #[cfg(feature = "lz77")] CompressionMethods::LZ77 => "Lempel-Ziv 77",
                     #[cfg(feature = "lz78")] CompressionMethods::LZ78 => "Lempel-Ziv 78",
-                    #[cfg(feature = "range_coding")] CompressionMethods::RC => "Range Coding"
+                    #[cfg(feature = "range_coding")] CompressionMethods::RC => "Range Coding",
+                    #[cfg(feature = "lzss")] CompressionMethods::LZSS256 => "LZSS (256 byte window)",
+                    #[cfg(feature = "lzss")] CompressionMethods::LZSS1024 => "LZSS (1024 byte window)",
+                    #[cfg(feature = "lzss")] CompressionMethods::LZSS4096 => "LZSS (4096 byte window)"
                 };
                 if report.skipped {
                     println!("\t{}: (skipped)", method_name);
@@ -82,9 +85,13 @@ impl Into<TokenStream> for ProcError {
     }
 }
 
-fn generate_tokens(data_name: &str, data: &[u8], methods: &[CompressionMethods]) -> TokenStream {
-    let (compressed_data, reports) = compress(data, methods);
+fn generate_tokens(data_name: &str, data: &[u8], methods: &[CompressionMethods], level: CompressionLevel) -> TokenStream {
+    let (compressed_data, reports) = compress(data, methods, level);
     print_compression_report(data_name, data.len(), &reports);
+    #[cfg(feature = "verify")]
+    if let Err(err) = skylite_compress::verify(data, &compressed_data) {
+        return ProcError::Data(format!("Compressed data of {} does not decode to the original data: {}", data_name, err)).into();
+    }
     TokenTree::Group(Group::new(
         proc_macro2::Delimiter::Bracket,
         TokenStream::from_iter(
@@ -155,11 +162,10 @@ fn literals_to_methods(iter: DelimitedListIterator) -> Result<Vec<CompressionMet
         } else {
             Err(ProcError::Syntax("Expected compression methods identifier".to_owned()))
         })
-        .map(|l| match l?.to_string().as_str() {
-            #[cfg(feature = "lz77")] "lz77" => Ok(CompressionMethods::LZ77),
-            #[cfg(feature = "lz78")] "lz78" => Ok(CompressionMethods::LZ78),
-            #[cfg(feature = "range_coding")] "range_coding" => Ok(CompressionMethods::RC),
-            s @ _ => Err(ProcError::Data(format!("Unknown compression method {}", s)))
+        .map(|l| {
+            let name = l?.to_string();
+            CompressionMethods::from_name(&name)
+                .ok_or_else(|| ProcError::Data(format!("Unknown compression method {}", name)))
         })
         .collect();
 
@@ -206,22 +212,45 @@ fn compressed2(stream: TokenStream) -> TokenStream {
         _ => return ProcError::Syntax("Data name must be a string lteral".to_owned()).into()
     };
 
-    generate_tokens(&data_name, &data, &methods)
+    let level = match params.next() {
+        Some(Ok(TokenTree::Ident(token))) => {
+            let name = token.to_string();
+            match CompressionLevel::from_name(&name) {
+                Some(level) => level,
+                None => return ProcError::Data(format!("Unknown compression level {}", name)).into()
+            }
+        },
+        None => CompressionLevel::Default,
+        Some(Err(err)) => return err.into(),
+        _ => return ProcError::Syntax("Expected compression level identifier".to_owned()).into()
+    };
+
+    generate_tokens(&data_name, &data, &methods, level)
 }
 
 /// Compresses the data passed to it using the given compression methods and
 /// returns an array expression (`[ <data> ]`).
 ///
-/// Syntax: `compressed!([ <data> ], [ <methods> ], <name>)`.
+/// Syntax: `compressed!([ <data> ], [ <methods> ], <name>, <level>)`.
 ///
 /// `<data>` must be a comma-delimited list of u8 literals. `<methods>` must be a comma-delimited list
 /// contains any of the following identifiers:
 /// - `lz77`: Lempel-Ziv 77 compression
-/// - `rc`: Range Coding compression.
+/// - `range_coding` or `rc`: Range Coding compression.
+/// - `lzss256`, `lzss1024`, `lzss4096`: LZSS compression with a window of 256, 1024 or 4096 bytes.
+///   The decoder only needs a ring buffer of the window size, which makes these methods suitable
+///   for targets with little RAM. `lzss` is the same as `lzss4096`.
 ///
 /// The compression methods are applied in the given order, but some may be skipped, if it is found
 /// that the size was not reduced after compression.
 ///
+/// `<level>` is optional and must be one of `fast`, `default` or `max`. It controls how much effort
+/// is spent on searching for matches, see `skylite_compress::CompressionLevel`. `fast` reduces
+/// the time needed to expand the macro, at the cost of slightly larger data.
+///
+/// With the `verify` feature, the compressed data is decoded again and compared with
+/// the original data, to catch encoder bugs at compile time.
+///
 /// ## Example:
 ///
 /// ```rust
@@ -247,4 +276,13 @@ mod tests {
         let res = compressed2(quote!( [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3], [lz77, range_coding], "test" ));
         assert_eq!(res.to_string(), "[3u8 , 3u8 , 8u8 , 1u8 , 2u8 , 32u8 , 199u8 , 114u8 , 143u8 , 244u8 , 64u8 , 75u8 , 202u8 , 53u8 , 81u8 ,]");
     }
+
+    #[test]
+    fn compression_level() {
+        let res = compressed2(quote!( [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3], [lz77, range_coding], "test", default ));
+        assert_eq!(res.to_string(), "[3u8 , 3u8 , 8u8 , 1u8 , 2u8 , 32u8 , 199u8 , 114u8 , 143u8 , 244u8 , 64u8 , 75u8 , 202u8 , 53u8 , 81u8 ,]");
+
+        let res = compressed2(quote!( [0, 1, 2], [lz77], "test", fastest ));
+        assert!(res.to_string().contains("Unknown compression level fastest"));
+    }
 }
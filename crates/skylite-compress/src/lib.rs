@@ -17,8 +17,16 @@ mod lz78;
 #[cfg(feature = "lz78")]
 use lz78::*;
 
+#[cfg(feature = "lzss")]
+mod lzss;
+#[cfg(feature = "lzss")]
+use lzss::*;
+
 // mod fibonacci_code;
 
+mod stream;
+pub use stream::{StreamDecoder, StreamEncoder};
+
 /// A `Decoder` decodes a compressed data stream.
 pub trait Decoder {
 
@@ -56,13 +64,76 @@ impl<'a> Decoder for RawSliceDecoder<'a> {
     }
 }
 
+/// A random-access provider of compressed data.
+///
+/// `make_decoder` requires the complete compressed data to be addressable as a slice.
+/// Targets which keep their assets in banked ROM or in external flash can implement
+/// `DataSource` instead, and use [`make_decoder_from_source`], which reads the data
+/// one byte at a time without copying it into RAM first.
+pub trait DataSource {
+
+    /// Returns the byte at `offset`.
+    ///
+    /// The decoders may read a few bytes past the end of the data,
+    /// which should return 0.
+    fn read_u8(&self, offset: usize) -> u8;
+}
+
+impl DataSource for [u8] {
+    fn read_u8(&self, offset: usize) -> u8 {
+        self.get(offset).copied().unwrap_or(0)
+    }
+}
+
+impl<T: DataSource + ?Sized> DataSource for &T {
+    fn read_u8(&self, offset: usize) -> u8 {
+        (**self).read_u8(offset)
+    }
+}
+
+struct SourceDecoder<S: DataSource> {
+    source: S,
+    offset: usize
+}
+
+impl<S: DataSource> Decoder for SourceDecoder<S> {
+    fn decode_u8(&mut self) -> u8 {
+        let out = self.source.read_u8(self.offset);
+        self.offset += 1;
+        out
+    }
+}
+
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum CompressionMethods {
     Raw = 0,
     #[cfg(feature = "lz77")] LZ77 = 1,
     #[cfg(feature = "lz78")] LZ78 = 2,
-    #[cfg(feature = "range_coding")] RC = 3
+    #[cfg(feature = "range_coding")] RC = 3,
+    #[cfg(feature = "lzss")] LZSS256 = 4,
+    #[cfg(feature = "lzss")] LZSS1024 = 5,
+    #[cfg(feature = "lzss")] LZSS4096 = 6
+}
+
+impl CompressionMethods {
+
+    /// Returns the `CompressionMethods` for the given name, or `None`
+    /// if the name is unknown or the method is not enabled.
+    ///
+    /// `lzss` is the same as `lzss4096`.
+    pub fn from_name(name: &str) -> Option<CompressionMethods> {
+        match name {
+            "raw" => Some(CompressionMethods::Raw),
+            #[cfg(feature = "lz77")] "lz77" => Some(CompressionMethods::LZ77),
+            #[cfg(feature = "lz78")] "lz78" => Some(CompressionMethods::LZ78),
+            #[cfg(feature = "range_coding")] "range_coding" | "rc" => Some(CompressionMethods::RC),
+            #[cfg(feature = "lzss")] "lzss256" => Some(CompressionMethods::LZSS256),
+            #[cfg(feature = "lzss")] "lzss1024" => Some(CompressionMethods::LZSS1024),
+            #[cfg(feature = "lzss")] "lzss" | "lzss4096" => Some(CompressionMethods::LZSS4096),
+            _ => None
+        }
+    }
 }
 
 impl Display for CompressionMethods {
@@ -71,7 +142,58 @@ impl Display for CompressionMethods {
             CompressionMethods::Raw => write!(f, "Raw"),
             #[cfg(feature = "lz77")] CompressionMethods::LZ77 => write!(f, "LZ77"),
             #[cfg(feature = "lz78")] CompressionMethods::LZ78 => write!(f, "LZ78"),
-            #[cfg(feature = "range_coding")] CompressionMethods::RC => write!(f, "Range Coding")
+            #[cfg(feature = "range_coding")] CompressionMethods::RC => write!(f, "Range Coding"),
+            #[cfg(feature = "lzss")] CompressionMethods::LZSS256 => write!(f, "LZSS (256 byte window)"),
+            #[cfg(feature = "lzss")] CompressionMethods::LZSS1024 => write!(f, "LZSS (1024 byte window)"),
+            #[cfg(feature = "lzss")] CompressionMethods::LZSS4096 => write!(f, "LZSS (4096 byte window)")
+        }
+    }
+}
+
+/// Controls how much effort the encoders spend on searching for matches.
+///
+/// The level only affects the encoders. Data compressed with any level is
+/// decoded in the same way, so the level can be changed without changing
+/// the decoding side, e.g. to use `Fast` for debug builds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CompressionLevel {
+    /// Only searches the most recent data for matches. This is much faster,
+    /// but leads to slightly larger output.
+    Fast,
+    #[default]
+    Default,
+    /// Searches the full window of each method for matches.
+    Max
+}
+
+impl CompressionLevel {
+
+    /// Returns the `CompressionLevel` for the given name, or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<CompressionLevel> {
+        match name {
+            "fast" => Some(CompressionLevel::Fast),
+            "default" => Some(CompressionLevel::Default),
+            "max" => Some(CompressionLevel::Max),
+            _ => None
+        }
+    }
+
+    /// Number of previous positions that the LZ77 encoder compares with the current data.
+    #[cfg(feature = "lz77")]
+    fn lz77_search_depth(self) -> usize {
+        match self {
+            CompressionLevel::Fast => 32,
+            CompressionLevel::Default | CompressionLevel::Max => 256
+        }
+    }
+
+    /// Maximum length of the hash chains that the LZSS encoder follows when searching for a match.
+    #[cfg(feature = "lzss")]
+    fn lzss_max_chain(self) -> usize {
+        match self {
+            CompressionLevel::Fast => 16,
+            CompressionLevel::Default => 256,
+            CompressionLevel::Max => usize::MAX
         }
     }
 }
@@ -99,20 +221,25 @@ impl Display for CompressionReport {
 
 /// Compresses the data using the list of `CompressionMethods`.
 /// If the use of a compression did not decrease the size of the data,
-/// it is skipped.
+/// it is skipped. The `level` trades compression speed for the size
+/// of the output, see [`CompressionLevel`].
 ///
 /// The function returns both the compressed data and a list of `CompressionReport`s,
 /// with one entry for each compression method.
-pub fn compress(data: &[u8], methods: &[CompressionMethods]) -> (Vec<u8>, Vec<CompressionReport>) {
+#[cfg_attr(not(any(feature = "lz77", feature = "lzss")), allow(unused_variables))]
+pub fn compress(data: &[u8], methods: &[CompressionMethods], level: CompressionLevel) -> (Vec<u8>, Vec<CompressionReport>) {
     let mut out = data.to_owned();
     let mut reports = Vec::with_capacity(methods.len());
     out.insert(0, 0);
     for method in methods {
         let mut new = match method {
             CompressionMethods::Raw => out.clone(),
-            #[cfg(feature = "lz77")] CompressionMethods::LZ77 => encode_lz77(&out),
+            #[cfg(feature = "lz77")] CompressionMethods::LZ77 => encode_lz77(&out, level.lz77_search_depth()),
             #[cfg(feature = "lz78")] CompressionMethods::LZ78 => encode_lz78(&out),
-            #[cfg(feature = "range_coding")] CompressionMethods::RC => encode_rc(&out)
+            #[cfg(feature = "range_coding")] CompressionMethods::RC => encode_rc(&out),
+            #[cfg(feature = "lzss")] CompressionMethods::LZSS256 => encode_lzss(&out, 256, level.lzss_max_chain()),
+            #[cfg(feature = "lzss")] CompressionMethods::LZSS1024 => encode_lzss(&out, 1024, level.lzss_max_chain()),
+            #[cfg(feature = "lzss")] CompressionMethods::LZSS4096 => encode_lzss(&out, 4096, level.lzss_max_chain())
         };
         if new.len() + 1 < out.len() {
             let mut tag = vec![method.to_owned() as u8];
@@ -135,13 +262,62 @@ pub fn compress(data: &[u8], methods: &[CompressionMethods]) -> (Vec<u8>, Vec<Co
 /// end of the original data will likely also panic.
 #[no_mangle]
 pub fn make_decoder<'a>(data: &'a [u8]) -> Box<dyn Decoder + 'a> {
-    let mut decoder: Box<dyn Decoder + 'a> = Box::new(RawSliceDecoder::new(data));
+    chain_decoders(Box::new(RawSliceDecoder::new(data)))
+}
+
+/// Creates a `Decoder` for the compressed data provided by `source`.
+///
+/// This works like [`make_decoder`], but reads the data through a [`DataSource`].
+pub fn make_decoder_from_source<'a, S: DataSource + 'a>(source: S) -> Box<dyn Decoder + 'a> {
+    chain_decoders(Box::new(SourceDecoder { source, offset: 0 }))
+}
+
+/// A byte at which the decoded data differs from the original data, see [`verify`].
+#[cfg(feature = "verify")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VerifyError {
+    /// The index of the byte in the original data.
+    pub index: usize,
+    /// The decoded byte.
+    pub actual: u8,
+    /// The byte of the original data.
+    pub expected: u8
+}
+
+#[cfg(feature = "verify")]
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "byte {} is {}, expected {}", self.index, self.actual, self.expected)
+    }
+}
+
+/// Decodes `compressed` and compares the result with `original`.
+///
+/// This catches bugs in the encoders when the data is compressed,
+/// instead of when the corrupted data is decoded.
+#[cfg(feature = "verify")]
+pub fn verify(original: &[u8], compressed: &[u8]) -> Result<(), VerifyError> {
+    let mut decoder = make_decoder(compressed);
+    for (index, &expected) in original.iter().enumerate() {
+        let actual = decoder.decode_u8();
+        if actual != expected {
+            return Err(VerifyError { index, actual, expected });
+        }
+    }
+    Ok(())
+}
+
+/// Reads the method tags from the start of the data and wraps `decoder` in the respective decoders.
+fn chain_decoders<'a>(mut decoder: Box<dyn Decoder + 'a>) -> Box<dyn Decoder + 'a> {
     loop {
         let method = decoder.decode_u8();
         match method {
             #[cfg(feature = "lz77")] 1 => decoder = Box::new(LZ77Decoder::new(decoder)),
             #[cfg(feature = "lz78")] 2 => decoder = Box::new(LZ78Decoder::new(decoder)),
             #[cfg(feature = "range_coding")] 3 => decoder = Box::new(RCDecoder::new(decoder)),
+            #[cfg(feature = "lzss")] 4 => decoder = Box::new(LZSSDecoder::<256>::new(decoder)),
+            #[cfg(feature = "lzss")] 5 => decoder = Box::new(LZSSDecoder::<1024>::new(decoder)),
+            #[cfg(feature = "lzss")] 6 => decoder = Box::new(LZSSDecoder::<4096>::new(decoder)),
             _ => return decoder,
         }
     }
@@ -155,7 +331,7 @@ mod tests {
 
     use std::{cmp::Ordering, iter::repeat_with};
 
-    use crate::{compress, make_decoder, CompressionMethods};
+    use crate::{compress, make_decoder, make_decoder_from_source, CompressionLevel, CompressionMethods, DataSource};
 
     use super::quickcheck::{
         quickcheck, TestResult
@@ -172,11 +348,80 @@ mod tests {
                 return TestResult::discard();
             }
 
-            let (encoded, _) = compress(&expanded_data, &[CompressionMethods::LZ77, CompressionMethods::RC]);
+            let (encoded, _) = compress(&expanded_data, &[CompressionMethods::LZ77, CompressionMethods::RC], CompressionLevel::Default);
+
+            let mut decoder = make_decoder(&encoded);
+            let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(expanded_data.len()).collect();
+            TestResult::from_bool(decoded.cmp(&expanded_data) == Ordering::Equal)
+        }
+
+        fn lzss_data_can_be_decoded(data: Vec<u8>) -> TestResult {
+            let expanded_data: Vec<u8> = data.chunks_exact(2)
+                .flat_map(|d| {
+                    std::iter::repeat_n(d[1], d[0] as usize)
+                })
+                .collect();
+            if expanded_data.is_empty() {
+                return TestResult::discard();
+            }
+
+            let (encoded, _) = compress(&expanded_data, &[CompressionMethods::LZSS1024, CompressionMethods::RC], CompressionLevel::Default);
 
             let mut decoder = make_decoder(&encoded);
             let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(expanded_data.len()).collect();
             TestResult::from_bool(decoded.cmp(&expanded_data) == Ordering::Equal)
         }
     }
+
+    #[test]
+    fn test_compression_levels() {
+        let data: Vec<u8> = (0..4000_u32).map(|i| ((i * 7) % 251 ^ (i / 300)) as u8).collect();
+        for methods in [[CompressionMethods::LZ77, CompressionMethods::RC], [CompressionMethods::LZSS4096, CompressionMethods::RC]] {
+            let mut sizes = Vec::new();
+            for level in [CompressionLevel::Fast, CompressionLevel::Default, CompressionLevel::Max] {
+                let (encoded, _) = compress(&data, &methods, level);
+                let mut decoder = make_decoder(&encoded);
+                let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(data.len()).collect();
+                assert_eq!(decoded, data);
+                sizes.push(encoded.len());
+            }
+            assert!(sizes[0] >= sizes[1] && sizes[1] >= sizes[2]);
+        }
+    }
+
+    /// A data source which is split into banks of 16 bytes, like banked ROM.
+    struct BankedSource {
+        banks: Vec<Vec<u8>>
+    }
+
+    impl DataSource for BankedSource {
+        fn read_u8(&self, offset: usize) -> u8 {
+            self.banks.get(offset / 16).and_then(|bank| bank.get(offset % 16)).copied().unwrap_or(0)
+        }
+    }
+
+    #[test]
+    fn test_make_decoder_from_source() {
+        let data: Vec<u8> = (0..300_u32).map(|i| (i % 7 + i / 50) as u8).collect();
+        let (encoded, _) = compress(&data, &[CompressionMethods::LZ77, CompressionMethods::RC], CompressionLevel::Default);
+        let source = BankedSource { banks: encoded.chunks(16).map(|c| c.to_vec()).collect() };
+
+        let mut decoder = make_decoder_from_source(&source);
+        let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(data.len()).collect();
+        assert_eq!(decoded, data);
+
+        let mut decoder = make_decoder_from_source(encoded.as_slice());
+        let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(data.len()).collect();
+        assert_eq!(decoded, data);
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_verify() {
+        let data = [0, 0, 0, 0, 1, 1, 1, 1];
+        let (mut compressed, _) = compress(&data, &[CompressionMethods::LZ77], CompressionLevel::Default);
+        assert_eq!(crate::verify(&data, &compressed), Ok(()));
+        compressed[2] ^= 0x10;
+        assert!(crate::verify(&data, &compressed).is_err());
+    }
 }
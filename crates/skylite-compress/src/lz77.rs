@@ -72,6 +72,7 @@ fn calc_max_correlation_offset(data_counts: &[u32; 256], control_code_counts: &[
 }
 
 struct LZ77Encoder {
+    search_depth: usize,
     pending_symbols: usize,
     buffer: RingBuffer,
     recall_distances: Vec<usize>,
@@ -81,8 +82,9 @@ struct LZ77Encoder {
 
 impl LZ77Encoder {
 
-    pub fn new() -> LZ77Encoder {
+    pub fn new(search_depth: usize) -> LZ77Encoder {
         LZ77Encoder {
+            search_depth: search_depth.min(MAX_RECALL_DIST),
             pending_symbols: 0,
             buffer: RingBuffer::new(),
             recall_distances: Vec::new(),
@@ -130,7 +132,7 @@ impl LZ77Encoder {
         }
 
         if self.recall_distances.is_empty() {
-            self.recall_distances = (0 .. MAX_RECALL_DIST).filter(|dist| self.buffer.read(*dist) == symbol).collect();
+            self.recall_distances = (0 .. self.search_depth).filter(|dist| self.buffer.read(*dist) == symbol).collect();
             if !self.recall_distances.is_empty() {
                 self.recall_length = 1;
             }
@@ -187,8 +189,9 @@ impl LZ77Encoder {
     }
 }
 
-pub fn encode_lz77<'a>(data: &[u8]) -> Vec<u8> {
-    let mut encoder = LZ77Encoder::new();
+/// Encode `data` using LZ77. Only the `search_depth` most recent bytes are searched for matches.
+pub fn encode_lz77<'a>(data: &[u8], search_depth: usize) -> Vec<u8> {
+    let mut encoder = LZ77Encoder::new(search_depth);
     for b in data {
         encoder.push_symbol(*b);
     }
@@ -263,7 +266,7 @@ mod tests {
         quickcheck, TestResult
     };
 
-    use crate::{encode_lz77, lz77::LZ77Decoder, Decoder, RawSliceDecoder};
+    use crate::{encode_lz77, lz77::{LZ77Decoder, MAX_RECALL_DIST}, Decoder, RawSliceDecoder};
 
     #[test]
     fn test_compression() {
@@ -277,7 +280,7 @@ mod tests {
             })
             .collect();
 
-        let encoded = encode_lz77(&data);
+        let encoded = encode_lz77(&data, MAX_RECALL_DIST);
 
         let expectation = &[238, 28, 0, 17, 17, 17, 0, 85, 17, 27, 17, 147, 17, 11, 17, 11, 17, 11, 17, 11, 17, 11, 5, 11];
         assert_eq!(&encoded[..], expectation);
@@ -298,7 +301,7 @@ mod tests {
                 return TestResult::discard();
             }
 
-            let encoded = encode_lz77(&expanded_data);
+            let encoded = encode_lz77(&expanded_data, MAX_RECALL_DIST);
 
             let mut decoder = LZ77Decoder::new(Box::new(RawSliceDecoder::new(&encoded)));
             let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(expanded_data.len()).collect();
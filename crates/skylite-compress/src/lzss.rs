@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::Decoder;
+
+// LZSS data consists of groups of up to eight tokens, each group being preceded
+// by a flag byte. Bit n of the flag byte (starting from the least significant bit)
+// gives the type of the n-th token in the group: A set bit denotes a literal, which
+// is a single byte that is copied directly to the output. A cleared bit denotes a
+// back-reference, which is stored as a big-endian u16. The upper bits of the
+// back-reference hold the distance - 1, the lower bits hold the length - MIN_MATCH.
+// The number of bits used for the distance depends on the window size.
+
+const MIN_MATCH: usize = 3;
+
+const fn offset_bits(window: usize) -> u32 {
+    window.trailing_zeros()
+}
+
+const fn max_match(window: usize) -> usize {
+    (1 << (16 - offset_bits(window))) - 1 + MIN_MATCH
+}
+
+fn hash_key(data: &[u8], pos: usize) -> u32 {
+    (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32
+}
+
+struct LZSSEncoder {
+    out: Vec<u8>,
+    flags_idx: usize,
+    num_tokens: u8
+}
+
+impl LZSSEncoder {
+    fn new() -> LZSSEncoder {
+        LZSSEncoder {
+            out: Vec::new(),
+            flags_idx: 0,
+            num_tokens: 8
+        }
+    }
+
+    fn start_token(&mut self, literal: bool) {
+        if self.num_tokens >= 8 {
+            self.flags_idx = self.out.len();
+            self.out.push(0);
+            self.num_tokens = 0;
+        }
+        if literal {
+            self.out[self.flags_idx] |= 1 << self.num_tokens;
+        }
+        self.num_tokens += 1;
+    }
+
+    fn emit_literal(&mut self, value: u8) {
+        self.start_token(true);
+        self.out.push(value);
+    }
+
+    fn emit_match(&mut self, window: usize, distance: usize, len: usize) {
+        self.start_token(false);
+        let code = (((distance - 1) << (16 - offset_bits(window))) | (len - MIN_MATCH)) as u16;
+        self.out.extend_from_slice(&code.to_be_bytes());
+    }
+}
+
+/// Encode `data` using LZSS with a window of `window` bytes.
+///
+/// `window` must be one of 256, 1024 or 4096. At most `max_chain` previous
+/// positions are checked when searching for a match.
+pub fn encode_lzss(data: &[u8], window: usize, max_chain: usize) -> Vec<u8> {
+    assert!(window == 256 || window == 1024 || window == 4096);
+    let max_len = max_match(window);
+
+    let mut encoder = LZSSEncoder::new();
+
+    // Hash chains over the first three bytes at each position.
+    let mut heads: HashMap<u32, usize> = HashMap::new();
+    let mut prev: Vec<usize> = vec![usize::MAX; data.len()];
+
+    let insert = |pos: usize, heads: &mut HashMap<u32, usize>, prev: &mut Vec<usize>| {
+        if pos + MIN_MATCH <= data.len() {
+            if let Some(old) = heads.insert(hash_key(data, pos), pos) {
+                prev[pos] = old;
+            }
+        }
+    };
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+
+        if pos + MIN_MATCH <= data.len() {
+            let limit = max_len.min(data.len() - pos);
+            let mut candidate = heads.get(&hash_key(data, pos)).copied();
+            let mut chain = 0;
+            while let Some(c) = candidate {
+                if pos - c > window || chain >= max_chain {
+                    break;
+                }
+                let len = (0..limit).take_while(|i| data[c + i] == data[pos + i]).count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = pos - c;
+                    if len == limit {
+                        break;
+                    }
+                }
+                candidate = if prev[c] == usize::MAX { None } else { Some(prev[c]) };
+                chain += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            encoder.emit_match(window, best_dist, best_len);
+            for p in pos .. pos + best_len {
+                insert(p, &mut heads, &mut prev);
+            }
+            pos += best_len;
+        } else {
+            encoder.emit_literal(data[pos]);
+            insert(pos, &mut heads, &mut prev);
+            pos += 1;
+        }
+    }
+
+    encoder.out
+}
+
+/// Decoder for LZSS data with a window of `WINDOW` bytes.
+///
+/// Apart from the source, the decoder only needs the window itself
+/// and does not allocate any memory.
+pub struct LZSSDecoder<'a, const WINDOW: usize> {
+    source: Box<dyn Decoder + 'a>,
+    window: [u8; WINDOW],
+    window_idx: usize,
+    flags: u8,
+    num_flags: u8,
+    distance: usize,
+    remaining: usize
+}
+
+impl<'a, const WINDOW: usize> LZSSDecoder<'a, WINDOW> {
+    pub fn new<'b>(source: Box<dyn Decoder + 'b>) -> LZSSDecoder<'b, WINDOW> {
+        LZSSDecoder {
+            source,
+            window: [0; WINDOW],
+            window_idx: 0,
+            flags: 0,
+            num_flags: 0,
+            distance: 0,
+            remaining: 0
+        }
+    }
+
+    fn push(&mut self, value: u8) {
+        self.window[self.window_idx] = value;
+        self.window_idx = (self.window_idx + 1) & (WINDOW - 1);
+    }
+}
+
+impl<'a, const WINDOW: usize> Decoder for LZSSDecoder<'a, WINDOW> {
+
+    fn decode_u8(&mut self) -> u8 {
+        if self.remaining == 0 {
+            if self.num_flags == 0 {
+                self.flags = self.source.decode_u8();
+                self.num_flags = 8;
+            }
+            let literal = self.flags & 1 != 0;
+            self.flags >>= 1;
+            self.num_flags -= 1;
+
+            if literal {
+                let out = self.source.decode_u8();
+                self.push(out);
+                return out;
+            }
+
+            let code = u16::from_be_bytes([self.source.decode_u8(), self.source.decode_u8()]) as usize;
+            let len_bits = 16 - offset_bits(WINDOW);
+            self.distance = (code >> len_bits) + 1;
+            self.remaining = (code & ((1 << len_bits) - 1)) + MIN_MATCH;
+        }
+
+        let out = self.window[(self.window_idx + WINDOW - self.distance) & (WINDOW - 1)];
+        self.push(out);
+        self.remaining -= 1;
+        out
+    }
+}
+
+#[cfg(test)]
+extern crate quickcheck;
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Ordering, iter::repeat_with};
+
+    use super::quickcheck::{
+        quickcheck, TestResult
+    };
+
+    use crate::{lzss::{encode_lzss, LZSSDecoder}, Decoder, RawSliceDecoder};
+
+    fn roundtrip<const WINDOW: usize>(data: &[u8]) -> bool {
+        let encoded = encode_lzss(data, WINDOW, 256);
+        let mut decoder = LZSSDecoder::<WINDOW>::new(Box::new(RawSliceDecoder::new(&encoded)));
+        let decoded: Vec<u8> = repeat_with(|| decoder.decode_u8()).take(data.len()).collect();
+        decoded.cmp(&data.to_vec()) == Ordering::Equal
+    }
+
+    #[test]
+    fn test_compression() {
+        let data: Vec<u8> = (0..1024)
+            .map(|i| match i % 10 {
+                1 => 0x11,
+                2 => 0x11,
+                3 => 0x11,
+                5 => 0x55,
+                _ => 0
+            })
+            .collect();
+
+        let encoded = encode_lzss(&data, 256, 256);
+        // Seven literals, a run of zeros, and back-references to the first period for the rest of the data.
+        let expectation = &[127, 0, 17, 17, 17, 0, 85, 0, 0, 1, 0, 9, 255, 9, 255, 9, 255, 9, 236];
+        assert_eq!(&encoded[..], expectation);
+        assert!(roundtrip::<256>(&data));
+    }
+
+    #[test]
+    fn test_window_sizes() {
+        let data: Vec<u8> = (0..8192_u32).map(|i| (i * 7 % 251) as u8 ^ (i / 300) as u8).collect();
+        assert!(roundtrip::<256>(&data));
+        assert!(roundtrip::<1024>(&data));
+        assert!(roundtrip::<4096>(&data));
+    }
+
+    quickcheck! {
+        fn encoded_data_can_be_decoded(data: Vec<u8>) -> TestResult {
+            let expanded_data: Vec<u8> = data.chunks_exact(2)
+                .flat_map(|d| {
+                    std::iter::repeat_n(d[1], d[0] as usize)
+                })
+                .collect();
+            if expanded_data.is_empty() {
+                return TestResult::discard();
+            }
+
+            TestResult::from_bool(roundtrip::<256>(&expanded_data)
+                && roundtrip::<1024>(&expanded_data)
+                && roundtrip::<4096>(&expanded_data))
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::{self, Read, Write};
+
+use crate::{compress, make_decoder, CompressionLevel, CompressionMethods, Decoder};
+
+// A compressed stream consists of a sequence of chunks. Each chunk starts with
+// a header made up of the size of the compressed chunk and the size of the original
+// data, both as big-endian u32. The header is followed by the chunk data, which is
+// the output of `compress` for the original data. The stream is terminated by
+// a header where both sizes are 0.
+
+const HEADER_SIZE: usize = 8;
+
+/// The maximum size of the original data of a chunk, see [`StreamEncoder::new`].
+const MAX_CHUNK_SIZE: usize = u16::MAX as usize - 1;
+
+/// Compresses a stream of data in fixed-size chunks.
+///
+/// Unlike `compress`, a `StreamEncoder` never holds more than a single chunk
+/// of uncompressed data in memory, so it can be used to compress data of
+/// arbitrary size, e.g. when writing to a file. Each chunk is compressed
+/// independently with the given `CompressionMethods` and `CompressionLevel`.
+///
+/// The stream must be terminated by calling [`StreamEncoder::finish`],
+/// which writes the remaining data.
+pub struct StreamEncoder<W: Write> {
+    sink: W,
+    methods: Vec<CompressionMethods>,
+    level: CompressionLevel,
+    chunk: Vec<u8>,
+    chunk_size: usize
+}
+
+impl<W: Write> StreamEncoder<W> {
+
+    /// Creates a new `StreamEncoder` which writes to `sink`.
+    ///
+    /// `level` only affects the speed of the encoder and the size of the output,
+    /// the stream is decoded the same way for all levels.
+    ///
+    /// `chunk_size` is the number of bytes of uncompressed data per chunk.
+    /// Larger chunks generally compress better, but require more memory
+    /// both while encoding and decoding. Since each chunk is decoded with
+    /// `make_decoder`, `chunk_size` must be less than 65535.
+    pub fn new(sink: W, methods: &[CompressionMethods], level: CompressionLevel, chunk_size: usize) -> StreamEncoder<W> {
+        assert!(chunk_size > 0 && chunk_size <= MAX_CHUNK_SIZE);
+        StreamEncoder {
+            sink,
+            methods: methods.to_owned(),
+            level,
+            chunk: Vec::with_capacity(chunk_size),
+            chunk_size
+        }
+    }
+
+    fn write_chunk(&mut self) -> io::Result<()> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+
+        let (data, _) = compress(&self.chunk, &self.methods, self.level);
+        self.sink.write_all(&(data.len() as u32).to_be_bytes())?;
+        self.sink.write_all(&(self.chunk.len() as u32).to_be_bytes())?;
+        self.sink.write_all(&data)?;
+        self.chunk.clear();
+        Ok(())
+    }
+
+    /// Writes the remaining data and the end of the stream, and returns the sink.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk()?;
+        self.sink.write_all(&[0; HEADER_SIZE])?;
+        self.sink.flush()?;
+        Ok(self.sink)
+    }
+}
+
+impl<W: Write> Write for StreamEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk_size - self.chunk.len());
+        self.chunk.extend_from_slice(&buf[..len]);
+        if self.chunk.len() >= self.chunk_size {
+            self.write_chunk()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_chunk()?;
+        self.sink.flush()
+    }
+}
+
+/// Decodes a stream created by a [`StreamEncoder`].
+///
+/// The decoder only holds a single chunk of decoded data in memory at a time.
+/// It can be used either through `std::io::Read`, which reports errors from
+/// the underlying source and the end of the stream, or as a [`Decoder`].
+pub struct StreamDecoder<R: Read> {
+    source: R,
+    chunk: Vec<u8>,
+    index: usize,
+    finished: bool
+}
+
+impl<R: Read> StreamDecoder<R> {
+    pub fn new(source: R) -> StreamDecoder<R> {
+        StreamDecoder {
+            source,
+            chunk: Vec::new(),
+            index: 0,
+            finished: false
+        }
+    }
+
+    /// Reads and decodes the next chunk. Returns `false` if the end of the stream was reached.
+    ///
+    /// The sizes in the header are not trusted: a chunk that is larger than any chunk written by
+    /// a `StreamEncoder`, or that is longer than the remaining input, is reported as an error
+    /// instead of allocating memory for it.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        if self.finished {
+            return Ok(false);
+        }
+
+        let mut header = [0; HEADER_SIZE];
+        self.source.read_exact(&mut header)?;
+        let compressed_size = u32::from_be_bytes(header[0..4].try_into().unwrap()) as usize;
+        let size = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+        if compressed_size == 0 {
+            self.finished = true;
+            return Ok(false);
+        }
+
+        if size > MAX_CHUNK_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Chunk size of {} bytes is too large", size)));
+        }
+
+        // Reading through `take` only allocates as much memory as the source actually provides.
+        let mut data = Vec::new();
+        (&mut self.source).take(compressed_size as u64).read_to_end(&mut data)?;
+        if data.len() < compressed_size {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Compressed stream ends inside of a chunk"));
+        }
+        let mut decoder = make_decoder(&data);
+        self.chunk.clear();
+        self.chunk.extend((0..size).map(|_| decoder.decode_u8()));
+        self.index = 0;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for StreamDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.index >= self.chunk.len() {
+            if !self.next_chunk()? {
+                return Ok(0);
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.index);
+        buf[..len].copy_from_slice(&self.chunk[self.index .. self.index + len]);
+        self.index += len;
+        Ok(len)
+    }
+}
+
+impl<R: Read> Decoder for StreamDecoder<R> {
+
+    /// Decodes the next byte from the stream.
+    ///
+    /// Returns 0 after the end of the stream. Panics if the
+    /// underlying source returns an error.
+    fn decode_u8(&mut self) -> u8 {
+        let mut out = [0];
+        match self.read(&mut out) {
+            Ok(_) => out[0],
+            Err(err) => panic!("Error reading compressed stream: {}", err)
+        }
+    }
+}
+
+#[cfg(test)]
+extern crate quickcheck;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::quickcheck::{
+        quickcheck, TestResult
+    };
+
+    use crate::{CompressionLevel, CompressionMethods, Decoder};
+
+    use super::{StreamDecoder, StreamEncoder};
+
+    fn encode(data: &[u8], chunk_size: usize) -> Vec<u8> {
+        let mut encoder = StreamEncoder::new(Vec::new(), &[CompressionMethods::LZ77, CompressionMethods::RC], CompressionLevel::Default, chunk_size);
+        // Write in uneven pieces, so that writes cross chunk boundaries.
+        for piece in data.chunks(7) {
+            encoder.write_all(piece).unwrap();
+        }
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_stream_roundtrip() {
+        let data: Vec<u8> = (0..1000_u32).map(|i| (i % 13) as u8).collect();
+        let encoded = encode(&data, 64);
+
+        let mut decoded = Vec::new();
+        StreamDecoder::new(&encoded[..]).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_stream_decoder() {
+        let data = [1, 2, 3, 4, 5];
+        let encoded = encode(&data, 2);
+
+        let mut decoder = StreamDecoder::new(&encoded[..]);
+        let decoded: Vec<u8> = (0..7).map(|_| decoder.decode_u8()).collect();
+        assert_eq!(decoded, [1, 2, 3, 4, 5, 0, 0]);
+    }
+
+    #[test]
+    fn test_truncated_stream() {
+        let data = [1, 2, 3, 4, 5];
+        let encoded = encode(&data, 2);
+
+        let mut decoded = Vec::new();
+        assert!(StreamDecoder::new(&encoded[..encoded.len() - 1]).read_to_end(&mut decoded).is_err());
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        // The compressed size is larger than the remaining input.
+        let mut stream = vec![0xff, 0xff, 0xff, 0xff, 0, 0, 0, 1];
+        stream.extend_from_slice(&[0; 16]);
+        let err = StreamDecoder::new(&stream[..]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        // The original size is larger than any chunk written by a `StreamEncoder`.
+        let stream = [0, 0, 0, 1, 0xff, 0xff, 0xff, 0xff, 0];
+        let err = StreamDecoder::new(&stream[..]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_compression_level() {
+        let data: Vec<u8> = (0..1000_u32).map(|i| (i % 13) as u8).collect();
+        for level in [CompressionLevel::Fast, CompressionLevel::Default, CompressionLevel::Max] {
+            let mut encoder = StreamEncoder::new(Vec::new(), &[CompressionMethods::LZ77, CompressionMethods::RC], level, 256);
+            encoder.write_all(&data).unwrap();
+            let encoded = encoder.finish().unwrap();
+
+            let mut decoded = Vec::new();
+            StreamDecoder::new(&encoded[..]).read_to_end(&mut decoded).unwrap();
+            assert_eq!(decoded, data);
+        }
+    }
+
+    quickcheck! {
+        fn stream_can_be_decoded(data: Vec<u8>, chunk_size: u8) -> TestResult {
+            if chunk_size == 0 {
+                return TestResult::discard();
+            }
+
+            let encoded = encode(&data, chunk_size as usize);
+            let mut decoded = Vec::new();
+            StreamDecoder::new(&encoded[..]).read_to_end(&mut decoded).unwrap();
+            TestResult::from_bool(decoded == data)
+        }
+    }
+}
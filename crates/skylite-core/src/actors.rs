@@ -1,6 +1,8 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use skylite_compress::Decoder;
 
-use crate::{ecs::Entity, scenes::Scene, DrawContext, ProjectControls, SkyliteProject};
+use crate::{decode::DecodeError, ecs::Entity, scenes::Scene, DrawContext, ProjectControls, RenderLayer, SkyliteProject, UpdatePhase};
 
 /// **For internal use only.**
 ///
@@ -31,8 +33,8 @@ pub trait ActorBase: InstanceId {
     type P: SkyliteProject;
 
     #[doc(hidden)] fn _private_decode(decoder: &mut dyn Decoder) -> Self;
-    #[doc(hidden)] fn _private_update(&mut self, scene: &mut dyn Scene<P=Self::P>, controls: &mut ProjectControls<Self::P>);
-    #[doc(hidden)] fn _private_render(&self, ctx: &DrawContext<Self::P>);
+    #[doc(hidden)] fn _private_update(&mut self, scene: &mut dyn Scene<P=Self::P>, controls: &mut ProjectControls<Self::P>, phase: UpdatePhase);
+    #[doc(hidden)] fn _private_render(&self, ctx: &mut DrawContext<Self::P>);
 
     /// Returns a reference to the underlying entity for this actor.
     fn get_entity(&self) -> &Entity;
@@ -40,6 +42,26 @@ pub trait ActorBase: InstanceId {
     /// Returns a mutable reference to the underlying entity for this actor.
     fn get_entity_mut(&mut self) -> &mut Entity;
 
+    /// Returns the handle of this actor, see [`ActorHandle`].
+    fn handle(&self) -> ActorHandle {
+        self.get_entity().handle()
+    }
+
+    /// Returns the actor's properties for inspection at runtime.
+    ///
+    /// For generated actors, this returns the properties when the `debug-reflection`
+    /// feature of `skylite_proc` is enabled. The default implementation returns `None`.
+    #[cfg(feature = "debug-reflection")]
+    fn reflect(&self) -> Option<&dyn crate::reflect::Reflect> {
+        None
+    }
+
+    /// Returns the actor's properties for modification at runtime, see [`ActorBase::reflect`].
+    #[cfg(feature = "debug-reflection")]
+    fn reflect_mut(&mut self) -> Option<&mut dyn crate::reflect::Reflect> {
+        None
+    }
+
     /// Returns the z-order of the actor.
     ///
     /// The z-order determines the order in which actors
@@ -52,6 +74,54 @@ pub trait ActorBase: InstanceId {
     fn z_order(&self) -> i16 {
         1
     }
+
+    /// Returns the layer in which the actor is rendered.
+    ///
+    /// The layer takes precedence over the z-order. For generated actors, the layer
+    /// is set with `skylite_proc::render_layer!`. The default layer is [`RenderLayer::Main`].
+    fn render_layer(&self) -> RenderLayer {
+        RenderLayer::Main
+    }
+
+    /// Returns the factors by which the focus is scaled when the actor is rendered.
+    ///
+    /// For generated actors, the factors are set with `skylite_proc::parallax!`.
+    /// The default is `(1.0, 1.0)`, i.e. the actor moves with the focus.
+    fn parallax(&self) -> (f32, f32) {
+        (1.0, 1.0)
+    }
+
+    /// Returns the priority with which the actor is updated.
+    ///
+    /// Within the named actors and within the extras of a scene, actors with a lower
+    /// priority are updated before actors with a higher priority. Actors with the same
+    /// priority are updated in the order in which they are declared, or for extras,
+    /// in the order in which they were added. Named actors are always updated before extras.
+    ///
+    /// For generated actors, the priority is set with `skylite_proc::update_priority!`.
+    /// The default priority is `0`.
+    fn update_priority(&self) -> i16 {
+        0
+    }
+}
+
+/// A weak reference to an actor.
+///
+/// Every actor receives a new handle when it is created or decoded, and handles are never
+/// reused. A handle can therefore be stored across updates, e.g. by an enemy to remember its
+/// target, and resolved with [`Scene::get_actor`] or `get_actor_as` when it is needed. Once
+/// the actor has been removed from the scene, the handle no longer resolves to any actor.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ActorHandle(u64);
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+impl ActorHandle {
+
+    #[doc(hidden)]
+    pub fn _private_next() -> ActorHandle {
+        ActorHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed))
+    }
 }
 
 /// An [`Actor`] from the point of view of a [`Scene`].
@@ -64,6 +134,13 @@ pub trait ActorBase: InstanceId {
 /// This allows the `Scene` from storing `Actors` of different
 /// types in a single container.
 pub trait AnyActor: ActorBase {
+
+    /// Decodes an actor, and returns an error if the data contains an unknown actor id.
+    ///
+    /// `ActorBase::_private_decode` is a wrapper around this function, which calls
+    /// the project's `#[skylite_proc::unknown_actor]` function if there is one, or
+    /// panics otherwise.
+    #[doc(hidden)] fn _private_try_decode(decoder: &mut dyn Decoder) -> Result<Self, DecodeError> where Self: Sized;
     #[doc(hidden)] unsafe fn _private_transmute_mut<A: Actor>(&mut self) -> &mut A;
     #[doc(hidden)] unsafe fn _private_transmute<A: Actor>(&self) -> &A;
 }
@@ -0,0 +1,103 @@
+/// A clipping rectangle in screen coordinates, given as `(left, top, right, bottom)`.
+/// The right and bottom edges are exclusive.
+pub(crate) type ClipRect = (i32, i32, i32, i32);
+
+/// A region of a texture atlas, given as `(src_x, src_y, src_w, src_h)`.
+pub(crate) type SrcRect = (i16, i16, u16, u16);
+
+/// Returns the intersection of two clipping rectangles.
+pub(crate) fn intersect(a: ClipRect, b: ClipRect) -> ClipRect {
+    let left = a.0.max(b.0);
+    let top = a.1.max(b.1);
+    (left, top, a.2.min(b.2).max(left), a.3.min(b.3).max(top))
+}
+
+/// Returns the part of `rect` which is inside of `clip` and on a screen of size `screen`,
+/// or `None` if nothing of `rect` is visible.
+pub(crate) fn visible_rect(rect: ClipRect, clip: Option<ClipRect>, screen: (u16, u16)) -> Option<ClipRect> {
+    let mut out = intersect(rect, (0, 0, screen.0 as i32, screen.1 as i32));
+    if let Some(clip) = clip {
+        out = intersect(out, clip);
+    }
+    if out.0 < out.2 && out.1 < out.3 { Some(out) } else { None }
+}
+
+/// Restricts a draw call at screen position `x`, `y` to the rectangle `clip`, by cutting the source region.
+///
+/// Returns the new screen position and source region, or `None` if nothing remains visible.
+/// The cuts are computed on the screen, and mapped back through the rotation and flips,
+/// so that the visible part of the region is drawn exactly as without clipping.
+pub(crate) fn clip_draw(clip: ClipRect, x: i32, y: i32, src: SrcRect, flip_h: bool, flip_v: bool, rotate: bool) -> Option<(i32, i32, SrcRect)> {
+    let (src_x, src_y, src_w, src_h) = src;
+    let (w, h) = if rotate { (src_h as i32, src_w as i32) } else { (src_w as i32, src_h as i32) };
+
+    // Cuts on the screen.
+    let left = (clip.0 - x).max(0);
+    let top = (clip.1 - y).max(0);
+    let right = (x + w - clip.2).max(0);
+    let bottom = (y + h - clip.3).max(0);
+    if left + right >= w || top + bottom >= h {
+        return None;
+    }
+
+    // Undo the rotation by 90 degrees clockwise: the left edge of the screen
+    // region is the bottom edge of the unrotated region, and so on.
+    let (mut cut_l, mut cut_t, mut cut_r, mut cut_b) = if rotate {
+        (top, right, bottom, left)
+    } else {
+        (left, top, right, bottom)
+    };
+
+    // Undo the flips.
+    if flip_h {
+        std::mem::swap(&mut cut_l, &mut cut_r);
+    }
+    if flip_v {
+        std::mem::swap(&mut cut_t, &mut cut_b);
+    }
+
+    let src = (
+        src_x + cut_l as i16,
+        src_y + cut_t as i16,
+        src_w - (cut_l + cut_r) as u16,
+        src_h - (cut_t + cut_b) as u16
+    );
+    Some((x + left, y + top, src))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clip_draw, intersect, visible_rect};
+
+    #[test]
+    fn test_intersect() {
+        assert_eq!(intersect((0, 0, 10, 10), (5, -5, 20, 8)), (5, 0, 10, 8));
+        assert_eq!(intersect((0, 0, 10, 10), (20, 20, 30, 30)), (20, 20, 20, 20));
+    }
+
+    #[test]
+    fn test_visible_rect() {
+        assert_eq!(visible_rect((-5, 2, 10, 8), None, (160, 144)), Some((0, 2, 10, 8)));
+        assert_eq!(visible_rect((-5, 2, 10, 8), Some((4, 4, 6, 6)), (160, 144)), Some((4, 4, 6, 6)));
+        assert_eq!(visible_rect((150, 2, 170, 8), Some((0, 0, 20, 20)), (160, 144)), None);
+    }
+
+    #[test]
+    fn test_clip_draw() {
+        let clip = (10, 10, 20, 20);
+
+        // Fully inside and fully outside.
+        assert_eq!(clip_draw(clip, 12, 12, (0, 0, 4, 4), false, false, false), Some((12, 12, (0, 0, 4, 4))));
+        assert_eq!(clip_draw(clip, 30, 12, (0, 0, 4, 4), false, false, false), None);
+
+        // Cut on the left and bottom.
+        assert_eq!(clip_draw(clip, 8, 17, (0, 0, 4, 6), false, false, false), Some((10, 17, (2, 0, 2, 3))));
+
+        // With flips, the cuts move to the opposite edges of the source region.
+        assert_eq!(clip_draw(clip, 8, 17, (0, 0, 4, 6), true, true, false), Some((10, 17, (0, 3, 2, 3))));
+
+        // Rotated, the screen region is 6x4. The left cut removes the bottom of the source
+        // region and the bottom cut removes its right part.
+        assert_eq!(clip_draw(clip, 8, 18, (0, 0, 4, 6), false, false, true), Some((10, 18, (0, 0, 2, 4))));
+    }
+}
@@ -0,0 +1,365 @@
+//! A debug server for inspecting a running project from another process.
+//!
+//! This module is only available with the `debug-server` feature, which also enables
+//! `debug-reflection`. The server accepts connections on a TCP socket and answers
+//! requests for the actors of the current scene, their properties, and the time taken
+//! and draw calls made by the most recent update and render. `cargo skylite inspect <address>` is a terminal
+//! client for the server.
+//!
+//! The protocol is line based. Each request is a single line, and each response consists
+//! of any number of lines, followed by a line containing only `.`. The requests are:
+//!
+//! - `actors`: One line per actor of the current scene, containing the actor's index,
+//!   its name (or `-` for extras) and its handle.
+//! - `props <index>`: One line per property of the actor at `<index>`, containing the
+//!   property's name, type and value. Values of properties that are not editable are shown as `?`.
+//! - `set <index> <name> <value>`: Sets a property of the actor at `<index>`.
+//! - `timings`: The durations of the most recent update and render in microseconds.
+//! - `draws`: The number of draw calls and drawn pixels of the most recent render, one line for
+//!   the whole frame, followed by one line per layer. See [`DrawStats`].
+//!
+//! Errors are reported as a single line starting with `error:`.
+//!
+//! The server does not run in the background. Instead, [`DebugServer::poll`] must be
+//! called regularly, typically from a `#[skylite_proc::post_update]` function:
+//!
+//! ```ignore
+//! #[skylite_proc::post_update]
+//! fn post_update(project: &mut MyProject) {
+//!     DEBUG_SERVER.with_borrow_mut(|server| server.poll(project.scene.as_mut()));
+//! }
+//! ```
+//!
+//! The draw statistics are passed to the server after each render:
+//!
+//! ```ignore
+//! #[skylite_proc::post_render]
+//! fn post_render(ctx: &mut DrawContext<MyProject>) {
+//!     DEBUG_SERVER.with_borrow_mut(|server| server.set_draw_stats(*ctx.stats()));
+//! }
+//! ```
+
+use std::{io::{self, ErrorKind, Read, Write}, net::{TcpListener, TcpStream, ToSocketAddrs}, time::{Duration, Instant}};
+
+use crate::{actors::ActorBase, reflect::ReflectValue, scenes::{IterActors, Scene}, stats::{DrawStats, LayerStats}, RenderLayer, SkyliteProject};
+
+/// A request sent by a debug client.
+#[derive(Debug, PartialEq)]
+enum Request<'a> {
+    Actors,
+    Props(usize),
+    Set(usize, &'a str, &'a str),
+    Timings,
+    Draws
+}
+
+impl<'a> Request<'a> {
+
+    fn parse(line: &'a str) -> Result<Request<'a>, String> {
+        let parse_index = |index: Option<&str>| -> Result<usize, String> {
+            let index = index.ok_or("Missing actor index")?;
+            index.parse().map_err(|_| format!("Invalid actor index {}", index))
+        };
+
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("actors") => Ok(Request::Actors),
+            Some("props") => Ok(Request::Props(parse_index(words.next())?)),
+            Some("set") => {
+                let index = parse_index(words.next())?;
+                let name = words.next().ok_or("Missing property name")?;
+                let value = words.next().ok_or("Missing property value")?;
+                Ok(Request::Set(index, name, value))
+            },
+            Some("timings") => Ok(Request::Timings),
+            Some("draws") => Ok(Request::Draws),
+            Some(other) => Err(format!("Unknown request {}", other)),
+            None => Err("Empty request".to_owned())
+        }
+    }
+}
+
+/// Parses `text` as a value of the same kind as `current`.
+fn parse_value(text: &str, current: ReflectValue) -> Option<ReflectValue> {
+    match current {
+        ReflectValue::Bool(_) => text.parse().ok().map(ReflectValue::Bool),
+        ReflectValue::Int(_) => text.parse().ok().map(ReflectValue::Int),
+        ReflectValue::UInt(_) => text.parse().ok().map(ReflectValue::UInt),
+        ReflectValue::Float(_) => text.parse().ok().map(ReflectValue::Float)
+    }
+}
+
+fn format_value(value: ReflectValue) -> String {
+    match value {
+        ReflectValue::Bool(v) => v.to_string(),
+        ReflectValue::Int(v) => v.to_string(),
+        ReflectValue::UInt(v) => v.to_string(),
+        ReflectValue::Float(v) => v.to_string()
+    }
+}
+
+fn format_draw_stats(stats: &DrawStats) -> Vec<String> {
+    let line = |name: &str, stats: LayerStats| format!("{} {} {}", name, stats.draw_calls, stats.pixels);
+    vec![
+        line("total", stats.total()),
+        line("background", stats.layer(RenderLayer::Background)),
+        line("main", stats.layer(RenderLayer::Main)),
+        line("ui", stats.layer(RenderLayer::Ui))
+    ]
+}
+
+/// The maximum length of a request. Clients which send more data without a newline are dropped.
+const MAX_REQUEST_LEN: usize = 1024;
+
+/// The maximum amount of response data waiting to be sent to a client.
+/// Clients which do not read their responses are dropped once this is exceeded.
+const MAX_PENDING_OUTPUT: usize = 64 * 1024;
+
+struct Client {
+    stream: TcpStream,
+    input: Vec<u8>,
+    output: Vec<u8>
+}
+
+/// Writes as much of `output` to `stream` as possible without blocking, and removes the written bytes.
+fn write_pending<W: Write>(stream: &mut W, output: &mut Vec<u8>) -> io::Result<()> {
+    let mut written = 0;
+    while written < output.len() {
+        match stream.write(&output[written..]) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(len) => written += len,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(e)
+        }
+    }
+    output.drain(..written);
+    Ok(())
+}
+
+/// Serves the state of a running project to debug clients, see the [module documentation](self).
+pub struct DebugServer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    update_start: Option<Instant>,
+    render_start: Option<Instant>,
+    update_time: Duration,
+    render_time: Duration,
+    draw_stats: DrawStats
+}
+
+impl DebugServer {
+
+    /// Creates a server listening on `addr`, e.g. `"127.0.0.1:7878"`.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<DebugServer> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(DebugServer {
+            listener,
+            clients: Vec::new(),
+            update_start: None,
+            render_start: None,
+            update_time: Duration::ZERO,
+            render_time: Duration::ZERO,
+            draw_stats: DrawStats::default()
+        })
+    }
+
+    /// Marks the start of an update, for the `timings` request.
+    pub fn start_update(&mut self) {
+        self.update_start = Some(Instant::now());
+    }
+
+    /// Marks the end of an update, for the `timings` request.
+    pub fn finish_update(&mut self) {
+        if let Some(start) = self.update_start.take() {
+            self.update_time = start.elapsed();
+        }
+    }
+
+    /// Marks the start of a render, for the `timings` request.
+    pub fn start_render(&mut self) {
+        self.render_start = Some(Instant::now());
+    }
+
+    /// Marks the end of a render, for the `timings` request.
+    pub fn finish_render(&mut self) {
+        if let Some(start) = self.render_start.take() {
+            self.render_time = start.elapsed();
+        }
+    }
+
+    /// Sets the statistics of the most recent render, for the `draws` request.
+    pub fn set_draw_stats(&mut self, stats: DrawStats) {
+        self.draw_stats = stats;
+    }
+
+    /// Accepts new clients and answers all complete requests, without blocking.
+    ///
+    /// Responses which cannot be sent right away are kept, and sent by later calls.
+    /// Clients that disconnect, cause an I/O error, send overly long requests or
+    /// do not read their responses are dropped.
+    pub fn poll<P: SkyliteProject>(&mut self, scene: &mut dyn Scene<P=P>) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(Client { stream, input: Vec::new(), output: Vec::new() });
+            }
+        }
+
+        let mut clients = std::mem::take(&mut self.clients);
+        clients.retain_mut(|client| self.serve(client, scene).is_ok());
+        self.clients = clients;
+    }
+
+    fn serve<P: SkyliteProject>(&self, client: &mut Client, scene: &mut dyn Scene<P=P>) -> io::Result<()> {
+        let mut chunk = [0; 256];
+        loop {
+            match client.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(len) => {
+                    client.input.extend_from_slice(&chunk[..len]);
+                    self.answer_requests(client, scene);
+                    if client.input.len() > MAX_REQUEST_LEN {
+                        return Err(io::Error::new(ErrorKind::InvalidData, "Request is too long"));
+                    }
+                },
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e)
+            }
+        }
+
+        write_pending(&mut client.stream, &mut client.output)?;
+        if client.output.len() > MAX_PENDING_OUTPUT {
+            return Err(io::Error::other("Client does not read the responses"));
+        }
+        Ok(())
+    }
+
+    /// Appends the responses to all complete requests in the client's input to its output.
+    fn answer_requests<P: SkyliteProject>(&self, client: &mut Client, scene: &mut dyn Scene<P=P>) {
+        while let Some(end) = client.input.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = client.input.drain(..=end).collect();
+            let response = match std::str::from_utf8(&line) {
+                Ok(line) => self.respond(line.trim(), scene),
+                Err(_) => vec!["error: Request is not valid UTF-8".to_owned()]
+            };
+            for line in response {
+                client.output.extend_from_slice(line.as_bytes());
+                client.output.push(b'\n');
+            }
+            client.output.extend_from_slice(b".\n");
+        }
+    }
+
+    fn respond<P: SkyliteProject>(&self, line: &str, scene: &mut dyn Scene<P=P>) -> Vec<String> {
+        let request = match Request::parse(line) {
+            Ok(request) => request,
+            Err(msg) => return vec![format!("error: {}", msg)]
+        };
+
+        match request {
+            Request::Actors => {
+                let names = scene.actor_names();
+                scene.iter_actors(IterActors::All)
+                    .enumerate()
+                    .map(|(index, actor)| format!("{} {} {:?}", index, names.get(index).unwrap_or(&"-"), actor.handle()))
+                    .collect()
+            },
+            Request::Props(index) => {
+                let Some(actor) = scene.iter_actors(IterActors::All).nth(index) else {
+                    return vec![format!("error: No actor at index {}", index)];
+                };
+                let Some(reflect) = actor.reflect() else {
+                    return vec![format!("error: Actor {} does not support reflection", index)];
+                };
+                reflect.properties().iter()
+                    .map(|p| {
+                        let value = reflect.get_property(p.name).map(format_value).unwrap_or_else(|| "?".to_owned());
+                        format!("{} {} {}", p.name, p.type_name, value)
+                    })
+                    .collect()
+            },
+            Request::Set(index, name, text) => {
+                let Some(actor) = scene.iter_actors_mut(IterActors::All).nth(index) else {
+                    return vec![format!("error: No actor at index {}", index)];
+                };
+                let Some(reflect) = actor.reflect_mut() else {
+                    return vec![format!("error: Actor {} does not support reflection", index)];
+                };
+                let Some(current) = reflect.get_property(name) else {
+                    return vec![format!("error: Property {} does not exist or is not editable", name)];
+                };
+                match parse_value(text, current) {
+                    Some(value) if reflect.set_property(name, value) => vec![],
+                    _ => vec![format!("error: Invalid value {} for property {}", text, name)]
+                }
+            },
+            Request::Timings => vec![format!("update {} render {}", self.update_time.as_micros(), self.render_time.as_micros())],
+            Request::Draws => format_draw_stats(&self.draw_stats)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, ErrorKind, Write};
+
+    use crate::reflect::ReflectValue;
+
+    use super::{parse_value, write_pending, Request};
+
+    /// A writer which accepts `capacity` bytes, and then blocks.
+    struct BlockingWriter {
+        written: Vec<u8>,
+        capacity: usize
+    }
+
+    impl Write for BlockingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.capacity - self.written.len()).min(4);
+            if len == 0 {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(Request::parse("actors"), Ok(Request::Actors));
+        assert_eq!(Request::parse("props 3"), Ok(Request::Props(3)));
+        assert_eq!(Request::parse("set 0  hp 12"), Ok(Request::Set(0, "hp", "12")));
+        assert_eq!(Request::parse("draws"), Ok(Request::Draws));
+        assert!(Request::parse("props x").is_err());
+        assert!(Request::parse("set 0 hp").is_err());
+        assert!(Request::parse("reload").is_err());
+        assert!(Request::parse("").is_err());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("-4", ReflectValue::Int(0)), Some(ReflectValue::Int(-4)));
+        assert_eq!(parse_value("-4", ReflectValue::UInt(0)), None);
+        assert_eq!(parse_value("true", ReflectValue::Bool(false)), Some(ReflectValue::Bool(true)));
+        assert_eq!(parse_value("0.5", ReflectValue::Float(0.0)), Some(ReflectValue::Float(0.5)));
+    }
+
+    #[test]
+    fn test_write_pending() {
+        let mut writer = BlockingWriter { written: Vec::new(), capacity: 10 };
+        let mut output = b"actors\n0 player\n.\n".to_vec();
+        write_pending(&mut writer, &mut output).unwrap();
+        assert_eq!(writer.written, b"actors\n0 p");
+        assert_eq!(output, b"layer\n.\n");
+
+        writer.capacity = 100;
+        write_pending(&mut writer, &mut output).unwrap();
+        assert_eq!(writer.written, b"actors\n0 player\n.\n");
+        assert!(output.is_empty());
+    }
+}
@@ -1,7 +1,28 @@
 // This module is the counterpart to `generate/encode.rs` in skylite-proc.
 
 #![allow(non_snake_case)]
-use skylite_compress::Decoder;
+
+// Re-exported, so that generated code does not require a direct dependency on skylite-compress.
+pub use skylite_compress::{DataSource, Decoder, make_decoder, make_decoder_from_source};
+
+/// Error for data which can not be decoded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecodeError {
+    /// The data contains an id which does not belong to any known type,
+    /// e.g. because it was written by a different version of the project.
+    /// `kind` describes what the id refers to, e.g. `"actor"`.
+    UnknownId { kind: &'static str, id: usize }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnknownId { kind, id } => write!(f, "Unknown {} id: {}", kind, id)
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
 
 pub trait Deserialize {
     fn deserialize(decoder: &mut dyn Decoder) -> Self;
@@ -24,22 +45,75 @@ macro_rules! deserialize_for_primitive {
 deserialize_for_primitive!(u8, 1);
 deserialize_for_primitive!(u16, 2);
 deserialize_for_primitive!(u32, 4);
+deserialize_for_primitive!(u64, 8);
 deserialize_for_primitive!(i8, 1);
 deserialize_for_primitive!(i16, 2);
 deserialize_for_primitive!(i32, 4);
+deserialize_for_primitive!(i64, 8);
 deserialize_for_primitive!(f32, 4);
 deserialize_for_primitive!(f64, 8);
 
+/// A binary file embedded with `skylite_proc::embedded_asset!`.
+///
+/// The file is stored compressed. Use [`EmbeddedAsset::decoder`] to read its content.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedAsset {
+    data: &'static [u8],
+    len: usize
+}
+
+impl EmbeddedAsset {
+    #[doc(hidden)]
+    pub const fn _private_new(data: &'static [u8], len: usize) -> EmbeddedAsset {
+        EmbeddedAsset { data, len }
+    }
+
+    /// Returns the size of the original file.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the compressed data, as produced by `skylite_compress::compress`.
+    pub const fn compressed_data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    /// Returns a decoder for the content of the file.
+    pub fn decoder(&self) -> Box<dyn Decoder> {
+        make_decoder(self.data)
+    }
+
+    /// Decodes the entire file.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut decoder = self.decoder();
+        (0..self.len).map(|_| decoder.decode_u8()).collect()
+    }
+}
+
+/// The maximum number of bytes of a varint, which is enough for any `u32` value.
+pub const MAX_VARINT_LEN: usize = 5;
+
+/// Reads a varint, which is used for lengths, counts and ids in the encoded data.
+///
+/// Varints are stored as unsigned LEB128: the value is split into groups of 7 bits, starting
+/// with the least significant group, and each group is stored in one byte. The highest bit is
+/// set on every byte except the last one. Varints are limited to `u32` values, so that data
+/// encoded on a 64-bit host decodes the same on 32-bit targets like WASM. At most
+/// [`MAX_VARINT_LEN`] bytes are read, and bits beyond the range of `u32` are ignored.
 pub fn read_varint(decoder: &mut dyn Decoder) -> usize {
-    let mut out = 0;
-    loop {
+    let mut out = 0_u32;
+    for i in 0..MAX_VARINT_LEN {
         let byte = decoder.decode_u8();
-        out = (out << 7) + (byte & 0x7f) as usize;
+        out |= ((byte & 0x7f) as u32) << (i * 7);
         if byte < 0x80 {
             break;
         }
     }
-    out
+    out as usize
 }
 
 impl<T: Deserialize> Deserialize for Vec<T> {
@@ -101,6 +175,16 @@ impl Deserialize for bool {
     }
 }
 
+impl<T: Deserialize> Deserialize for Option<T> {
+    fn deserialize(decoder: &mut dyn Decoder) -> Self {
+        if bool::deserialize(decoder) {
+            Some(T::deserialize(decoder))
+        } else {
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use skylite_compress::make_decoder;
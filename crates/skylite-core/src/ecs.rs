@@ -1,36 +1,96 @@
-use std::{cell::UnsafeCell, mem::transmute};
+use std::{cell::{Cell, UnsafeCell}, mem::transmute, ops::{Deref, DerefMut}, sync::atomic::{AtomicU64, Ordering}};
 
-use crate::actors::{InstanceId, TypeId};
+use crate::{actors::{ActorBase, ActorHandle, InstanceId, TypeId}, scenes::Scene, SkyliteProject};
 
 /// Marks a type as a component. This trait should only
 /// be implemented through `#[derive(Component)]`.
 pub trait Component: TypeId + InstanceId {}
 
+static CHANGE_TICK: AtomicU64 = AtomicU64::new(1);
+
+/// Returns a new change tick, which is larger than all previous ones.
+///
+/// The ticks are 64 bits wide, so that they do not overflow during the lifetime of a program.
+fn next_tick() -> u64 {
+    CHANGE_TICK.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Records when a component was added to an `Entity`, and when it was last modified.
+struct ComponentTicks {
+    added: u64,
+    changed: Cell<u64>
+}
+
+/// Mutable access to a component, which marks the component as changed when it is modified.
+///
+/// Reading the component through a `Mut` does not mark it as changed, so that `Changed`
+/// filters in `system!` only report components which were actually written to.
+pub struct Mut<'a, C> {
+    value: &'a mut C,
+    changed: &'a Cell<u64>,
+    tick: u64
+}
+
+impl<C> Deref for Mut<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.value
+    }
+}
+
+impl<C> DerefMut for Mut<'_, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.changed.set(self.tick);
+        self.value
+    }
+}
+
 /// An `Entity` is a list of components.
+///
+/// Every `Entity` also has a unique [`ActorHandle`], which identifies the actor it belongs to.
 pub struct Entity {
-    components: Vec<Box<UnsafeCell<dyn Component>>>
+    components: Vec<Box<UnsafeCell<dyn Component>>>,
+    ticks: Vec<ComponentTicks>,
+    /// The tick of the last run of each filtered system that has visited this `Entity`.
+    last_runs: Vec<(u64, u64)>,
+    handle: ActorHandle
 }
 
 impl Entity {
     pub fn new() -> Entity {
-        Entity { components: Vec::new() }
+        Entity { components: Vec::new(), ticks: Vec::new(), last_runs: Vec::new(), handle: ActorHandle::_private_next() }
+    }
+
+    /// Returns the handle of the actor this `Entity` belongs to.
+    pub fn handle(&self) -> ActorHandle {
+        self.handle
+    }
+
+    fn find(&self, type_id: usize) -> Option<usize> {
+        self.components.iter().position(|c| unsafe { &*c.get() }.get_id() == type_id)
     }
 
     /// Adds a component to the `Entity`. An `Entity` can only contain a single instance
     /// of any type of component, so if the same type is added multiple times, this
     /// function will panic.
     pub fn add_component(&mut self, new_component: Box<dyn Component>) {
-        if self.components.iter().any(|c| unsafe { &*c.get() }.get_id() == new_component.get_id()) {
+        if self.find(new_component.get_id()).is_some() {
             panic!("Component already exists in entity.");
         } else {
             // SAFETY: UnsafeCell has repr(transparent) (i.e. the same
             // memory layout as its contents) so this is ok:
             self.components.push(unsafe { transmute(new_component) });
+            let tick = next_tick();
+            self.ticks.push(ComponentTicks { added: tick, changed: Cell::new(tick) });
         }
     }
 
     fn remove_component_by_type_id(&mut self, type_id: usize) {
-        self.components.retain(|c| unsafe { &*c.get() }.get_id() != type_id);
+        if let Some(idx) = self.find(type_id) {
+            self.components.remove(idx);
+            self.ticks.remove(idx);
+        }
     }
 
     /// Removes a component of a particular type from the `Entity`.
@@ -43,28 +103,165 @@ impl Entity {
     /// type from the `Entity`, or None if the `Entity` does not have
     /// that type of component.
     pub fn get_component<C: Component>(&self) -> Option<&C> {
-        self.components.iter()
-            .find(|c| unsafe { &*c.get() }.get_id() == <C as TypeId>::get_id())
+        self.find(<C as TypeId>::get_id())
             // Hopefully this pointer cast is defined behavior? It should just discard the vtable.
-            .map(|c| unsafe { &*(c.get() as *const C) })
+            .map(|idx| unsafe { &*(self.components[idx].get() as *const C) })
     }
 
-    fn get_component_mut_unsafe<C: Component>(&self) -> Option<&mut C> {
-        self.components.iter()
-            .find(|c| unsafe { &*c.get() }.get_id() == <C as TypeId>::get_id())
-            .map(|c| unsafe { &mut *(c.get() as *mut C) })
+    /// Returns mutable access to the component, which marks it as changed at `tick` when it is modified.
+    fn get_component_mut_unsafe<C: Component>(&self, tick: u64) -> Option<Mut<'_, C>> {
+        self.find(<C as TypeId>::get_id())
+            .map(|idx| Mut {
+                value: unsafe { &mut *(self.components[idx].get() as *mut C) },
+                changed: &self.ticks[idx].changed,
+                tick
+            })
     }
 
-    /// Returns a mutable reference to the component with the given
+    /// Returns mutable access to the component with the given
     /// type from the `Entity`, or None if the `Entity` does not have
     /// that type of component.
-    pub fn get_component_mut<C: Component>(&mut self) -> Option<&mut C> {
-        self.get_component_mut_unsafe::<C>()
+    ///
+    /// The component is considered changed by `Changed` filters in `system!`
+    /// once it is modified through the returned [`Mut`].
+    pub fn get_component_mut<C: Component>(&mut self) -> Option<Mut<'_, C>> {
+        self.get_component_mut_unsafe::<C>(next_tick())
+    }
+
+    /// Sets the tick of the last run of `system` to `tick`, and returns the previous one,
+    /// or `0` if the system has not visited this `Entity` before.
+    fn swap_last_run(&mut self, system: u64, tick: u64) -> u64 {
+        match self.last_runs.iter_mut().find(|(id, _)| *id == system) {
+            Some((_, last_run)) => std::mem::replace(last_run, tick),
+            None => {
+                self.last_runs.push((system, tick));
+                0
+            }
+        }
+    }
+}
+
+enum Command<P: SkyliteProject> {
+    Spawn(P::Actors),
+    Despawn(ActorHandle),
+    Insert(ActorHandle, Box<dyn Component>),
+    Remove(ActorHandle, usize)
+}
+
+/// A queue of structural changes to the actors of a scene, which are applied after a system has run.
+///
+/// Systems cannot add or remove actors or components while they iterate over the entities,
+/// so they record the changes in `Commands` instead. The entity that a system is currently
+/// called for can be identified by requesting its [`ActorHandle`] in `system!`.
+///
+/// ```ignore
+/// let mut commands = Commands::new();
+/// skylite_proc::system!(entities, |handle: ActorHandle, health: &mut Health| {
+///     if health.0 == 0 {
+///         commands.despawn(handle);
+///     }
+/// });
+/// commands.apply(scene);
+/// ```
+pub struct Commands<P: SkyliteProject> {
+    queue: Vec<Command<P>>
+}
+
+impl<P: SkyliteProject> Commands<P> {
+    pub fn new() -> Commands<P> {
+        Commands { queue: Vec::new() }
+    }
+
+    /// Returns `true` if no commands have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Adds `extra` to the scene, see [`Scene::add_extra`].
+    pub fn spawn(&mut self, extra: P::Actors) {
+        self.queue.push(Command::Spawn(extra));
+    }
+
+    /// Removes the extra referenced by `handle` from the scene. Named actors cannot be removed.
+    pub fn despawn(&mut self, handle: ActorHandle) {
+        self.queue.push(Command::Despawn(handle));
+    }
+
+    /// Adds `component` to the entity of the actor referenced by `handle`.
+    ///
+    /// If the entity already has a component of the same type, it is replaced.
+    pub fn insert(&mut self, handle: ActorHandle, component: Box<dyn Component>) {
+        self.queue.push(Command::Insert(handle, component));
+    }
+
+    /// Removes the component of type `C` from the entity of the actor referenced by `handle`.
+    pub fn remove<C: Component>(&mut self, handle: ActorHandle) {
+        self.queue.push(Command::Remove(handle, <C as TypeId>::get_id()));
+    }
+
+    /// Applies the recorded commands to `scene`, in the order in which they were recorded.
+    ///
+    /// Commands for actors which are no longer part of the scene are skipped. This must not be
+    /// called while the scene's actors are being updated, e.g. from an action, because the actors
+    /// are not part of the scene during their update.
+    pub fn apply(&mut self, scene: &mut dyn Scene<P=P>) {
+        for command in self.queue.drain(..) {
+            match command {
+                Command::Spawn(extra) => scene.add_extra(extra),
+                Command::Despawn(handle) => { scene.remove_extra_by_handle(handle); },
+                Command::Insert(handle, component) => if let Some(actor) = scene.get_actor_mut(handle) {
+                    let entity = actor.get_entity_mut();
+                    entity.remove_component_by_type_id(component.get_id());
+                    entity.add_component(component);
+                },
+                Command::Remove(handle, type_id) => if let Some(actor) = scene.get_actor_mut(handle) {
+                    actor.get_entity_mut().remove_component_by_type_id(type_id);
+                }
+            }
+        }
+    }
+}
+
+impl<P: SkyliteProject> Default for Commands<P> {
+    fn default() -> Commands<P> {
+        Commands::new()
     }
 }
 
 pub mod __private {
-    use super::{Component, Entity};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::actors::ActorHandle;
+
+    use super::{next_tick, Component, Entity, Mut, TypeId};
+
+    static NEXT_SYSTEM_ID: AtomicU64 = AtomicU64::new(1);
+
+    /// Identifies a filtered `system!` invocation. The entities remember when each system has
+    /// last visited them, so that a system only reports the changes it has not seen yet,
+    /// independently for the entities of each scene.
+    pub struct SystemId(AtomicU64);
+
+    impl SystemId {
+        #[allow(clippy::new_without_default)]
+        pub const fn new() -> SystemId {
+            SystemId(AtomicU64::new(0))
+        }
+
+        /// Returns the id, which is assigned on first use.
+        fn get(&self) -> u64 {
+            match self.0.load(Ordering::Relaxed) {
+                0 => {
+                    let id = NEXT_SYSTEM_ID.fetch_add(1, Ordering::Relaxed);
+                    match self.0.compare_exchange(0, id, Ordering::Relaxed, Ordering::Relaxed) {
+                        Ok(_) => id,
+                        Err(existing) => existing
+                    }
+                },
+                id => id
+            }
+        }
+    }
 
     fn check_distinct(addresses: &[usize]) -> bool {
         for i in 0..addresses.len() {
@@ -77,22 +274,63 @@ pub mod __private {
         true
     }
 
+    /// A `Changed<T>` or `Added<T>` filter of a system.
+    pub struct Filter {
+        type_id: usize,
+        added: bool
+    }
+
+    impl Filter {
+        pub fn changed<C: Component>() -> Filter {
+            Filter { type_id: <C as TypeId>::get_id(), added: false }
+        }
+
+        pub fn added<C: Component>() -> Filter {
+            Filter { type_id: <C as TypeId>::get_id(), added: true }
+        }
+
+        /// Returns `true` if the entity's component was added or changed after the tick `since`.
+        fn matches(&self, entity: &Entity, since: u64) -> bool {
+            match entity.find(self.type_id) {
+                Some(idx) if self.added => entity.ticks[idx].added > since,
+                Some(idx) => entity.ticks[idx].changed.get() > since,
+                None => false
+            }
+        }
+    }
+
     macro_rules! system_fn {
-        ($name:ident, $($var:ident),*) => {
+        ($name:ident, $filtered_name:ident, $($var:ident),*) => {
+            #[allow(non_snake_case)]
+            pub fn $name<'a, $($var: Component),*>(entities: impl IntoIterator<Item=&'a mut Entity>, mut fun: impl FnMut($(Mut<'_, $var>),*)) {
+                $filtered_name(entities, &[], None, |_, $($var),*| fun($($var),*));
+            }
+
+            /// Runs the system only for the entities matching all `filters`. The filters compare
+            /// the ticks of the components with the tick at which `system` last visited each entity,
+            /// which is updated to the current run. In addition to the components, `fun` receives
+            /// the handle of each entity.
             #[allow(non_snake_case)]
-            pub fn $name<'a, $($var: Component),*>(entities: impl IntoIterator<Item=&'a mut Entity>, mut fun: impl FnMut($(&mut $var),*))
+            pub fn $filtered_name<'a, $($var: Component),*>(entities: impl IntoIterator<Item=&'a mut Entity>, filters: &[Filter], system: Option<&SystemId>, mut fun: impl FnMut(ActorHandle, $(Mut<'_, $var>),*))
             {
+                let tick = next_tick();
+                let system = system.map(SystemId::get);
+
                 // The Cx-types are guaranteed to be distinct when the systemx
                 // functions are called through the proc-macro.
                 for e in entities {
-                    $(let $var = e.get_component_mut_unsafe::<$var>();)*
+                    let since = system.map(|id| e.swap_last_run(id, tick)).unwrap_or(0);
+                    if !filters.iter().all(|f| f.matches(e, since)) {
+                        continue;
+                    }
+                    $(let $var = e.get_component_mut_unsafe::<$var>(tick);)*
                     match ($($var,)*) {
                         ($(Some($var),)*) => {
                             #[cfg(debug_assertions)]
-                            if !check_distinct(&[$($var as *mut _ as usize),*]) {
+                            if !check_distinct(&[$(&*$var as *const $var as usize),*]) {
                                 panic!("Indistinguishable components found. This is probably a bug.");
                             }
-                            fun($($var),*);
+                            fun(e.handle, $($var),*);
                         },
                         _ => {}
                     }
@@ -101,22 +339,22 @@ pub mod __private {
         };
     }
 
-    system_fn!(system1, C1);
-    system_fn!(system2, C1, C2);
-    system_fn!(system3, C1, C2, C3);
-    system_fn!(system4, C1, C2, C3, C4);
-    system_fn!(system5, C1, C2, C3, C4, C5);
-    system_fn!(system6, C1, C2, C3, C4, C5, C6);
-    system_fn!(system7, C1, C2, C3, C4, C5, C6, C7);
-    system_fn!(system8, C1, C2, C3, C4, C5, C6, C7, C8);
+    system_fn!(system1, filtered_system1, C1);
+    system_fn!(system2, filtered_system2, C1, C2);
+    system_fn!(system3, filtered_system3, C1, C2, C3);
+    system_fn!(system4, filtered_system4, C1, C2, C3, C4);
+    system_fn!(system5, filtered_system5, C1, C2, C3, C4, C5);
+    system_fn!(system6, filtered_system6, C1, C2, C3, C4, C5, C6);
+    system_fn!(system7, filtered_system7, C1, C2, C3, C4, C5, C6, C7);
+    system_fn!(system8, filtered_system8, C1, C2, C3, C4, C5, C6, C7, C8);
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{actors::TypeId, ecs::__private::system1};
+    use crate::{actors::TypeId, ecs::__private::{filtered_system1, system1, Filter, SystemId}};
 
-    use super::{Component, Entity};
+    use super::{Component, Entity, Mut};
 
     struct Component1(usize);
 
@@ -138,8 +376,8 @@ mod tests {
 
     fn get_test_entities() -> Vec<Entity> {
         let mut entities = vec![
-            Entity { components: vec![] },
-            Entity { components: vec![] }
+            Entity::new(),
+            Entity::new()
         ];
         entities[0].add_component(Box::new(Component1(5)));
         entities[0].add_component(Box::new(Component2(10)));
@@ -148,6 +386,13 @@ mod tests {
         entities
     }
 
+    #[test]
+    fn test_handles() {
+        let entities = get_test_entities();
+        assert_ne!(entities[0].handle(), entities[1].handle());
+        assert_ne!(Entity::new().handle(), entities[1].handle());
+    }
+
     #[test]
     fn test_components() {
         let entities = get_test_entities();
@@ -162,12 +407,55 @@ mod tests {
     fn test_system() {
         let mut entities = get_test_entities();
         let mut sum = 0;
-        system1(entities.iter_mut(), |c: &mut Component1| sum += c.0);
+        system1(entities.iter_mut(), |c: Mut<Component1>| sum += c.0);
 
         assert_eq!(sum, 25);
 
         sum = 0;
-        system1(entities.iter_mut(), |c: &mut Component2| sum += c.0);
+        system1(entities.iter_mut(), |c: Mut<Component2>| sum += c.0);
         assert_eq!(sum, 10);
     }
+
+    #[test]
+    fn test_filters() {
+        let mut entities = get_test_entities();
+        let added = SystemId::new();
+        let changed = SystemId::new();
+        let mut count = 0;
+
+        // Everything is new on the first run.
+        filtered_system1(entities.iter_mut(), &[Filter::added::<Component1>()], Some(&added), |_, _: Mut<Component1>| count += 1);
+        assert_eq!(count, 2);
+        count = 0;
+        filtered_system1(entities.iter_mut(), &[Filter::added::<Component1>()], Some(&added), |_, _: Mut<Component1>| count += 1);
+        assert_eq!(count, 0);
+
+        // Changes made by the system itself are not reported to its next run.
+        count = 0;
+        filtered_system1(entities.iter_mut(), &[Filter::changed::<Component1>()], Some(&changed), |_, mut c: Mut<Component1>| c.0 += 1);
+        filtered_system1(entities.iter_mut(), &[Filter::changed::<Component1>()], Some(&changed), |_, _: Mut<Component1>| count += 1);
+        assert_eq!(count, 0);
+
+        // Reading a component does not mark it as changed.
+        count = 0;
+        assert_eq!(entities[0].get_component_mut::<Component1>().unwrap().0, 6);
+        entities[1].get_component_mut::<Component1>().unwrap().0 = 21;
+        filtered_system1(entities.iter_mut(), &[Filter::changed::<Component1>()], Some(&changed), |_, c: Mut<Component1>| count += c.0);
+        assert_eq!(count, 21);
+    }
+
+    #[test]
+    fn test_filters_per_entity() {
+        let system = SystemId::new();
+        let mut count = 0;
+        let mut scene1 = get_test_entities();
+        filtered_system1(scene1.iter_mut(), &[Filter::added::<Component1>()], Some(&system), |_, _: Mut<Component1>| count += 1);
+        assert_eq!(count, 2);
+
+        // The same system still sees the components of other entities, e.g. from a different scene, as new.
+        count = 0;
+        let mut scene2 = get_test_entities();
+        filtered_system1(scene2.iter_mut(), &[Filter::added::<Component1>()], Some(&system), |_, _: Mut<Component1>| count += 1);
+        assert_eq!(count, 2);
+    }
 }
@@ -0,0 +1,197 @@
+// This module is the counterpart to `decode.rs`. It produces the same format
+// as `generate/encode.rs` in skylite-proc, but is available at runtime.
+
+#![allow(non_snake_case)]
+
+use crate::strings::StrId;
+
+/// Types which can be written in the format read by [`Deserialize`][crate::decode::Deserialize].
+///
+/// The data is written uncompressed. To use it with [`make_decoder`][crate::decode::make_decoder],
+/// it must be prefixed by a `0` byte, or be compressed with `skylite_compress::compress`.
+pub trait Serialize {
+    fn serialize(&self, out: &mut Vec<u8>);
+}
+
+/// Writes `val` in the format read by [`read_varint`][crate::decode::read_varint].
+///
+/// Panics if `val` does not fit into a `u32`.
+pub fn write_varint(mut val: usize, out: &mut Vec<u8>) {
+    assert!(val as u64 <= u32::MAX as u64, "Varint {} does not fit into a u32", val);
+    while val >= 0x80 {
+        out.push((val & 0x7f) as u8 | 0x80);
+        val >>= 7;
+    }
+    out.push(val as u8);
+}
+
+macro_rules! serialize_for_primitive {
+    ($typename:ident) => {
+        impl Serialize for $typename {
+            fn serialize(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+        }
+    };
+}
+
+serialize_for_primitive!(u8);
+serialize_for_primitive!(u16);
+serialize_for_primitive!(u32);
+serialize_for_primitive!(u64);
+serialize_for_primitive!(i8);
+serialize_for_primitive!(i16);
+serialize_for_primitive!(i32);
+serialize_for_primitive!(i64);
+serialize_for_primitive!(f32);
+serialize_for_primitive!(f64);
+
+impl Serialize for bool {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl<T: Serialize> Serialize for [T] {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        write_varint(self.len(), out);
+        for item in self {
+            item.serialize(out);
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.as_slice().serialize(out);
+    }
+}
+
+/// Optional values are written as a `bool` flag, followed by the value if it is present.
+impl<T: Serialize> Serialize for Option<T> {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.is_some().serialize(out);
+        if let Some(value) = self {
+            value.serialize(out);
+        }
+    }
+}
+
+impl Serialize for str {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.as_bytes().serialize(out);
+    }
+}
+
+impl Serialize for String {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        self.as_str().serialize(out);
+    }
+}
+
+impl Serialize for StrId {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        write_varint(self._private_index(), out);
+    }
+}
+
+macro_rules! serialize_for_tuple {
+    ($($t:ident),+) => {
+        impl<$($t: Serialize),+> Serialize for ($($t),+,) {
+            fn serialize(&self, out: &mut Vec<u8>) {
+                let ($($t),+,) = self;
+                $(
+                    $t.serialize(out);
+                )+
+            }
+        }
+    };
+}
+
+serialize_for_tuple!(T1);
+serialize_for_tuple!(T1, T2);
+serialize_for_tuple!(T1, T2, T3);
+serialize_for_tuple!(T1, T2, T3, T4);
+serialize_for_tuple!(T1, T2, T3, T4, T5);
+serialize_for_tuple!(T1, T2, T3, T4, T5, T6);
+serialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+serialize_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+#[cfg(test)]
+mod tests {
+    use skylite_compress::make_decoder;
+
+    use crate::decode::{read_varint, Deserialize, MAX_VARINT_LEN};
+
+    use super::{write_varint, Serialize};
+
+    #[test]
+    fn test_write_varint() {
+        let mut out = Vec::new();
+        write_varint(0, &mut out);
+        write_varint(127, &mut out);
+        write_varint(128, &mut out);
+        write_varint(257, &mut out);
+        write_varint(0x12345, &mut out);
+        assert_eq!(out, [0, 127, 128, 1, 129, 2, 197, 198, 4]);
+
+        out.insert(0, 0);
+        let mut decoder = make_decoder(&out);
+        let decoded: Vec<usize> = (0..5).map(|_| read_varint(decoder.as_mut())).collect();
+        assert_eq!(decoded, [0, 127, 128, 257, 0x12345]);
+    }
+
+    #[test]
+    fn test_varint_u32_boundary() {
+        let mut out = vec![0];
+        write_varint(u32::MAX as usize, &mut out);
+        assert_eq!(out.len() - 1, MAX_VARINT_LEN);
+
+        // A varint never takes more than MAX_VARINT_LEN bytes, even if the continuation bit is set.
+        out.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 7]);
+        let mut decoder = make_decoder(&out);
+        assert_eq!(read_varint(decoder.as_mut()), u32::MAX as usize);
+        assert_eq!(read_varint(decoder.as_mut()), u32::MAX as usize);
+        assert_eq!(read_varint(decoder.as_mut()), 7);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    #[should_panic]
+    fn test_varint_overflow() {
+        write_varint(u32::MAX as usize + 1, &mut Vec::new());
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let value = (
+            5_u8,
+            -1234_i16,
+            0x12345678_u32,
+            -5_i64,
+            0.5_f32,
+            true,
+            "A Test! 🎵".to_owned(),
+            vec![(1_u16, false), (300, true)]
+        );
+
+        // Raw data (method tag 0).
+        let mut out = vec![0];
+        value.serialize(&mut out);
+        let mut decoder = make_decoder(&out);
+        assert_eq!(<(u8, i16, u32, i64, f32, bool, String, Vec<(u16, bool)>)>::deserialize(decoder.as_mut()), value);
+    }
+
+    #[test]
+    fn test_optional_roundtrip() {
+        let value = (Some(0x1234_u16), None::<u8>, Some(vec![true]));
+
+        // Raw data (method tag 0).
+        let mut out = vec![0];
+        value.serialize(&mut out);
+        assert_eq!(out, [0, 1, 0x12, 0x34, 0, 1, 1, 1]);
+
+        let mut decoder = make_decoder(&out);
+        assert_eq!(<(Option<u16>, Option<u8>, Option<Vec<bool>>)>::deserialize(decoder.as_mut()), value);
+    }
+}
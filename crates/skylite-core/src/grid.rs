@@ -0,0 +1,155 @@
+/// Arranges cells of a fixed size on a grid, e.g. for menus, inventories or tile previews.
+///
+/// The `GridLayout` only computes positions, so the content of the cells is stored and drawn
+/// by the owning actor. Cells are numbered row by row, so the index of a cell can be used
+/// to look up its content in a `Vec`:
+///
+/// ```ignore
+/// let layout = GridLayout::new(8, 0, 4, 3, 20, 20);
+/// for (idx, x, y) in layout.visible_cells(camera_x, camera_y, 160, 160) {
+///     // Draw the item at `idx` at (`x`, `y`).
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GridLayout {
+    x: i32,
+    y: i32,
+    cols: u16,
+    rows: u16,
+    cell_w: u16,
+    cell_h: u16
+}
+
+impl GridLayout {
+
+    /// Creates a grid of `cols` by `rows` cells of `cell_w` by `cell_h` pixels, with the top-left corner at (`x`, `y`).
+    pub const fn new(x: i32, y: i32, cols: u16, rows: u16, cell_w: u16, cell_h: u16) -> GridLayout {
+        GridLayout { x, y, cols, rows, cell_w, cell_h }
+    }
+
+    pub fn cols(&self) -> u16 {
+        self.cols
+    }
+
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    /// Returns the number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.cols as usize * self.rows as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the index of the cell in column `col` and row `row`, or `None` if the cell is outside the grid.
+    pub fn index(&self, col: u16, row: u16) -> Option<usize> {
+        (col < self.cols && row < self.rows).then(|| row as usize * self.cols as usize + col as usize)
+    }
+
+    /// Returns the column and row of the cell with index `idx`, or `None` if the index is out of range.
+    pub fn cell(&self, idx: usize) -> Option<(u16, u16)> {
+        (idx < self.len()).then(|| ((idx % self.cols as usize) as u16, (idx / self.cols as usize) as u16))
+    }
+
+    /// Returns the position of the top-left corner of the cell in column `col` and row `row`.
+    pub fn cell_position(&self, col: u16, row: u16) -> (i32, i32) {
+        (self.x + col as i32 * self.cell_w as i32, self.y + row as i32 * self.cell_h as i32)
+    }
+
+    /// Returns the column and row of the cell containing the position (`x`, `y`),
+    /// or `None` if the position is outside the grid.
+    pub fn cell_at(&self, x: i32, y: i32) -> Option<(u16, u16)> {
+        if self.cell_w == 0 || self.cell_h == 0 {
+            return None;
+        }
+        let col = (x - self.x).div_euclid(self.cell_w as i32);
+        let row = (y - self.y).div_euclid(self.cell_h as i32);
+        if col < 0 || row < 0 || col >= self.cols as i32 || row >= self.rows as i32 {
+            return None;
+        }
+        Some((col as u16, row as u16))
+    }
+
+    /// Iterates over the cells of row `row`, as the index and the position of each cell.
+    pub fn row(&self, row: u16) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        (0..if row < self.rows { self.cols } else { 0 })
+            .map(move |col| self.entry(col, row))
+    }
+
+    /// Iterates over the cells of column `col`, as the index and the position of each cell.
+    pub fn column(&self, col: u16) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        (0..if col < self.cols { self.rows } else { 0 })
+            .map(move |row| self.entry(col, row))
+    }
+
+    /// Iterates over all cells, row by row, as the index and the position of each cell.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        self.cells_in(0, self.cols, 0, self.rows)
+    }
+
+    /// Iterates over the cells that overlap the rectangle at (`x`, `y`) with size `w` by `h`,
+    /// e.g. the visible area of the screen. The cells are returned in the same form and order as
+    /// with [`GridLayout::cells`], but cells outside the rectangle are skipped without visiting them.
+    pub fn visible_cells(&self, x: i32, y: i32, w: u16, h: u16) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        if self.cell_w == 0 || self.cell_h == 0 || w == 0 || h == 0 {
+            return self.cells_in(0, 0, 0, 0);
+        }
+        let span = |start: i32, len: u16, origin: i32, size: u16, count: u16| {
+            let first = (start - origin).div_euclid(size as i32).clamp(0, count as i32);
+            let last = (start + len as i32 - 1 - origin).div_euclid(size as i32).clamp(-1, count as i32 - 1);
+            (first as u16, (last + 1).max(first) as u16)
+        };
+        let (col_start, col_end) = span(x, w, self.x, self.cell_w, self.cols);
+        let (row_start, row_end) = span(y, h, self.y, self.cell_h, self.rows);
+        self.cells_in(col_start, col_end, row_start, row_end)
+    }
+
+    fn entry(&self, col: u16, row: u16) -> (usize, i32, i32) {
+        let (x, y) = self.cell_position(col, row);
+        (row as usize * self.cols as usize + col as usize, x, y)
+    }
+
+    fn cells_in(&self, col_start: u16, col_end: u16, row_start: u16, row_end: u16) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        (row_start..row_end)
+            .flat_map(move |row| (col_start..col_end).map(move |col| self.entry(col, row)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GridLayout;
+
+    #[test]
+    fn test_grid_layout() {
+        let layout = GridLayout::new(10, 20, 3, 2, 16, 8);
+        assert_eq!(layout.len(), 6);
+        assert_eq!(layout.index(2, 1), Some(5));
+        assert_eq!(layout.index(3, 0), None);
+        assert_eq!(layout.cell(4), Some((1, 1)));
+        assert_eq!(layout.cell(6), None);
+        assert_eq!(layout.cell_position(1, 1), (26, 28));
+        assert_eq!(layout.cell_at(26, 35), Some((1, 1)));
+        assert_eq!(layout.cell_at(9, 20), None);
+        assert_eq!(layout.cell_at(58, 20), None);
+
+        assert_eq!(layout.row(1).collect::<Vec<_>>(), [(3, 10, 28), (4, 26, 28), (5, 42, 28)]);
+        assert_eq!(layout.column(2).collect::<Vec<_>>(), [(2, 42, 20), (5, 42, 28)]);
+        assert_eq!(layout.row(2).count(), 0);
+        assert_eq!(layout.cells().map(|(idx, _, _)| idx).collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_visible_cells() {
+        let layout = GridLayout::new(0, 0, 10, 10, 16, 16);
+        let visible = |x, y, w, h| layout.visible_cells(x, y, w, h).map(|(idx, _, _)| idx).collect::<Vec<_>>();
+        assert_eq!(visible(20, 0, 16, 16), [1, 2]);
+        assert_eq!(visible(16, 16, 16, 16), [11]);
+        assert_eq!(visible(-100, -100, 110, 110), [0]);
+        assert_eq!(visible(160, 0, 16, 16), Vec::<usize>::new());
+        assert_eq!(visible(-20, 0, 16, 16), Vec::<usize>::new());
+        assert_eq!(visible(150, 150, 100, 100), [99]);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{DrawContext, SkyliteProject};
+
+/// Splits `value` into `count` decimal digits, most significant first.
+///
+/// Values with more digits than `count` are clamped to the largest value that can be displayed.
+fn decimal_digits(value: u32, count: u8) -> impl Iterator<Item = u8> {
+    let max = 10_u64.saturating_pow(count as u32) - 1;
+    let value = (value as u64).min(max);
+    (0..count as u32).rev().map(move |i| ((value / 10_u64.saturating_pow(i)) % 10) as u8)
+}
+
+/// Returns the number of pixels of a gauge with `width` pixels that are filled for `value` out of `max`.
+fn gauge_fill(value: u32, max: u32, width: u16) -> u16 {
+    if max == 0 {
+        return 0;
+    }
+    (value.min(max) as u64 * width as u64 / max as u64) as u16
+}
+
+/// A numeric counter for the HUD, e.g. for the score or the number of coins.
+///
+/// The digits are drawn from a graphics asset, which must contain the digits `0` to `9`
+/// next to each other in its top row, each `digit_w` by `digit_h` pixels in size.
+///
+/// The counter stores the displayed value, which the owning actor keeps in sync with a global
+/// or an observed property during its update:
+///
+/// ```ignore
+/// actor.properties.coins.set(controls.globals().coins as u32);
+/// ```
+pub struct Counter<P: SkyliteProject> {
+    graphic: P::Graphics,
+    digit_w: u16,
+    digit_h: u16,
+    digits: u8,
+    value: u32
+}
+
+impl<P: SkyliteProject> Counter<P> {
+
+    /// Creates a new `Counter` which always displays `digits` digits, with leading zeros.
+    pub fn new(graphic: P::Graphics, digit_w: u16, digit_h: u16, digits: u8) -> Counter<P> {
+        Counter { graphic, digit_w, digit_h, digits, value: 0 }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Sets the displayed value. Returns `true` if the value has changed.
+    pub fn set(&mut self, value: u32) -> bool {
+        let changed = self.value != value;
+        self.value = value;
+        changed
+    }
+
+    /// Draws the counter with its top-left corner at (`x`, `y`) in the current layer.
+    pub fn render(&self, ctx: &mut DrawContext<P>, x: i32, y: i32) {
+        for (i, digit) in decimal_digits(self.value, self.digits).enumerate() {
+            let digit_x = x + i as i32 * self.digit_w as i32;
+            let src_x = digit as i16 * self.digit_w as i16;
+            ctx.draw_graphic(self.graphic, digit_x, y, src_x, 0, self.digit_w, self.digit_h, false, false, false);
+        }
+    }
+}
+
+/// A gauge for the HUD, e.g. a health bar.
+///
+/// The gauge is drawn from a graphics asset, which contains the empty gauge in its
+/// top row and the full gauge directly below, each `w` by `h` pixels in size. The full
+/// gauge is drawn over the empty gauge, up to the fraction given by the value.
+pub struct Gauge<P: SkyliteProject> {
+    graphic: P::Graphics,
+    w: u16,
+    h: u16,
+    value: u32,
+    max: u32
+}
+
+impl<P: SkyliteProject> Gauge<P> {
+
+    pub fn new(graphic: P::Graphics, w: u16, h: u16, max: u32) -> Gauge<P> {
+        Gauge { graphic, w, h, value: max, max }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Sets the displayed value, clamped to the maximum. Returns `true` if the value has changed.
+    pub fn set(&mut self, value: u32) -> bool {
+        let value = value.min(self.max);
+        let changed = self.value != value;
+        self.value = value;
+        changed
+    }
+
+    /// Sets the maximum value, and clamps the current value to it.
+    pub fn set_max(&mut self, max: u32) {
+        self.max = max;
+        self.value = self.value.min(max);
+    }
+
+    /// Draws the gauge with its top-left corner at (`x`, `y`) in the current layer.
+    pub fn render(&self, ctx: &mut DrawContext<P>, x: i32, y: i32) {
+        ctx.draw_graphic(self.graphic, x, y, 0, 0, self.w, self.h, false, false, false);
+        let fill = gauge_fill(self.value, self.max, self.w);
+        if fill > 0 {
+            ctx.draw_graphic(self.graphic, x, y, 0, self.h as i16, fill, self.h, false, false, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decimal_digits, gauge_fill};
+
+    #[test]
+    fn test_decimal_digits() {
+        assert_eq!(decimal_digits(42, 4).collect::<Vec<u8>>(), [0, 0, 4, 2]);
+        assert_eq!(decimal_digits(12345, 3).collect::<Vec<u8>>(), [9, 9, 9]);
+        assert_eq!(decimal_digits(0, 1).collect::<Vec<u8>>(), [0]);
+    }
+
+    #[test]
+    fn test_gauge_fill() {
+        assert_eq!(gauge_fill(5, 10, 32), 16);
+        assert_eq!(gauge_fill(20, 10, 32), 32);
+        assert_eq!(gauge_fill(1, 3, 32), 10);
+        assert_eq!(gauge_fill(5, 0, 32), 0);
+    }
+}
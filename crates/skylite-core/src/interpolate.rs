@@ -0,0 +1,130 @@
+/// Types which can be interpolated linearly.
+pub trait Lerp: Copy {
+
+    /// Returns the value at `alpha` between `from` (`alpha = 0.0`) and `to` (`alpha = 1.0`).
+    fn lerp(from: Self, to: Self, alpha: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(from: f32, to: f32, alpha: f32) -> f32 {
+        from + (to - from) * alpha
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(from: f64, to: f64, alpha: f32) -> f64 {
+        from + (to - from) * alpha as f64
+    }
+}
+
+macro_rules! lerp_for_integer {
+    ($typename:ident) => {
+        impl Lerp for $typename {
+            fn lerp(from: $typename, to: $typename, alpha: f32) -> $typename {
+                (from as f64 + (to as f64 - from as f64) * alpha as f64).round() as $typename
+            }
+        }
+    };
+}
+
+lerp_for_integer!(u8);
+lerp_for_integer!(u16);
+lerp_for_integer!(u32);
+lerp_for_integer!(i8);
+lerp_for_integer!(i16);
+lerp_for_integer!(i32);
+
+impl<A: Lerp, B: Lerp> Lerp for (A, B) {
+    fn lerp(from: (A, B), to: (A, B), alpha: f32) -> (A, B) {
+        (A::lerp(from.0, to.0, alpha), B::lerp(from.1, to.1, alpha))
+    }
+}
+
+/// A property which remembers its value from before the current update.
+///
+/// When the main loop renders more often than it updates, drawing actors at the
+/// positions from the last update makes movement look choppy. Instead, `render`
+/// can draw them between the previous and the current position, with
+/// [`Interpolated::lerp_value`] and the alpha from [`DrawContext::alpha`](crate::DrawContext::alpha).
+///
+/// Actor properties of this type must be marked with `#[skylite_proc::interpolated]`,
+/// which stores the previous value at the start of every update.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Interpolated<T: Lerp> {
+    previous: T,
+    current: T
+}
+
+impl<T: Lerp> Interpolated<T> {
+
+    pub fn new(value: T) -> Interpolated<T> {
+        Interpolated { previous: value, current: value }
+    }
+
+    /// Returns the current value.
+    pub fn get(&self) -> T {
+        self.current
+    }
+
+    /// Sets the current value. The previous value is not changed.
+    pub fn set(&mut self, value: T) {
+        self.current = value;
+    }
+
+    /// Sets both the previous and the current value, so that the change is not
+    /// interpolated, e.g. when an actor is teleported.
+    pub fn reset(&mut self, value: T) {
+        self.previous = value;
+        self.current = value;
+    }
+
+    /// Returns the value from the start of the current update.
+    pub fn previous(&self) -> T {
+        self.previous
+    }
+
+    /// Returns the value at `alpha` between the previous (`0.0`) and the current value (`1.0`).
+    pub fn lerp_value(&self, alpha: f32) -> T {
+        T::lerp(self.previous, self.current, alpha)
+    }
+
+    #[doc(hidden)]
+    pub fn _private_store(&mut self) {
+        self.previous = self.current;
+    }
+}
+
+impl<T: Lerp> From<T> for Interpolated<T> {
+    fn from(value: T) -> Interpolated<T> {
+        Interpolated::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interpolated, Lerp};
+
+    #[test]
+    fn test_lerp() {
+        assert_eq!(f32::lerp(2.0, 4.0, 0.25), 2.5);
+        assert_eq!(i16::lerp(-10, 10, 0.5), 0);
+        assert_eq!(u8::lerp(10, 0, 0.26), 7);
+        assert_eq!(<(i32, i32)>::lerp((0, 100), (10, 0), 0.5), (5, 50));
+    }
+
+    #[test]
+    fn test_interpolated() {
+        let mut x = Interpolated::new(10_i32);
+        x.set(20);
+        assert_eq!(x.lerp_value(0.5), 15);
+        assert_eq!(x.lerp_value(1.0), 20);
+
+        x._private_store();
+        assert_eq!(x.lerp_value(0.0), 20);
+        x.set(30);
+        assert_eq!(x.lerp_value(0.5), 25);
+
+        x.reset(0);
+        assert_eq!(x.lerp_value(0.5), 0);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{decode::{read_varint, Decoder, Deserialize}, encode::{write_varint, Serialize}};
+
+/// An enum which identifies the assets of one kind, such as the enums generated for custom asset kinds.
+///
+/// The ids are numbered from `0` to `COUNT - 1`, in the order in which the assets are listed.
+pub trait AssetId: Copy + Eq {
+    /// The number of assets of this kind.
+    const COUNT: usize;
+
+    /// Returns the number of this asset.
+    fn index(self) -> usize;
+
+    /// Returns the asset with the number `index`, or `None` if there is no such asset.
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+/// A collection of items, identified by asset ids, with a count for each item.
+///
+/// The inventory holds at most `CAPACITY` different items, and keeps them in the order in
+/// which they were first added. The count of each item is at most `u16::MAX`; adding more
+/// items of the same kind is not an error, the count just stays at the maximum.
+///
+/// An inventory is serialized as the number of items, followed by the index and count of each item.
+/// It can therefore be stored in the save data directly, and keeps working when item assets are
+/// appended to the project. Items with an unknown index are dropped when deserializing.
+///
+/// ```ignore
+/// let mut bag: Inventory<ItemAssets, 16> = Inventory::new();
+/// bag.add(ItemAssets::Potion, 3);
+/// if bag.remove(ItemAssets::Potion, 1) {
+///     // heal
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Inventory<I: AssetId, const CAPACITY: usize> {
+    items: Vec<(I, u16)>
+}
+
+impl<I: AssetId, const CAPACITY: usize> Inventory<I, CAPACITY> {
+
+    /// Creates an empty `Inventory`.
+    pub fn new() -> Inventory<I, CAPACITY> {
+        Inventory { items: Vec::new() }
+    }
+
+    fn find(&self, item: I) -> Option<usize> {
+        self.items.iter().position(|(i, _)| *i == item)
+    }
+
+    /// Returns how many of `item` are in the inventory.
+    pub fn count(&self, item: I) -> u16 {
+        self.find(item).map(|i| self.items[i].1).unwrap_or(0)
+    }
+
+    pub fn contains(&self, item: I) -> bool {
+        self.find(item).is_some()
+    }
+
+    /// Adds `amount` of `item` to the inventory.
+    ///
+    /// Returns `false` without changing the inventory if `item` is not yet in the
+    /// inventory and the inventory is full.
+    pub fn add(&mut self, item: I, amount: u16) -> bool {
+        if amount == 0 {
+            return true;
+        }
+        match self.find(item) {
+            Some(i) => {
+                self.items[i].1 = self.items[i].1.saturating_add(amount);
+                true
+            },
+            None if self.is_full() => false,
+            None => {
+                self.items.push((item, amount));
+                true
+            }
+        }
+    }
+
+    /// Removes `amount` of `item` from the inventory. The item is removed completely
+    /// when its count drops to zero.
+    ///
+    /// Returns `false` without changing the inventory if there are less than `amount` of `item`.
+    pub fn remove(&mut self, item: I, amount: u16) -> bool {
+        let Some(i) = self.find(item) else { return amount == 0 };
+        match self.items[i].1.checked_sub(amount) {
+            Some(0) => {
+                self.items.remove(i);
+                true
+            },
+            Some(count) => {
+                self.items[i].1 = count;
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Removes all items from the inventory.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Returns the number of different items in the inventory.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= CAPACITY
+    }
+
+    /// Iterates over the items and their counts, in the order in which they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (I, u16)> + '_ {
+        self.items.iter().copied()
+    }
+}
+
+impl<I: AssetId, const CAPACITY: usize> Default for Inventory<I, CAPACITY> {
+    fn default() -> Self {
+        Inventory::new()
+    }
+}
+
+impl<I: AssetId, const CAPACITY: usize> Serialize for Inventory<I, CAPACITY> {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        write_varint(self.items.len(), out);
+        for (item, count) in &self.items {
+            write_varint(item.index(), out);
+            count.serialize(out);
+        }
+    }
+}
+
+impl<I: AssetId, const CAPACITY: usize> Deserialize for Inventory<I, CAPACITY> {
+    fn deserialize(decoder: &mut dyn Decoder) -> Inventory<I, CAPACITY> {
+        let len = read_varint(decoder);
+        let mut out = Inventory::new();
+        for _ in 0..len {
+            let index = read_varint(decoder);
+            let count = u16::deserialize(decoder);
+            if let Some(item) = I::from_index(index) {
+                out.add(item, count);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skylite_compress::make_decoder;
+
+    use crate::{decode::Deserialize, encode::Serialize};
+
+    use super::{AssetId, Inventory};
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Items {
+        Potion,
+        Key,
+        Gem
+    }
+
+    impl AssetId for Items {
+        const COUNT: usize = 3;
+
+        fn index(self) -> usize {
+            self as usize
+        }
+
+        fn from_index(index: usize) -> Option<Items> {
+            [Items::Potion, Items::Key, Items::Gem].get(index).copied()
+        }
+    }
+
+    #[test]
+    fn test_inventory() {
+        let mut inventory: Inventory<Items, 2> = Inventory::new();
+        assert!(inventory.add(Items::Potion, 3));
+        assert!(inventory.add(Items::Key, 1));
+        assert!(inventory.is_full());
+        assert!(!inventory.add(Items::Gem, 1));
+        assert!(inventory.add(Items::Potion, u16::MAX));
+        assert_eq!(inventory.count(Items::Potion), u16::MAX);
+
+        assert!(!inventory.remove(Items::Key, 2));
+        assert!(inventory.remove(Items::Key, 1));
+        assert!(!inventory.contains(Items::Key));
+        assert_eq!(inventory.count(Items::Key), 0);
+        assert!(inventory.add(Items::Gem, 1));
+        assert_eq!(inventory.iter().collect::<Vec<_>>(), [(Items::Potion, u16::MAX), (Items::Gem, 1)]);
+    }
+
+    #[test]
+    fn test_inventory_roundtrip() {
+        let mut inventory: Inventory<Items, 4> = Inventory::new();
+        inventory.add(Items::Gem, 2);
+        inventory.add(Items::Potion, 300);
+
+        // Raw data (method tag 0).
+        let mut out = vec![0];
+        inventory.serialize(&mut out);
+        assert_eq!(out, [0, 2, 2, 0, 2, 0, 1, 44]);
+
+        let mut decoder = make_decoder(&out);
+        assert_eq!(Inventory::<Items, 4>::deserialize(decoder.as_mut()), inventory);
+
+        // Items with an unknown index are dropped.
+        let mut decoder = make_decoder(&[0, 2, 9, 0, 1, 1, 0, 5]);
+        let inventory = Inventory::<Items, 4>::deserialize(decoder.as_mut());
+        assert_eq!(inventory.iter().collect::<Vec<_>>(), [(Items::Key, 5)]);
+    }
+}
@@ -1,10 +1,34 @@
-use actors::AnyActor;
+use actors::{Actor, AnyActor, TypeId};
 use scenes::Scene;
+use strings::StrId;
 
 pub mod decode;
+pub mod encode;
 pub mod scenes;
+pub mod schedule;
+pub mod shake;
+pub mod spatial;
+pub mod stats;
+pub mod storage;
 pub mod actors;
+mod clip;
+#[cfg(feature = "debug-server")]
+pub mod debug_server;
 pub mod ecs;
+pub mod grid;
+pub mod hud;
+pub mod interpolate;
+pub mod inventory;
+pub mod map;
+pub mod menu;
+#[cfg(feature = "debug-reflection")]
+pub mod reflect;
+pub mod pacing;
+pub mod save;
+pub mod strings;
+pub mod trigger;
+pub mod viewport;
+pub mod prelude;
 
 /// Defines which functions a backend must provide to work with Skylite.
 pub trait SkyliteTarget {
@@ -31,10 +55,52 @@ pub trait SkyliteTarget {
     fn get_screen_size(&self) -> (u16, u16);
 
     /// Writes the given data at the given offset into persistent storage.
+    ///
+    /// Through these methods, every target is also a [`StorageBackend`](storage::StorageBackend),
+    /// which projects use unless a different backend is set.
     fn write_storage(&mut self, offset: usize, data: &[u8]);
 
     /// Reads some amount of data from persistent storage, starting at the given offset.
     fn read_storage(&self, offset: usize, len: usize) -> Vec<u8>;
+
+    /// Returns the target as `Any`, so that render code can access target-specific
+    /// functionality through [`DrawContext::target_ext`].
+    ///
+    /// The default implementation returns `None`, meaning that the target does not
+    /// provide any extensions.
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        None
+    }
+
+    /// Fills a rectangle on the screen with a solid color.
+    ///
+    /// The position `x`, `y` and size `w`, `h` are in screen coordinates, and the rectangle
+    /// is always completely on the screen. Like the data for `draw_sub`, the format of `color`
+    /// is up to the target. Returns `false` if the target does not support drawing shapes, in which
+    /// case [`DrawContext`] falls back to drawing the shape pixel by pixel, see [`DrawContext::set_shape_fallback`].
+    ///
+    /// The default implementation returns `false`.
+    fn fill_rect(&mut self, _x: i16, _y: i16, _w: u16, _h: u16, _color: u32) -> bool {
+        false
+    }
+
+    /// Returns `true` if the target implements [`SkyliteTarget::draw_sub_transformed`].
+    ///
+    /// The default implementation returns `false`.
+    fn supports_transform(&self) -> bool {
+        false
+    }
+
+    /// Draws a region from a texture atlas, rotated by an arbitrary angle and scaled.
+    ///
+    /// Unlike `draw_sub`, the position `x`, `y` is the center of the drawn region on the screen.
+    /// The region is flipped first, then scaled by `scale_x` and `scale_y`, and then rotated
+    /// clockwise by `angle` radians around its center. The remaining parameters are the same as for `draw_sub`.
+    ///
+    /// This is only called if [`SkyliteTarget::supports_transform`] returns `true`.
+    /// The default implementation does nothing.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_sub_transformed(&mut self, _data: &[u8], _x: i16, _y: i16, _src_x: i16, _src_y: i16, _src_w: u16, _src_h: u16, _flip_h: bool, _flip_v: bool, _angle: f32, _scale_x: f32, _scale_y: f32) {}
 }
 
 /// The main type for skylite projects.
@@ -43,9 +109,83 @@ pub trait SkyliteProject {
     type TileType: Copy;
     type Actors: AnyActor<P = Self>;
 
+    /// The global variables defined in the `globals` section of the project definition.
+    type Globals;
+
+    /// The ids of the graphics assets of the project.
+    type Graphics: Copy;
+
     fn new(target: Self::Target) -> Self;
-    fn render(&mut self);
+
+    /// Creates a new project instance which starts with `scene` instead of the
+    /// initial scene from the project file.
+    ///
+    /// This is mostly useful for tests and examples that need to start in a
+    /// specific scene.
+    fn new_with_scene(target: Self::Target, scene: Box<dyn Scene<P=Self>>) -> Self where Self: Sized;
+
+    /// Renders the current state of the project.
+    ///
+    /// This is the same as `render_interpolated(1.0)`.
+    fn render(&mut self) {
+        self.render_interpolated(1.0);
+    }
+
+    /// Renders the project at `alpha` between the previous and the current update.
+    ///
+    /// The `alpha` is available to the render code through [`DrawContext::alpha`], and is
+    /// usually taken from a [`FramePacer`](crate::pacing::FramePacer).
+    fn render_interpolated(&mut self, alpha: f32);
+
     fn update(&mut self);
+
+    /// Returns the content of a string from the project's string table.
+    fn get_str(id: StrId) -> &'static str;
+
+    /// Returns the data of a graphics asset, as it is passed to [`SkyliteTarget::draw_sub`].
+    fn get_graphics(id: Self::Graphics) -> &'static [u8];
+
+    /// The order in which the update hooks of the actors are called, see [`UpdateMode`].
+    const UPDATE_MODE: UpdateMode = UpdateMode::Nested;
+}
+
+/// The phases of an actor update.
+///
+/// `Pre` calls the actor's `pre_update` hook, `Main` runs the current action,
+/// and `Post` calls the `post_update` hook.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpdatePhase {
+    Pre,
+    Main,
+    Post
+}
+
+/// Determines how the phases of the actor updates are ordered within a scene update.
+///
+/// With `Nested`, each actor runs all of its phases before the next actor is updated.
+/// With `Phased`, the `pre_update` hooks of all actors run first, then the actions of
+/// all actors, and then the `post_update` hooks of all actors. This way, every `pre_update`
+/// sees the state from the end of the previous update, and every `post_update` sees the
+/// state after all actions have run. Extras added during one of the passes are only
+/// updated from the next pass on.
+///
+/// The mode is set with `(update-mode . phased)` in the project definition.
+/// The default is `Nested`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpdateMode {
+    Nested,
+    Phased
+}
+
+impl UpdateMode {
+
+    /// Returns the passes over the actors of a scene, with the phases run in each pass.
+    pub fn passes(self) -> &'static [&'static [UpdatePhase]] {
+        match self {
+            UpdateMode::Nested => &[&[UpdatePhase::Pre, UpdatePhase::Main, UpdatePhase::Post]],
+            UpdateMode::Phased => &[&[UpdatePhase::Pre], &[UpdatePhase::Main], &[UpdatePhase::Post]]
+        }
+    }
 }
 
 /// Holds the rendering state.
@@ -57,7 +197,295 @@ pub struct DrawContext<'project, P: SkyliteProject> {
     #[doc(hidden)] pub target: &'project mut P::Target,
     #[doc(hidden)] pub graphics_cache: &'project mut Vec<std::rc::Weak<u8>>,
     #[doc(hidden)] pub focus_x: i32,
-    #[doc(hidden)] pub focus_y: i32
+    #[doc(hidden)] pub focus_y: i32,
+    #[doc(hidden)] pub layer: RenderLayer,
+    #[doc(hidden)] pub parallax: (f32, f32),
+    #[doc(hidden)] pub alpha: f32,
+    #[doc(hidden)] pub offset: (i32, i32),
+    #[doc(hidden)] pub offset_stack: Vec<(i32, i32)>,
+    #[doc(hidden)] pub clip: Option<(i32, i32, i32, i32)>,
+    #[doc(hidden)] pub clip_stack: Vec<Option<(i32, i32, i32, i32)>>,
+    #[doc(hidden)] pub shape_fallback: Option<(&'static [u8], i16, i16)>,
+    #[doc(hidden)] pub stats: stats::DrawStats
+}
+
+/// The layers in which actors are rendered.
+///
+/// Layers are rendered in the order `Background`, `Main`, `Ui`, so that all
+/// actors in a later layer are drawn on top of all actors in an earlier layer,
+/// regardless of their z-order. The `Ui` layer does not follow the focus, so
+/// actors in this layer are drawn at fixed screen positions.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum RenderLayer {
+    Background,
+    Main,
+    Ui
+}
+
+impl RenderLayer {
+
+    /// Returns `true` if positions in this layer are relative to the focus.
+    pub fn follows_focus(self) -> bool {
+        self != RenderLayer::Ui
+    }
+}
+
+impl<'project, P: SkyliteProject> DrawContext<'project, P> {
+
+    /// Returns the layer which is currently being rendered.
+    pub fn layer(&self) -> RenderLayer {
+        self.layer
+    }
+
+    /// Returns the fraction of time between the previous and the current update at which the
+    /// project is rendered, between `0.0` and `1.0`.
+    ///
+    /// This is passed to [`Interpolated::lerp_value`](crate::interpolate::Interpolated::lerp_value)
+    /// to draw actors between their previous and current positions.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Returns the statistics of the draw calls made so far in the current frame.
+    ///
+    /// The statistics are only counted with the `debug-draw-stats` feature. A function marked
+    /// with `#[skylite_proc::post_render]` sees the statistics of the complete frame.
+    pub fn stats(&self) -> &stats::DrawStats {
+        &self.stats
+    }
+
+    /// Counts `draw_calls` calls to the target, which together drew the part of `rect` that is on the screen.
+    #[cfg_attr(not(feature = "debug-draw-stats"), allow(unused_variables))]
+    fn record_draws(&mut self, draw_calls: u32, rect: clip::ClipRect) {
+        #[cfg(feature = "debug-draw-stats")]
+        {
+            let pixels = match clip::visible_rect(rect, None, self.target.get_screen_size()) {
+                Some((left, top, right, bottom)) => (right - left) as u64 * (bottom - top) as u64,
+                None => 0
+            };
+            self.stats.record(self.layer, draw_calls, pixels);
+        }
+    }
+
+    /// Returns the position which is drawn at the center of the screen for the actor
+    /// that is currently being rendered.
+    ///
+    /// This is the focus scaled by the actor's parallax factors, see [`DrawContext::get_focus_scaled`].
+    pub fn get_focus(&self) -> (i32, i32) {
+        self.get_focus_scaled(self.parallax.0, self.parallax.1)
+    }
+
+    /// Returns the focus scaled by the factors `fx` and `fy`, rounded to the nearest integer.
+    ///
+    /// Factors below `1.0` make the content scroll slower than the focus, which is used
+    /// for parallax scrolling of backgrounds. For layers that do not follow the focus,
+    /// this is always the center of the screen, so positions in these layers are screen coordinates.
+    pub fn get_focus_scaled(&self, fx: f32, fy: f32) -> (i32, i32) {
+        if self.layer.follows_focus() {
+            ((self.focus_x as f32 * fx).round() as i32, (self.focus_y as f32 * fy).round() as i32)
+        } else {
+            let (w, h) = self.target.get_screen_size();
+            (w as i32 / 2, h as i32 / 2)
+        }
+    }
+
+    /// Converts a position in the current layer to screen coordinates.
+    ///
+    /// The result includes the current render offset, see [`DrawContext::push_offset`].
+    pub fn to_screen(&self, x: i32, y: i32) -> (i32, i32) {
+        let (w, h) = self.target.get_screen_size();
+        let (focus_x, focus_y) = self.get_focus();
+        (x - focus_x + w as i32 / 2 + self.offset.0, y - focus_y + h as i32 / 2 + self.offset.1)
+    }
+
+    /// Returns the offset that is currently added to all draw calls, in pixels.
+    ///
+    /// At the start of rendering, this is the offset of the current screen shake,
+    /// see [`ProjectControls::shake`].
+    pub fn offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    /// Moves all subsequent draw calls by `dx` and `dy` pixels, until the matching
+    /// [`DrawContext::pop_offset`]. Offsets are added to the offsets pushed before.
+    pub fn push_offset(&mut self, dx: i32, dy: i32) {
+        self.offset_stack.push(self.offset);
+        self.offset = (self.offset.0 + dx, self.offset.1 + dy);
+    }
+
+    /// Restores the offset from before the last call to [`DrawContext::push_offset`].
+    ///
+    /// Panics if there is no offset to pop.
+    pub fn pop_offset(&mut self) {
+        self.offset = self.offset_stack.pop().expect("pop_offset called without matching push_offset");
+    }
+
+    /// Restricts all subsequent draw calls to a rectangle, until the matching [`DrawContext::pop_clip`].
+    ///
+    /// The rectangle is given by its top-left corner `x`, `y` in the current layer, like the positions
+    /// for [`DrawContext::draw_sub`], and its size `w`, `h`. It is converted to screen coordinates when
+    /// it is pushed, so later changes to the layer or offset do not move it. If there is already a
+    /// clipping rectangle, the new one is intersected with it.
+    pub fn push_clip(&mut self, x: i32, y: i32, w: u16, h: u16) {
+        let (left, top) = self.to_screen(x, y);
+        let rect = (left, top, left + w as i32, top + h as i32);
+        self.clip_stack.push(self.clip);
+        self.clip = Some(match self.clip {
+            Some(current) => clip::intersect(current, rect),
+            None => rect
+        });
+    }
+
+    /// Restores the clipping rectangle from before the last call to [`DrawContext::push_clip`].
+    ///
+    /// Panics if there is no clipping rectangle to pop.
+    pub fn pop_clip(&mut self) {
+        self.clip = self.clip_stack.pop().expect("pop_clip called without matching push_clip");
+    }
+
+    /// Sets a 1x1 region of a graphics asset, which is used to draw shapes on targets
+    /// that do not implement [`SkyliteTarget::fill_rect`].
+    ///
+    /// The region should be a single white pixel. Shapes drawn with the fallback are
+    /// drawn with one `draw_sub` call per pixel and ignore their color. Without a fallback,
+    /// shapes are not drawn on such targets. The fallback is reset on every render,
+    /// so it is usually set in a `pre_render` function.
+    pub fn set_shape_fallback(&mut self, graphic: P::Graphics, src_x: i16, src_y: i16) {
+        self.shape_fallback = Some((P::get_graphics(graphic), src_x, src_y));
+    }
+
+    /// Fills a rectangle at a position in the current layer with `color`.
+    ///
+    /// See [`SkyliteTarget::fill_rect`] for the format of `color`.
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u16, h: u16, color: u32) {
+        let (left, top) = self.to_screen(x, y);
+        let rect = (left, top, left + w as i32, top + h as i32);
+        let Some((left, top, right, bottom)) = clip::visible_rect(rect, self.clip, self.target.get_screen_size()) else {
+            return;
+        };
+        if self.target.fill_rect(left as i16, top as i16, (right - left) as u16, (bottom - top) as u16, color) {
+            self.record_draws(1, (left, top, right, bottom));
+            return;
+        }
+        if let Some((data, src_x, src_y)) = self.shape_fallback {
+            for py in top..bottom {
+                for px in left..right {
+                    self.target.draw_sub(data, px as i16, py as i16, src_x, src_y, 1, 1, false, false, false);
+                }
+            }
+            self.record_draws(((right - left) * (bottom - top)) as u32, (left, top, right, bottom));
+        }
+    }
+
+    /// Draws the outline of a rectangle at a position in the current layer, with a width of one pixel.
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: u16, h: u16, color: u32) {
+        if w <= 2 || h <= 2 {
+            self.fill_rect(x, y, w, h, color);
+            return;
+        }
+        self.hline(x, y, w, color);
+        self.hline(x, y + h as i32 - 1, w, color);
+        self.vline(x, y + 1, h - 2, color);
+        self.vline(x + w as i32 - 1, y + 1, h - 2, color);
+    }
+
+    /// Draws a horizontal line of `len` pixels, starting at a position in the current layer.
+    pub fn hline(&mut self, x: i32, y: i32, len: u16, color: u32) {
+        self.fill_rect(x, y, len, 1, color);
+    }
+
+    /// Draws a vertical line of `len` pixels, starting at a position in the current layer.
+    pub fn vline(&mut self, x: i32, y: i32, len: u16, color: u32) {
+        self.fill_rect(x, y, 1, len, color);
+    }
+
+    /// Draws a single pixel at a position in the current layer.
+    pub fn pixel(&mut self, x: i32, y: i32, color: u32) {
+        self.fill_rect(x, y, 1, 1, color);
+    }
+
+    /// Draws a region from a graphics asset of the project at a position in the current layer.
+    ///
+    /// This is the same as [`DrawContext::draw_sub`], but the texture atlas is given by its id.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_graphic(&mut self, graphic: P::Graphics, x: i32, y: i32, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
+        self.draw_sub(P::get_graphics(graphic), x, y, src_x, src_y, src_w, src_h, flip_h, flip_v, rotate);
+    }
+
+    /// Returns the target as type `T`, if the target provides extensions and is of type `T`.
+    ///
+    /// This allows render code to use drawing functions that are specific to a target,
+    /// e.g. direct framebuffer access, while still working on other targets:
+    ///
+    /// ```ignore
+    /// if let Some(target) = ctx.target_ext::<Wasm4Target>() {
+    ///     target.set_pixel(10, 10, 3);
+    /// }
+    /// ```
+    ///
+    /// Note that functions on the target always use screen coordinates, see [`DrawContext::to_screen`].
+    pub fn target_ext<T: 'static>(&mut self) -> Option<&mut T> {
+        self.target.as_any_mut()?.downcast_mut::<T>()
+    }
+
+    /// Returns `true` if the target can draw regions with arbitrary rotation and scaling,
+    /// see [`DrawContext::draw_sub_transformed`].
+    pub fn supports_transform(&self) -> bool {
+        self.target.supports_transform()
+    }
+
+    /// Draws a region from a texture atlas, rotated clockwise by `angle` radians and scaled by `scale_x` and `scale_y`.
+    ///
+    /// The position `x`, `y` in the current layer is the center of the drawn region.
+    /// If the target does not support transformations, or if a clipping rectangle is set,
+    /// the region is drawn unscaled with `draw_sub` instead, rotated by the multiple of
+    /// 90 degrees which is closest to `angle`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sub_transformed(&mut self, data: &[u8], x: i32, y: i32, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, angle: f32, scale_x: f32, scale_y: f32) {
+        if self.clip.is_none() && self.target.supports_transform() {
+            let (screen_x, screen_y) = self.to_screen(x, y);
+            if let (Ok(screen_x), Ok(screen_y)) = (i16::try_from(screen_x), i16::try_from(screen_y)) {
+                self.target.draw_sub_transformed(data, screen_x, screen_y, src_x, src_y, src_w, src_h, flip_h, flip_v, angle, scale_x, scale_y);
+                // The drawn pixels are estimated by the scaled region without rotation.
+                let (w, h) = ((src_w as f32 * scale_x.abs()) as i32, (src_h as f32 * scale_y.abs()) as i32);
+                self.record_draws(1, (screen_x as i32 - w / 2, screen_y as i32 - h / 2, screen_x as i32 + (w + 1) / 2, screen_y as i32 + (h + 1) / 2));
+            }
+            return;
+        }
+
+        // Rotating by 180 degrees is the same as flipping in both directions.
+        let quarter_turns = (angle / std::f32::consts::FRAC_PI_2).round().rem_euclid(4.0) as u8;
+        let rotate = quarter_turns % 2 == 1;
+        let half_turn = quarter_turns >= 2;
+        let (w, h) = if rotate { (src_h as i32, src_w as i32) } else { (src_w as i32, src_h as i32) };
+        self.draw_sub(data, x - w / 2, y - h / 2, src_x, src_y, src_w, src_h, flip_h != half_turn, flip_v != half_turn, rotate);
+    }
+
+    /// Draws a region from a texture atlas at a position in the current layer.
+    ///
+    /// See [`SkyliteTarget::draw_sub`] for the meaning of the parameters. Regions
+    /// that are too far outside of the screen to be represented by the target's
+    /// screen coordinates are skipped. If a clipping rectangle is set, only the part
+    /// of the region inside of it is drawn, see [`DrawContext::push_clip`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sub(&mut self, data: &[u8], x: i32, y: i32, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
+        let (mut screen_x, mut screen_y) = self.to_screen(x, y);
+        let (mut src_x, mut src_y, mut src_w, mut src_h) = (src_x, src_y, src_w, src_h);
+        if let Some(rect) = self.clip {
+            match clip::clip_draw(rect, screen_x, screen_y, (src_x, src_y, src_w, src_h), flip_h, flip_v, rotate) {
+                Some((x, y, src)) => {
+                    (screen_x, screen_y) = (x, y);
+                    (src_x, src_y, src_w, src_h) = src;
+                },
+                None => return
+            }
+        }
+        if let (Ok(screen_x), Ok(screen_y)) = (i16::try_from(screen_x), i16::try_from(screen_y)) {
+            self.target.draw_sub(data, screen_x, screen_y, src_x, src_y, src_w, src_h, flip_h, flip_v, rotate);
+            let (w, h) = if rotate { (src_h as i32, src_w as i32) } else { (src_w as i32, src_h as i32) };
+            self.record_draws(1, (screen_x as i32, screen_y as i32, screen_x as i32 + w, screen_y as i32 + h));
+        }
+    }
 }
 
 /// Type used to change various parts of a `SkyliteProject` instance.
@@ -65,5 +493,107 @@ pub struct DrawContext<'project, P: SkyliteProject> {
 /// This is the main type that scenes and actors have access to in their
 /// update/action methods.
 pub struct ProjectControls<P: SkyliteProject> {
-    #[doc(hidden)] pub pending_scene: Option<Box<dyn Scene<P=P>>>
+    #[doc(hidden)] pub pending_scene: Option<Box<dyn Scene<P=P>>>,
+    #[doc(hidden)] pub globals: P::Globals,
+    #[doc(hidden)] pub changes: Vec<PropertyChange>,
+    #[doc(hidden)] pub pending_changes: Vec<PropertyChange>,
+    #[doc(hidden)] pub events: schedule::EventQueue,
+    #[doc(hidden)] pub shake: shake::ScreenShake
+}
+
+/// Records that an observed property of an actor has changed.
+///
+/// Properties are observed by marking them with `#[skylite_proc::observed]`
+/// in `skylite_proc::properties!`. The generated setter for the property
+/// records a `PropertyChange` whenever the new value differs from the old one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PropertyChange {
+    actor_id: usize,
+    property: &'static str
+}
+
+impl PropertyChange {
+
+    /// Returns the name of the property that has changed.
+    pub fn property(&self) -> &'static str {
+        self.property
+    }
+
+    /// Returns `true` if the change concerns property `property` of an actor of type `A`.
+    pub fn is<A: Actor>(&self, property: &str) -> bool {
+        self.actor_id == <A as TypeId>::get_id() && self.property == property
+    }
+}
+
+impl<P: SkyliteProject> ProjectControls<P> {
+
+    /// Returns the project's global variables.
+    pub fn globals(&self) -> &P::Globals {
+        &self.globals
+    }
+
+    /// Returns the project's global variables for modification.
+    pub fn globals_mut(&mut self) -> &mut P::Globals {
+        &mut self.globals
+    }
+
+    /// Returns the changes to observed properties that were made during the previous update.
+    pub fn changes(&self) -> &[PropertyChange] {
+        &self.changes
+    }
+
+    /// Returns `true` if property `property` of any actor of type `A` changed during the previous update.
+    pub fn has_changed<A: Actor>(&self, property: &str) -> bool {
+        self.changes.iter().any(|c| c.is::<A>(property))
+    }
+
+    /// Schedules `event` to be delivered `delay_updates` updates after the current one.
+    ///
+    /// This replaces dedicated timer properties for one-off delayed effects. The event
+    /// is received through `events` or `has_event` during the update in which it is due.
+    pub fn schedule(&mut self, delay_updates: u32, event: &'static str) {
+        self.events.schedule(delay_updates, event);
+    }
+
+    /// Returns the scheduled events that are due during the current update.
+    pub fn events(&self) -> &[&'static str] {
+        self.events.due()
+    }
+
+    /// Returns `true` if the scheduled event `event` is due during the current update.
+    pub fn has_event(&self, event: &str) -> bool {
+        self.events.due().contains(&event)
+    }
+
+    /// Shakes the screen with a maximum offset of `intensity` pixels for `duration_updates` updates.
+    ///
+    /// The shake only affects rendering, the focus is not changed. A running shake is
+    /// only replaced if it is currently weaker than the new one.
+    pub fn shake(&mut self, intensity: u16, duration_updates: u32) {
+        self.shake.start(intensity, duration_updates);
+    }
+
+    /// Stops the current screen shake.
+    pub fn stop_shake(&mut self) {
+        self.shake.stop();
+    }
+
+    #[doc(hidden)]
+    pub fn _private_record_change<A: Actor>(&mut self, property: &'static str) {
+        let change = PropertyChange { actor_id: <A as TypeId>::get_id(), property };
+        if !self.pending_changes.contains(&change) {
+            self.pending_changes.push(change);
+        }
+    }
+
+    /// Makes the changes recorded during the current update available
+    /// through `changes`, delivers the scheduled events that are due and
+    /// advances the screen shake. Called at the start of every update.
+    #[doc(hidden)]
+    pub fn _private_next_update(&mut self) {
+        std::mem::swap(&mut self.changes, &mut self.pending_changes);
+        self.pending_changes.clear();
+        self.events.advance();
+        self.shake.advance();
+    }
 }
@@ -0,0 +1,146 @@
+use crate::{decode::{read_varint, Decoder, Deserialize}, encode::{write_varint, Serialize}};
+
+/// A map with keys in sorted order, used for variables of type `(map <key> <value>)`.
+///
+/// The entries are stored in a single `Vec`, and lookups use binary search. This is
+/// more compact than a `HashMap` for the small, mostly read-only maps defined in asset
+/// files, and keeps the iteration order deterministic.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SortedMap<K: Ord, V> {
+    entries: Vec<(K, V)>
+}
+
+impl<K: Ord, V> SortedMap<K, V> {
+
+    /// Creates an empty `SortedMap`.
+    pub fn new() -> SortedMap<K, V> {
+        SortedMap { entries: Vec::new() }
+    }
+
+    /// Creates a `SortedMap` from entries which are already sorted by their keys
+    /// and do not contain duplicate keys. This is used by generated code.
+    #[doc(hidden)]
+    pub fn _private_from_sorted(entries: Vec<(K, V)>) -> SortedMap<K, V> {
+        debug_assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+        SortedMap { entries }
+    }
+
+    fn find(&self, key: &K) -> Result<usize, usize> {
+        self.entries.binary_search_by(|(k, _)| k.cmp(key))
+    }
+
+    /// Returns the value for `key`, or `None` if the map does not contain `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).ok().map(|i| &self.entries[i].1)
+    }
+
+    /// Returns a mutable reference to the value for `key`, or `None` if the map does not contain `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.find(key).ok().map(|i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_ok()
+    }
+
+    /// Inserts a value into the map, and returns the previous value for `key`, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.find(&key) {
+            Ok(i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            Err(i) => {
+                self.entries.insert(i, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes `key` from the map and returns its value, if any.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.find(key).ok().map(|i| self.entries.remove(i).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries of the map, in the order of their keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K: Ord, V> Default for SortedMap<K, V> {
+    fn default() -> Self {
+        SortedMap::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SortedMap<K, V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut out = SortedMap::new();
+        for (k, v) in iter {
+            out.insert(k, v);
+        }
+        out
+    }
+}
+
+// A map is encoded in the same way as a `Vec` of key-value tuples, sorted by key.
+
+impl<K: Ord + Deserialize, V: Deserialize> Deserialize for SortedMap<K, V> {
+    fn deserialize(decoder: &mut dyn Decoder) -> SortedMap<K, V> {
+        let len = read_varint(decoder);
+        (0..len)
+            .map(|_| <(K, V) as Deserialize>::deserialize(decoder))
+            .collect()
+    }
+}
+
+impl<K: Ord + Serialize, V: Serialize> Serialize for SortedMap<K, V> {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        write_varint(self.entries.len(), out);
+        for (k, v) in &self.entries {
+            k.serialize(out);
+            v.serialize(out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skylite_compress::make_decoder;
+
+    use crate::{decode::Deserialize, encode::Serialize};
+
+    use super::SortedMap;
+
+    #[test]
+    fn test_sorted_map() {
+        let mut map: SortedMap<u8, &str> = [(5, "five"), (1, "one"), (3, "three")].into_iter().collect();
+        assert_eq!(map.iter().map(|(k, _)| *k).collect::<Vec<u8>>(), [1, 3, 5]);
+        assert_eq!(map.get(&3), Some(&"three"));
+        assert_eq!(map.get(&4), None);
+
+        assert_eq!(map.insert(3, "drei"), Some("three"));
+        assert_eq!(map.insert(4, "four"), None);
+        assert_eq!(map.remove(&1), Some("one"));
+        assert_eq!(map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<(u8, &str)>>(), [(3, "drei"), (4, "four"), (5, "five")]);
+    }
+
+    #[test]
+    fn test_sorted_map_roundtrip() {
+        let map: SortedMap<u16, bool> = [(300, true), (2, false)].into_iter().collect();
+
+        // Raw data (method tag 0).
+        let mut out = vec![0];
+        map.serialize(&mut out);
+        assert_eq!(out, [0, 2, 0, 2, 0, 1, 44, 1]);
+
+        let mut decoder = make_decoder(&out);
+        assert_eq!(SortedMap::<u16, bool>::deserialize(decoder.as_mut()), map);
+    }
+}
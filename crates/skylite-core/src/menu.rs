@@ -0,0 +1,112 @@
+/// The state of a list selection, such as a menu.
+///
+/// A `Menu` holds a list of items and a cursor pointing to one of them. The cursor is
+/// moved with [`Menu::move_cursor`], and the current item is selected with [`Menu::confirm`].
+/// The `Menu` only manages the state, so reading the input and drawing the items is up
+/// to the actor which owns the menu. The items are typically [`StrId`][crate::strings::StrId]s
+/// from a `(vec string)` parameter:
+///
+/// ```ignore
+/// if input_down { actor.properties.menu.move_cursor(1); }
+/// if input_confirm {
+///     if let Some(item) = actor.properties.menu.confirm() { /* ... */ }
+/// }
+///
+/// for (idx, item, selected) in actor.properties.menu.entries() {
+///     // Draw the item, highlighted if `selected` is true.
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Menu<T> {
+    items: Vec<T>,
+    cursor: usize,
+    wrap: bool
+}
+
+impl<T> Menu<T> {
+
+    /// Creates a new `Menu` with the cursor on the first item.
+    ///
+    /// If `wrap` is `true`, moving the cursor past the last item moves it to the
+    /// first item and vice versa. Otherwise, the cursor stops at the first and last items.
+    pub fn new(items: Vec<T>, wrap: bool) -> Menu<T> {
+        Menu { items, cursor: 0, wrap }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the index of the item under the cursor.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Moves the cursor to the item at `idx`, clamped to the number of items.
+    pub fn set_cursor(&mut self, idx: usize) {
+        self.cursor = idx.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Returns the item under the cursor, or `None` if the menu is empty.
+    pub fn current(&self) -> Option<&T> {
+        self.items.get(self.cursor)
+    }
+
+    /// Moves the cursor by `delta` items. Negative values move the cursor up.
+    ///
+    /// Returns `true` if the cursor has moved.
+    pub fn move_cursor(&mut self, delta: isize) -> bool {
+        let len = self.items.len() as isize;
+        if len == 0 {
+            return false;
+        }
+        let target = self.cursor as isize + delta;
+        let new_cursor = if self.wrap {
+            target.rem_euclid(len)
+        } else {
+            target.clamp(0, len - 1)
+        } as usize;
+        let moved = new_cursor != self.cursor;
+        self.cursor = new_cursor;
+        moved
+    }
+
+    /// Selects the item under the cursor and returns it, or `None` if the menu is empty.
+    pub fn confirm(&self) -> Option<&T> {
+        self.current()
+    }
+
+    /// Iterates over the items, together with their index and whether the cursor is on the item.
+    pub fn entries(&self) -> impl Iterator<Item = (usize, &T, bool)> {
+        self.items.iter().enumerate().map(|(idx, item)| (idx, item, idx == self.cursor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Menu;
+
+    #[test]
+    fn test_menu_wrap() {
+        let mut menu = Menu::new(vec!["start", "options", "quit"], true);
+        assert_eq!(menu.current(), Some(&"start"));
+        assert!(menu.move_cursor(-1));
+        assert_eq!(menu.current(), Some(&"quit"));
+        assert!(menu.move_cursor(2));
+        assert_eq!(menu.confirm(), Some(&"options"));
+        assert_eq!(menu.entries().filter(|(_, _, selected)| *selected).count(), 1);
+    }
+
+    #[test]
+    fn test_menu_clamp() {
+        let mut menu = Menu::new(vec![1, 2, 3], false);
+        assert!(!menu.move_cursor(-1));
+        assert_eq!(menu.cursor(), 0);
+        assert!(menu.move_cursor(5));
+        assert_eq!(menu.cursor(), 2);
+
+        let mut empty: Menu<u8> = Menu::new(vec![], true);
+        assert!(!empty.move_cursor(1));
+        assert_eq!(empty.confirm(), None);
+    }
+}
@@ -0,0 +1,76 @@
+/// Paces the updates of a project with a fixed update rate, independent of the frame rate.
+///
+/// The main loop passes the time since the last frame to [`FramePacer::advance`], which
+/// returns the number of updates to run. The time that is left over is available as
+/// [`FramePacer::alpha`], the fraction of the next update interval that has passed,
+/// which is used to interpolate between the last two updates while rendering:
+///
+/// ```ignore
+/// let mut pacer = FramePacer::new(60);
+/// loop {
+///     for _ in 0..pacer.advance(elapsed_seconds) {
+///         project.update();
+///     }
+///     project.render_interpolated(pacer.alpha());
+/// }
+/// ```
+pub struct FramePacer {
+    interval: f32,
+    accumulator: f32,
+    max_updates: u32
+}
+
+impl FramePacer {
+
+    /// Creates a `FramePacer` for `updates_per_second` updates per second.
+    pub fn new(updates_per_second: u32) -> FramePacer {
+        FramePacer { interval: 1.0 / updates_per_second.max(1) as f32, accumulator: 0.0, max_updates: 5 }
+    }
+
+    /// Sets the maximum number of updates returned by a single call to `advance`.
+    ///
+    /// If a frame takes longer than this many updates, e.g. because the game was paused
+    /// by the system, the remaining time is dropped instead of being caught up. The default is 5.
+    pub fn with_max_updates(mut self, max_updates: u32) -> FramePacer {
+        self.max_updates = max_updates.max(1);
+        self
+    }
+
+    /// Adds `elapsed` seconds and returns the number of updates which are due.
+    pub fn advance(&mut self, elapsed: f32) -> u32 {
+        self.accumulator += elapsed.max(0.0);
+        let mut updates = 0;
+        while self.accumulator >= self.interval {
+            self.accumulator -= self.interval;
+            updates += 1;
+            if updates >= self.max_updates {
+                self.accumulator = self.accumulator.min(self.interval * 0.999);
+                break;
+            }
+        }
+        updates
+    }
+
+    /// Returns the fraction of the current update interval which has passed, between `0.0` and `1.0`.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.interval).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FramePacer;
+
+    #[test]
+    fn test_frame_pacer() {
+        let mut pacer = FramePacer::new(50);
+        assert_eq!(pacer.advance(0.01), 0);
+        assert!((pacer.alpha() - 0.5).abs() < 0.001);
+        assert_eq!(pacer.advance(0.05), 3);
+        assert!(pacer.alpha() < 0.001);
+
+        // Long frames are not caught up.
+        assert_eq!(pacer.advance(10.0), 5);
+        assert!(pacer.alpha() < 1.0);
+    }
+}
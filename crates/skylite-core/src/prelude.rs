@@ -0,0 +1,33 @@
+//! The supported public API of `skylite_core`.
+//!
+//! The items in this module are meant to be used by user code, as well as by
+//! the code generated by `skylite_proc`. Everything that is not reachable through
+//! the prelude, and in particular every item starting with `_private`, is an
+//! implementation detail and may change in any release.
+//!
+//! ```
+//! use skylite_core::prelude::*;
+//! ```
+
+pub use crate::{DrawContext, ProjectControls, PropertyChange, RenderLayer, SkyliteProject, SkyliteTarget, UpdateMode, UpdatePhase};
+pub use crate::actors::{Actor, ActorAction, ActorBase, ActorHandle, AnyActor};
+pub use crate::decode::{make_decoder, make_decoder_from_source, read_varint, DataSource, DecodeError, Decoder, Deserialize, EmbeddedAsset, MAX_VARINT_LEN};
+pub use crate::encode::{write_varint, Serialize};
+pub use crate::ecs::{Commands, Component, Entity, Mut};
+pub use crate::grid::GridLayout;
+pub use crate::hud::{Counter, Gauge};
+pub use crate::interpolate::{Interpolated, Lerp};
+pub use crate::inventory::{AssetId, Inventory};
+pub use crate::map::SortedMap;
+pub use crate::menu::Menu;
+pub use crate::pacing::FramePacer;
+pub use crate::save::{NoTransform, SaveSlots, SaveTransform, SlotMetadata, XorTransform};
+pub use crate::scenes::{ActorIterator, ActorIteratorFiltered, ActorIteratorFilteredMut, ActorIteratorMut, IterActors, Scene};
+pub use crate::schedule::EventQueue;
+pub use crate::shake::ScreenShake;
+pub use crate::spatial::SpatialGrid;
+pub use crate::stats::{DrawStats, LayerStats};
+pub use crate::storage::{MemoryStorage, Storage, StorageBackend};
+pub use crate::strings::StrId;
+pub use crate::trigger::{TriggerEvent, TriggerZone};
+pub use crate::viewport::Viewport;
@@ -0,0 +1,126 @@
+//! Runtime access to actor properties, for in-game inspectors and debuggers.
+//!
+//! This module is only available with the `debug-reflection` feature. The implementations
+//! of [`Reflect`] for the actors' properties are generated by `skylite_proc`, whose
+//! `debug-reflection` feature is enabled along with it.
+
+/// The value of a property, converted for display and editing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReflectValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64)
+}
+
+/// Describes a single property of a reflected type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PropertyInfo {
+    /// The name of the property, as declared in `skylite_proc::properties!`.
+    pub name: &'static str,
+    /// The Rust type of the property, as written in the declaration.
+    pub type_name: &'static str,
+    /// `true` if the property has a primitive type, so that it can be read and
+    /// written with [`Reflect::get_property`] and [`Reflect::set_property`].
+    pub editable: bool
+}
+
+/// Types whose properties can be enumerated and edited at runtime.
+pub trait Reflect {
+
+    /// Returns the properties of the type, in the order of their declaration.
+    fn properties(&self) -> &'static [PropertyInfo];
+
+    /// Returns the value of the property `name`, or `None` if there is no such property or it is not editable.
+    fn get_property(&self, name: &str) -> Option<ReflectValue>;
+
+    /// Sets the property `name` to `value`.
+    ///
+    /// Returns `false` if there is no such property, if it is not editable, or if `value`
+    /// cannot be converted to the type of the property without loss.
+    fn set_property(&mut self, name: &str, value: ReflectValue) -> bool;
+}
+
+/// Conversion of primitive property types from and to [`ReflectValue`].
+pub trait ReflectPrimitive: Sized {
+    fn to_value(&self) -> ReflectValue;
+    fn from_value(value: ReflectValue) -> Option<Self>;
+}
+
+impl ReflectPrimitive for bool {
+    fn to_value(&self) -> ReflectValue {
+        ReflectValue::Bool(*self)
+    }
+
+    fn from_value(value: ReflectValue) -> Option<bool> {
+        match value {
+            ReflectValue::Bool(v) => Some(v),
+            _ => None
+        }
+    }
+}
+
+macro_rules! reflect_integer {
+    ($typename:ident, $variant:ident, $repr:ident) => {
+        impl ReflectPrimitive for $typename {
+            fn to_value(&self) -> ReflectValue {
+                ReflectValue::$variant(*self as $repr)
+            }
+
+            fn from_value(value: ReflectValue) -> Option<$typename> {
+                match value {
+                    ReflectValue::Int(v) => $typename::try_from(v).ok(),
+                    ReflectValue::UInt(v) => $typename::try_from(v).ok(),
+                    _ => None
+                }
+            }
+        }
+    };
+}
+
+reflect_integer!(u8, UInt, u64);
+reflect_integer!(u16, UInt, u64);
+reflect_integer!(u32, UInt, u64);
+reflect_integer!(u64, UInt, u64);
+reflect_integer!(i8, Int, i64);
+reflect_integer!(i16, Int, i64);
+reflect_integer!(i32, Int, i64);
+reflect_integer!(i64, Int, i64);
+
+macro_rules! reflect_float {
+    ($typename:ident) => {
+        impl ReflectPrimitive for $typename {
+            fn to_value(&self) -> ReflectValue {
+                ReflectValue::Float(*self as f64)
+            }
+
+            fn from_value(value: ReflectValue) -> Option<$typename> {
+                match value {
+                    ReflectValue::Float(v) => Some(v as $typename),
+                    ReflectValue::Int(v) => Some(v as $typename),
+                    ReflectValue::UInt(v) => Some(v as $typename),
+                    _ => None
+                }
+            }
+        }
+    };
+}
+
+reflect_float!(f32);
+reflect_float!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::{ReflectPrimitive, ReflectValue};
+
+    #[test]
+    fn test_reflect_primitive() {
+        assert_eq!(200_u8.to_value(), ReflectValue::UInt(200));
+        assert_eq!((-3_i16).to_value(), ReflectValue::Int(-3));
+        assert_eq!(u8::from_value(ReflectValue::Int(255)), Some(255));
+        assert_eq!(u8::from_value(ReflectValue::Int(256)), None);
+        assert_eq!(i8::from_value(ReflectValue::UInt(5)), Some(5));
+        assert_eq!(f32::from_value(ReflectValue::Int(2)), Some(2.0));
+        assert_eq!(bool::from_value(ReflectValue::UInt(1)), None);
+    }
+}
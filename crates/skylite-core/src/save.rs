@@ -0,0 +1,300 @@
+use crate::storage::StorageBackend;
+
+/// Marks the header of a used save slot.
+const SLOT_MAGIC: [u8; 2] = *b"SK";
+
+/// Size of the header in front of the data of each slot.
+///
+/// The header consists of the magic bytes, the play time (`u32`), the progress
+/// marker (`u16`), the length of the data (`u16`) and the checksum (`u16`),
+/// with all numbers in little endian.
+pub const SLOT_HEADER_LEN: usize = 12;
+
+/// The metadata of a used save slot, which is stored in front of the save data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SlotMetadata {
+    /// The play time in seconds.
+    pub play_time: u32,
+    /// A project-specific marker for the progress, e.g. the number of the current chapter.
+    pub progress: u16,
+    /// The checksum of the metadata and the data, which is used to detect corrupted slots.
+    pub checksum: u16
+}
+
+/// Computes the Fletcher-16 checksum of `data`.
+pub(crate) fn fletcher16<'a>(data: impl IntoIterator<Item = &'a u8>) -> u16 {
+    let (mut a, mut b) = (0_u16, 0_u16);
+    for &byte in data {
+        a = (a + byte as u16) % 255;
+        b = (b + a) % 255;
+    }
+    (b << 8) | a
+}
+
+/// A reversible transform applied to the save data before it is written to persistent storage,
+/// and reversed after it is read back.
+///
+/// This can be used to obfuscate or encrypt the save data, so that it can not be edited trivially.
+/// The transform only applies to the data of a slot, not to the header, and the checksum is computed
+/// on the untransformed data. Editing the transformed bytes therefore invalidates the checksum.
+pub trait SaveTransform {
+    /// Transforms the save data of slot `slot` in place, before it is written.
+    fn apply(&self, slot: usize, data: &mut [u8]);
+
+    /// Reverses `apply` on the save data of slot `slot`, after it is read.
+    fn reverse(&self, slot: usize, data: &mut [u8]);
+}
+
+/// The identity transform, which stores the save data as-is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct NoTransform;
+
+impl SaveTransform for NoTransform {
+    fn apply(&self, _slot: usize, _data: &mut [u8]) {}
+
+    fn reverse(&self, _slot: usize, _data: &mut [u8]) {}
+}
+
+/// Combines the save data with a keystream derived from a key and the slot number.
+///
+/// Since the keystream depends on the slot, copying the data from one slot to another does
+/// not produce a valid save. This is obfuscation rather than encryption: the key is part of
+/// the game binary and can be recovered by a determined user.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct XorTransform {
+    key: u32
+}
+
+impl XorTransform {
+
+    pub const fn new(key: u32) -> XorTransform {
+        XorTransform { key }
+    }
+
+    fn xor_keystream(&self, slot: usize, data: &mut [u8]) {
+        // xorshift32, whose state must never be zero.
+        let mut state = (self.key ^ (slot as u32).wrapping_mul(0x9e37_79b9)) | 1;
+        for byte in data {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            *byte ^= (state >> 24) as u8;
+        }
+    }
+}
+
+impl SaveTransform for XorTransform {
+    fn apply(&self, slot: usize, data: &mut [u8]) {
+        self.xor_keystream(slot, data);
+    }
+
+    fn reverse(&self, slot: usize, data: &mut [u8]) {
+        self.xor_keystream(slot, data);
+    }
+}
+
+/// Splits the persistent storage of a target into a number of save slots.
+///
+/// Each slot occupies `SLOT_HEADER_LEN + slot_size` bytes, starting at `offset`. A slot
+/// is either empty, or contains a header with the [`SlotMetadata`], followed by up to
+/// `slot_size` bytes of save data. Slots with a wrong checksum are treated as empty.
+///
+/// For generated projects, the layout is set with the `save-slots` entry of the project
+/// definition, and is available as the project's `SAVE_SLOTS` constant.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SaveSlots {
+    offset: usize,
+    slot_size: usize,
+    count: usize
+}
+
+impl SaveSlots {
+
+    /// Creates a layout with `count` slots of `slot_size` bytes each, starting at `offset`.
+    ///
+    /// Panics if `slot_size` is larger than `u16::MAX`, since the length of the data
+    /// is stored as a `u16` in the header of each slot.
+    pub const fn new(offset: usize, slot_size: usize, count: usize) -> SaveSlots {
+        assert!(slot_size <= u16::MAX as usize, "Save slot size must not be larger than 65535 bytes");
+        SaveSlots { offset, slot_size, count }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the maximum size of the save data in each slot.
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    /// Returns the offset of the slot `slot` in the persistent storage.
+    pub fn slot_offset(&self, slot: usize) -> usize {
+        self.offset + slot * (SLOT_HEADER_LEN + self.slot_size)
+    }
+
+    /// Returns the total number of bytes used by all slots.
+    pub fn storage_len(&self) -> usize {
+        self.count * (SLOT_HEADER_LEN + self.slot_size)
+    }
+
+    fn check_slot(&self, slot: usize) {
+        assert!(slot < self.count, "Save slot {} out of range, there are {} slots", slot, self.count);
+    }
+
+    /// Writes `data` to the slot `slot`, together with its metadata.
+    ///
+    /// Panics if `slot` is out of range or if `data` is larger than the slot size.
+    pub fn write<T: StorageBackend + ?Sized>(&self, target: &mut T, slot: usize, play_time: u32, progress: u16, data: &[u8]) {
+        self.write_with(target, slot, play_time, progress, data, &NoTransform)
+    }
+
+    /// Like `write`, but applies `transform` to `data` before writing it.
+    pub fn write_with<T: StorageBackend + ?Sized, X: SaveTransform>(&self, target: &mut T, slot: usize, play_time: u32, progress: u16, data: &[u8], transform: &X) {
+        self.check_slot(slot);
+        assert!(data.len() <= self.slot_size, "Save data of {} bytes does not fit into slot of {} bytes", data.len(), self.slot_size);
+
+        let mut out = Vec::with_capacity(SLOT_HEADER_LEN + data.len());
+        out.extend_from_slice(&SLOT_MAGIC);
+        out.extend_from_slice(&play_time.to_le_bytes());
+        out.extend_from_slice(&progress.to_le_bytes());
+        out.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        let checksum = fletcher16(out[2..].iter().chain(data));
+        out.extend_from_slice(&checksum.to_le_bytes());
+        out.extend_from_slice(data);
+        transform.apply(slot, &mut out[SLOT_HEADER_LEN..]);
+        target.write_storage(self.slot_offset(slot), &out);
+    }
+
+    /// Marks the slot `slot` as empty.
+    pub fn clear<T: StorageBackend + ?Sized>(&self, target: &mut T, slot: usize) {
+        self.check_slot(slot);
+        target.write_storage(self.slot_offset(slot), &[0; SLOT_HEADER_LEN]);
+    }
+
+    /// Reads the slot `slot`. Returns `None` if the slot is empty or corrupted.
+    pub fn read<T: StorageBackend + ?Sized>(&self, target: &T, slot: usize) -> Option<(SlotMetadata, Vec<u8>)> {
+        self.read_with(target, slot, &NoTransform)
+    }
+
+    /// Like `read`, but reverses `transform` on the data after reading it.
+    pub fn read_with<T: StorageBackend + ?Sized, X: SaveTransform>(&self, target: &T, slot: usize, transform: &X) -> Option<(SlotMetadata, Vec<u8>)> {
+        self.check_slot(slot);
+        let offset = self.slot_offset(slot);
+        let header = target.read_storage(offset, SLOT_HEADER_LEN);
+        if header[0..2] != SLOT_MAGIC {
+            return None;
+        }
+
+        let play_time = u32::from_le_bytes(header[2..6].try_into().unwrap());
+        let progress = u16::from_le_bytes(header[6..8].try_into().unwrap());
+        let len = u16::from_le_bytes(header[8..10].try_into().unwrap()) as usize;
+        let checksum = u16::from_le_bytes(header[10..12].try_into().unwrap());
+        if len > self.slot_size {
+            return None;
+        }
+
+        let mut data = target.read_storage(offset + SLOT_HEADER_LEN, len);
+        transform.reverse(slot, &mut data);
+        if fletcher16(header[2..10].iter().chain(&data)) != checksum {
+            return None;
+        }
+        Some((SlotMetadata { play_time, progress, checksum }, data))
+    }
+
+    /// Returns the metadata of every slot, or `None` for empty slots.
+    ///
+    /// This reads the data of all slots, to verify the checksums.
+    pub fn list<T: StorageBackend + ?Sized>(&self, target: &T) -> Vec<Option<SlotMetadata>> {
+        self.list_with(target, &NoTransform)
+    }
+
+    /// Like `list`, but reverses `transform` on the data of each slot before verifying the checksum.
+    pub fn list_with<T: StorageBackend + ?Sized, X: SaveTransform>(&self, target: &T, transform: &X) -> Vec<Option<SlotMetadata>> {
+        (0..self.count)
+            .map(|slot| self.read_with(target, slot, transform).map(|(meta, _)| meta))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkyliteTarget;
+
+    use super::{fletcher16, SaveSlots, XorTransform, SLOT_HEADER_LEN};
+
+    struct StorageTarget {
+        storage: Vec<u8>
+    }
+
+    impl SkyliteTarget for StorageTarget {
+        fn draw_sub(&mut self, _data: &[u8], _x: i16, _y: i16, _src_x: i16, _src_y: i16, _src_w: u16, _src_h: u16, _flip_h: bool, _flip_v: bool, _rotate: bool) {}
+
+        fn get_screen_size(&self) -> (u16, u16) {
+            (160, 160)
+        }
+
+        fn write_storage(&mut self, offset: usize, data: &[u8]) {
+            self.storage[offset..offset + data.len()].copy_from_slice(data);
+        }
+
+        fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+            self.storage[offset..offset + len].to_vec()
+        }
+    }
+
+    #[test]
+    fn test_fletcher16() {
+        assert_eq!(fletcher16(b"abcde"), 0xc8f0);
+        assert_eq!(fletcher16(b"abcdef"), 0x2057);
+    }
+
+    #[test]
+    fn test_save_slots() {
+        let slots = SaveSlots::new(4, 8, 3);
+        let mut target = StorageTarget { storage: vec![0; 4 + slots.storage_len()] };
+        assert_eq!(slots.slot_offset(2), 4 + 2 * (SLOT_HEADER_LEN + 8));
+        assert_eq!(slots.list(&target), [None, None, None]);
+
+        slots.write(&mut target, 1, 3600, 2, &[1, 2, 3]);
+        let (meta, data) = slots.read(&target, 1).unwrap();
+        assert_eq!((meta.play_time, meta.progress), (3600, 2));
+        assert_eq!(data, [1, 2, 3]);
+        assert_eq!(slots.list(&target), [None, Some(meta), None]);
+
+        // Corrupt the data of the slot.
+        target.storage[slots.slot_offset(1) + SLOT_HEADER_LEN] = 9;
+        assert_eq!(slots.read(&target, 1), None);
+
+        slots.write(&mut target, 0, 10, 0, &[]);
+        slots.clear(&mut target, 0);
+        assert_eq!(slots.read(&target, 0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Save slot size")]
+    fn test_slot_size_limit() {
+        SaveSlots::new(0, 65536, 1);
+    }
+
+    #[test]
+    fn test_xor_transform() {
+        let slots = SaveSlots::new(0, 8, 2);
+        let mut target = StorageTarget { storage: vec![0; slots.storage_len()] };
+        let transform = XorTransform::new(0x1234_5678);
+
+        slots.write_with(&mut target, 0, 60, 1, &[1, 2, 3, 4], &transform);
+        let stored = &target.storage[SLOT_HEADER_LEN..SLOT_HEADER_LEN + 4];
+        assert_ne!(stored, [1, 2, 3, 4]);
+        assert_eq!(slots.read_with(&target, 0, &transform).unwrap().1, [1, 2, 3, 4]);
+
+        // Without the transform, or with a different key, the checksum does not match.
+        assert_eq!(slots.read(&target, 0), None);
+        assert_eq!(slots.read_with(&target, 0, &XorTransform::new(1)), None);
+
+        // The keystream depends on the slot.
+        let copy = target.storage[0..SLOT_HEADER_LEN + 8].to_vec();
+        target.storage[slots.slot_offset(1)..].copy_from_slice(&copy);
+        assert_eq!(slots.read_with(&target, 1, &transform), None);
+    }
+}
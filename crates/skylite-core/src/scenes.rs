@@ -2,7 +2,7 @@ use std::{iter::Chain, marker::PhantomData, slice::{Iter, IterMut}};
 
 use skylite_compress::Decoder;
 
-use crate::{actors::{Actor, AnyActor, TypeId}, DrawContext, ProjectControls, SkyliteProject};
+use crate::{actors::{Actor, ActorBase, ActorHandle, AnyActor, TypeId}, DrawContext, ProjectControls, SkyliteProject};
 
 /// Immutable iterator over actors in a `Scene`.
 pub struct ActorIterator<'scene, Type: AnyActor> {
@@ -10,7 +10,8 @@ pub struct ActorIterator<'scene, Type: AnyActor> {
 }
 
 impl<'scene, Type: AnyActor> ActorIterator<'scene, Type> {
-    pub fn _private_new<'s>(main: &'s [Type], extras: &'s [Type]) -> ActorIterator<'s, Type> {
+    /// Creates an iterator which first yields the actors from `main`, then the actors from `extras`.
+    pub fn new<'s>(main: &'s [Type], extras: &'s [Type]) -> ActorIterator<'s, Type> {
         ActorIterator {
             inner: main.iter().chain(extras.iter())
         }
@@ -48,7 +49,8 @@ impl<'scene, Type: AnyActor> Iterator for ActorIteratorMut<'scene, Type> {
 }
 
 impl<'scene, Type: AnyActor> ActorIteratorMut<'scene, Type> {
-    pub fn _private_new<'s>(main: &'s mut [Type], extras: &'s mut [Type]) -> ActorIteratorMut<'s, Type> {
+    /// Creates an iterator which first yields the actors from `main`, then the actors from `extras`.
+    pub fn new<'s>(main: &'s mut [Type], extras: &'s mut [Type]) -> ActorIteratorMut<'s, Type> {
         ActorIteratorMut {
             inner: main.iter_mut().chain(extras.iter_mut())
         }
@@ -135,7 +137,7 @@ pub trait Scene {
 
     #[doc(hidden)] fn _private_decode(decode: &mut dyn Decoder) -> Self where Self: Sized;
     #[doc(hidden)] fn _private_update(&mut self, controls: &mut ProjectControls<Self::P>);
-    #[doc(hidden)] fn _private_render(&self, ctx: &DrawContext<Self::P>);
+    #[doc(hidden)] fn _private_render(&self, ctx: &mut DrawContext<Self::P>);
 
     /// Returns an iterator over all the actors in the scene.
     fn iter_actors(&self, which: IterActors) -> ActorIterator<<Self::P as SkyliteProject>::Actors>;
@@ -150,29 +152,132 @@ pub trait Scene {
     /// Must be called from an `Actor` context, i.e. an action
     /// or one of the update hooks.
     fn remove_current_extra(&mut self);
+
+    /// Removes the extra referenced by `handle`, and returns `true` if it was part of the scene.
+    ///
+    /// Like `iter_actors`, this does not find the actors which are currently being updated.
+    fn remove_extra_by_handle(&mut self, handle: ActorHandle) -> bool;
+
+    /// Returns the names of the named actors, as declared in the scene definition.
+    ///
+    /// The names are in the same order as the actors returned by `iter_actors(IterActors::Named)`.
+    fn actor_names(&self) -> &'static [&'static str];
+
+    /// Returns the id of the scene's type, which is its index in `assets::SCENE_NAMES` of the project.
+    fn scene_id(&self) -> usize;
+
+    /// Returns the named actor `name`, or `None` if the scene has no actor with that name.
+    fn find_actor(&self, name: &str) -> Option<&<Self::P as SkyliteProject>::Actors> {
+        let idx = self.actor_names().iter().position(|n| *n == name)?;
+        self.iter_actors(IterActors::Named).nth(idx)
+    }
+
+    /// Returns the named actor `name` for modification, or `None` if the scene has no actor with that name.
+    fn find_actor_mut(&mut self, name: &str) -> Option<&mut <Self::P as SkyliteProject>::Actors> {
+        let idx = self.actor_names().iter().position(|n| *n == name)?;
+        self.iter_actors_mut(IterActors::Named).nth(idx)
+    }
+
+    /// Returns the actor referenced by `handle`, or `None` if the actor is no longer part of the scene.
+    ///
+    /// Like `iter_actors`, this does not find the actors which are currently being updated.
+    fn get_actor(&self, handle: ActorHandle) -> Option<&<Self::P as SkyliteProject>::Actors> {
+        self.iter_actors(IterActors::All).find(|a| a.handle() == handle)
+    }
+
+    /// Returns the actor referenced by `handle` for modification, or `None` if the actor is no longer part of the scene.
+    fn get_actor_mut(&mut self, handle: ActorHandle) -> Option<&mut <Self::P as SkyliteProject>::Actors> {
+        self.iter_actors_mut(IterActors::All).find(|a| a.handle() == handle)
+    }
+
+    /// Returns `true` if the actor referenced by `handle` is part of the scene.
+    fn is_alive(&self, handle: ActorHandle) -> bool {
+        self.get_actor(handle).is_some()
+    }
+}
+
+fn downcast<T: AnyActor, A: Actor>(actor: &T) -> Option<&A> {
+    if actor.get_id() == <A as TypeId>::get_id() {
+        unsafe { Some(actor._private_transmute()) }
+    } else {
+        None
+    }
+}
+
+fn downcast_mut<T: AnyActor, A: Actor>(actor: &mut T) -> Option<&mut A> {
+    if actor.get_id() == <A as TypeId>::get_id() {
+        unsafe { Some(actor._private_transmute_mut()) }
+    } else {
+        None
+    }
+}
+
+impl<P: SkyliteProject> dyn Scene<P=P> + '_ {
+
+    /// Returns the named actor `name` as an actor of type `A`.
+    ///
+    /// Returns `None` if the scene has no actor with that name, or if the actor is not of type `A`.
+    /// This is meant to be used instead of passing references to specific actors around:
+    ///
+    /// ```ignore
+    /// if let Some(player) = scene.find_actor_as::<Player>("player") {
+    ///     target = player.properties.pos;
+    /// }
+    /// ```
+    pub fn find_actor_as<A: Actor>(&self, name: &str) -> Option<&A> {
+        downcast(self.find_actor(name)?)
+    }
+
+    /// Returns the named actor `name` as an actor of type `A` for modification.
+    ///
+    /// Returns `None` if the scene has no actor with that name, or if the actor is not of type `A`.
+    pub fn find_actor_as_mut<A: Actor>(&mut self, name: &str) -> Option<&mut A> {
+        downcast_mut(self.find_actor_mut(name)?)
+    }
+
+    /// Returns the actor referenced by `handle` as an actor of type `A`.
+    ///
+    /// Returns `None` if the actor is no longer part of the scene, or if it is not of type `A`.
+    pub fn get_actor_as<A: Actor>(&self, handle: ActorHandle) -> Option<&A> {
+        downcast(self.get_actor(handle)?)
+    }
+
+    /// Returns the actor referenced by `handle` as an actor of type `A` for modification.
+    ///
+    /// Returns `None` if the actor is no longer part of the scene, or if it is not of type `A`.
+    pub fn get_actor_as_mut<A: Actor>(&mut self, handle: ActorHandle) -> Option<&mut A> {
+        downcast_mut(self.get_actor_mut(handle)?)
+    }
 }
 
 #[doc(hidden)]
 pub mod _private {
-    use crate::{actors::ActorBase, DrawContext, SkyliteProject};
+    use crate::{actors::ActorBase, DrawContext, RenderLayer, SkyliteProject};
+
+    /// Returns the indices of `actors` in the order in which they are updated,
+    /// see [`ActorBase::update_priority`].
+    pub fn update_order<A: ActorBase>(actors: &[A]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..actors.len()).collect();
+        // The sort is stable, so actors with the same priority keep their order.
+        order.sort_by_key(|&idx| actors[idx].update_priority());
+        order
+    }
 
     use super::{IterActors, Scene};
 
-    pub fn render_scene<'scene, P: SkyliteProject>(scene: &'scene dyn Scene<P=P>, ctx: &DrawContext<P>) {
-        let mut z_sorted: Vec<&P::Actors> = Vec::new();
-        let mut insert_by_z_order = |actor: &'scene P::Actors| {
-            for (i, a) in z_sorted.iter().enumerate() {
-                if actor.z_order() <= a.z_order() {
-                    z_sorted.insert(i, actor);
-                    return;
-                }
-            }
-            z_sorted.push(actor);
-        };
-
-        scene.iter_actors(IterActors::All).for_each(&mut insert_by_z_order);
-        scene.iter_actors(IterActors::All).for_each(&mut insert_by_z_order);
-
-        z_sorted.iter().for_each(|a| a._private_render(ctx));
+    /// Renders the actors of a scene, ordered by their layer first and their z-order second.
+    pub fn render_scene<P: SkyliteProject>(scene: &dyn Scene<P=P>, ctx: &mut DrawContext<P>) {
+        let mut sorted: Vec<&P::Actors> = scene.iter_actors(IterActors::All).collect();
+        // The sort is stable, so actors with the same layer and z-order
+        // are always drawn in the same order.
+        sorted.sort_by_key(|a| (a.render_layer(), a.z_order()));
+
+        for actor in sorted {
+            ctx.layer = actor.render_layer();
+            ctx.parallax = actor.parallax();
+            actor._private_render(ctx);
+        }
+        ctx.layer = RenderLayer::Main;
+        ctx.parallax = (1.0, 1.0);
     }
 }
@@ -0,0 +1,91 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Delivers events after a delay, measured in updates.
+///
+/// Events are identified by name. Every project keeps an `EventQueue` in its
+/// [`ProjectControls`](crate::ProjectControls), where events are scheduled with
+/// `schedule` and received with `events` or `has_event`:
+///
+/// ```ignore
+/// // In the update of a door actor:
+/// controls.schedule(60, "close-door");
+///
+/// // One second later, at 60 updates per second:
+/// if controls.has_event("close-door") { ... }
+/// ```
+///
+/// Events which are due during the same update are delivered in the order in which they were scheduled.
+pub struct EventQueue {
+    update: u64,
+    seq: u64,
+    queue: BinaryHeap<Reverse<(u64, u64, &'static str)>>,
+    due: Vec<&'static str>
+}
+
+impl EventQueue {
+
+    pub fn new() -> EventQueue {
+        EventQueue { update: 0, seq: 0, queue: BinaryHeap::new(), due: Vec::new() }
+    }
+
+    /// Schedules `event` to be delivered `delay_updates` updates after the current one.
+    ///
+    /// A delay of 0 is treated as 1, so the event is never delivered during the update that scheduled it.
+    pub fn schedule(&mut self, delay_updates: u32, event: &'static str) {
+        let at = self.update + delay_updates.max(1) as u64;
+        self.queue.push(Reverse((at, self.seq, event)));
+        self.seq += 1;
+    }
+
+    /// Returns the number of events which have been scheduled, but not delivered yet.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns the events that are delivered during the current update.
+    pub fn due(&self) -> &[&'static str] {
+        &self.due
+    }
+
+    /// Advances to the next update and collects the events that are due.
+    pub fn advance(&mut self) {
+        self.update += 1;
+        self.due.clear();
+        while self.queue.peek().is_some_and(|Reverse((at, _, _))| *at <= self.update) {
+            let Reverse((_, _, event)) = self.queue.pop().unwrap();
+            self.due.push(event);
+        }
+    }
+}
+
+impl Default for EventQueue {
+    fn default() -> EventQueue {
+        EventQueue::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventQueue;
+
+    #[test]
+    fn test_event_queue() {
+        let mut queue = EventQueue::new();
+        queue.schedule(2, "b");
+        queue.schedule(0, "a");
+        queue.schedule(2, "c");
+        assert_eq!(queue.pending(), 3);
+        assert!(queue.due().is_empty());
+
+        queue.advance();
+        assert_eq!(queue.due(), ["a"]);
+
+        queue.schedule(1, "d");
+        queue.advance();
+        assert_eq!(queue.due(), ["b", "c", "d"]);
+
+        queue.advance();
+        assert!(queue.due().is_empty());
+        assert_eq!(queue.pending(), 0);
+    }
+}
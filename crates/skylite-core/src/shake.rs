@@ -0,0 +1,104 @@
+/// A screen shake, which offsets everything that is rendered for a number of updates.
+///
+/// The shake is started through [`ProjectControls::shake`](crate::ProjectControls::shake). Its
+/// offset is applied by the `DrawContext` on top of the focus, so the focus itself is not
+/// changed and can still be used by the game logic, e.g. for a camera that follows the player.
+///
+/// The strength of the shake decreases linearly over its duration. The offsets follow a fixed
+/// pseudo-random sequence, so a shake always looks the same for the same parameters.
+pub struct ScreenShake {
+    intensity: u16,
+    duration: u32,
+    remaining: u32
+}
+
+impl ScreenShake {
+
+    pub fn new() -> ScreenShake {
+        ScreenShake { intensity: 0, duration: 0, remaining: 0 }
+    }
+
+    /// Starts a shake with a maximum offset of `intensity` pixels, which lasts for `duration_updates` updates.
+    ///
+    /// A running shake is replaced, unless it is currently stronger than the new one.
+    pub fn start(&mut self, intensity: u16, duration_updates: u32) {
+        if self.current_intensity() <= intensity as i32 {
+            self.intensity = intensity;
+            self.duration = duration_updates;
+            self.remaining = duration_updates;
+        }
+    }
+
+    /// Stops the current shake.
+    pub fn stop(&mut self) {
+        self.remaining = 0;
+    }
+
+    /// Returns `true` if a shake is currently running.
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    fn current_intensity(&self) -> i32 {
+        if self.remaining == 0 {
+            return 0;
+        }
+        (self.intensity as u32 * self.remaining).div_ceil(self.duration) as i32
+    }
+
+    /// Returns the offset for the current update.
+    pub fn offset(&self) -> (i32, i32) {
+        let intensity = self.current_intensity();
+        if intensity == 0 {
+            return (0, 0);
+        }
+
+        // A small integer hash of the remaining updates, so that consecutive offsets differ.
+        let mut h = self.remaining.wrapping_mul(0x9e37_79b9);
+        h ^= h >> 15;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        let range = 2 * intensity as u32 + 1;
+        ((h % range) as i32 - intensity, ((h >> 16) % range) as i32 - intensity)
+    }
+
+    /// Advances the shake by one update.
+    pub fn advance(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+}
+
+impl Default for ScreenShake {
+    fn default() -> ScreenShake {
+        ScreenShake::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScreenShake;
+
+    #[test]
+    fn test_screen_shake() {
+        let mut shake = ScreenShake::new();
+        assert_eq!(shake.offset(), (0, 0));
+
+        shake.start(4, 8);
+        let mut offsets = Vec::new();
+        while shake.is_active() {
+            let (x, y) = shake.offset();
+            assert!(x.abs() <= 4 && y.abs() <= 4);
+            offsets.push((x, y));
+            shake.advance();
+        }
+        assert_eq!(offsets.len(), 8);
+        assert!(offsets.windows(2).any(|w| w[0] != w[1]));
+        assert_eq!(shake.offset(), (0, 0));
+
+        // A weaker shake does not replace a stronger one.
+        shake.start(4, 8);
+        shake.start(1, 100);
+        shake.advance();
+        assert_eq!(shake.current_intensity(), 4);
+    }
+}
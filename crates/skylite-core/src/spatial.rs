@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+/// The entries of a single cell, with their positions.
+type Cell<K> = Vec<(K, i32, i32)>;
+
+/// A uniform grid for finding entries close to a position.
+///
+/// Entries are registered with a key and a position, and are sorted into square cells of
+/// `cell_size` pixels. Queries only look at the cells that overlap the queried area, so finding
+/// the neighbors of all actors does not require comparing every actor with every other actor.
+///
+/// The grid does not track moving entries. Instead, it is cleared and filled again on
+/// every update, after the positions have changed. The keys are typically the indices
+/// of the actors in the scene:
+///
+/// ```ignore
+/// grid.clear();
+/// for (idx, actor) in scene.iter_actors(IterActors::All).enumerate() {
+///     grid.insert(idx, x, y);
+/// }
+/// let neighbors = grid.query_radius(x, y, 16);
+/// ```
+pub struct SpatialGrid<K: Copy> {
+    cell_size: i32,
+    cells: HashMap<(i32, i32), Cell<K>>,
+    len: usize
+}
+
+impl<K: Copy> SpatialGrid<K> {
+
+    /// Creates an empty grid. `cell_size` should be around the size of the areas that are usually queried.
+    pub fn new(cell_size: u16) -> SpatialGrid<K> {
+        SpatialGrid { cell_size: cell_size.max(1) as i32, cells: HashMap::new(), len: 0 }
+    }
+
+    fn cell_of(&self, x: i32, y: i32) -> (i32, i32) {
+        (x.div_euclid(self.cell_size), y.div_euclid(self.cell_size))
+    }
+
+    /// Removes all entries. The memory of the cells is kept for the next update.
+    pub fn clear(&mut self) {
+        for entries in self.cells.values_mut() {
+            entries.clear();
+        }
+        self.len = 0;
+    }
+
+    /// Returns the number of entries in the grid.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Registers the entry `key` at the position (`x`, `y`).
+    pub fn insert(&mut self, key: K, x: i32, y: i32) {
+        let cell = self.cell_of(x, y);
+        self.cells.entry(cell).or_default().push((key, x, y));
+        self.len += 1;
+    }
+
+    /// Calls `f` for every entry in the cells overlapping the rectangle from (`x1`, `y1`) to (`x2`, `y2`), inclusive.
+    fn visit_cells(&self, x1: i32, y1: i32, x2: i32, y2: i32, mut f: impl FnMut(K, i32, i32)) {
+        let (cx1, cy1) = self.cell_of(x1, y1);
+        let (cx2, cy2) = self.cell_of(x2, y2);
+        for cy in cy1..=cy2 {
+            for cx in cx1..=cx2 {
+                if let Some(entries) = self.cells.get(&(cx, cy)) {
+                    for &(key, ex, ey) in entries {
+                        f(key, ex, ey);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the keys of all entries inside the rectangle at (`x`, `y`) with size `w` by `h`.
+    pub fn query_rect(&self, x: i32, y: i32, w: u16, h: u16) -> Vec<K> {
+        let mut out = Vec::new();
+        if w == 0 || h == 0 {
+            return out;
+        }
+        let (x2, y2) = (x + w as i32 - 1, y + h as i32 - 1);
+        self.visit_cells(x, y, x2, y2, |key, ex, ey| {
+            if ex >= x && ex <= x2 && ey >= y && ey <= y2 {
+                out.push(key);
+            }
+        });
+        out
+    }
+
+    /// Returns the keys of all entries within `radius` pixels of (`x`, `y`), inclusive.
+    pub fn query_radius(&self, x: i32, y: i32, radius: u16) -> Vec<K> {
+        let mut out = Vec::new();
+        let r = radius as i32;
+        let r_squared = (r as i64) * (r as i64);
+        self.visit_cells(x - r, y - r, x + r, y + r, |key, ex, ey| {
+            let (dx, dy) = ((ex - x) as i64, (ey - y) as i64);
+            if dx * dx + dy * dy <= r_squared {
+                out.push(key);
+            }
+        });
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpatialGrid;
+
+    fn create_test_grid() -> SpatialGrid<usize> {
+        let mut grid = SpatialGrid::new(16);
+        grid.insert(0, 0, 0);
+        grid.insert(1, 10, 10);
+        grid.insert(2, 40, 0);
+        grid.insert(3, -20, -5);
+        grid
+    }
+
+    #[test]
+    fn test_query_rect() {
+        let grid = create_test_grid();
+        let mut found = grid.query_rect(-5, -5, 20, 20);
+        found.sort();
+        assert_eq!(found, [0, 1]);
+
+        let mut found = grid.query_rect(-20, -5, 61, 1);
+        found.sort();
+        assert_eq!(found, [3]);
+        assert!(grid.query_rect(0, 0, 0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_query_radius() {
+        let mut grid = create_test_grid();
+        let mut found = grid.query_radius(0, 0, 20);
+        found.sort();
+        assert_eq!(found, [0, 1]);
+
+        // (10, 10) is 14.1 pixels away from (0, 0).
+        assert_eq!(grid.query_radius(0, 0, 14), [0]);
+
+        grid.clear();
+        assert!(grid.is_empty());
+        assert!(grid.query_radius(0, 0, 100).is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+//! Statistics about the draw calls of a frame, for finding out why rendering is slow.
+//!
+//! The statistics are only counted with the `debug-draw-stats` feature. Without it, all
+//! counts stay at `0`, so that release builds do not pay for the bookkeeping.
+
+use crate::RenderLayer;
+
+/// The draw calls of a single layer, or of a whole frame.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct LayerStats {
+    /// The number of calls to the drawing functions of the target.
+    pub draw_calls: u32,
+    /// The number of pixels drawn by these calls, including transparent pixels.
+    /// Pixels outside of the screen are not counted.
+    pub pixels: u64
+}
+
+/// Counts the draw calls of a frame, separately for each [`RenderLayer`].
+///
+/// The statistics of the current frame are available through [`DrawContext::stats`](crate::DrawContext::stats),
+/// and those of the most recent frame through the `draw_stats` method of the generated project type.
+///
+/// Only calls through the [`DrawContext`](crate::DrawContext) are counted, not calls made directly
+/// on the target, e.g. through [`DrawContext::target_ext`](crate::DrawContext::target_ext).
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct DrawStats {
+    layers: [LayerStats; 3]
+}
+
+impl DrawStats {
+
+    /// Returns the statistics of a single layer.
+    pub fn layer(&self, layer: RenderLayer) -> LayerStats {
+        self.layers[layer as usize]
+    }
+
+    /// Returns the sum of the statistics of all layers.
+    pub fn total(&self) -> LayerStats {
+        self.layers.iter().fold(LayerStats::default(), |total, layer| LayerStats {
+            draw_calls: total.draw_calls + layer.draw_calls,
+            pixels: total.pixels + layer.pixels
+        })
+    }
+
+    #[cfg_attr(not(feature = "debug-draw-stats"), allow(dead_code))]
+    pub(crate) fn record(&mut self, layer: RenderLayer, draw_calls: u32, pixels: u64) {
+        let stats = &mut self.layers[layer as usize];
+        stats.draw_calls += draw_calls;
+        stats.pixels += pixels;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RenderLayer;
+
+    use super::{DrawStats, LayerStats};
+
+    #[test]
+    fn test_draw_stats() {
+        let mut stats = DrawStats::default();
+        stats.record(RenderLayer::Main, 1, 64);
+        stats.record(RenderLayer::Main, 2, 2);
+        stats.record(RenderLayer::Ui, 1, 10);
+        assert_eq!(stats.layer(RenderLayer::Background), LayerStats::default());
+        assert_eq!(stats.layer(RenderLayer::Main), LayerStats { draw_calls: 3, pixels: 66 });
+        assert_eq!(stats.total(), LayerStats { draw_calls: 4, pixels: 76 });
+    }
+}
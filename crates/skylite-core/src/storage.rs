@@ -0,0 +1,400 @@
+use crate::{save::fletcher16, SkyliteTarget};
+
+/// Persistent storage for save data, addressed by byte offsets.
+///
+/// Every [`SkyliteTarget`] is a storage backend through its `read_storage` and `write_storage`
+/// methods, and projects use the target's storage by default. A different backend can be set
+/// with the `storage` option of the project's builder, e.g. to store the save data at a
+/// platform-specific path on desktop builds, or to keep it in memory for tests.
+pub trait StorageBackend {
+
+    /// Writes the given data at the given offset into persistent storage.
+    fn write_storage(&mut self, offset: usize, data: &[u8]);
+
+    /// Reads `len` bytes from persistent storage, starting at the given offset.
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8>;
+}
+
+impl<T: SkyliteTarget> StorageBackend for T {
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        SkyliteTarget::write_storage(self, offset, data)
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        SkyliteTarget::read_storage(self, offset, len)
+    }
+}
+
+/// A [`StorageBackend`] that keeps the data in memory.
+///
+/// The storage grows as needed when data is written. Bytes that were never written read as `0`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MemoryStorage {
+    data: Vec<u8>
+}
+
+impl MemoryStorage {
+
+    pub fn new() -> MemoryStorage {
+        MemoryStorage { data: Vec::new() }
+    }
+
+    /// Creates a storage with the given initial content, e.g. from a save file.
+    pub fn from_data(data: Vec<u8>) -> MemoryStorage {
+        MemoryStorage { data }
+    }
+
+    /// Returns the data written so far.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        if self.data.len() < offset + data.len() {
+            self.data.resize(offset + data.len(), 0);
+        }
+        self.data[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        let mut out = vec![0; len];
+        if offset < self.data.len() {
+            let end = self.data.len().min(offset + len);
+            out[..end - offset].copy_from_slice(&self.data[offset..end]);
+        }
+        out
+    }
+}
+
+/// Marks the start of a journal entry.
+const ENTRY_MARKER: u8 = 0xa5;
+
+/// Size of the generation at the start of the journal.
+const GENERATION_LEN: usize = 4;
+
+/// Size of the header of a journal entry: the marker, the generation (`u32`), the logical offset (`u16`)
+/// and the length (`u8`).
+const ENTRY_HEADER_LEN: usize = 1 + GENERATION_LEN + 3;
+
+/// Size of the checksum at the end of a journal entry.
+const ENTRY_CHECKSUM_LEN: usize = 2;
+
+/// Maximum number of data bytes in a single journal entry.
+const MAX_ENTRY_DATA: usize = u8::MAX as usize;
+
+/// An append-only journal over a region of persistent storage.
+///
+/// Some targets back their storage with flash memory, which is slow to write and only
+/// supports a limited number of write cycles. `Storage` keeps a copy of the stored data in
+/// memory, and collects all writes until [`Storage::flush`] is called. The changed bytes are
+/// then appended to a journal instead of being written in place, so that repeated writes to
+/// the same data are spread over the whole region. Only when the journal is full, the current
+/// data is written as a new snapshot and the journal starts over.
+///
+/// The region starts at `offset` and is `capacity` bytes large. The first `len` bytes hold
+/// the snapshot, and the rest is used for the journal, so `capacity` should be several times `len`.
+///
+/// The journal starts with a generation number (`u32`), which is incremented whenever a new snapshot
+/// is written. Each entry records the generation it was written in, and only entries of the current
+/// generation are replayed. Entries from before the last snapshot stay in storage until they are
+/// overwritten, but are never applied again. The generation is wide enough that it never wraps
+/// around in practice, so stale entries can not become valid again.
+///
+/// A flush which is interrupted while appending to the journal loses the writes of that flush,
+/// but keeps all earlier data. A flush interrupted while writing a new snapshot may leave the
+/// bytes written by that flush partially updated.
+pub struct Storage {
+    offset: usize,
+    capacity: usize,
+    data: Vec<u8>,
+    dirty: Vec<bool>,
+    generation: u32,
+    /// The end of the entries in the journal, after the generation.
+    journal_end: usize
+}
+
+impl Storage {
+
+    /// Opens the storage region at `offset`, and restores its data from the
+    /// snapshot and the journal. The data is `len` bytes long.
+    ///
+    /// Panics if `len` is larger than 64KiB or if the region is too small for
+    /// the snapshot and at least one journal entry.
+    pub fn open<T: StorageBackend + ?Sized>(target: &T, offset: usize, len: usize, capacity: usize) -> Storage {
+        assert!(len <= u16::MAX as usize + 1, "Storage of {} bytes is too large for the journal", len);
+        assert!(capacity >= len + GENERATION_LEN + ENTRY_HEADER_LEN + 1 + ENTRY_CHECKSUM_LEN, "Storage capacity of {} bytes is too small for {} bytes", capacity, len);
+
+        let mut data = target.read_storage(offset, len);
+        let journal = target.read_storage(offset + len, capacity - len);
+        let generation = u32::from_le_bytes(journal[..GENERATION_LEN].try_into().unwrap());
+        let journal_end = replay_journal(&journal[GENERATION_LEN..], generation, &mut data);
+        Storage { offset, capacity, dirty: vec![false; len], data, generation, journal_end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the current data, including writes that have not been flushed yet.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Reads `len` bytes starting at `offset`.
+    pub fn read(&self, offset: usize, len: usize) -> &[u8] {
+        &self.data[offset..offset + len]
+    }
+
+    /// Writes `data` at `offset`. The data is only written to the target on the next [`Storage::flush`].
+    pub fn write(&mut self, offset: usize, data: &[u8]) {
+        for (i, &byte) in data.iter().enumerate() {
+            if self.data[offset + i] != byte {
+                self.data[offset + i] = byte;
+                self.dirty[offset + i] = true;
+            }
+        }
+    }
+
+    /// Returns `true` if there are writes which have not been flushed yet.
+    pub fn has_pending_writes(&self) -> bool {
+        self.dirty.contains(&true)
+    }
+
+    /// Returns the ranges of changed bytes, split into chunks that fit into a journal entry.
+    fn dirty_ranges(&self) -> Vec<(usize, usize)> {
+        let mut out = Vec::new();
+        let mut idx = 0;
+        while idx < self.dirty.len() {
+            if !self.dirty[idx] {
+                idx += 1;
+                continue;
+            }
+            let start = idx;
+            while idx < self.dirty.len() && self.dirty[idx] && idx - start < MAX_ENTRY_DATA {
+                idx += 1;
+            }
+            out.push((start, idx - start));
+        }
+        out
+    }
+
+    /// Writes all pending changes to the target.
+    ///
+    /// The changes are appended to the journal if there is enough space left.
+    /// Otherwise, the storage is compacted, see [`Storage::compact`].
+    pub fn flush<T: StorageBackend + ?Sized>(&mut self, target: &mut T) {
+        let ranges = self.dirty_ranges();
+        if ranges.is_empty() {
+            return;
+        }
+
+        let mut entries = Vec::new();
+        for (start, len) in ranges {
+            let entry_start = entries.len();
+            entries.push(ENTRY_MARKER);
+            entries.extend_from_slice(&self.generation.to_le_bytes());
+            entries.extend_from_slice(&(start as u16).to_le_bytes());
+            entries.push(len as u8);
+            entries.extend_from_slice(&self.data[start..start + len]);
+            let checksum = fletcher16(&entries[entry_start..]);
+            entries.extend_from_slice(&checksum.to_le_bytes());
+        }
+
+        let journal_start = self.offset + self.data.len() + GENERATION_LEN;
+        let journal_capacity = self.capacity - self.data.len() - GENERATION_LEN;
+        if self.journal_end + entries.len() > journal_capacity {
+            self.compact(target);
+            return;
+        }
+
+        // Terminate the journal after the new entries, unless they fill it completely.
+        // The terminator is overwritten by the next entry.
+        let entries_len = entries.len();
+        if self.journal_end + entries_len < journal_capacity {
+            entries.push(0);
+        }
+        target.write_storage(journal_start + self.journal_end, &entries);
+        self.journal_end += entries_len;
+        self.dirty.iter_mut().for_each(|d| *d = false);
+    }
+
+    /// Writes the current data as a new snapshot and clears the journal.
+    ///
+    /// The journal is cleared by incrementing the generation, which invalidates all existing entries.
+    pub fn compact<T: StorageBackend + ?Sized>(&mut self, target: &mut T) {
+        target.write_storage(self.offset, &self.data);
+        self.generation = self.generation.wrapping_add(1);
+        let mut header = self.generation.to_le_bytes().to_vec();
+        header.push(0);
+        target.write_storage(self.offset + self.data.len(), &header);
+        self.journal_end = 0;
+        self.dirty.iter_mut().for_each(|d| *d = false);
+    }
+}
+
+/// Applies the entries of `journal` to `data`, and returns the length of the valid part of the journal.
+///
+/// The journal ends at the first byte which is not an entry marker, at the first entry from a
+/// different generation than `generation`, or at the first entry which is incomplete or has a
+/// wrong checksum, e.g. because of an interrupted write.
+fn replay_journal(journal: &[u8], generation: u32, data: &mut [u8]) -> usize {
+    let mut pos = 0;
+    while pos + ENTRY_HEADER_LEN <= journal.len()
+        && journal[pos] == ENTRY_MARKER
+        && journal[pos + 1..pos + 1 + GENERATION_LEN] == generation.to_le_bytes()
+    {
+        let header = &journal[pos + 1 + GENERATION_LEN..pos + ENTRY_HEADER_LEN];
+        let offset = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let len = header[2] as usize;
+        let data_start = pos + ENTRY_HEADER_LEN;
+        let entry_end = data_start + len + ENTRY_CHECKSUM_LEN;
+        if entry_end > journal.len() || offset + len > data.len() {
+            break;
+        }
+        let checksum = u16::from_le_bytes([journal[entry_end - 2], journal[entry_end - 1]]);
+        if fletcher16(&journal[pos..data_start + len]) != checksum {
+            break;
+        }
+        data[offset..offset + len].copy_from_slice(&journal[data_start..data_start + len]);
+        pos = entry_end;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkyliteTarget;
+
+    use super::{MemoryStorage, Storage, StorageBackend};
+
+    struct StorageTarget {
+        storage: Vec<u8>,
+        bytes_written: usize
+    }
+
+    impl SkyliteTarget for StorageTarget {
+        fn draw_sub(&mut self, _data: &[u8], _x: i16, _y: i16, _src_x: i16, _src_y: i16, _src_w: u16, _src_h: u16, _flip_h: bool, _flip_v: bool, _rotate: bool) {}
+
+        fn get_screen_size(&self) -> (u16, u16) {
+            (160, 160)
+        }
+
+        fn write_storage(&mut self, offset: usize, data: &[u8]) {
+            self.storage[offset..offset + data.len()].copy_from_slice(data);
+            self.bytes_written += data.len();
+        }
+
+        fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+            self.storage[offset..offset + len].to_vec()
+        }
+    }
+
+    #[test]
+    fn test_journal() {
+        let mut target = StorageTarget { storage: vec![0; 128], bytes_written: 0 };
+        let mut storage = Storage::open(&target, 8, 16, 64);
+        assert_eq!(storage.data(), [0; 16]);
+
+        storage.write(2, &[1, 2, 3]);
+        storage.write(3, &[5]);
+        storage.write(10, &[0]);
+        assert!(storage.has_pending_writes());
+        storage.flush(&mut target);
+        assert!(!storage.has_pending_writes());
+        // A single entry with 3 bytes of data and a terminator.
+        assert_eq!(target.bytes_written, 8 + 3 + 2 + 1);
+
+        let reopened = Storage::open(&target, 8, 16, 64);
+        assert_eq!(reopened.read(2, 3), [1, 5, 3]);
+
+        // An interrupted entry is ignored.
+        storage.write(0, &[9]);
+        storage.flush(&mut target);
+        target.storage[8 + 16 + 4 + 13 + 8] = 7;
+        let reopened = Storage::open(&target, 8, 16, 64);
+        assert_eq!(reopened.read(0, 4), [0, 0, 1, 5]);
+    }
+
+    #[test]
+    fn test_compaction() {
+        let mut target = StorageTarget { storage: vec![0; 64], bytes_written: 0 };
+        let mut storage = Storage::open(&target, 0, 8, 40);
+        for i in 0..20 {
+            storage.write(i % 8, &[i as u8 + 1]);
+            storage.flush(&mut target);
+            let reopened = Storage::open(&target, 0, 8, 40);
+            assert_eq!(reopened.data(), storage.data());
+        }
+        assert_eq!(storage.data(), [17, 18, 19, 20, 13, 14, 15, 16]);
+    }
+
+    #[test]
+    fn test_torn_flush() {
+        let mut target = StorageTarget { storage: vec![0; 64], bytes_written: 0 };
+        let mut storage = Storage::open(&target, 0, 4, 52);
+        // Four entries of 11 bytes fill the journal, so the fifth flush writes a new snapshot.
+        for i in 0..5 {
+            storage.write(0, &[i + 1]);
+            storage.flush(&mut target);
+        }
+
+        // The flush is interrupted before its terminator is written, so that the
+        // entry is followed by the entries from before the snapshot.
+        let before = target.storage.clone();
+        storage.write(1, &[9]);
+        storage.flush(&mut target);
+        let terminator = 4 + 4 + 11;
+        target.storage[terminator] = before[terminator];
+
+        let reopened = Storage::open(&target, 0, 4, 52);
+        assert_eq!(reopened.data(), [5, 9, 0, 0]);
+    }
+
+    #[test]
+    fn test_generation_wrap() {
+        let mut target = StorageTarget { storage: vec![0; 64], bytes_written: 0 };
+        let mut storage = Storage::open(&target, 0, 4, 52);
+        // Fill the journal of the first generation with entries for the last byte.
+        for i in 0..4 {
+            storage.write(3, &[i + 1]);
+            storage.flush(&mut target);
+        }
+
+        // After 256 more snapshots, the entries of the first generation are still in storage.
+        storage.write(3, &[0]);
+        for _ in 0..256 {
+            storage.compact(&mut target);
+        }
+        assert_eq!(Storage::open(&target, 0, 4, 52).data(), [0; 4]);
+
+        // A torn flush must not make them valid again.
+        let before = target.storage.clone();
+        storage.write(0, &[9]);
+        storage.flush(&mut target);
+        let terminator = 4 + 4 + 11;
+        target.storage[terminator] = before[terminator];
+
+        let reopened = Storage::open(&target, 0, 4, 52);
+        assert_eq!(reopened.data(), [9, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let mut backend = MemoryStorage::new();
+        assert_eq!(backend.read_storage(4, 2), [0, 0]);
+        backend.write_storage(2, &[1, 2]);
+        assert_eq!(backend.data(), [0, 0, 1, 2]);
+        assert_eq!(backend.read_storage(1, 4), [0, 1, 2, 0]);
+
+        let mut storage = Storage::open(&backend, 0, 4, 32);
+        storage.write(0, &[7]);
+        storage.flush(&mut backend);
+        assert_eq!(Storage::open(&backend, 0, 4, 32).data(), [7, 0, 1, 2]);
+    }
+}
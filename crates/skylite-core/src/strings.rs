@@ -0,0 +1,88 @@
+use skylite_compress::Decoder;
+
+use crate::{decode::{read_varint, Deserialize}, SkyliteProject};
+
+/// Identifies a string in the string table of a project.
+///
+/// Strings from asset files are not stored directly in the generated types.
+/// Instead, all strings used by a project's assets are collected into a single
+/// static table, and values of type `string` are represented by a `StrId` into
+/// that table. A `StrId` is `Copy` and does not own any heap memory.
+///
+/// Use [`StrId::as_str`] to retrieve the actual string.
+///
+/// Since the string table is sorted, `StrId`s are ordered in the same way
+/// as the strings they refer to.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct StrId(u16);
+
+impl StrId {
+    #[doc(hidden)]
+    pub const fn _private_new(id: u16) -> StrId {
+        StrId(id)
+    }
+
+    #[doc(hidden)]
+    pub const fn _private_index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the string for this id from the string table of project `P`.
+    pub fn as_str<P: SkyliteProject>(self) -> &'static str {
+        P::get_str(self)
+    }
+}
+
+impl Deserialize for StrId {
+    fn deserialize(decoder: &mut dyn Decoder) -> StrId {
+        StrId(read_varint(decoder) as u16)
+    }
+}
+
+#[doc(hidden)]
+pub mod _private {
+
+    /// `const` version of `a == b`.
+    pub const fn str_eq(a: &str, b: &str) -> bool {
+        let a = a.as_bytes();
+        let b = b.as_bytes();
+        if a.len() != b.len() {
+            return false;
+        }
+
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skylite_compress::make_decoder;
+
+    use crate::decode::Deserialize;
+
+    use super::{_private::str_eq, StrId};
+
+    #[test]
+    fn test_deserialize_str_id() {
+        // Raw data (method tag 0), followed by the varints 5 and 257.
+        let input = [0, 5, 129, 2];
+        let mut decoder = make_decoder(&input);
+        assert_eq!(StrId::deserialize(decoder.as_mut()), StrId(5));
+        assert_eq!(StrId::deserialize(decoder.as_mut()), StrId(257));
+    }
+
+    #[test]
+    fn test_str_eq() {
+        assert!(str_eq("", ""));
+        assert!(str_eq("test", "test"));
+        assert!(!str_eq("test", "tests"));
+        assert!(!str_eq("test", "tent"));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::spatial::SpatialGrid;
+
+/// A change of the entries inside a [`TriggerZone`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TriggerEvent<K> {
+    /// The entry has entered the zone since the last update.
+    Enter(K),
+    /// The entry has left the zone since the last update.
+    Exit(K)
+}
+
+/// A rectangular area that reports when entries enter or leave it, e.g. for doors or cutscene triggers.
+///
+/// The zone remembers which entries were inside during the last update, and compares them with
+/// the entries that are inside now. Like with [`SpatialGrid`], the keys are typically the indices
+/// of the actors in the scene, and the owning actor decides which actors are passed to the zone:
+///
+/// ```ignore
+/// let players = scene.iter_actors(IterActors::All).enumerate()
+///     .filter_map(|(idx, actor)| as_player(actor).map(|p| (idx, p.x, p.y)));
+/// for event in actor.properties.door.update(players) {
+///     if let TriggerEvent::Enter(_) = event {
+///         controls.schedule(0, "open-door");
+///     }
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TriggerZone<K: Copy + Eq> {
+    x: i32,
+    y: i32,
+    w: u16,
+    h: u16,
+    inside: Vec<K>
+}
+
+impl<K: Copy + Eq> TriggerZone<K> {
+
+    /// Creates an empty zone covering the rectangle at (`x`, `y`) with size `w` by `h`.
+    pub fn new(x: i32, y: i32, w: u16, h: u16) -> TriggerZone<K> {
+        TriggerZone { x, y, w, h, inside: Vec::new() }
+    }
+
+    /// Moves the zone. Entries are only reported as entering or leaving on the next update.
+    pub fn set_rect(&mut self, x: i32, y: i32, w: u16, h: u16) {
+        (self.x, self.y, self.w, self.h) = (x, y, w, h);
+    }
+
+    /// Returns `true` if the position (`x`, `y`) is inside the zone.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.w as i32 && y < self.y + self.h as i32
+    }
+
+    /// Returns the entries that were inside the zone during the last update.
+    pub fn occupants(&self) -> &[K] {
+        &self.inside
+    }
+
+    /// Updates the zone with the current positions of the entries, and returns the
+    /// entries that have entered or left the zone since the last update.
+    ///
+    /// Entries that are not passed to `update` at all are treated as being outside.
+    pub fn update(&mut self, entries: impl IntoIterator<Item = (K, i32, i32)>) -> Vec<TriggerEvent<K>> {
+        let inside: Vec<K> = entries.into_iter()
+            .filter(|(_, x, y)| self.contains(*x, *y))
+            .map(|(key, _, _)| key)
+            .collect();
+        self.replace_inside(inside)
+    }
+
+    /// Like [`TriggerZone::update`], but takes the entries inside the zone from a [`SpatialGrid`].
+    pub fn update_from_grid(&mut self, grid: &SpatialGrid<K>) -> Vec<TriggerEvent<K>> {
+        let inside = grid.query_rect(self.x, self.y, self.w, self.h);
+        self.replace_inside(inside)
+    }
+
+    fn replace_inside(&mut self, inside: Vec<K>) -> Vec<TriggerEvent<K>> {
+        let mut events: Vec<TriggerEvent<K>> = self.inside.iter()
+            .filter(|key| !inside.contains(key))
+            .map(|key| TriggerEvent::Exit(*key))
+            .collect();
+        events.extend(inside.iter()
+            .filter(|key| !self.inside.contains(key))
+            .map(|key| TriggerEvent::Enter(*key)));
+        self.inside = inside;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spatial::SpatialGrid;
+
+    use super::{TriggerEvent, TriggerZone};
+
+    #[test]
+    fn test_trigger_zone() {
+        let mut zone = TriggerZone::new(0, 0, 16, 16);
+        assert_eq!(zone.update([(1, 4, 4), (2, 20, 4)]), [TriggerEvent::Enter(1)]);
+        assert_eq!(zone.update([(1, 5, 4), (2, 15, 15)]), [TriggerEvent::Enter(2)]);
+        assert_eq!(zone.occupants(), [1, 2]);
+        assert_eq!(zone.update([(1, 16, 4)]), [TriggerEvent::Exit(1), TriggerEvent::Exit(2)]);
+        assert!(zone.update([(1, 16, 4)]).is_empty());
+
+        zone.set_rect(16, 0, 8, 8);
+        assert_eq!(zone.update([(1, 16, 4)]), [TriggerEvent::Enter(1)]);
+    }
+
+    #[test]
+    fn test_trigger_zone_grid() {
+        let mut grid = SpatialGrid::new(16);
+        grid.insert(7, 40, 40);
+        let mut zone = TriggerZone::new(32, 32, 16, 16);
+        assert_eq!(zone.update_from_grid(&grid), [TriggerEvent::Enter(7)]);
+
+        grid.clear();
+        grid.insert(7, 60, 40);
+        assert_eq!(zone.update_from_grid(&grid), [TriggerEvent::Exit(7)]);
+    }
+}
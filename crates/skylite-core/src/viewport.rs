@@ -0,0 +1,115 @@
+/// Maps the logical screen of a project to a larger window.
+///
+/// The logical screen is scaled by the largest integer factor that fits into the window,
+/// and centered, leaving black bars (letterboxing) on the sides where it does not fill
+/// the window. This is meant to be used by targets for desktop platforms, where the window
+/// size is not fixed.
+///
+/// ```
+/// use skylite_core::prelude::Viewport;
+///
+/// let viewport = Viewport::fit((160, 144), (800, 600));
+/// assert_eq!(viewport.scale(), 4);
+/// assert_eq!(viewport.offset(), (80, 12));
+/// assert_eq!(viewport.window_to_logical(80, 12), Some((0, 0)));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Viewport {
+    logical_w: u16,
+    logical_h: u16,
+    scale: u32,
+    offset_x: u32,
+    offset_y: u32
+}
+
+impl Viewport {
+
+    /// Creates a `Viewport` which fits a logical screen of size `logical` into a window of size `window`.
+    ///
+    /// The scale is always at least 1, even if the window is smaller than the logical screen.
+    /// In that case, the logical screen is cropped on the right and bottom.
+    pub fn fit(logical: (u16, u16), window: (u32, u32)) -> Viewport {
+        let (logical_w, logical_h) = logical;
+        let (window_w, window_h) = window;
+        let scale = (window_w / (logical_w.max(1) as u32))
+            .min(window_h / (logical_h.max(1) as u32))
+            .max(1);
+        Viewport {
+            logical_w,
+            logical_h,
+            scale,
+            offset_x: window_w.saturating_sub(logical_w as u32 * scale) / 2,
+            offset_y: window_h.saturating_sub(logical_h as u32 * scale) / 2
+        }
+    }
+
+    /// Returns the factor by which each logical pixel is scaled.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the position of the top-left corner of the logical screen in the window.
+    pub fn offset(&self) -> (u32, u32) {
+        (self.offset_x, self.offset_y)
+    }
+
+    /// Returns the size of the scaled logical screen in the window.
+    pub fn scaled_size(&self) -> (u32, u32) {
+        (self.logical_w as u32 * self.scale, self.logical_h as u32 * self.scale)
+    }
+
+    /// Translates a position in the window, such as the pointer position, to a position on the logical screen.
+    ///
+    /// Returns `None` if the position is outside of the logical screen, i.e. on the letterbox bars.
+    pub fn window_to_logical(&self, x: i32, y: i32) -> Option<(i16, i16)> {
+        let lx = (x as i64 - self.offset_x as i64).div_euclid(self.scale as i64);
+        let ly = (y as i64 - self.offset_y as i64).div_euclid(self.scale as i64);
+        if lx < 0 || ly < 0 || lx >= self.logical_w as i64 || ly >= self.logical_h as i64 {
+            None
+        } else {
+            Some((lx as i16, ly as i16))
+        }
+    }
+
+    /// Translates a position on the logical screen to the position of the top-left corner of the
+    /// corresponding scaled pixel in the window.
+    pub fn logical_to_window(&self, x: i16, y: i16) -> (i32, i32) {
+        (
+            self.offset_x as i32 + x as i32 * self.scale as i32,
+            self.offset_y as i32 + y as i32 * self.scale as i32
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Viewport;
+
+    #[test]
+    fn test_viewport_fit() {
+        let viewport = Viewport::fit((160, 144), (800, 600));
+        assert_eq!(viewport.scale(), 4);
+        assert_eq!(viewport.offset(), (80, 12));
+        assert_eq!(viewport.scaled_size(), (640, 576));
+
+        // Window smaller than the logical screen.
+        let viewport = Viewport::fit((160, 144), (100, 100));
+        assert_eq!(viewport.scale(), 1);
+        assert_eq!(viewport.offset(), (0, 0));
+    }
+
+    #[test]
+    fn test_viewport_coordinates() {
+        let viewport = Viewport::fit((160, 144), (800, 600));
+        assert_eq!(viewport.window_to_logical(80, 12), Some((0, 0)));
+        assert_eq!(viewport.window_to_logical(83, 15), Some((0, 0)));
+        assert_eq!(viewport.window_to_logical(84, 16), Some((1, 1)));
+        assert_eq!(viewport.window_to_logical(719, 587), Some((159, 143)));
+        assert_eq!(viewport.window_to_logical(79, 12), None);
+        assert_eq!(viewport.window_to_logical(720, 100), None);
+        assert_eq!(viewport.window_to_logical(-5, -5), None);
+
+        assert_eq!(viewport.logical_to_window(0, 0), (80, 12));
+        assert_eq!(viewport.logical_to_window(159, 143), (716, 584));
+    }
+}
@@ -1,3 +1,4 @@
 '((name . TestProject1)
   (tile-types . (solid non-solid semi-solid))
+  (globals . ((steps u16 0)))
   (initial-scene . (test_scene #f 5)))
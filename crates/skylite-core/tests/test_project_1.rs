@@ -1,16 +1,16 @@
+use std::cell::Cell;
+
 use skylite_proc::skylite_project;
 use skylite_mock::MockTarget;
-use skylite_core::SkyliteTarget;
+use skylite_core::prelude::*;
 
 skylite_proc::actor_definition! {
-    use skylite_core::DrawContext;
-    use skylite_core::actors::Actor;
-    use skylite_core::scenes::Scene;
-    use skylite_core::ProjectControls;
+    use skylite_core::prelude::*;
 
     skylite_proc::asset_file!("./tests/test-project-1/project.scm", "test_actor");
 
     skylite_proc::properties! {
+        #[skylite_proc::observed]
         pub x: i16,
         pub y: i16
     }
@@ -21,17 +21,18 @@ skylite_proc::actor_definition! {
     }
 
     #[skylite_proc::action("move")]
-    fn r#move(actor: &mut TestActor, _scene: &mut dyn Scene<P=TestProject1>, _controls: &mut ProjectControls<TestProject1>, dx: i8, dy: i8) {
+    fn r#move(actor: &mut TestActor, _scene: &mut dyn Scene<P=TestProject1>, controls: &mut ProjectControls<TestProject1>, dx: i8, dy: i8) {
         actor.properties.x += dx as i16;
         actor.properties.y += dy as i16;
+        controls.globals_mut().steps += 1;
     }
 
     #[skylite_proc::action("idle")]
     fn idle(_actor: &mut TestActor, _scene: &mut dyn Scene<P=TestProject1>, _controls: &mut ProjectControls<TestProject1>) {}
 
     #[skylite_proc::action("set-position")]
-    fn set_position(actor: &mut TestActor, _scene: &mut dyn Scene<P=TestProject1>, _controls: &mut ProjectControls<TestProject1>, x: i16, y: i16) {
-        actor.properties.x = x;
+    fn set_position(actor: &mut TestActor, _scene: &mut dyn Scene<P=TestProject1>, controls: &mut ProjectControls<TestProject1>, x: i16, y: i16) {
+        actor.properties.set_x(x, controls);
         actor.properties.y = y;
 
         // Change the current action by using a variant from the actor's action type.
@@ -39,7 +40,7 @@ skylite_proc::actor_definition! {
     }
 
     #[skylite_proc::render]
-    fn render(_actor: &TestActor, _ctx: &DrawContext<TestProject1>) {
+    fn render(_actor: &TestActor, _ctx: &mut DrawContext<TestProject1>) {
         // Draw something to the screen.
     }
 }
@@ -61,6 +62,18 @@ skylite_proc::scene_definition! {
 }
 
 
+thread_local! {
+    /// Makes `pre_render` draw the test graphics, for the tests which check the draw statistics.
+    static DRAW_TEST_GRAPHICS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// An 8x8 atlas for the `MockTarget`, which uses one byte per pixel followed by the width of the atlas.
+const TEST_ATLAS: [u8; 65] = {
+    let mut atlas = [1; 65];
+    atlas[64] = 8;
+    atlas
+};
+
 skylite_project! {
 
     skylite_proc::project_file!("./tests/test-project-1/project.scm");
@@ -71,4 +84,90 @@ skylite_project! {
     fn pre_update(project: &mut TestProject1) {
 
     }
+
+    #[skylite_proc::pre_render]
+    fn pre_render(ctx: &mut DrawContext<TestProject1>) {
+        if !DRAW_TEST_GRAPHICS.get() {
+            return;
+        }
+
+        // The focus starts at the center of the screen, so positions in the main layer are screen coordinates.
+        ctx.draw_sub(&TEST_ATLAS, 10, 10, 0, 0, 8, 8, false, false, false);
+        ctx.draw_sub(&TEST_ATLAS, 124, 0, 0, 0, 8, 8, false, false, false);
+
+        ctx.push_clip(20, 20, 4, 2);
+        ctx.draw_sub(&TEST_ATLAS, 18, 18, 0, 0, 8, 8, true, false, false);
+        ctx.draw_sub(&TEST_ATLAS, 40, 40, 0, 0, 8, 8, false, false, false);
+        ctx.pop_clip();
+
+        ctx.layer = RenderLayer::Ui;
+        ctx.draw_sub(&TEST_ATLAS, 0, 0, 0, 0, 2, 3, false, false, true);
+        ctx.layer = RenderLayer::Main;
+    }
+}
+
+#[test]
+fn test_new_with_scene() {
+    let mut project = TestProject1::new_with_scene(MockTarget::new(), Box::new(TestScene::new(true, 10)));
+    project.update();
+    project.render();
+    assert_eq!(project.draw_stats(), DrawStats::default());
+
+    let scene = project.scene();
+    assert_eq!(scene.scene_id(), assets::scene_id("TestScene").unwrap());
+    assert_eq!(scene.actor_names(), ["actor-1", "actor-2"]);
+
+    // The actors from the scene definition are spawned with their initial positions,
+    // and keep them, since their initial action is `idle`.
+    let positions = |which: IterActors| scene.iter_actors(which)
+        .map(|actor| {
+            let actor = scene.get_actor_as::<TestActor>(actor.handle()).unwrap();
+            (actor.properties.x, actor.properties.y)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(positions(IterActors::Named), [(10, 10), (20, 20)]);
+    assert_eq!(positions(IterActors::Extra), [(30, 30)]);
+}
+
+#[cfg(feature = "debug-draw-stats")]
+#[test]
+fn test_draw_stats() {
+    let mut project = TestProject1::new_with_scene(MockTarget::new(), Box::new(TestScene::new(true, 10)));
+    DRAW_TEST_GRAPHICS.set(true);
+    project.render();
+    DRAW_TEST_GRAPHICS.set(false);
+
+    let stats = project.draw_stats();
+    // The second region is partly outside of the screen, and only a 4x2 part of the third region
+    // is inside of the clipping rectangle. The fourth region is clipped completely and not drawn.
+    assert_eq!(stats.layer(RenderLayer::Main), LayerStats { draw_calls: 3, pixels: 64 + 32 + 8 });
+    assert_eq!(stats.layer(RenderLayer::Ui), LayerStats { draw_calls: 1, pixels: 6 });
+    assert_eq!(stats.layer(RenderLayer::Background), LayerStats::default());
+    assert_eq!(stats.total(), LayerStats { draw_calls: 4, pixels: 110 });
+}
+
+#[test]
+fn test_builder() {
+    let mut project = TestProject1Builder::new(MockTarget::new())
+        .scene(Box::new(TestScene::new(true, 10)))
+        .build();
+    project.update();
+    project.render();
+}
+
+#[derive(skylite_proc::SkyliteSerialize, skylite_proc::SkyliteDeserialize, PartialEq, Debug)]
+struct SaveSlot {
+    name: String,
+    level: u8,
+    position: (i16, i16)
+}
+
+#[test]
+fn test_derive_serialize() {
+    let slot = SaveSlot { name: "Test".to_owned(), level: 3, position: (-5, 20) };
+    // Raw data (method tag 0).
+    let mut data = vec![0];
+    slot.serialize(&mut data);
+    let mut decoder = make_decoder(&data);
+    assert_eq!(SaveSlot::deserialize(decoder.as_mut()), slot);
 }
@@ -0,0 +1,351 @@
+use std::{fs::read_to_string, path::Path};
+
+use crate::{format::migrate, scheme_util::{eval_asset, parse_symbol, with_guile}, SkyliteModelError};
+
+use super::{guile::{scm_car, scm_cdr, scm_is_false, scm_is_null, scm_list_p, scm_pair_p, SCM}, project::AssetGroup, scheme_util::{assq_str, form_to_string, iter_list, parse_string}, values::{parse_argument_list, parse_variable_definition, TypedValue, ValueType, Variable}};
+
+#[derive(Debug, PartialEq)]
+pub struct Action {
+    pub name: String,
+    pub params: Vec<Variable>,
+    pub description: Option<String>
+}
+
+impl Action {
+    pub(crate) fn from_scheme(def: SCM, value_types: &[ValueType], prelude: &str) -> Result<Action, SkyliteModelError> {
+        unsafe {
+            if scm_is_false(scm_list_p(def)) && !scm_is_null(def) {
+                return Err(SkyliteModelError::DataError(format!("Expected list for action definition, got {}", form_to_string(def))));
+            }
+
+            let name = parse_symbol(scm_car(def))?;
+
+            let tail = scm_cdr(def);
+            if scm_is_null(tail) {
+                return Ok(Action { name, params: Vec::new(), description: None });
+            }
+
+            let params = iter_list(scm_car(tail))?
+                .map(|p| parse_variable_definition(p, value_types, prelude))
+                .collect::<Result<Vec<Variable>, SkyliteModelError>>()?;
+
+            let tail = scm_cdr(tail);
+            if scm_is_null(tail) {
+                return Ok(Action { name, params, description: None });
+            }
+
+            let description = Some(parse_string(scm_car(tail))?);
+
+            Ok(Action {
+                name, params, description
+            })
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ActionInstance {
+    pub name: String,
+    pub args: Vec<TypedValue>
+}
+
+impl ActionInstance {
+    pub(crate) fn from_scheme(def: SCM, actions: &[Action], prelude: &str) -> Result<ActionInstance, SkyliteModelError> {
+        unsafe {
+            if scm_is_false(scm_pair_p(def)) && !scm_is_null(def) {
+                return Err(SkyliteModelError::DataError(format!("Expected list for action instantiation, got {}", form_to_string(def))));
+            }
+
+            let name = parse_symbol(scm_car(def))?;
+            let action = match actions.iter().find(|a| a.name == name) {
+                Some(a) => a,
+                None => return Err(SkyliteModelError::DataError(format!("No action {} found", name)))
+            };
+
+            let args = parse_argument_list(scm_cdr(def), &action.params, prelude)?;
+
+            Ok(ActionInstance {
+                name, args
+            })
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Actor {
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: Vec<Variable>,
+    pub actions: Vec<Action>,
+    pub initial_action: ActionInstance
+}
+
+/// Merges the `items` of a derived actor into the items inherited from its base actor.
+///
+/// Items of the derived actor replace inherited items with the same name, while keeping
+/// the inherited item's position. New items are appended at the end.
+fn merge_named<T>(inherited: Vec<T>, items: Vec<T>, name: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut out = inherited;
+    for item in items {
+        match out.iter().position(|i| name(i) == name(&item)) {
+            Some(idx) => out[idx] = item,
+            None => out.push(item)
+        }
+    }
+    out
+}
+
+impl Actor {
+    /// Parses an actor from its Scheme definition.
+    ///
+    /// If the actor extends another actor, the already parsed `base` actor must be given.
+    /// Parameters and actions are inherited from `base`, and can be overridden by redefining
+    /// them under the same name. The initial action is inherited as well, unless it is given
+    /// explicitly. Numeric expressions in the definition are evaluated with the project's `prelude`.
+    pub(crate) fn from_scheme(def: SCM, name: &str, base: Option<Actor>, value_types: &[ValueType], prelude: &str) -> Result<Actor, SkyliteModelError> {
+        unsafe {
+            if scm_is_false(scm_pair_p(def)) && !scm_is_null(def) {
+                return Err(SkyliteModelError::DataError(format!("Expected list for actor, got {}", form_to_string(def))));
+            }
+
+            let maybe_parameters = assq_str("parameters", def)?;
+            let maybe_actions = assq_str("actions", def)?;
+            let maybe_initial_action = assq_str("initial-action", def)?;
+            // The description documents a single asset, so it is not inherited.
+            let description = match assq_str("description", def)? {
+                Some(d) => Some(parse_string(d)?),
+                None => None
+            };
+
+            let (base_parameters, base_actions, base_initial_action) = match base {
+                Some(b) => (b.parameters, b.actions, Some(b.initial_action)),
+                None => (Vec::new(), Vec::new(), None)
+            };
+
+            let parameters = if let Some(ps) = maybe_parameters {
+                iter_list(ps)?
+                    .map(|p| parse_variable_definition(p, value_types, prelude))
+                    .collect::<Result<Vec<Variable>, SkyliteModelError>>()?
+            } else {
+                Vec::new()
+            };
+            let parameters = merge_named(base_parameters, parameters, |p| &p.name);
+
+            let actions = if let Some(cs) = maybe_actions {
+                iter_list(cs)?
+                    .map(|a| if scm_is_false(scm_pair_p(a)) {
+                        Err(SkyliteModelError::DataError(format!("Expected (name params [description]) for action definition, got {}", form_to_string(a))))
+                    } else {
+                        Action::from_scheme(a, value_types, prelude)
+                    })
+                    .collect::<Result<Vec<Action>, SkyliteModelError>>()?
+            } else {
+                Vec::new()
+            };
+            let overridden_actions: Vec<String> = actions.iter().map(|a| a.name.clone()).collect();
+            let actions = merge_named(base_actions, actions, |a| &a.name);
+            if actions.is_empty() {
+                return Err(SkyliteModelError::DataError(format!("Actor must contain at least one action")));
+            }
+
+            let initial_action = if let Some(action) = maybe_initial_action {
+                ActionInstance::from_scheme(action, &actions, prelude)?
+            } else {
+                match base_initial_action {
+                    // The arguments of the inherited initial action were parsed for the base
+                    // actor's version of the action, so they cannot be reused if the action was overridden.
+                    Some(instance) if overridden_actions.contains(&instance.name) =>
+                        return Err(SkyliteModelError::DataError(format!("Inherited initial action {} is overridden, so 'initial-action' must be given explicitly", instance.name))),
+                    Some(instance) => instance,
+                    None => return Err(SkyliteModelError::DataError(format!("Missing required field 'initial-action'")))
+                }
+            };
+
+            Ok(Actor {
+                name: name.to_owned(), description, parameters, actions, initial_action
+            })
+        }
+    }
+
+    /// Loads an actor from an actor asset file.
+    ///
+    /// If the actor extends another actor, the base actor is resolved from `actor_assets`.
+    /// Parameter types can refer to the project's `value_types`, and the definition can use
+    /// the procedures from the project's `prelude`.
+    pub fn from_file(path: &Path, actor_assets: &AssetGroup, value_types: &[ValueType], prelude: &str) -> Result<Actor, SkyliteModelError> {
+        Actor::from_file_extended_by(path, actor_assets, value_types, prelude, &[])
+    }
+
+    /// Loads an actor as the base of the actors in `extended_by`, which are the actors that are
+    /// currently being loaded, starting with the most derived one. This is used to detect cycles.
+    fn from_file_extended_by(path: &Path, actor_assets: &AssetGroup, value_types: &[ValueType], prelude: &str, extended_by: &[String]) -> Result<Actor, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn from_file_guile(args: &(&Path, &AssetGroup, &[ValueType], &str, &[String])) -> Result<Actor, SkyliteModelError> {
+            let (path, actor_assets, value_types, prelude, extended_by) = *args;
+            let name = actor_assets.asset_name(path);
+            if extended_by.contains(&name) {
+                let cycle = extended_by.iter().skip_while(|n| **n != name).chain([&name]);
+                return Err(SkyliteModelError::DataError(format!("Cycle in actor inheritance: {}", cycle.map(String::as_str).collect::<Vec<&str>>().join(" -> "))));
+            }
+
+            let definition_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading actor definition: {}", e)))?;
+            let definition = unsafe {
+                migrate(eval_asset(&definition_raw, prelude)?, &format!("Actor {}", name))?
+            };
+
+            let base = match unsafe { assq_str("extends", definition)? } {
+                Some(base_name) => {
+                    let base_name = unsafe { parse_symbol(base_name)? };
+                    let (_, base_path) = actor_assets.find_asset(&base_name)?;
+                    let extended_by: Vec<String> = extended_by.iter().cloned().chain([name.clone()]).collect();
+                    Some(Actor::from_file_extended_by(&base_path, actor_assets, value_types, prelude, &extended_by)?)
+                },
+                None => None
+            };
+            Actor::from_scheme(definition, &name, base, value_types, prelude)
+        }
+
+        with_guile(from_file_guile, &(path, actor_assets, value_types, prelude, extended_by))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, remove_dir_all, write};
+
+    use crate::{actors::{Action, ActionInstance, TypedValue}, project::asset_group_from_single, scheme_util::{eval_str, with_guile}, values::{Type, Variable}, SkyliteModelError};
+
+    use super::{merge_named, Actor};
+
+
+    extern "C" fn test_parse_actor_impl(_: &()) {
+        unsafe {
+            let def = eval_str("
+                '((parameters . ((x u16) (y u16)))
+                  (actions .
+                    ((action1 ((dx u8) (dy u8)) \"action 1\")
+                     (action2 ((val u8)) \"test\")
+                     (action3)))
+                   (initial-action . (action2 5))
+                   (description . \"An actor for testing\"))").unwrap();
+            let actor = Actor::from_scheme(def, "TestActor", None, &[], "").unwrap();
+            assert_eq!(actor, Actor {
+                name: "TestActor".to_owned(),
+                description: Some("An actor for testing".to_owned()),
+                parameters: vec![
+                    Variable { name: "x".to_owned(), typename: Type::U16, documentation: None, default: None },
+                    Variable { name: "y".to_owned(), typename: Type::U16, documentation: None, default: None },
+                ],
+                actions: vec![
+                    Action {
+                        name: "action1".to_owned(),
+                        params: vec![
+                            Variable { name: "dx".to_owned(), typename: Type::U8, documentation: None, default: None },
+                            Variable { name: "dy".to_owned(), typename: Type::U8, documentation: None, default: None }
+                        ],
+                        description: Some("action 1".to_owned())
+                    },
+                    Action {
+                        name: "action2".to_owned(),
+                        params: vec![
+                            Variable { name: "val".to_owned(), typename: Type::U8, documentation: None, default: None }
+                        ],
+                        description: Some("test".to_owned())
+                    },
+                    Action {
+                        name: "action3".to_owned(),
+                        params: vec![],
+                        description: None
+                    }
+                ],
+                initial_action: ActionInstance { name: "action2".to_owned(), args: vec![TypedValue::U8(5)] }
+            });
+        }
+    }
+
+    #[test]
+    fn test_parse_actor() {
+        with_guile(test_parse_actor_impl, &());
+    }
+
+    extern "C" fn test_parse_derived_actor_impl(_: &()) {
+        unsafe {
+            let base_def = eval_str("
+                '((parameters . ((x u16) (y u16)))
+                  (actions .
+                    ((action1 ((dx u8) (dy u8)) \"action 1\")
+                     (action2 ((val u8)) \"test\")))
+                  (initial-action . (action2 5)))").unwrap();
+            let base = Actor::from_scheme(base_def, "BaseActor", None, &[], "").unwrap();
+
+            let def = eval_str("
+                '((extends . base-actor)
+                  (parameters . ((y i16) (z u8)))
+                  (actions .
+                    ((action1 ((dx i8)) \"overridden\")
+                     (action3))))").unwrap();
+            let actor = Actor::from_scheme(def, "DerivedActor", Some(base), &[], "").unwrap();
+            assert_eq!(actor, Actor {
+                name: "DerivedActor".to_owned(),
+                description: None,
+                parameters: vec![
+                    Variable { name: "x".to_owned(), typename: Type::U16, documentation: None, default: None },
+                    Variable { name: "y".to_owned(), typename: Type::I16, documentation: None, default: None },
+                    Variable { name: "z".to_owned(), typename: Type::U8, documentation: None, default: None },
+                ],
+                actions: vec![
+                    Action {
+                        name: "action1".to_owned(),
+                        params: vec![
+                            Variable { name: "dx".to_owned(), typename: Type::I8, documentation: None, default: None }
+                        ],
+                        description: Some("overridden".to_owned())
+                    },
+                    Action {
+                        name: "action2".to_owned(),
+                        params: vec![
+                            Variable { name: "val".to_owned(), typename: Type::U8, documentation: None, default: None }
+                        ],
+                        description: Some("test".to_owned())
+                    },
+                    Action {
+                        name: "action3".to_owned(),
+                        params: vec![],
+                        description: None
+                    }
+                ],
+                initial_action: ActionInstance { name: "action2".to_owned(), args: vec![TypedValue::U8(5)] }
+            });
+        }
+    }
+
+    #[test]
+    fn test_parse_derived_actor() {
+        with_guile(test_parse_derived_actor_impl, &());
+    }
+
+    #[test]
+    fn test_extends_cycle() {
+        let test_dir = std::env::temp_dir().join(format!("skylite_actor_cycle_{}", std::process::id()));
+        create_dir(&test_dir).unwrap();
+        write(test_dir.join("a.scm"), "'((extends . b))").unwrap();
+        write(test_dir.join("b.scm"), "'((extends . c))").unwrap();
+        write(test_dir.join("c.scm"), "'((extends . b))").unwrap();
+
+        let actor_assets = asset_group_from_single("./*.scm", &test_dir);
+        match Actor::from_file(&test_dir.join("a.scm"), &actor_assets, &[], "") {
+            Err(SkyliteModelError::DataError(msg)) => assert_eq!(msg, "Cycle in actor inheritance: b -> c -> b"),
+            res => panic!("Expected DataError, got {:?}", res)
+        }
+
+        remove_dir_all(test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_named() {
+        let merged = merge_named(vec![("a", 1), ("b", 2)], vec![("b", 3), ("c", 4)], |item| item.0);
+        assert_eq!(merged, vec![("a", 1), ("b", 3), ("c", 4)]);
+    }
+}
@@ -0,0 +1,85 @@
+use std::{fs::read_to_string, path::Path};
+
+use crate::{format::{is_format_entry, migrate}, scheme_util::{eval_asset, iter_list, with_guile}, util::{change_case, IdentCase}, SkyliteModelError};
+
+use super::{project::{parse_global, AssetGroup}, values::{ValueType, Variable}};
+
+/// A config asset, i.e. a set of named constants which can be tuned without changing code.
+///
+/// A config file evaluates to a list of entries of the form `(name type value)`,
+/// the same as the entries in the `globals` section of the project definition:
+///
+/// ```scheme
+/// '((gravity f32 0.25)
+///   (jump-speed f32 4.5)
+///   (coyote-frames u8 6))
+/// ```
+///
+/// The list can also contain a `(format . N)` entry, see `crate::format`.
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub name: String,
+    pub values: Vec<Variable>
+}
+
+impl Config {
+    pub fn from_file(path: &Path, name: &str, value_types: &[ValueType], prelude: &str) -> Result<Config, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn from_file_guile(params: &(&Path, &str, &[ValueType], &str)) -> Result<Config, SkyliteModelError> {
+            let (path, name, value_types, prelude) = *params;
+            let definition_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading config {}: {}", path.display(), e)))?;
+            let values = unsafe {
+                let definition = migrate(eval_asset(&definition_raw, prelude)?, &format!("Config {}", name))?;
+                iter_list(definition)?
+                    .filter(|entry| !is_format_entry(*entry))
+                    .map(|entry| parse_global(entry, value_types, prelude))
+                    .collect::<Result<Vec<Variable>, SkyliteModelError>>()?
+            };
+            let name = change_case(name, IdentCase::UpperCamelCase);
+            Ok(Config { name, values })
+        }
+
+        with_guile(from_file_guile, &(path, name, value_types, prelude))
+    }
+}
+
+/// Loads all configs in an asset group.
+pub fn load_configs(group: &AssetGroup, value_types: &[ValueType], prelude: &str) -> Result<Vec<Config>, SkyliteModelError> {
+    group.into_iter()
+        .map(|path_res| {
+            let path = path_res.map_err(|err| SkyliteModelError::OtherError(format!("GlobError: {}", err)))?;
+            Config::from_file(&path, &group.asset_name(&path), value_types, prelude)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, remove_dir_all, write};
+
+    use crate::{project::asset_group_from_single, values::{Type, TypedValue, Variable}};
+
+    use super::{load_configs, Config};
+
+    #[test]
+    fn test_load_configs() {
+        let test_dir = std::env::temp_dir().join(format!("skylite_configs_{}", std::process::id()));
+        create_dir(&test_dir).unwrap();
+        write(test_dir.join("physics.scm"), "'((gravity f32 0.25) (max-fall-speed u8 6))").unwrap();
+
+        let configs = load_configs(&asset_group_from_single("./*.scm", &test_dir), &[], "").unwrap();
+        assert_eq!(configs, vec![
+            Config {
+                name: "Physics".to_owned(),
+                values: vec![
+                    Variable { name: "gravity".to_owned(), typename: Type::F32, documentation: None, default: Some(TypedValue::F32(0.25)) },
+                    Variable { name: "max-fall-speed".to_owned(), typename: Type::U8, documentation: None, default: Some(TypedValue::U8(6)) }
+                ]
+            }
+        ]);
+
+        remove_dir_all(test_dir).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+use std::{fs::read_to_string, path::{Path, PathBuf}};
+
+use crate::{guile::{scm_car, scm_cdr, scm_is_symbol, scm_is_true, scm_pair_p, SCM}, project::AssetGroup, scheme_util::{cxr, eval_asset, form_to_string, iter_list, parse_int, parse_string, parse_symbol, with_guile, CXROp::{CAR, CDR}}, util::{change_case, IdentCase}, SkyliteModelError};
+
+/// Scheme expression which applies an encoder to an asset.
+///
+/// The encoder must return either a bytevector or a list of integers
+/// in the range 0-255.
+fn encode_expr(encoder_raw: &str, asset_raw: &str) -> String {
+    format!("\
+        (let* ((encode {})
+               (result (encode {})))
+          (if (bytevector? result) (bytevector->u8-list result) result))", encoder_raw, asset_raw)
+}
+
+/// A kind of asset which is not known to Skylite.
+///
+/// Custom asset kinds are declared in the `custom` section of the project's assets:
+///
+/// ```scheme
+/// (custom
+///   (nav-mesh "./nav-meshes/*.scm"
+///     (encoder "./plugins/nav-mesh.scm")
+///     (type "crate::nav::NavMesh")))
+/// ```
+///
+/// The encoder is a Scheme file which evaluates to a procedure. The procedure
+/// is called with the evaluated content of each asset file and returns the encoded asset.
+/// Both the encoder and the asset files can use the procedures from the project's `prelude`.
+/// If a type is given, it must implement `skylite_core::prelude::Deserialize`, and
+/// accessors which decode the assets into that type are generated.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CustomAssetKind {
+    pub name: String,
+    pub assets: AssetGroup,
+    pub encoder: PathBuf,
+    pub rust_type: Option<String>
+}
+
+/// A single encoded custom asset.
+#[derive(Debug, PartialEq)]
+pub struct CustomAsset {
+    pub name: String,
+    pub data: Vec<u8>
+}
+
+impl CustomAssetKind {
+    pub(crate) fn from_scheme(def: SCM, base_dir: &Path) -> Result<CustomAssetKind, SkyliteModelError> {
+        unsafe {
+            let name = parse_symbol(cxr(def, &[CAR])?)?;
+
+            let mut encoder = None;
+            let mut rust_type = None;
+            let mut group_entries = Vec::new();
+            for entry in iter_list(scm_cdr(def))? {
+                let option = if scm_is_true(scm_pair_p(entry)) && scm_is_symbol(scm_car(entry)) {
+                    Some(parse_symbol(scm_car(entry))?)
+                } else {
+                    None
+                };
+                match option.as_deref() {
+                    Some("encoder") => encoder = Some(base_dir.join(parse_string(cxr(entry, &[CDR, CAR])?)?)),
+                    Some("type") => rust_type = Some(parse_string(cxr(entry, &[CDR, CAR])?)?),
+                    _ => group_entries.push(entry)
+                }
+            }
+
+            let encoder = encoder.ok_or_else(|| SkyliteModelError::DataError(format!("Custom asset kind {} is missing an encoder", name)))?;
+
+            Ok(CustomAssetKind {
+                assets: AssetGroup::from_entries(&group_entries, base_dir)?,
+                name,
+                encoder,
+                rust_type
+            })
+        }
+    }
+
+    /// Runs the encoder on all assets of this kind, with the procedures from the project's `prelude`.
+    pub fn encode_assets(&self, prelude: &str) -> Result<Vec<CustomAsset>, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn encode_guile(params: &(&str, &Path, &str, &str)) -> Result<CustomAsset, SkyliteModelError> {
+            let (encoder_raw, path, name, prelude) = *params;
+            let asset_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading asset {}: {}", path.display(), e)))?;
+            let data = unsafe {
+                let result = eval_asset(&encode_expr(encoder_raw, &asset_raw), prelude)?;
+                iter_list(result)
+                    .map_err(|_| SkyliteModelError::DataError(format!("Encoder must return a bytevector or list of bytes, got {}", form_to_string(result))))?
+                    .map(|b| parse_int::<u8>(b))
+                    .collect::<Result<Vec<u8>, SkyliteModelError>>()?
+            };
+            let name = change_case(name, IdentCase::UpperCamelCase);
+            Ok(CustomAsset { name, data })
+        }
+
+        let encoder_raw = read_to_string(&self.encoder)
+            .map_err(|e| SkyliteModelError::OtherError(format!("Error reading encoder {}: {}", self.encoder.display(), e)))?;
+        self.assets.into_iter()
+            .map(|path_res| {
+                let path = path_res.map_err(|err| SkyliteModelError::OtherError(format!("GlobError: {}", err)))?;
+                let name = self.assets.asset_name(&path);
+                with_guile(encode_guile, &(encoder_raw.as_str(), path.as_path(), name.as_str(), prelude))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::{create_dir, remove_dir_all, write}, path::PathBuf};
+
+    use crate::scheme_util::{eval_str, with_guile};
+
+    use super::{CustomAsset, CustomAssetKind};
+
+    extern "C" fn test_custom_assets_impl(test_dir: &PathBuf) {
+        let def = unsafe { eval_str("'(doubled \"./data/*.scm\" (encoder \"./double.scm\") (type \"crate::Doubled\"))").unwrap() };
+        let kind = CustomAssetKind::from_scheme(def, test_dir).unwrap();
+        assert_eq!(kind.name, "doubled");
+        assert_eq!(kind.encoder, test_dir.join("./double.scm"));
+        assert_eq!(kind.rust_type.as_deref(), Some("crate::Doubled"));
+
+        let assets = kind.encode_assets("(define factor 2)").unwrap();
+        assert_eq!(assets, vec![
+            CustomAsset { name: "FirstAsset".to_owned(), data: vec![2, 4, 6] }
+        ]);
+    }
+
+    #[test]
+    fn test_custom_assets() {
+        let test_dir = std::env::temp_dir().join(format!("skylite_custom_{}", std::process::id()));
+        create_dir(&test_dir).unwrap();
+        create_dir(test_dir.join("data")).unwrap();
+        write(test_dir.join("double.scm"), "(lambda (asset) (map (lambda (x) (* factor x)) asset))").unwrap();
+        write(test_dir.join("data/first_asset.scm"), "'(1 2 3)").unwrap();
+
+        with_guile(test_custom_assets_impl, &test_dir);
+
+        remove_dir_all(test_dir).unwrap();
+    }
+}
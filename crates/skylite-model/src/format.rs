@@ -0,0 +1,101 @@
+use crate::{guile::{scm_car, scm_cdr, scm_is_false, scm_is_integer, scm_is_symbol, scm_pair_p, SCM}, scheme_util::{assq_str, parse_int, parse_symbol}, SkyliteModelError};
+
+/// The newest version of the asset format, which is the one understood by this version of skylite-proc.
+///
+/// The project definition and the actor, scene and config files declare the version
+/// they were written for with a `(format . N)` entry. Files without this entry use version 1.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Upgrades a definition from one version of the asset format to the next.
+type Migration = unsafe fn(SCM) -> Result<SCM, SkyliteModelError>;
+
+/// Registered migrations, where `MIGRATIONS[i]` upgrades a definition from version `i + 1` to `i + 2`.
+///
+/// When the asset format changes, `FORMAT_VERSION` is incremented and a migration for the
+/// previous version is added here, so that existing asset files keep their meaning.
+const MIGRATIONS: &[Migration] = &[];
+
+const _: () = assert!(MIGRATIONS.len() == FORMAT_VERSION as usize - 1, "Every old format version needs a migration");
+
+/// Returns the format version declared by `definition`.
+///
+/// Only an integer is accepted as the version, since a config can contain a value named `format`.
+unsafe fn format_version(definition: SCM) -> Result<u32, SkyliteModelError> {
+    if scm_is_false(scm_pair_p(definition)) {
+        return Ok(1);
+    }
+    match assq_str("format", definition)? {
+        Some(version) if scm_is_integer(version) != 0 => parse_int(version),
+        _ => Ok(1)
+    }
+}
+
+/// Returns `true` if `entry` is the `(format . N)` entry of a definition.
+pub(crate) unsafe fn is_format_entry(entry: SCM) -> bool {
+    !scm_is_false(scm_pair_p(entry))
+        && scm_is_symbol(scm_car(entry))
+        && parse_symbol(scm_car(entry)).is_ok_and(|s| s == "format")
+        && scm_is_integer(scm_cdr(entry)) != 0
+}
+
+unsafe fn migrate_with(definition: SCM, what: &str, current: u32, migrations: &[Migration]) -> Result<SCM, SkyliteModelError> {
+    let version = format_version(definition)?;
+    if version == 0 {
+        return Err(SkyliteModelError::DataError(format!("{}: Invalid asset format 0, versions start at 1", what)));
+    }
+    if version > current {
+        return Err(SkyliteModelError::DataError(format!("{} uses asset format {}, but this version of skylite only supports formats up to {}. Update skylite to use this file.", what, version, current)));
+    }
+
+    let mut out = definition;
+    for migration in &migrations[version as usize - 1 .. current as usize - 1] {
+        out = migration(out)?;
+    }
+    Ok(out)
+}
+
+/// Upgrades `definition` to the current asset format, using the registered migrations.
+///
+/// Returns an error if `definition` uses a newer format than this version of skylite-proc.
+/// `what` describes the definition for error messages.
+pub(crate) unsafe fn migrate(definition: SCM, what: &str) -> Result<SCM, SkyliteModelError> {
+    migrate_with(definition, what, FORMAT_VERSION, MIGRATIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{guile::SCM, scheme_util::{assq_str, eval_str, with_guile}, SkyliteModelError};
+
+    use super::{is_format_entry, migrate_with, Migration};
+
+    unsafe fn mark_migrated(_: SCM) -> Result<SCM, SkyliteModelError> {
+        eval_str("'((format . 2) (migrated . #t))")
+    }
+
+    extern "C" fn test_migrate_impl(_: &()) {
+        unsafe {
+            let migrations: &[Migration] = &[mark_migrated];
+
+            let old = eval_str("'((actions))").unwrap();
+            assert!(assq_str("migrated", migrate_with(old, "Test", 2, migrations).unwrap()).unwrap().is_some());
+
+            let current = eval_str("'((format . 2) (actions))").unwrap();
+            assert!(assq_str("migrated", migrate_with(current, "Test", 2, migrations).unwrap()).unwrap().is_none());
+
+            let newer = eval_str("'((format . 3) (actions))").unwrap();
+            assert!(migrate_with(newer, "Test", 2, migrations).is_err());
+
+            // Config values named `format` are not a format version.
+            let config = eval_str("'((format u8 3))").unwrap();
+            assert!(migrate_with(config, "Test", 1, &[]).is_ok());
+
+            assert!(is_format_entry(eval_str("'(format . 1)").unwrap()));
+            assert!(!is_format_entry(eval_str("'(format u8 3)").unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_migrate() {
+        with_guile(test_migrate_impl, &());
+    }
+}
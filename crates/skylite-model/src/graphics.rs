@@ -0,0 +1,80 @@
+use std::{fs::read_to_string, path::Path};
+
+use crate::{scheme_util::{eval_asset, form_to_string, iter_list, parse_int, with_guile}, util::{change_case, IdentCase}, SkyliteModelError};
+
+use super::project::AssetGroup;
+
+/// A graphics asset, i.e. a texture atlas.
+///
+/// A graphics file evaluates to a bytevector or a list of integers in the range 0-255.
+/// The data is passed to `SkyliteTarget::draw_sub` unchanged, so it must be in the
+/// format expected by the target.
+#[derive(Debug, PartialEq)]
+pub struct Graphic {
+    pub name: String,
+    pub data: Vec<u8>
+}
+
+impl Graphic {
+
+    /// Loads a graphics file. The file is evaluated with the procedures from the project's `prelude`.
+    pub fn from_file(path: &Path, name: &str, prelude: &str) -> Result<Graphic, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn from_file_guile(params: &(&Path, &str, &str)) -> Result<Graphic, SkyliteModelError> {
+            let (path, name, prelude) = *params;
+            let definition_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading graphics {}: {}", path.display(), e)))?;
+            let data = unsafe {
+                let definition = eval_asset(&format!("(let ((result {})) (if (bytevector? result) (bytevector->u8-list result) result))", definition_raw), prelude)?;
+                iter_list(definition)
+                    .map_err(|_| SkyliteModelError::DataError(format!("Graphics must be a bytevector or list of bytes, got {}", form_to_string(definition))))?
+                    .map(|b| parse_int::<u8>(b))
+                    .collect::<Result<Vec<u8>, SkyliteModelError>>()?
+            };
+            let name = change_case(name, IdentCase::UpperCamelCase);
+            Ok(Graphic { name, data })
+        }
+
+        with_guile(from_file_guile, &(path, name, prelude))
+    }
+}
+
+/// Loads all graphics in an asset group, with the procedures from the project's `prelude`.
+pub fn load_graphics(group: &AssetGroup, prelude: &str) -> Result<Vec<Graphic>, SkyliteModelError> {
+    group.into_iter()
+        .map(|path_res| {
+            let path = path_res.map_err(|err| SkyliteModelError::OtherError(format!("GlobError: {}", err)))?;
+            Graphic::from_file(&path, &group.asset_name(&path), prelude)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, remove_dir_all, write};
+
+    use crate::project::asset_group_from_single;
+
+    use super::{load_graphics, Graphic};
+
+    #[test]
+    fn test_load_graphics() {
+        let test_dir = std::env::temp_dir().join(format!("skylite_graphics_{}", std::process::id()));
+        create_dir(&test_dir).unwrap();
+        write(test_dir.join("tiles.scm"), "'(1 2 3)").unwrap();
+        write(test_dir.join("player_sprites.scm"), "#u8(4 5)").unwrap();
+        write(test_dir.join("stripes.scm"), "(stripes 2)").unwrap();
+
+        let prelude = "(define (stripes n) (if (= n 0) '() (cons 0 (cons 255 (stripes (- n 1))))))";
+        let mut graphics = load_graphics(&asset_group_from_single("./*.scm", &test_dir), prelude).unwrap();
+        graphics.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(graphics, vec![
+            Graphic { name: "PlayerSprites".to_owned(), data: vec![4, 5] },
+            Graphic { name: "Stripes".to_owned(), data: vec![0, 255, 0, 255] },
+            Graphic { name: "Tiles".to_owned(), data: vec![1, 2, 3] }
+        ]);
+
+        remove_dir_all(test_dir).unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+//! Parser for Skylite projects.
+//!
+//! This crate contains the part of `skylite-proc` which reads the project definition and the asset
+//! files. It is a separate crate so that external tools, such as editors, exporters or linters, can
+//! read projects with the same parser that is used by the macros.
+//!
+//! The asset files are evaluated with Guile. The entry points, [`project::SkyliteProjectStub::from_file`]
+//! and [`project::SkyliteProject::from_stub`], as well as the `from_file` functions of the individual
+//! assets, enter Guile on their own.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use std::path::Path;
+//!
+//! use skylite_model::project::{SkyliteProject, SkyliteProjectStub};
+//!
+//! fn main() -> Result<(), skylite_model::SkyliteModelError> {
+//!     let stub = SkyliteProjectStub::from_file(Path::new("project.scm"), None)?;
+//!     let project = SkyliteProject::from_stub(stub)?;
+//!     for actor in &project.actors {
+//!         println!("{}: {} parameters", actor.name, actor.parameters.len());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+pub(crate) mod guile;
+pub mod project;
+pub mod actors;
+pub mod scenes;
+pub mod config;
+pub mod custom;
+pub mod format;
+pub mod graphics;
+pub(crate) mod placements;
+pub(crate) mod scheme_util;
+pub mod util;
+pub mod values;
+
+/// An error while reading a project or one of its assets.
+#[derive(Debug, Clone)]
+pub enum SkyliteModelError {
+    /// An exception raised while evaluating Scheme code, with the formatted message and a backtrace.
+    GuileException { message: String, backtrace: String },
+    DataError(String),
+    SyntaxError(String),
+    OtherError(String)
+}
+
+impl std::fmt::Display for SkyliteModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::GuileException { message, backtrace } => write!(f, "Scheme Exception: {}\nBacktrace:\n{}", message, backtrace),
+            Self::DataError(str) => write!(f, "Data Error: {}", str),
+            Self::SyntaxError(str) => write!(f, "Syntax Error: {}", str),
+            Self::OtherError(str) => write!(f, "Error: {}", str)
+        }
+    }
+}
+
+impl std::error::Error for SkyliteModelError {}
@@ -0,0 +1,286 @@
+use std::{fs::read_to_string, iter::Peekable, path::Path, str::Chars};
+
+use crate::{guile::SCM, scheme_util::{assq_str, eval_str, iter_list, parse_string}, SkyliteModelError};
+
+/// A JSON value, as far as it is needed for placement files.
+///
+/// Numbers keep their source text, since they are passed on to Scheme unchanged.
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>)
+}
+
+struct JsonParser<'a> {
+    chars: Peekable<Chars<'a>>
+}
+
+impl<'a> JsonParser<'a> {
+    fn error(&self, msg: &str) -> SkyliteModelError {
+        SkyliteModelError::DataError(format!("Invalid JSON: {}", msg))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SkyliteModelError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(&format!("Expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(&format!("Expected '{}', found end of input", expected)))
+        }
+    }
+
+    fn parse_keyword(&mut self, keyword: &str, value: Json) -> Result<Json, SkyliteModelError> {
+        for expected in keyword.chars() {
+            if self.chars.next() != Some(expected) {
+                return Err(self.error(&format!("Expected {}", keyword)));
+            }
+        }
+        Ok(value)
+    }
+
+    fn parse_string(&mut self) -> Result<String, SkyliteModelError> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let code: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        let c = u32::from_str_radix(&code, 16).ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error(&format!("Invalid escape \\u{}", code)))?;
+                        out.push(c);
+                    },
+                    Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                    _ => return Err(self.error("Invalid escape sequence in string"))
+                },
+                Some(c) => out.push(c),
+                None => return Err(self.error("Unterminated string"))
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Json, SkyliteModelError> {
+        let mut out = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')) {
+            out.push(c);
+        }
+        if out.parse::<f64>().is_err() {
+            return Err(self.error(&format!("Invalid number {}", out)));
+        }
+        Ok(Json::Number(out))
+    }
+
+    fn parse_value(&mut self) -> Result<Json, SkyliteModelError> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('n') => self.parse_keyword("null", Json::Null),
+            Some('t') => self.parse_keyword("true", Json::Bool(true)),
+            Some('f') => self.parse_keyword("false", Json::Bool(false)),
+            Some('"') => self.parse_string().map(Json::String),
+            Some('[') => {
+                self.chars.next();
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.chars.next_if_eq(&']').is_some() {
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => continue,
+                        Some(']') => return Ok(Json::Array(items)),
+                        _ => return Err(self.error("Expected ',' or ']' in array"))
+                    }
+                }
+            },
+            Some('{') => {
+                self.chars.next();
+                let mut entries = Vec::new();
+                self.skip_whitespace();
+                if self.chars.next_if_eq(&'}').is_some() {
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    let key = self.parse_string()?;
+                    self.expect(':')?;
+                    entries.push((key, self.parse_value()?));
+                    self.skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => self.skip_whitespace(),
+                        Some('}') => return Ok(Json::Object(entries)),
+                        _ => return Err(self.error("Expected ',' or '}' in object"))
+                    }
+                }
+            },
+            Some(_) => self.parse_number(),
+            None => Err(self.error("Unexpected end of input"))
+        }
+    }
+}
+
+fn parse_json(input: &str) -> Result<Json, SkyliteModelError> {
+    let mut parser = JsonParser { chars: input.chars().peekable() };
+    let out = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.chars.next().is_some() {
+        return Err(parser.error("Unexpected data after value"));
+    }
+    Ok(out)
+}
+
+/// Returns `name` if it can be used as a Scheme symbol without quoting.
+fn scheme_symbol(name: &str) -> Result<&str, SkyliteModelError> {
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit() || c == '#')
+        && name.chars().all(|c| c.is_alphanumeric() || "-_!?*+/<>=.".contains(c));
+    if valid {
+        Ok(name)
+    } else {
+        Err(SkyliteModelError::DataError(format!("Invalid name in placements: {:?}", name)))
+    }
+}
+
+/// Converts a JSON value to the source of the equivalent Scheme datum.
+///
+/// Arrays become lists and objects become alists with symbols as keys, which is the form
+/// used for tuples, vecs and value types in the asset files. `null` becomes the symbol `none`.
+fn json_to_scheme(value: &Json) -> Result<String, SkyliteModelError> {
+    Ok(match value {
+        Json::Null => "none".to_owned(),
+        Json::Bool(true) => "#t".to_owned(),
+        Json::Bool(false) => "#f".to_owned(),
+        Json::Number(n) => n.clone(),
+        Json::String(s) => {
+            let mut out = String::from("\"");
+            for c in s.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    c if (c as u32) < 0x20 => out.push_str(&format!("\\x{:x};", c as u32)),
+                    c => out.push(c)
+                }
+            }
+            out.push('"');
+            out
+        },
+        Json::Array(items) => format!("({})", items.iter().map(json_to_scheme).collect::<Result<Vec<String>, SkyliteModelError>>()?.join(" ")),
+        Json::Object(entries) => format!("({})", entries.iter()
+            .map(|(k, v)| Ok(format!("({} . {})", scheme_symbol(k)?, json_to_scheme(v)?)))
+            .collect::<Result<Vec<String>, SkyliteModelError>>()?
+            .join(" "))
+    })
+}
+
+/// Converts a single placement to an actor instance form with named arguments,
+/// and the name of the instance, if any.
+fn placement_to_scheme(placement: &Json) -> Result<(Option<String>, String), SkyliteModelError> {
+    let Json::Object(entries) = placement else {
+        return Err(SkyliteModelError::DataError("Each placement must be a JSON object".to_owned()));
+    };
+
+    let mut name = None;
+    let mut actor = None;
+    let mut args = Vec::new();
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("name", Json::String(s)) => name = Some(scheme_symbol(s)?.to_owned()),
+            ("type", Json::String(s)) => actor = Some(scheme_symbol(s)?.to_owned()),
+            ("x" | "y", value) => args.push(format!("({} . {})", key, json_to_scheme(value)?)),
+            ("args", Json::Object(named_args)) => for (arg, value) in named_args {
+                args.push(format!("({} . {})", scheme_symbol(arg)?, json_to_scheme(value)?));
+            },
+            (other, _) => return Err(SkyliteModelError::DataError(format!("Unexpected entry {} in placement", other)))
+        }
+    }
+
+    let actor = actor.ok_or_else(|| SkyliteModelError::DataError("Placement is missing the actor type".to_owned()))?;
+    Ok((name, format!("({} {})", actor, args.join(" "))))
+}
+
+/// Converts the content of a placement file to the Scheme source of the named actors and the extras.
+fn placements_to_scheme(input: &str) -> Result<(String, String), SkyliteModelError> {
+    let Json::Array(placements) = parse_json(input)? else {
+        return Err(SkyliteModelError::DataError("A placement file must contain a JSON array".to_owned()));
+    };
+
+    let mut named = Vec::new();
+    let mut extras = Vec::new();
+    for placement in &placements {
+        match placement_to_scheme(placement)? {
+            (Some(name), instance) => named.push(format!("({} . {})", name, instance)),
+            (None, instance) => extras.push(instance)
+        }
+    }
+    Ok((format!("'({})", named.join(" ")), format!("'({})", extras.join(" "))))
+}
+
+/// Loads the placement file given by the `(placements . "file.json")` entry of a scene,
+/// and returns the named actors and extras it contains, in the same form as the `actors`
+/// and `extras` entries of the scene. Without a placement file, both lists are empty.
+///
+/// The path of the placement file is relative to `base_dir`, the directory of the scene file.
+pub(crate) unsafe fn load_placements(definition: SCM, base_dir: &Path) -> Result<(Vec<SCM>, Vec<SCM>), SkyliteModelError> {
+    let Some(file) = assq_str("placements", definition)? else { return Ok((Vec::new(), Vec::new())) };
+    let path = base_dir.join(parse_string(file)?);
+    let input = read_to_string(&path)
+        .map_err(|e| SkyliteModelError::OtherError(format!("Error reading placements {}: {}", path.display(), e)))?;
+    let (named, extras) = placements_to_scheme(&input)
+        .map_err(|e| SkyliteModelError::DataError(format!("{}: {}", path.display(), e)))?;
+    Ok((
+        iter_list(eval_str(&named)?)?.collect(),
+        iter_list(eval_str(&extras)?)?.collect()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_json, placements_to_scheme, Json};
+
+    #[test]
+    fn test_parse_json() {
+        assert_eq!(
+            parse_json(r#" {"a": [1, -2.5e3, true, null], "b\n": "x\"A"} "#).unwrap(),
+            Json::Object(vec![
+                ("a".to_owned(), Json::Array(vec![Json::Number("1".to_owned()), Json::Number("-2.5e3".to_owned()), Json::Bool(true), Json::Null])),
+                ("b\n".to_owned(), Json::String("x\"A".to_owned()))
+            ])
+        );
+        assert_eq!(parse_json("[]").unwrap(), Json::Array(vec![]));
+        assert!(parse_json("[1, 2").is_err());
+        assert!(parse_json("{\"a\" 1}").is_err());
+        assert!(parse_json("[1] 2").is_err());
+        assert!(parse_json("tru").is_err());
+    }
+
+    #[test]
+    fn test_placements_to_scheme() {
+        let input = r#"[
+            {"name": "door-1", "type": "door", "x": 16, "y": 32, "args": {"target": "level-2"}},
+            {"type": "coin", "x": 8, "y": 8},
+            {"type": "spawner", "args": {"wave": {"count": 3, "delay": 0.5}, "enemies": ["slime", "bat"], "boss": null}}
+        ]"#;
+        let (named, extras) = placements_to_scheme(input).unwrap();
+        assert_eq!(named, "'((door-1 . (door (x . 16) (y . 32) (target . \"level-2\"))))");
+        assert_eq!(extras, "'((coin (x . 8) (y . 8)) (spawner (wave . ((count . 3) (delay . 0.5))) (enemies . (\"slime\" \"bat\")) (boss . none)))");
+
+        assert!(placements_to_scheme("{}").is_err());
+        assert!(placements_to_scheme(r#"[{"x": 1}]"#).is_err());
+        assert!(placements_to_scheme(r#"[{"type": "coin) (evil"}]"#).is_err());
+    }
+}
@@ -0,0 +1,1123 @@
+use std::fs::{metadata, read_to_string};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR_STR};
+use std::sync::{Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::guile::{scm_car, scm_is_false, scm_is_real, scm_is_symbol, scm_is_true, scm_list_p, scm_pair_p, scm_string_p, SCM};
+use crate::scheme_util::{
+    CXROp::{CAR, CDR},
+    {assq_str, form_to_string, parse_int, parse_string, parse_symbol, cxr, eval_asset, eval_str, iter_list, with_guile}
+};
+use crate::format::migrate;
+use crate::util::{change_case, IdentCase};
+use crate::SkyliteModelError;
+use glob::{GlobError, Pattern};
+use skylite_compress::CompressionMethods;
+
+use super::actors::Actor;
+use super::{config::{load_configs, Config}, custom::{CustomAsset, CustomAssetKind}, graphics::{load_graphics, Graphic}};
+use super::scenes::{Scene, SceneInstance};
+use super::values::{parse_type, parse_typed_value, Type, TypedValue, ValueType, Variable};
+
+
+fn normalize_glob(glob: &str, base_dir: &Path) -> String {
+    if Path::new(&glob).is_relative() {
+        base_dir.to_str().unwrap().to_owned() + MAIN_SEPARATOR_STR + &glob
+    } else {
+        glob.to_owned()
+    }
+}
+
+/// The order in which the update phases of the actors are run, see `UpdateMode` in `skylite_core`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UpdateMode {
+    Nested,
+    Phased
+}
+
+unsafe fn parse_update_mode(form: SCM) -> Result<UpdateMode, SkyliteModelError> {
+    match parse_symbol(form)?.as_str() {
+        "nested" => Ok(UpdateMode::Nested),
+        "phased" => Ok(UpdateMode::Phased),
+        other => Err(SkyliteModelError::DataError(format!("Unknown update mode: {}. Expected nested or phased", other)))
+    }
+}
+
+/// The largest value of the varints in the encoded data, which are used for lengths, counts and ids.
+///
+/// Varints are always limited to `u32`. With `U16`, data with larger values is rejected at compile time,
+/// which guarantees that every varint takes up at most three bytes and fits into a `u16` on the target.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CountWidth {
+    U16,
+    U32
+}
+
+impl CountWidth {
+    pub fn max(self) -> usize {
+        match self {
+            CountWidth::U16 => u16::MAX as usize,
+            CountWidth::U32 => u32::MAX as usize
+        }
+    }
+}
+
+unsafe fn parse_count_width(form: SCM) -> Result<CountWidth, SkyliteModelError> {
+    match parse_symbol(form)?.as_str() {
+        "u16" => Ok(CountWidth::U16),
+        "u32" => Ok(CountWidth::U32),
+        other => Err(SkyliteModelError::DataError(format!("Unknown count width: {}. Expected u16 or u32", other)))
+    }
+}
+
+/// Which numeric types may be used in the types of the assets.
+///
+/// With `FixedOnly`, `f32` and `f64` are rejected, so that an asset edit cannot introduce
+/// floating-point math into builds which must be deterministic, e.g. for lockstep netplay.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Numerics {
+    Any,
+    FixedOnly
+}
+
+impl Numerics {
+    /// Checks that `typename` is allowed. `item` describes where the type is used, for the error message.
+    pub fn check(self, typename: &Type, item: &str) -> Result<(), SkyliteModelError> {
+        if self == Numerics::FixedOnly && typename.contains_float() {
+            return Err(SkyliteModelError::DataError(format!("{} has floating-point type {:?}, which is not allowed with (numerics . fixed-only)", item, typename)));
+        }
+        Ok(())
+    }
+
+    fn check_all(self, variables: &[Variable], item: &str) -> Result<(), SkyliteModelError> {
+        variables.iter().try_for_each(|v| self.check(&v.typename, &format!("{} {}", item, v.name)))
+    }
+}
+
+unsafe fn parse_numerics(form: SCM) -> Result<Numerics, SkyliteModelError> {
+    match parse_symbol(form)?.as_str() {
+        "any" => Ok(Numerics::Any),
+        "fixed-only" => Ok(Numerics::FixedOnly),
+        other => Err(SkyliteModelError::DataError(format!("Unknown numerics: {}. Expected any or fixed-only", other)))
+    }
+}
+
+/// Collects the project's constants, which are declared with `(define-const NAME expr)` entries
+/// in the project definition, and returns their definitions as Scheme source.
+///
+/// The definitions are prepended to the project's prelude, so the constants are bound in the same
+/// `let` as the prelude when an asset is evaluated, see `eval_asset`. This makes them available in the
+/// expressions of numeric asset values, as well as in the code of the asset files and the prelude.
+/// Each constant can refer to the constants declared before it.
+unsafe fn define_constants(definition: SCM) -> Result<String, SkyliteModelError> {
+    let mut constants = String::new();
+    for entry in iter_list(definition)? {
+        if !scm_is_true(scm_pair_p(entry)) || !scm_is_symbol(scm_car(entry)) || parse_symbol(scm_car(entry))? != "define-const" {
+            continue;
+        }
+        let name = parse_symbol(cxr(entry, &[CDR, CAR])?)
+            .map_err(|_| SkyliteModelError::DataError(format!("Expected (define-const NAME expr), found {}", form_to_string(entry))))?;
+        let value = eval_asset(&form_to_string(cxr(entry, &[CDR, CDR, CAR])?), &constants)?;
+        if scm_is_real(value) == 0 {
+            return Err(SkyliteModelError::DataError(format!("Constant {} must be a number, found {}", name, form_to_string(value))));
+        }
+        constants.push_str(&format!("(define {} {})\n", name, form_to_string(value)));
+    }
+    Ok(constants)
+}
+
+/// The layout of the save slots in persistent storage, see `SaveSlots` in `skylite_core`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SaveSlotsConfig {
+    pub offset: usize,
+    pub slot_size: usize,
+    pub count: usize
+}
+
+impl SaveSlotsConfig {
+    /// Parses the `save-slots` entry of the project definition, which is an alist of the
+    /// form `((count . <n>) (size . <bytes>) (offset . <bytes>))`. The offset is optional
+    /// and defaults to 0.
+    unsafe fn from_scheme(alist: SCM) -> Result<SaveSlotsConfig, SkyliteModelError> {
+        let count = assq_str("count", alist)?
+            .ok_or(SkyliteModelError::DataError(format!("Missing required field 'count' in save-slots")))?;
+        let slot_size = assq_str("size", alist)?
+            .ok_or(SkyliteModelError::DataError(format!("Missing required field 'size' in save-slots")))?;
+        let offset = match assq_str("offset", alist)? {
+            Some(o) => parse_int(o)?,
+            None => 0
+        };
+        let slot_size = parse_int::<u16>(slot_size)
+            .map_err(|_| SkyliteModelError::DataError(format!("Invalid save slot size {}, expected at most 65535 bytes", form_to_string(slot_size))))?;
+        Ok(SaveSlotsConfig { offset, slot_size: slot_size as usize, count: parse_int(count)? })
+    }
+}
+
+/// Parses a chain of compression methods.
+///
+/// The chain is either the symbol `none`, in which case the data is stored
+/// uncompressed, or a list of compression method names, e.g. `(lz77 rc)`.
+pub(crate) unsafe fn parse_compression(form: SCM) -> Result<Vec<CompressionMethods>, SkyliteModelError> {
+    if scm_is_symbol(form) && parse_symbol(form)? == "none" {
+        return Ok(Vec::new());
+    }
+
+    iter_list(form)?
+        .map(|m| {
+            let name = parse_symbol(m)?;
+            CompressionMethods::from_name(&name)
+                .ok_or_else(|| SkyliteModelError::DataError(format!("Unknown compression method: {}", name)))
+        })
+        .collect()
+}
+
+/// A collection of similar assets, e.g. tilesets or maps.
+///
+/// An `AssetGroup` is represented by a set of globs for matching
+/// the files containing the assets. If a glob is relative,
+/// it is resolved relative to the directory containing the
+/// project definition file.
+///
+/// An `AssetGroup` can also specify the compression methods
+/// used for its assets, through an entry `(compression <methods>)`
+/// among the globs.
+///
+/// With an entry `(namespaced)`, the names of the assets include the
+/// directories between the start of the glob and the asset file, see
+/// `AssetGroup::asset_name`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AssetGroup {
+    globs: Vec<String>,
+    pub compression: Option<Vec<CompressionMethods>>,
+    pub namespaced: bool
+}
+
+/// Returns the part of `glob` before the first component containing a wildcard.
+fn glob_base(glob: &str) -> PathBuf {
+    Path::new(glob).components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect()
+}
+
+impl AssetGroup {
+    fn from_scheme(list: SCM, base_dir: &Path) -> Result<AssetGroup, SkyliteModelError> {
+        let entries = unsafe { iter_list(list)?.collect::<Vec<SCM>>() };
+        AssetGroup::from_entries(&entries, base_dir)
+    }
+
+    /// Creates an `AssetGroup` from the entries of an asset group definition,
+    /// which are globs and an optional compression setting.
+    pub(crate) fn from_entries(entries: &[SCM], base_dir: &Path) -> Result<AssetGroup, SkyliteModelError> {
+        let mut globs: Vec<String> = Vec::new();
+        let mut compression = None;
+        let mut namespaced = false;
+        unsafe {
+            for &g in entries {
+                if scm_is_true(scm_string_p(g)) {
+                    let glob = normalize_glob(&parse_string(g)?, base_dir);
+                    Pattern::new(&glob).map_err(|err| SkyliteModelError::DataError(format!("Error parsing glob: {}", err)))?;
+                    globs.push(glob);
+                } else if scm_is_true(scm_pair_p(g)) && scm_is_symbol(scm_car(g)) && parse_symbol(scm_car(g))? == "compression" {
+                    compression = Some(parse_compression(cxr(g, &[CDR, CAR])?)?);
+                } else if scm_is_true(scm_pair_p(g)) && scm_is_symbol(scm_car(g)) && parse_symbol(scm_car(g))? == "namespaced" {
+                    namespaced = true;
+                } else {
+                    return Err(SkyliteModelError::DataError(format!("Expected glob, compression setting or (namespaced), got {}", form_to_string(g))));
+                }
+            }
+        }
+
+        if globs.is_empty() {
+            return Err(SkyliteModelError::DataError(format!("Asset group must contain at least one glob")));
+        }
+        Ok(AssetGroup { globs, compression, namespaced })
+    }
+
+    /// Returns the name of the asset at `path`, which must have been matched by this `AssetGroup`.
+    ///
+    /// The name of an asset is the last component of its filename without the file extension.
+    /// If the group is namespaced, the directories between the start of the matching glob and the
+    /// file are prepended, separated by `/`. For example, with the glob `./actors/**/*.scm`, the
+    /// name of the asset at `./actors/ui/button.scm` would be `ui/button`.
+    pub fn asset_name(&self, path: &Path) -> String {
+        let stem = path.file_stem().unwrap().to_string_lossy().into_owned();
+        if !self.namespaced {
+            return stem;
+        }
+
+        self.globs.iter()
+            .find_map(|glob| path.strip_prefix(glob_base(glob)).ok())
+            .and_then(|relative| relative.parent())
+            .map(|dir| dir.components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned() + "/")
+                .collect::<String>() + &stem)
+            .unwrap_or(stem)
+    }
+
+    /// Returns an error if two assets of the group have the same name.
+    ///
+    /// `kind` is the kind of the assets, e.g. `Actor`, for the error message.
+    pub fn check_names(&self, kind: &str) -> Result<(), SkyliteModelError> {
+        let mut seen: Vec<(String, PathBuf)> = Vec::new();
+        for entry_res in self {
+            let entry = entry_res.map_err(|err| SkyliteModelError::OtherError(format!("IO Error: {}", err)))?;
+            let name = change_case(&self.asset_name(&entry), IdentCase::UpperCamelCase);
+            if let Some((_, prev_entry)) = seen.iter().find(|(n, _)| *n == name) {
+                return Err(ambiguous_name_error(kind, &name, prev_entry, &entry));
+            }
+            seen.push((name, entry));
+        }
+        Ok(())
+    }
+
+    /// Returns a unique id and the file path for a given asset name, see `AssetGroup::asset_name`.
+    /// Names are compared after normalizing them to UpperCamelCase, so the name of the asset at
+    /// `./tilesets/town_1.scm` can be given as `town-1`, `town_1` or `Town1`.
+    ///
+    /// This method will return an error if the name does not exist, or
+    /// is ambiguous among the assets matched by the `AssetGroup`.
+    ///
+    /// The ids can be used to reference a particular asset in the encoded data
+    /// of other assets.
+    pub fn find_asset(&self, name: &str) -> Result<(usize, PathBuf), SkyliteModelError> {
+        let name_camel_case = change_case(name, IdentCase::UpperCamelCase);
+
+        let mut out: Option<(usize, PathBuf)> = None;
+        for (idx, entry_res) in self.into_iter().enumerate() {
+            let entry = match entry_res {
+                Ok(e) => e,
+                Err(err) => return Err(SkyliteModelError::OtherError(format!("IO Error: {}", err)))
+            };
+
+            if change_case(&self.asset_name(&entry), IdentCase::UpperCamelCase) != name_camel_case {
+                continue;
+            }
+
+            if let Some((_, prev_entry)) = out {
+                return Err(ambiguous_name_error("Asset", name, &prev_entry, &entry));
+            }
+
+            out = Some((idx, entry));
+        }
+
+        if let Some(id_and_path) = out {
+            Ok(id_and_path)
+        } else {
+            Err(SkyliteModelError::DataError(format!("Name not found: {}", name)))
+        }
+    }
+}
+
+fn ambiguous_name_error(kind: &str, name: &str, a: &Path, b: &Path) -> SkyliteModelError {
+    SkyliteModelError::DataError(format!(
+        "{} name {} is ambiguous; both {} and {} match. Rename one of the files, or add (namespaced) \
+         to the asset group, so that the names include the directories of the files, e.g. ui/button. \
+         References to the assets of a namespaced group must then use the new names.",
+        kind, name, a.display(), b.display()))
+}
+
+pub struct AssetIterator<'base> {
+    current_iter: glob::Paths,
+    glob_idx: usize,
+    asset_group: &'base AssetGroup
+}
+
+impl<'base> Iterator for AssetIterator<'base> {
+    type Item = Result<PathBuf, GlobError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(res) = self.current_iter.next() {
+            Some(res)
+        } else if self.glob_idx < self.asset_group.globs.len() - 1 {
+            self.glob_idx += 1;
+            self.current_iter = glob::glob(&self.asset_group.globs[self.glob_idx]).unwrap();
+            self.current_iter.next()
+        } else {
+            None
+        }
+    }
+}
+
+impl<'base> IntoIterator for &'base AssetGroup {
+    type Item = Result<PathBuf, GlobError>;
+
+    type IntoIter = AssetIterator<'base>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        AssetIterator {
+            current_iter: glob::glob(&self.globs[0]).unwrap(),
+            glob_idx: 0,
+            asset_group: self
+        }
+    }
+}
+
+/// Container for `AssetGroups` for all asset types used by Skylite.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AssetGroups {
+    pub actors: AssetGroup,
+    pub scenes: AssetGroup,
+    pub plays: AssetGroup,
+    pub graphics: AssetGroup,
+    pub sprites: AssetGroup,
+    pub tilesets: AssetGroup,
+    pub maps: AssetGroup,
+    pub configs: AssetGroup,
+    pub custom: Vec<CustomAssetKind>
+}
+
+impl AssetGroups {
+    fn from_scheme(alist: SCM, base_dir: &Path) -> Result<AssetGroups, SkyliteModelError> {
+        unsafe {
+            if scm_is_false(scm_list_p(alist)) {
+                return Err(SkyliteModelError::DataError(format!("Asset directories must be defined as an associative list.")));
+            }
+            let mut out = create_default_asset_groups(base_dir);
+
+            if let Some(expr) = assq_str("actors", alist)? {
+                out.actors = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("scenes", alist)? {
+                out.scenes = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("plays", alist)? {
+                out.plays = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("graphics", alist)? {
+                out.graphics = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("sprites", alist)? {
+                out.sprites = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("tilesets", alist)? {
+                out.tilesets = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("maps", alist)? {
+                out.maps = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("configs", alist)? {
+                out.configs = AssetGroup::from_scheme(expr, base_dir)?;
+            }
+            if let Some(expr) = assq_str("custom", alist)? {
+                out.custom = iter_list(expr)?
+                    .map(|def| CustomAssetKind::from_scheme(def, base_dir))
+                    .collect::<Result<Vec<CustomAssetKind>, SkyliteModelError>>()?;
+            }
+
+            Ok(out)
+        }
+    }
+
+    /// Adds the assets of a pack to the assets of the project.
+    ///
+    /// The globs of the pack are placed after the project's own globs, so that
+    /// the ids of the project's assets do not change when a pack is added. The
+    /// compression settings and custom asset kinds of the pack are not used.
+    fn merge_pack(&mut self, pack: AssetGroups) {
+        self.actors.globs.extend(pack.actors.globs);
+        self.scenes.globs.extend(pack.scenes.globs);
+        self.plays.globs.extend(pack.plays.globs);
+        self.graphics.globs.extend(pack.graphics.globs);
+        self.sprites.globs.extend(pack.sprites.globs);
+        self.tilesets.globs.extend(pack.tilesets.globs);
+        self.maps.globs.extend(pack.maps.globs);
+        self.configs.globs.extend(pack.configs.globs);
+    }
+}
+
+/// Loads the asset groups of the asset pack in `pack_dir`.
+///
+/// An asset pack is a directory with a `pack.scm` file, which contains an `assets` section
+/// in the same format as the project definition. If the section is missing, the default
+/// asset groups relative to `pack_dir` are used.
+unsafe fn load_pack(pack_dir: &Path) -> Result<AssetGroups, SkyliteModelError> {
+    let path = pack_dir.join("pack.scm");
+    let definition_raw = read_to_string(&path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading asset pack {}: {}", path.display(), e)))?;
+    let definition = eval_str(&definition_raw)?;
+    match assq_str("assets", definition)? {
+        Some(alist) => AssetGroups::from_scheme(alist, pack_dir),
+        None => Ok(create_default_asset_groups(pack_dir))
+    }
+}
+
+pub fn asset_group_from_single(pattern: &str, base_dir: &Path) -> AssetGroup {
+    AssetGroup {
+        globs: vec![normalize_glob(pattern, base_dir)],
+        compression: None,
+        namespaced: false
+    }
+}
+
+fn create_default_asset_groups(base_dir: &Path) -> AssetGroups {
+    AssetGroups {
+        actors: asset_group_from_single("./actors/*.scm", base_dir),
+        scenes: asset_group_from_single("./scenes/*.scm", base_dir),
+        plays: asset_group_from_single("./plays/*.scm", base_dir),
+        graphics: asset_group_from_single("./graphics/*.scm", base_dir),
+        sprites: asset_group_from_single("./sprites/*.scm", base_dir),
+        tilesets: asset_group_from_single("./tilesets/*.scm", base_dir),
+        maps: asset_group_from_single("./maps/*.scm", base_dir),
+        configs: asset_group_from_single("./configs/*.scm", base_dir),
+        custom: Vec::new()
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct SaveItem {
+    name: String,
+    data: TypedValue
+}
+
+impl SaveItem {
+    fn from_scheme(definition: SCM, value_types: &[ValueType], numerics: Numerics, prelude: &str) -> Result<SaveItem, SkyliteModelError> {
+        unsafe {
+            let name = parse_symbol(cxr(definition, &[CAR])?)?;
+            let typename = parse_type(cxr(definition, &[CDR, CAR])?, value_types)?;
+            numerics.check(&typename, &format!("Save item {}", name))?;
+            Ok(SaveItem {
+                name,
+                data: parse_typed_value(
+                    &typename,
+                    cxr(definition, &[CDR, CDR, CAR])?,
+                    prelude
+                )?
+            })
+        }
+    }
+}
+
+/// Parses a global variable definition of the form `(name type default)`.
+pub(crate) fn parse_global(definition: SCM, value_types: &[ValueType], prelude: &str) -> Result<Variable, SkyliteModelError> {
+    unsafe {
+        let name = parse_symbol(cxr(definition, &[CAR])?)?;
+        let typename = parse_type(cxr(definition, &[CDR, CAR])?, value_types)?;
+        let default = parse_typed_value(&typename, cxr(definition, &[CDR, CDR, CAR])
+            .map_err(|_| SkyliteModelError::DataError(format!("Missing initial value for global {}", name)))?, prelude)?;
+        Ok(Variable {
+            name,
+            typename,
+            documentation: None,
+            default: Some(default)
+        })
+    }
+}
+
+/// Scheme procedure which resolves conditional sections in a project definition.
+///
+/// A conditional section has the form `(when (target <name>...) <entries>...)`. If the
+/// current target is one of the listed names, the entries are spliced into the surrounding
+/// alist, in front of all unconditional entries, so that they take precedence. Otherwise,
+/// the section is removed. Conditional sections are resolved at the top level of the project
+/// definition and inside the `assets` alist.
+const SELECT_TARGET_SECTIONS: &str = "\
+    (lambda (target def)
+      (define (when-section? e) (and (pair? e) (eq? (car e) 'when)))
+      (define (selected? condition)
+        (and (pair? condition)
+             (eq? (car condition) 'target)
+             (memq target (cdr condition))
+             #t))
+      (define (select alist)
+        (append
+          (apply append (map cddr (filter (lambda (e) (and (when-section? e) (selected? (cadr e)))) alist)))
+          (filter (lambda (e) (not (when-section? e))) alist)))
+      (map (lambda (e)
+             (if (and (pair? e) (eq? (car e) 'assets) (list? (cdr e)))
+                 (cons 'assets (select (cdr e)))
+                 e))
+           (select def)))";
+
+/// Checks that `target` is a valid target name, i.e. that it consists only of ASCII letters,
+/// digits, `-` and `_`, so that it can be passed to Scheme as a quoted symbol.
+fn check_target_name(target: &str) -> Result<(), SkyliteModelError> {
+    if target.is_empty() || !target.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(SkyliteModelError::DataError(format!("Invalid target name {:?}. Target names may only contain ASCII letters, digits, - and _", target)));
+    }
+    Ok(())
+}
+
+/// Evaluates the project definition `definition_raw` and resolves its conditional sections
+/// for the given `target`. If `target` is `None`, all conditional sections are removed.
+unsafe fn eval_with_target(definition_raw: &str, target: Option<&str>) -> Result<SCM, SkyliteModelError> {
+    let target_expr = match target {
+        Some(t) => {
+            check_target_name(t)?;
+            format!("'{}", t)
+        },
+        None => "#f".to_owned()
+    };
+    eval_str(&format!("({} {} {})", SELECT_TARGET_SECTIONS, target_expr, definition_raw))
+}
+
+// Early form of `SkyliteProject`, where the assets are not yet
+// resolved and parsed. Used for contexts where the full representation
+// of the project is not required, e.g. actor_definition and `scene_definition`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SkyliteProjectStub {
+    pub name: String,
+    pub assets: AssetGroups,
+    pub save_data: Vec<SaveItem>,
+    pub globals: Vec<Variable>,
+    pub initial_scene: SceneInstance,
+    pub tile_types: Vec<String>,
+    pub compression: Option<Vec<CompressionMethods>>,
+    pub update_mode: UpdateMode,
+    pub value_types: Vec<ValueType>,
+    pub save_slots: Option<SaveSlotsConfig>,
+    pub count_width: CountWidth,
+    pub numerics: Numerics,
+    /// Definitions of the project's constants, followed by the source of the prelude files.
+    /// This is evaluated together with each asset file and custom asset encoder.
+    pub prelude: String
+}
+
+impl SkyliteProjectStub {
+    /// Parses a project definition. The paths of all files that are read besides the
+    /// definition itself are added to `sources`.
+    fn from_scheme(definition: SCM, project_root: &Path, sources: &mut Vec<PathBuf>) -> Result<SkyliteProjectStub, SkyliteModelError> {
+        unsafe {
+            let name = parse_symbol(
+                assq_str("name", definition)?.ok_or(SkyliteModelError::DataError("Missing required field 'name'".to_owned()))?
+            )?;
+
+            let mut assets = if let Some(alist) = assq_str("assets", definition)? {
+                AssetGroups::from_scheme(alist, &project_root)?
+            } else {
+                create_default_asset_groups(&project_root)
+            };
+
+            if let Some(list) = assq_str("packs", definition)? {
+                for pack in iter_list(list)? {
+                    let pack_dir = project_root.join(parse_string(pack)?);
+                    assets.merge_pack(load_pack(&pack_dir)?);
+                    sources.push(pack_dir.join("pack.scm"));
+                }
+            }
+
+            let mut prelude = define_constants(definition)?;
+            if let Some(list) = assq_str("prelude", definition)? {
+                for file in iter_list(list)? {
+                    let path = project_root.join(parse_string(file)?);
+                    let source = read_to_string(&path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading prelude {}: {}", path.display(), e)))?;
+                    prelude.push_str(&source);
+                    prelude.push('\n');
+                    sources.push(path);
+                }
+            }
+
+            let numerics = match assq_str("numerics", definition)? {
+                Some(n) => parse_numerics(n)?,
+                None => Numerics::Any
+            };
+
+            // Value types are parsed first, since all other types can refer to them.
+            // Each value type can only refer to the value types declared before it.
+            let mut value_types = Vec::new();
+            if let Some(list) = assq_str("value-types", definition)? {
+                for def in iter_list(list)? {
+                    let value_type = ValueType::from_scheme(def, &value_types, &prelude)?;
+                    numerics.check_all(&value_type.fields, &format!("Field of value type {}:", value_type.name))?;
+                    value_types.push(value_type);
+                }
+            }
+
+            let save_data = if let Some(list) = assq_str("save-data", definition)? {
+                iter_list(list)?
+                    .map(|item| SaveItem::from_scheme(item, &value_types, numerics, &prelude))
+                    .collect::<Result<Vec<SaveItem>, SkyliteModelError>>()?
+            } else {
+                Vec::new()
+            };
+
+            let globals = if let Some(list) = assq_str("globals", definition)? {
+                iter_list(list)?
+                    .map(|global| parse_global(global, &value_types, &prelude))
+                    .collect::<Result<Vec<Variable>, SkyliteModelError>>()?
+            } else {
+                Vec::new()
+            };
+            numerics.check_all(&globals, "Global")?;
+
+            let initial_scene = {
+                let instance_def = assq_str("initial-scene", definition)?.ok_or(SkyliteModelError::DataError(format!("Missing required field 'initial-scene'")))?;
+                let (_, scene_path) = assets.scenes.find_asset(&parse_symbol(scm_car(instance_def))?)?;
+                sources.push(scene_path);
+                SceneInstance::from_scheme(instance_def, &assets.scenes, &value_types, &prelude)?
+            };
+
+
+            let tile_types = if let Some(list) = assq_str("tile-types", definition)? {
+                iter_list(list)?
+                    .map(|t| parse_symbol(t))
+                    .collect::<Result<Vec<String>, SkyliteModelError>>()?
+            } else {
+                Vec::new()
+            };
+
+            if tile_types.len() == 0 {
+                return Err(SkyliteModelError::DataError("At least one tile-type must be defined.".to_owned()))
+            }
+
+            let compression = match assq_str("compression", definition)? {
+                Some(c) => Some(parse_compression(c)?),
+                None => None
+            };
+
+            let update_mode = match assq_str("update-mode", definition)? {
+                Some(m) => parse_update_mode(m)?,
+                None => UpdateMode::Nested
+            };
+
+            let save_slots = match assq_str("save-slots", definition)? {
+                Some(alist) => Some(SaveSlotsConfig::from_scheme(alist)?),
+                None => None
+            };
+
+            let count_width = match assq_str("count-width", definition)? {
+                Some(w) => parse_count_width(w)?,
+                None => CountWidth::U32
+            };
+
+            Ok(SkyliteProjectStub {
+                name,
+                assets,
+                save_data,
+                globals,
+                initial_scene,
+                tile_types,
+                compression,
+                update_mode,
+                value_types,
+                save_slots,
+                count_width,
+                numerics,
+                prelude
+            })
+        }
+    }
+
+    /// Loads a project from a project definition file.
+    ///
+    /// The file at the given `Path` will be evaluated as a Scheme file, and the
+    /// resulting form will be parsed into an instance of `SkyliteProjectStub`.
+    /// Conditional sections in the project definition are resolved against
+    /// `target`. If `target` is `None`, all conditional sections are removed.
+    ///
+    /// The parsed stubs are cached for the lifetime of the process, see `STUB_CACHE`.
+    pub fn from_file(path: &Path, target: Option<&str>) -> Result<SkyliteProjectStub, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn from_file_guile(params: &(&Path, Option<&str>)) -> Result<(SkyliteProjectStub, Vec<PathBuf>), SkyliteModelError> {
+            let (path, target) = *params;
+            let definition_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading project definition: {}", e)))?;
+            let definition = unsafe {
+                migrate(eval_with_target(&definition_raw, target)?, "Project definition")?
+            };
+
+            let project_root = path.parent().unwrap();
+            let mut sources = vec![path.to_owned()];
+            let stub = SkyliteProjectStub::from_scheme(definition, project_root, &mut sources)?;
+            Ok((stub, sources))
+        }
+
+        let resolved_path = path.canonicalize().map_err(|e| SkyliteModelError::OtherError(format!("Error resolving project path: {}", e)))?;
+        let target = target.map(str::to_owned);
+
+        // The lock is held while parsing, so that concurrent expansions wait for the
+        // first one to finish, instead of parsing the same project again.
+        let mut cache = STUB_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(entry) = cache.iter().find(|e| e.path == resolved_path && e.target == target && is_fresh(&e.sources)) {
+            return Ok(entry.stub.clone());
+        }
+
+        let (stub, sources) = with_guile(from_file_guile, &(resolved_path.as_path(), target.as_deref()))?;
+        cache.retain(|e| e.path != resolved_path || e.target != target);
+        cache.push(CachedStub {
+            path: resolved_path,
+            target,
+            sources: sources.into_iter().map(|p| { let modified = modified(&p); (p, modified) }).collect(),
+            stub: stub.clone()
+        });
+        Ok(stub)
+    }
+}
+
+struct CachedStub {
+    path: PathBuf,
+    target: Option<String>,
+    /// The files the stub was parsed from, with their modification times.
+    sources: Vec<(PathBuf, Option<SystemTime>)>,
+    stub: SkyliteProjectStub
+}
+
+/// Project stubs which have already been parsed in this process.
+///
+/// Every `actor_definition!`, `scene_definition!` and `embedded_asset!` needs the project stub,
+/// so without the cache, each expansion would parse the project definition again. Entries are
+/// keyed by the path of the project definition and the target, and are only reused
+/// while none of the files they were parsed from have been modified. This matters for
+/// long-running processes like language servers, which expand the macros repeatedly.
+static STUB_CACHE: Mutex<Vec<CachedStub>> = Mutex::new(Vec::new());
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn is_fresh(sources: &[(PathBuf, Option<SystemTime>)]) -> bool {
+    sources.iter().all(|(path, time)| modified(path) == *time)
+}
+
+/// Main type for managing the asset files and code generation
+/// of a Skylite project.
+pub struct SkyliteProject {
+    pub name: String,
+    pub actors: Vec<Actor>,
+    pub scenes: Vec<Scene>,
+    pub save_data: Vec<SaveItem>,
+    pub globals: Vec<Variable>,
+    pub initial_scene: SceneInstance,
+    pub tile_types: Vec<String>,
+    pub graphics: Vec<Graphic>,
+    pub configs: Vec<Config>,
+    pub custom: Vec<(CustomAssetKind, Vec<CustomAsset>)>,
+    pub update_mode: UpdateMode,
+    pub save_slots: Option<SaveSlotsConfig>,
+    pub count_width: CountWidth
+}
+
+/// Combines the errors from loading multiple assets into a single error, which lists all of them.
+fn combine_errors(mut errors: Vec<SkyliteModelError>) -> Result<(), SkyliteModelError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.pop().unwrap()),
+        n => Err(SkyliteModelError::DataError(format!("{} assets could not be loaded:\n{}", n, errors.iter()
+            .map(|err| format!("- {}", err))
+            .collect::<Vec<String>>()
+            .join("\n"))))
+    }
+}
+
+impl SkyliteProject {
+    pub fn from_stub(stub: SkyliteProjectStub) -> Result<SkyliteProject, SkyliteModelError> {
+        stub.assets.actors.check_names("Actor")?;
+        stub.assets.scenes.check_names("Scene")?;
+        stub.assets.graphics.check_names("Graphics")?;
+        stub.assets.configs.check_names("Config")?;
+        for kind in &stub.assets.custom {
+            kind.assets.check_names(&kind.name)?;
+        }
+
+        let actors = stub.assets.actors.into_iter()
+            .map(|path_res| {
+                let path = path_res.map_err(|err| SkyliteModelError::OtherError(format!("GlobError: {}", err.to_string())))?;
+                Actor::from_file(path.as_path(), &stub.assets.actors, &stub.value_types, &stub.prelude)
+            })
+            .collect::<Result<Vec<Actor>, SkyliteModelError>>()?;
+        for actor in &actors {
+            stub.numerics.check_all(&actor.parameters, &format!("Parameter of actor {}:", actor.name))?;
+            for action in &actor.actions {
+                stub.numerics.check_all(&action.params, &format!("Parameter of action {} of actor {}:", action.name, actor.name))?;
+            }
+        }
+
+        // All scenes are loaded before reporting errors, so that e.g. renaming an actor
+        // reports every scene that still uses the old name at once.
+        let (scenes, scene_errors): (Vec<_>, Vec<_>) = stub.assets.scenes.into_iter()
+            .map(|path_res| {
+                let path = path_res.map_err(|err| SkyliteModelError::OtherError(format!("GlobError: {}", err.to_string())))?;
+                let name = stub.assets.scenes.asset_name(&path);
+                let mut scene = Scene::from_file(path.as_path(), &name, &actors, &stub.value_types, &stub.prelude)?;
+                if scene.compression.is_none() {
+                    scene.compression = stub.assets.scenes.compression.clone().or(stub.compression.clone());
+                }
+                Ok(scene)
+            })
+            .partition(Result::is_ok);
+        let scenes: Vec<Scene> = scenes.into_iter().map(Result::unwrap).collect();
+        combine_errors(scene_errors.into_iter().map(Result::unwrap_err).collect())?;
+        for scene in &scenes {
+            stub.numerics.check_all(&scene.parameters, &format!("Parameter of scene {}:", scene.name))?;
+        }
+
+        let graphics = load_graphics(&stub.assets.graphics, &stub.prelude)?;
+        let configs = load_configs(&stub.assets.configs, &stub.value_types, &stub.prelude)?;
+        for config in &configs {
+            stub.numerics.check_all(&config.values, &format!("Value of config {}:", config.name))?;
+        }
+
+        let custom = stub.assets.custom.into_iter()
+            .map(|mut kind| {
+                if kind.assets.compression.is_none() {
+                    kind.assets.compression = stub.compression.clone();
+                }
+                let assets = kind.encode_assets(&stub.prelude)?;
+                Ok((kind, assets))
+            })
+            .collect::<Result<Vec<(CustomAssetKind, Vec<CustomAsset>)>, SkyliteModelError>>()?;
+
+        Ok(SkyliteProject {
+            name: stub.name,
+            actors,
+            scenes,
+            save_data: stub.save_data,
+            globals: stub.globals,
+            initial_scene: stub.initial_scene,
+            tile_types: stub.tile_types,
+            graphics,
+            configs,
+            custom,
+            update_mode: stub.update_mode,
+            save_slots: stub.save_slots,
+            count_width: stub.count_width
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::{create_dir, remove_dir_all, File}, path::PathBuf, str::FromStr, time::SystemTime};
+
+    use skylite_compress::CompressionMethods;
+
+    use crate::{project::{asset_group_from_single, create_default_asset_groups, eval_with_target, normalize_glob, parse_compression, AssetGroup, AssetGroups, CountWidth, Numerics, SaveItem, SaveSlotsConfig, UpdateMode}, scenes::SceneInstance, scheme_util::{assq_str, eval_str, form_to_string, parse_symbol, with_guile}, values::{Type, TypedValue, Variable}, SkyliteModelError};
+
+    use super::{combine_errors, is_fresh, modified, SkyliteProjectStub};
+
+    extern "C" fn test_project_parsing_impl(_: &()) {
+        unsafe {
+            let definition = eval_str(r#"
+                '((name . TestProject)
+                  (assets .
+                    ((actors . ("./test1/*.scm" "./test2/*.scm"))
+                     (maps . ("./test3/*.scm" (compression (lzss rc))))))
+
+                    (save-data .
+                      ((flag1 bool #f)
+                      (val2 u8 5)))
+
+                    (define-const TILE-SIZE 8)
+                    (define-const START-COINS (* TILE-SIZE 2))
+
+                    (globals .
+                      ((coins u16 (+ START-COINS 1))))
+
+                    (initial-scene . (test_scene #t 5))
+                    (tile-types . (solid semi-solid non-solid))
+                    (update-mode . phased)
+                    (count-width . u16)
+                    (numerics . fixed-only)
+                    (save-slots . ((count . 3) (size . 64))))"#).unwrap();
+
+            // Use a path to the test project to resolve the initial-scene
+            let project_root = PathBuf::from_str("../skylite-core/tests/test-project-1/").unwrap();
+            let project = SkyliteProjectStub::from_scheme(definition, &project_root, &mut Vec::new()).unwrap();
+            assert_eq!(project, SkyliteProjectStub {
+                name: "TestProject".to_owned(),
+                assets: AssetGroups {
+                    actors: AssetGroup {
+                        globs: vec![
+                            normalize_glob("./test1/*.scm", &project_root),
+                            normalize_glob("./test2/*.scm", &project_root),
+                        ],
+                        compression: None,
+                        namespaced: false
+                    },
+                    scenes: asset_group_from_single("./scenes/*.scm", &project_root),
+                    plays: asset_group_from_single("./plays/*.scm", &project_root),
+                    graphics: asset_group_from_single("./graphics/*.scm", &project_root),
+                    sprites: asset_group_from_single("./sprites/*.scm", &project_root),
+                    tilesets: asset_group_from_single("./tilesets/*.scm", &project_root),
+                    maps: AssetGroup {
+                        globs: vec![normalize_glob("./test3/*.scm", &project_root)],
+                        compression: Some(vec![CompressionMethods::LZSS4096, CompressionMethods::RC]),
+                        namespaced: false
+                    },
+                    configs: asset_group_from_single("./configs/*.scm", &project_root),
+                    custom: vec![]
+                },
+                save_data: vec![
+                    SaveItem {
+                        name: "flag1".to_owned(),
+                        data: TypedValue::Bool(false)
+                    },
+                    SaveItem {
+                        name: "val2".to_owned(),
+                        data: TypedValue::U8(5)
+                    }
+                ],
+                globals: vec![
+                    Variable {
+                        name: "coins".to_owned(),
+                        typename: Type::U16,
+                        documentation: None,
+                        default: Some(TypedValue::U16(17))
+                    }
+                ],
+                initial_scene: SceneInstance {
+                    name: "TestScene".to_owned(),
+                    args: vec![
+                        TypedValue::Bool(true),
+                        TypedValue::U8(5)
+                    ]
+                },
+                tile_types: vec!["solid".to_owned(), "semi-solid".to_owned(), "non-solid".to_owned()],
+                compression: None,
+                update_mode: UpdateMode::Phased,
+                value_types: vec![],
+                save_slots: Some(SaveSlotsConfig { offset: 0, slot_size: 64, count: 3 }),
+                count_width: CountWidth::U16,
+                numerics: Numerics::FixedOnly,
+                prelude: "(define TILE-SIZE 8)\n(define START-COINS 16)\n".to_owned()
+            });
+        }
+    }
+
+    #[test]
+    fn test_project_parsing() {
+        with_guile(test_project_parsing_impl, &());
+    }
+
+    extern "C" fn test_target_sections_impl(_: &()) {
+        unsafe {
+            let definition_raw = r#"
+                '((name . TestProject)
+                  (when (target wasm4)
+                    (name . TestProjectWasm4))
+                  (when (target desktop web)
+                    (tile-types . (solid)))
+                  (assets .
+                    ((when (target wasm4)
+                       (maps . ("./maps-small/*.scm")))
+                     (maps . ("./maps/*.scm")))))"#;
+
+            let definition = eval_with_target(definition_raw, Some("wasm4")).unwrap();
+            assert_eq!(parse_symbol(assq_str("name", definition).unwrap().unwrap()).unwrap(), "TestProjectWasm4");
+            assert!(assq_str("tile-types", definition).unwrap().is_none());
+            let maps = assq_str("maps", assq_str("assets", definition).unwrap().unwrap()).unwrap().unwrap();
+            assert_eq!(form_to_string(maps), "(\"./maps-small/*.scm\")");
+
+            let definition = eval_with_target(definition_raw, Some("web")).unwrap();
+            assert_eq!(parse_symbol(assq_str("name", definition).unwrap().unwrap()).unwrap(), "TestProject");
+            assert!(assq_str("tile-types", definition).unwrap().is_some());
+            let maps = assq_str("maps", assq_str("assets", definition).unwrap().unwrap()).unwrap().unwrap();
+            assert_eq!(form_to_string(maps), "(\"./maps/*.scm\")");
+
+            let definition = eval_with_target(definition_raw, None).unwrap();
+            assert_eq!(parse_symbol(assq_str("name", definition).unwrap().unwrap()).unwrap(), "TestProject");
+            assert!(assq_str("when", definition).unwrap().is_none());
+
+            assert!(eval_with_target(definition_raw, Some("wasm4) (exit")).is_err());
+        }
+    }
+
+    #[test]
+    fn test_target_sections() {
+        with_guile(test_target_sections_impl, &());
+    }
+
+    extern "C" fn test_parse_compression_impl(_: &()) {
+        unsafe {
+            assert_eq!(parse_compression(eval_str("'none").unwrap()).unwrap(), vec![]);
+            assert_eq!(parse_compression(eval_str("'(lz77 rc)").unwrap()).unwrap(), vec![CompressionMethods::LZ77, CompressionMethods::RC]);
+            assert_eq!(parse_compression(eval_str("'(lzss256)").unwrap()).unwrap(), vec![CompressionMethods::LZSS256]);
+            assert!(parse_compression(eval_str("'(zip)").unwrap()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_compression() {
+        with_guile(test_parse_compression_impl, &());
+    }
+
+    #[test]
+    fn test_merge_pack() {
+        let project_root = PathBuf::from_str("/game").unwrap();
+        let pack_root = PathBuf::from_str("/ui-kit").unwrap();
+        let mut assets = create_default_asset_groups(&project_root);
+        assets.merge_pack(create_default_asset_groups(&pack_root));
+
+        assert_eq!(assets.actors.globs, vec![
+            normalize_glob("./actors/*.scm", &project_root),
+            normalize_glob("./actors/*.scm", &pack_root)
+        ]);
+        assert_eq!(assets.configs.globs.len(), 2);
+    }
+
+    #[test]
+    fn test_numerics() {
+        let variables = vec![
+            Variable { name: "speed".to_owned(), typename: Type::I32, documentation: None, default: None },
+            Variable { name: "scale".to_owned(), typename: Type::Tuple(vec![Type::F32, Type::F32]), documentation: None, default: None }
+        ];
+        assert!(Numerics::Any.check_all(&variables, "Global").is_ok());
+        assert!(Numerics::FixedOnly.check(&variables[0].typename, "Global speed").is_ok());
+        assert!(Numerics::FixedOnly.check_all(&variables, "Global").is_err());
+    }
+
+    #[test]
+    fn test_calc_id_for_asset() {
+        let test_dir_name = format!("skylite_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
+        let test_dir = std::env::temp_dir().join(test_dir_name);
+        let sub_dir = test_dir.join("sub");
+        create_dir(&test_dir).unwrap();
+        create_dir(&sub_dir).unwrap();
+        drop(File::create(test_dir.clone().join("test_1.scm")).unwrap());
+        drop(File::create(test_dir.clone().join("test_2.scm")).unwrap());
+        drop(File::create(test_dir.clone().join("asset.scm")).unwrap());
+        drop(File::create(sub_dir.clone().join("asset.scm")).unwrap());
+
+        let asset_group1 = asset_group_from_single("test_?.scm", &test_dir);
+
+        // Test different casings
+        assert_eq!(asset_group1.find_asset("test_1").unwrap().0, 0);
+        assert_eq!(asset_group1.find_asset("TEST_1").unwrap().0, 0);
+        assert_eq!(asset_group1.find_asset("test-2").unwrap().0, 1);
+
+        // Test name not matched by glob
+        assert!(asset_group1.find_asset("asset").is_err());
+
+        // Test ambiguous name
+        let asset_group2 = asset_group_from_single("**/asset.scm", &test_dir);
+        assert!(asset_group2.find_asset("asset").is_err());
+        assert!(asset_group2.check_names("Actor").is_err());
+
+        // Namespaced names include the directories
+        let asset_group3 = AssetGroup { namespaced: true, ..asset_group_from_single("**/asset.scm", &test_dir) };
+        assert!(asset_group3.check_names("Actor").is_ok());
+        assert_eq!(asset_group3.asset_name(&sub_dir.join("asset.scm")), "sub/asset");
+        assert_eq!(asset_group3.find_asset("asset").unwrap().1, test_dir.join("asset.scm"));
+        assert_eq!(asset_group3.find_asset("sub/asset").unwrap().1, sub_dir.join("asset.scm"));
+
+        remove_dir_all(test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_combine_errors() {
+        assert!(combine_errors(vec![]).is_ok());
+        assert_eq!(combine_errors(vec![SkyliteModelError::OtherError("a".to_owned())]).unwrap_err().to_string(), "Error: a");
+        let combined = combine_errors(vec![
+            SkyliteModelError::DataError("Scene A references unknown actors: X".to_owned()),
+            SkyliteModelError::DataError("Scene B references unknown actors: X, Y".to_owned())
+        ]);
+        assert_eq!(combined.unwrap_err().to_string(), "Data Error: 2 assets could not be loaded:\n\
+            - Data Error: Scene A references unknown actors: X\n\
+            - Data Error: Scene B references unknown actors: X, Y");
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let test_dir = std::env::temp_dir().join(format!("skylite_fresh_{}", std::process::id()));
+        create_dir(&test_dir).unwrap();
+        let path = test_dir.join("project.scm");
+        let file = File::create(&path).unwrap();
+
+        let sources = vec![(path.clone(), modified(&path)), (test_dir.join("missing.scm"), None)];
+        assert!(is_fresh(&sources));
+
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert!(!is_fresh(&sources));
+
+        remove_dir_all(test_dir).unwrap();
+    }
+}
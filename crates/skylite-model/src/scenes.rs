@@ -0,0 +1,497 @@
+use std::{fs::read_to_string, path::Path};
+
+use skylite_compress::CompressionMethods;
+
+use crate::{format::migrate, guile::scm_pair_p, placements::load_placements, scheme_util::{eval_asset, iter_list, with_guile}, util::{change_case, IdentCase}, values::parse_variable_definition, SkyliteModelError};
+
+use super::{actors::Actor, guile::{scm_car, scm_cdr, scm_is_false, scm_is_null, scm_is_symbol, scm_is_true, scm_list_p, SCM}, project::{parse_compression, AssetGroup}, scheme_util::{assq_str, cxr, form_to_string, parse_int, parse_string, parse_symbol, CXROp::{CAR, CDR}}, values::{parse_argument_list, typed_value_from_i64, TypedValue, ValueType, Variable}};
+
+#[derive(Debug, PartialEq)]
+pub struct ActorInstance {
+    pub actor_name: String,
+    pub args: Vec<TypedValue>
+}
+
+impl ActorInstance {
+    fn from_scheme(form: SCM, actors: &[Actor], prelude: &str) -> Result<ActorInstance, SkyliteModelError> {
+        unsafe {
+            if scm_is_false(scm_list_p(form)) {
+                return Err(SkyliteModelError::DataError(format!("Expected list for actor instantiation, got {}", form_to_string(form))));
+            }
+
+            // Parse actor name
+            let actor_name = parse_symbol(scm_car(form))?;
+            let actor = match actors.iter().find(|a| a.name == actor_name) {
+                Some(a) => a,
+                None => return Err(SkyliteModelError::DataError(format!("Actor {} not found", actor_name)))
+            };
+
+            // Parse instance arguments
+            let args_raw = scm_cdr(form);
+            let args = parse_argument_list(args_raw, &actor.parameters, prelude)?;
+            Ok(ActorInstance {
+                actor_name, args
+            })
+        }
+    }
+}
+
+/// Expands a grid generator into individual actor instances.
+///
+/// A grid generator has the form `(grid <actor> (cols <n>) (rows <n>) (spacing <dx> <dy>) [(origin <x> <y>)] <args>...)`.
+/// It creates `cols * rows` instances of `<actor>`, in row-major order. The actor must have parameters
+/// named `x` and `y`, which receive the position of each instance. `<args>` are the arguments to the
+/// remaining parameters, in the same form as for a regular actor instance.
+unsafe fn expand_grid(form: SCM, actors: &[Actor], prelude: &str) -> Result<Vec<ActorInstance>, SkyliteModelError> {
+    let actor_name = parse_symbol(cxr(form, &[CDR, CAR])?)?;
+    let actor = match actors.iter().find(|a| a.name == actor_name) {
+        Some(a) => a,
+        None => return Err(SkyliteModelError::DataError(format!("Actor {} not found", actor_name)))
+    };
+
+    let mut cols: Option<i64> = None;
+    let mut rows: Option<i64> = None;
+    let mut spacing: Option<(i64, i64)> = None;
+    let mut origin = (0, 0);
+
+    // Options come first, everything after them are arguments to the actor.
+    let mut rest = cxr(form, &[CDR, CDR])?;
+    while scm_is_true(scm_pair_p(rest)) {
+        let option = scm_car(rest);
+        if scm_is_false(scm_list_p(option)) || scm_is_null(option) || !scm_is_symbol(scm_car(option)) {
+            break;
+        }
+        match &parse_symbol(scm_car(option))?[..] {
+            "cols" => cols = Some(parse_int(cxr(option, &[CDR, CAR])?)?),
+            "rows" => rows = Some(parse_int(cxr(option, &[CDR, CAR])?)?),
+            "spacing" => spacing = Some((parse_int(cxr(option, &[CDR, CAR])?)?, parse_int(cxr(option, &[CDR, CDR, CAR])?)?)),
+            "origin" => origin = (parse_int(cxr(option, &[CDR, CAR])?)?, parse_int(cxr(option, &[CDR, CDR, CAR])?)?),
+            _ => break
+        }
+        rest = scm_cdr(rest);
+    }
+
+    let (cols, rows, spacing) = match (cols, rows, spacing) {
+        (Some(c), Some(r), Some(s)) => (c, r, s),
+        _ => return Err(SkyliteModelError::DataError(format!("grid requires (cols <n>), (rows <n>) and (spacing <dx> <dy>), got {}", form_to_string(form))))
+    };
+
+    let x_param = actor.parameters.iter().find(|p| p.name == "x");
+    let y_param = actor.parameters.iter().find(|p| p.name == "y");
+    let (x_param, y_param) = match (x_param, y_param) {
+        (Some(x), Some(y)) => (x, y),
+        _ => return Err(SkyliteModelError::DataError(format!("Actor {} must have parameters x and y to be used in a grid", actor_name)))
+    };
+
+    let other_params: Vec<Variable> = actor.parameters.iter()
+        .filter(|p| p.name != "x" && p.name != "y")
+        .cloned()
+        .collect();
+    let other_args = parse_argument_list(rest, &other_params, prelude)?;
+
+    let mut out = Vec::with_capacity((cols * rows).max(0) as usize);
+    for row in 0..rows {
+        for col in 0..cols {
+            let x = typed_value_from_i64(&x_param.typename, origin.0 + col * spacing.0)?;
+            let y = typed_value_from_i64(&y_param.typename, origin.1 + row * spacing.1)?;
+            let mut other_args_iter = other_args.iter().cloned();
+            let args = actor.parameters.iter()
+                .map(|p| match &p.name[..] {
+                    "x" => x.clone(),
+                    "y" => y.clone(),
+                    _ => other_args_iter.next().unwrap()
+                })
+                .collect();
+            out.push(ActorInstance { actor_name: actor_name.clone(), args });
+        }
+    }
+    Ok(out)
+}
+
+/// Returns whether `form` is a generator form, which expands to multiple actor instances.
+unsafe fn is_generator(form: SCM) -> Result<bool, SkyliteModelError> {
+    Ok(scm_is_true(scm_pair_p(form))
+        && scm_is_symbol(scm_car(form))
+        && parse_symbol(scm_car(form))? == "grid")
+}
+
+/// Returns the forms of the named actors and the extras of a scene, including those
+/// from the scene's placement file, if it has one.
+unsafe fn actor_forms(definition: SCM, base_dir: &Path) -> Result<(Vec<SCM>, Vec<SCM>), SkyliteModelError> {
+    let mut named = match assq_str("actors", definition)? {
+        Some(actors_scm) => iter_list(actors_scm)?.collect(),
+        None => Vec::new()
+    };
+    let mut extras = match assq_str("extras", definition)? {
+        Some(extras_scm) => iter_list(extras_scm)?.collect(),
+        None => Vec::new()
+    };
+    let (placed_named, placed_extras) = load_placements(definition, base_dir)?;
+    named.extend(placed_named);
+    extras.extend(placed_extras);
+    Ok((named, extras))
+}
+
+/// Returns the names of the actors referenced by the actor forms of a scene which are not in `actors`,
+/// in the order of their first reference. Malformed forms are skipped, they are reported when the
+/// forms are parsed.
+unsafe fn unresolved_actors(named_forms: &[SCM], extra_forms: &[SCM], actors: &[Actor]) -> Result<Vec<String>, SkyliteModelError> {
+    unsafe fn instance_actor(form: SCM) -> Result<Option<String>, SkyliteModelError> {
+        if is_generator(form)? {
+            instance_actor(scm_cdr(form))
+        } else if scm_is_true(scm_pair_p(form)) && scm_is_symbol(scm_car(form)) {
+            Ok(Some(parse_symbol(scm_car(form))?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    let instances = named_forms.iter()
+        .filter(|form| scm_is_true(scm_pair_p(**form)))
+        .map(|form| scm_cdr(*form))
+        .chain(extra_forms.iter().copied());
+    for form in instances {
+        if let Some(name) = instance_actor(form)? {
+            if !actors.iter().any(|a| a.name == name) && !out.contains(&name) {
+                out.push(name);
+            }
+        }
+    }
+    Ok(out)
+}
+
+unsafe fn extract_parameters(definition: SCM, value_types: &[ValueType], prelude: &str) -> Result<Vec<Variable>, SkyliteModelError> {
+    let maybe_params_scm = assq_str("parameters", definition)?;
+    if let Some(parameters_scm) = maybe_params_scm {
+        Ok(iter_list(parameters_scm)?
+            .map(|param| parse_variable_definition(param, value_types, prelude))
+            .collect::<Result<Vec<Variable>, SkyliteModelError>>()?)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Scene {
+    pub name: String,
+    pub description: Option<String>,
+    pub actors: Vec<(String, ActorInstance)>,
+    pub extras: Vec<ActorInstance>,
+    pub parameters: Vec<Variable>,
+    pub compression: Option<Vec<CompressionMethods>>
+}
+
+/// Parses the optional `(description . "...")` entry of a scene.
+unsafe fn parse_description(definition: SCM) -> Result<Option<String>, SkyliteModelError> {
+    match assq_str("description", definition)? {
+        Some(d) => Ok(Some(parse_string(d)?)),
+        None => Ok(None)
+    }
+}
+
+impl Scene {
+    fn from_scheme(form: SCM, name: &str, actors: &[Actor], value_types: &[ValueType], prelude: &str, base_dir: &Path) -> Result<Scene, SkyliteModelError> {
+        unsafe {
+            let (named_forms, extra_forms) = actor_forms(form, base_dir)?;
+            let unresolved = unresolved_actors(&named_forms, &extra_forms, actors)?;
+            if !unresolved.is_empty() {
+                return Err(SkyliteModelError::DataError(format!("Scene {} references unknown actors: {}", name, unresolved.join(", "))));
+            }
+
+            let actor_instances = named_forms.into_iter()
+                .map(|e| if scm_is_false(scm_pair_p(e)) {
+                        Err(SkyliteModelError::DataError(format!("Expected pair (name . instance) for actor, got {}", form_to_string(e))))
+                    } else {
+                        Ok((parse_symbol(scm_car(e))?, ActorInstance::from_scheme(scm_cdr(e), actors, prelude)?))
+                    })
+                .collect::<Result<Vec<(String, ActorInstance)>, SkyliteModelError>>()?;
+
+            let extras = extra_forms.into_iter()
+                .map(|extra| if is_generator(extra)? {
+                    expand_grid(extra, actors, prelude)
+                } else {
+                    ActorInstance::from_scheme(extra, actors, prelude).map(|instance| vec![instance])
+                })
+                .collect::<Result<Vec<Vec<ActorInstance>>, SkyliteModelError>>()?
+                .into_iter()
+                .flatten()
+                .collect();
+
+            let parameters = extract_parameters(form, value_types, prelude)?;
+
+            let compression = match assq_str("compression", form)? {
+                Some(c) => Some(parse_compression(c)?),
+                None => None
+            };
+
+            Ok(Scene {
+                name: name.to_owned(),
+                description: parse_description(form)?,
+                actors: actor_instances,
+                extras,
+                parameters,
+                compression
+            })
+        }
+    }
+
+    pub fn from_file(path: &Path, name: &str, actors: &[Actor], value_types: &[ValueType], prelude: &str) -> Result<Scene, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn from_file_guile(params: &(&Path, &str, &[Actor], &[ValueType], &str)) -> Result<Scene, SkyliteModelError> {
+            let (path, name, actors, value_types, prelude) = params;
+            let definition_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading scene definition: {}", e)))?;
+            let definition = unsafe {
+                migrate(eval_asset(&definition_raw, prelude)?, &format!("Scene {}", name))?
+            };
+
+            let name = change_case(name, IdentCase::UpperCamelCase);
+            Scene::from_scheme(definition, &name, actors, value_types, prelude, path.parent().unwrap_or(Path::new(".")))
+        }
+
+        with_guile(from_file_guile, &(path, name, actors, value_types, prelude))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SceneInstance {
+    pub name: String,
+    pub args: Vec<TypedValue>
+}
+
+impl SceneInstance {
+    pub(crate) fn from_scheme(def: SCM, scene_assets: &AssetGroup, value_types: &[ValueType], prelude: &str) -> Result<SceneInstance, SkyliteModelError> {
+        unsafe {
+            if scm_is_false(scm_list_p(def)) {
+                return Err(SkyliteModelError::DataError(format!("Expected list for scene instantiation, got {}", form_to_string(def))));
+            }
+
+            let scene_name = parse_symbol(scm_car(def))?;
+            let (_, path) = scene_assets.find_asset(&scene_name)?;
+            let stub = SceneStub::from_file(&path, &scene_assets.asset_name(&path), value_types, prelude)?;
+            Ok(SceneInstance {
+                name: stub.name.clone(),
+                args: parse_argument_list(scm_cdr(def), &stub.parameters, prelude)?,
+            })
+        }
+    }
+}
+
+/// Reduced representation of a Scene.
+///
+/// This is used by scene_definition, so the proc-macro only has to parse
+/// the stuff it actually needs (specifically it does not have to parse all
+/// actors to match actor instantiations).
+pub struct SceneStub {
+    pub name: String,
+    pub description: Option<String>,
+    pub actor_names: Vec<String>,
+    pub parameters: Vec<Variable>
+}
+
+impl SceneStub {
+    pub(crate) fn from_scheme(definition: SCM, name: &str, value_types: &[ValueType], prelude: &str, base_dir: &Path) -> Result<SceneStub, SkyliteModelError> {
+        unsafe {
+            let (named_forms, _) = actor_forms(definition, base_dir)?;
+            let actor_names = named_forms.into_iter()
+                .map(|e| if scm_is_false(scm_pair_p(e)) {
+                        Err(SkyliteModelError::DataError(format!("Expected pair (name . instance) for actor, got {}", form_to_string(e))))
+                    } else {
+                        Ok(parse_symbol(scm_car(e))?)
+                    })
+                .collect::<Result<Vec<String>, SkyliteModelError>>()?;
+
+            let parameters = extract_parameters(definition, value_types, prelude)?;
+
+            Ok(SceneStub {
+                name: name.to_owned(),
+                description: parse_description(definition)?,
+                actor_names,
+                parameters
+            })
+        }
+    }
+
+    pub fn from_file(path: &Path, name: &str, value_types: &[ValueType], prelude: &str) -> Result<SceneStub, SkyliteModelError> {
+        // Since we are not actually accessing anything from this signature from C,
+        // we can get away with ignoring the missing C representations.
+        #[allow(improper_ctypes_definitions)]
+        extern "C" fn from_file_guile(params: &(&Path, &str, &[ValueType], &str)) -> Result<SceneStub, SkyliteModelError> {
+            let (path, name, value_types, prelude) = *params;
+            let definition_raw = read_to_string(path).map_err(|e| SkyliteModelError::OtherError(format!("Error reading scene definition: {}", e)))?;
+            let definition = unsafe {
+                migrate(eval_asset(&definition_raw, prelude)?, &format!("Scene {}", name))?
+            };
+
+            let name = change_case(name, IdentCase::UpperCamelCase);
+            SceneStub::from_scheme(definition, &name, value_types, prelude, path.parent().unwrap_or(Path::new(".")))
+        }
+
+        with_guile(from_file_guile, &(path, name, value_types, prelude))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, remove_dir_all, write};
+    use std::path::{Path, PathBuf};
+
+    use crate::scenes::{ActorInstance, TypedValue};
+    use crate::scheme_util::{eval_str, with_guile};
+
+    use crate::actors::Actor;
+    use crate::values::{Type, Variable};
+    use crate::SkyliteModelError;
+
+    use super::Scene;
+
+    extern "C" fn test_parse_scene_impl(_: &()) {
+        let def_scm = unsafe {
+            eval_str("'
+            ((actors .
+               ((a1 . (TestActor 1))
+                (a2 . (TestActor 2))))
+             (extras . ((TestActor 3) (TestActor 4)))
+             (parameters . ((val1 u8)))
+             (compression . none)
+             (description . \"A scene for testing\"))
+            ").unwrap()
+        };
+        let test_actor = unsafe { Actor::from_scheme(eval_str("
+            '((parameters . ((val u8)))
+              (actions .
+                ((default)))
+              (initial-action . (default)))").unwrap(), "TestActor", None, &[], "").unwrap()
+        };
+        let scene = Scene::from_scheme(def_scm, "TestScene", &[test_actor], &[], "", Path::new(".")).unwrap();
+
+        assert_eq!(scene,
+            Scene {
+                name: "TestScene".to_owned(),
+                description: Some("A scene for testing".to_owned()),
+                actors: vec![
+                    ("a1".to_owned(), ActorInstance { actor_name: "TestActor".to_owned(), args: vec![TypedValue::U8(1)] }),
+                    ("a2".to_owned(), ActorInstance { actor_name: "TestActor".to_owned(), args: vec![TypedValue::U8(2)] }),
+                ],
+                extras: vec![
+                    ActorInstance { actor_name: "TestActor".to_owned(), args: vec![TypedValue::U8(3)] },
+                    ActorInstance { actor_name: "TestActor".to_owned(), args: vec![TypedValue::U8(4)] },
+                ],
+                parameters: vec![
+                    Variable { name: "val1".to_owned(), typename: Type::U8, documentation: None, default: None}
+                ],
+                compression: Some(vec![])
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_scene() {
+        with_guile(test_parse_scene_impl, &());
+    }
+
+    extern "C" fn test_grid_generator_impl(_: &()) {
+        let def_scm = unsafe {
+            eval_str("'
+            ((extras .
+               ((grid TileActor (cols 2) (rows 2) (spacing 16 8) (origin 4 0) 7)
+                (TileActor 100 100 1))))
+            ").unwrap()
+        };
+        let tile_actor = unsafe { Actor::from_scheme(eval_str("
+            '((parameters . ((x i16) (y i16) (kind u8)))
+              (actions .
+                ((default)))
+              (initial-action . (default)))").unwrap(), "TileActor", None, &[], "").unwrap()
+        };
+        let scene = Scene::from_scheme(def_scm, "TestScene", &[tile_actor], &[], "", Path::new(".")).unwrap();
+
+        let instance = |x, y, kind| ActorInstance {
+            actor_name: "TileActor".to_owned(),
+            args: vec![TypedValue::I16(x), TypedValue::I16(y), TypedValue::U8(kind)]
+        };
+        assert_eq!(scene.extras, vec![
+            instance(4, 0, 7),
+            instance(20, 0, 7),
+            instance(4, 8, 7),
+            instance(20, 8, 7),
+            instance(100, 100, 1)
+        ]);
+    }
+
+    #[test]
+    fn test_grid_generator() {
+        with_guile(test_grid_generator_impl, &());
+    }
+
+    extern "C" fn test_unresolved_actors_impl(_: &()) {
+        let def_scm = unsafe {
+            eval_str("'
+            ((actors .
+               ((a . (OldActor 1))
+                (b . (TestActor 2))))
+             (extras .
+               ((grid Missing (cols 1) (rows 1) (spacing 0 0))
+                (OldActor 3))))
+            ").unwrap()
+        };
+        let test_actor = unsafe { Actor::from_scheme(eval_str("
+            '((parameters . ((val u8)))
+              (actions .
+                ((default)))
+              (initial-action . (default)))").unwrap(), "TestActor", None, &[], "").unwrap()
+        };
+        match Scene::from_scheme(def_scm, "TestScene", &[test_actor], &[], "", Path::new(".")) {
+            Err(SkyliteModelError::DataError(msg)) => assert_eq!(msg, "Scene TestScene references unknown actors: OldActor, Missing"),
+            res => panic!("Expected DataError, got {:?}", res)
+        }
+    }
+
+    #[test]
+    fn test_unresolved_actors() {
+        with_guile(test_unresolved_actors_impl, &());
+    }
+
+    extern "C" fn test_placements_impl(test_dir: &PathBuf) {
+        let def_scm = unsafe {
+            eval_str("'
+            ((actors .
+               ((first . (TileActor 0 0 1))))
+             (placements . \"level.json\"))
+            ").unwrap()
+        };
+        let tile_actor = unsafe { Actor::from_scheme(eval_str("
+            '((parameters . ((x i16) (y i16) (kind u8 0)))
+              (actions .
+                ((default)))
+              (initial-action . (default)))").unwrap(), "TileActor", None, &[], "").unwrap()
+        };
+        let scene = Scene::from_scheme(def_scm, "TestScene", &[tile_actor], &[], "", test_dir).unwrap();
+
+        let instance = |x, y, kind| ActorInstance {
+            actor_name: "TileActor".to_owned(),
+            args: vec![TypedValue::I16(x), TypedValue::I16(y), TypedValue::U8(kind)]
+        };
+        assert_eq!(scene.actors, vec![
+            ("first".to_owned(), instance(0, 0, 1)),
+            ("door".to_owned(), instance(16, -8, 2))
+        ]);
+        assert_eq!(scene.extras, vec![instance(4, 4, 0)]);
+    }
+
+    #[test]
+    fn test_placements() {
+        let test_dir = std::env::temp_dir().join(format!("skylite_placements_{}", std::process::id()));
+        create_dir(&test_dir).unwrap();
+        write(test_dir.join("level.json"), r#"[
+            {"name": "door", "type": "TileActor", "x": 16, "y": -8, "args": {"kind": 2}},
+            {"type": "TileActor", "x": 4, "y": 4}
+        ]"#).unwrap();
+
+        with_guile(test_placements_impl, &test_dir);
+
+        remove_dir_all(test_dir).unwrap();
+    }
+}
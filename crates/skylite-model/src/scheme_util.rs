@@ -1,6 +1,6 @@
 use std::{ffi::{c_void, CStr, CString}, fmt::Display, ptr::null_mut, sync::{Mutex, MutexGuard}};
 
-use crate::{parse::guile::{scm_assq, scm_c_eval_string, scm_cadr, scm_car, scm_cdr, scm_from_utf8_symbol, scm_is_bool, scm_is_false, scm_is_integer, scm_is_null, scm_is_real, scm_is_symbol, scm_is_true, scm_list_p, scm_object_to_string, scm_pair_p, scm_string_p, scm_symbol_to_string, scm_to_bool, scm_to_double, scm_to_int64, scm_to_utf8_stringn, scm_with_guile, wrapper_free, SCM}, SkyliteProcError};
+use crate::{guile::{scm_assq, scm_c_eval_string, scm_cadr, scm_car, scm_cdr, scm_from_utf8_symbol, scm_is_bool, scm_is_false, scm_is_integer, scm_is_null, scm_is_real, scm_is_symbol, scm_is_true, scm_list_p, scm_object_to_string, scm_pair_p, scm_string_p, scm_symbol_to_string, scm_to_bool, scm_to_double, scm_to_int64, scm_to_utf8_stringn, scm_with_guile, wrapper_free, SCM}, SkyliteModelError};
 
 static GUILE_INIT_LOCK: Mutex<()> = Mutex::new(());
 
@@ -12,7 +12,7 @@ struct CallInfo<'a, P: ?Sized, R> {
 }
 
 /// Runs code with access to Guile.
-pub(crate) fn with_guile<P: ?Sized, R>(func: extern "C" fn(&P) -> R, params: &P) -> R {
+pub fn with_guile<P: ?Sized, R>(func: extern "C" fn(&P) -> R, params: &P) -> R {
     // This function has to jump a few hoops to deal with some of
     // libguile's shenanigans:
     // - Any Guile function can do a nonlocal exit (via longjmp), which is
@@ -62,9 +62,9 @@ pub fn form_to_string(obj: SCM) -> String {
 }
 
 /// Returns the value associated with `key` in `alist`.
-pub(crate) unsafe fn assq_str(key: &str, alist: SCM) -> Result<Option<SCM>, SkyliteProcError> {
+pub unsafe fn assq_str(key: &str, alist: SCM) -> Result<Option<SCM>, SkyliteModelError> {
     if scm_is_false(scm_pair_p(alist)) {
-        return Err(SkyliteProcError::DataError(format!("Not an alist: {}", form_to_string(alist))))
+        return Err(SkyliteModelError::DataError(format!("Not an alist: {}", form_to_string(alist))))
     }
 
     let key_cstr = CString::new(Into::<Vec<u8>>::into(key.as_bytes().to_owned())).unwrap();
@@ -78,47 +78,47 @@ pub(crate) unsafe fn assq_str(key: &str, alist: SCM) -> Result<Option<SCM>, Skyl
 }
 
 /// Converts a Scheme fixnum to an an integer of type `T`.
-pub(crate) unsafe fn parse_int<T>(obj: SCM) -> Result<T, SkyliteProcError>
+pub unsafe fn parse_int<T>(obj: SCM) -> Result<T, SkyliteModelError>
 where
     T: TryFrom<i64>,
     <T as TryFrom<i64>>::Error: Display
 {
     if scm_is_integer(obj) == 0{
-        return Err(SkyliteProcError::DataError(format!("Expected integer, found {}", form_to_string(obj))));
+        return Err(SkyliteModelError::DataError(format!("Expected integer, found {}", form_to_string(obj))));
     }
     match T::try_from(scm_to_int64(obj)) {
         Ok(val) => Ok(val),
-        Err(err) => Err(SkyliteProcError::DataError(format!("{}", err)))
+        Err(err) => Err(SkyliteModelError::DataError(format!("{}", err)))
     }
 }
 
 /// Converts a Scheme flonum to an `f64`.
-pub(crate) unsafe fn parse_f64(obj: SCM) -> Result<f64, SkyliteProcError>
+pub unsafe fn parse_f64(obj: SCM) -> Result<f64, SkyliteModelError>
 {
     if scm_is_real(obj) == 0 {
-        return Err(SkyliteProcError::DataError(format!("Expected floating point numer, found {}", form_to_string(obj))));
+        return Err(SkyliteModelError::DataError(format!("Expected floating point numer, found {}", form_to_string(obj))));
     }
     Ok(scm_to_double(obj))
 }
 
 /// Converts a Scheme flonum to an `f32`.
-pub(crate) unsafe fn parse_f32(obj: SCM) -> Result<f32, SkyliteProcError> {
+pub unsafe fn parse_f32(obj: SCM) -> Result<f32, SkyliteModelError> {
     parse_f64(obj).map(|val| val as f32)
 }
 
 /// Converts a Scheme boolean to a Rust `bool`.
-pub(crate) unsafe fn parse_bool(obj: SCM) -> Result<bool, SkyliteProcError> {
+pub unsafe fn parse_bool(obj: SCM) -> Result<bool, SkyliteModelError> {
     if scm_is_bool(obj) == 0{
-        return Err(SkyliteProcError::DataError(format!("Expected boolean, found {}", form_to_string(obj))));
+        return Err(SkyliteModelError::DataError(format!("Expected boolean, found {}", form_to_string(obj))));
     }
 
     Ok(scm_is_true(obj))
 }
 
 /// Converts a Scheme string to a Rust `String`.
-pub(crate) unsafe fn parse_string(obj: SCM) -> Result<String, SkyliteProcError> {
+pub unsafe fn parse_string(obj: SCM) -> Result<String, SkyliteModelError> {
     if scm_is_false(scm_string_p(obj)) {
-        return Err(SkyliteProcError::DataError(format!("Expected string, found {}", form_to_string(obj))));
+        return Err(SkyliteModelError::DataError(format!("Expected string, found {}", form_to_string(obj))));
     }
 
     let raw_string = scm_to_utf8_stringn(obj, null_mut());
@@ -128,15 +128,15 @@ pub(crate) unsafe fn parse_string(obj: SCM) -> Result<String, SkyliteProcError>
 }
 
 /// Converts a Scheme symbol to a Rust `String`.
-pub(crate) unsafe fn parse_symbol(obj: SCM) -> Result<String, SkyliteProcError> {
+pub unsafe fn parse_symbol(obj: SCM) -> Result<String, SkyliteModelError> {
     if !scm_is_symbol(obj) {
-        return Err(SkyliteProcError::DataError(format!("Expected symbol, found {}", form_to_string(obj))));
+        return Err(SkyliteModelError::DataError(format!("Expected symbol, found {}", form_to_string(obj))));
     }
 
     Ok(parse_string(scm_symbol_to_string(obj)).unwrap())
 }
 
-pub(crate) struct SchemeListIterator {
+pub struct SchemeListIterator {
     cursor: SCM
 }
 
@@ -158,26 +158,26 @@ impl Iterator for SchemeListIterator {
 /// Iterate over items in a scheme list.
 ///
 /// Returns an `Err` if the input is not a list.
-pub(crate) unsafe fn iter_list(list: SCM) -> Result<SchemeListIterator, SkyliteProcError> {
+pub unsafe fn iter_list(list: SCM) -> Result<SchemeListIterator, SkyliteModelError> {
     if scm_is_false(scm_list_p(list)) {
-        Err(SkyliteProcError::DataError(format!("Not a list: {}", form_to_string(list))))
+        Err(SkyliteModelError::DataError(format!("Not a list: {}", form_to_string(list))))
     } else {
         Ok(SchemeListIterator { cursor: list })
     }
 }
 
-pub(crate) enum CXROp {
+pub enum CXROp {
     CAR, CDR
 }
 
 use CXROp::*;
 
 /// Performs a sequence of CAR/CDR operations.
-pub(crate) unsafe fn cxr(pair: SCM, ops: &[CXROp]) -> Result<SCM, SkyliteProcError> {
+pub unsafe fn cxr(pair: SCM, ops: &[CXROp]) -> Result<SCM, SkyliteModelError> {
     let mut cursor = pair;
     for op in ops {
         if scm_to_bool(scm_pair_p(cursor)) == 0 {
-            return Err(SkyliteProcError::DataError(format!("Not a pair, cannot do car/cdr: {}", form_to_string(cursor))));
+            return Err(SkyliteModelError::DataError(format!("Not a pair, cannot do car/cdr: {}", form_to_string(cursor))));
         }
         match op {
             CAR => cursor = scm_car(cursor),
@@ -187,24 +187,52 @@ pub(crate) unsafe fn cxr(pair: SCM, ops: &[CXROp]) -> Result<SCM, SkyliteProcErr
     Ok(cursor)
 }
 
-pub(crate) unsafe fn eval_str(expr: &str) -> Result<SCM, SkyliteProcError> {
+/// Evaluates `expr` and returns the result.
+///
+/// If the evaluation raises an exception, the error contains the formatted message of the exception
+/// and a backtrace. The backtrace is captured by a second handler, which runs before the stack is unwound.
+pub unsafe fn eval_str(expr: &str) -> Result<SCM, SkyliteModelError> {
     let safe_expr = format!("\
-        (with-exception-handler
-          (lambda (exc) `(err . ,exc))
-          (lambda () `(ok . ,{}))
-          #:unwind? #t)", expr);
+        (let ((backtrace \"\"))
+          (with-exception-handler
+            (lambda (exc)
+              `(err ,(call-with-output-string
+                       (lambda (port) (print-exception port #f (exception-kind exc) (exception-args exc))))
+                    ,backtrace))
+            (lambda ()
+              (with-exception-handler
+                (lambda (exc)
+                  (set! backtrace (call-with-output-string (lambda (port) (display-backtrace (make-stack #t) port))))
+                  (raise-exception exc))
+                (lambda () `(ok . ,{}))))
+            #:unwind? #t))", expr);
     let c_expr = CString::new(safe_expr).unwrap();
     let res = scm_c_eval_string(c_expr.as_ptr());
     if parse_symbol(scm_car(res))? == "err" {
-        Err(SkyliteProcError::GuileException(scm_cdr(res)))
+        Err(SkyliteModelError::GuileException {
+            message: parse_string(scm_cadr(res))?.trim_end().to_owned(),
+            backtrace: parse_string(cxr(res, &[CDR, CDR, CAR])?)?.trim_end().to_owned()
+        })
     } else {
         Ok(scm_cdr(res))
     }
 }
 
+/// Evaluates the source of an asset file, with the definitions of the project's `prelude` in scope.
+///
+/// The prelude and the asset are evaluated together in the body of a `let`, so the definitions
+/// of the prelude do not leak into the evaluation of other assets.
+pub unsafe fn eval_asset(source: &str, prelude: &str) -> Result<SCM, SkyliteModelError> {
+    if prelude.is_empty() {
+        eval_str(source)
+    } else {
+        eval_str(&format!("(let ()\n{}\n{})", prelude, source))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::parse::{guile::{scm_car, scm_from_int16, scm_from_int32, scm_to_int32}, scheme_util::{assq_str, eval_str}};
+    use crate::{guile::{scm_car, scm_from_int16, scm_from_int32, scm_to_int32}, scheme_util::{assq_str, eval_asset, eval_str, form_to_string}, SkyliteModelError};
 
     use super::with_guile;
 
@@ -241,4 +269,35 @@ mod tests {
     fn test_assq_str() {
         with_guile(test_assq_str_impl, &());
     }
+
+    extern "C" fn test_eval_error_impl(_: &()) {
+        unsafe {
+            match eval_str("(let ((xs '(1 2))) (car (cddr xs)))") {
+                Err(SkyliteModelError::GuileException { message, backtrace }) => {
+                    assert!(message.contains("car"), "{}", message);
+                    assert!(!backtrace.is_empty());
+                },
+                res => panic!("Expected GuileException, got {:?}", res)
+            }
+        }
+    }
+
+    extern "C" fn test_eval_asset_impl(_: &()) {
+        unsafe {
+            let prelude = "(define (pair-with x ys) (map (lambda (y) (cons x y)) ys))";
+            let res = eval_asset("`((actors . ,(pair-with 'slime '(1 2))))", prelude).unwrap();
+            assert_eq!(form_to_string(res), "((actors (slime . 1) (slime . 2)))");
+            assert!(eval_asset("(pair-with 'slime '(1 2))", "").is_err());
+        }
+    }
+
+    #[test]
+    fn test_eval_asset() {
+        with_guile(test_eval_asset_impl, &());
+    }
+
+    #[test]
+    fn test_eval_error() {
+        with_guile(test_eval_error_impl, &());
+    }
 }
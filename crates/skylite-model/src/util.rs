@@ -1,14 +1,14 @@
-pub(crate) enum IdentCase {
+pub enum IdentCase {
     UpperCamelCase,
     LowerCamelCase,
     UpperSnakeCase,
     LowerSnakeCase
 }
 
-pub(crate) fn change_case(input: &str, case: IdentCase) -> String {
+pub fn change_case(input: &str, case: IdentCase) -> String {
     input.chars()
         .scan((true, true, false), |(first, prev_lowercase, split_queued), c| {
-            let is_delimiter = c == ' ' || c == '-' || c == '_';
+            let is_delimiter = c == ' ' || c == '-' || c == '_' || c == '/';
             let do_split = is_delimiter || *split_queued || (!*first && *prev_lowercase && c.is_uppercase());
             *split_queued = false;
 
@@ -54,7 +54,7 @@ pub(crate) fn change_case(input: &str, case: IdentCase) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::parse::util::{change_case, IdentCase};
+    use crate::util::{change_case, IdentCase};
 
     #[test]
     fn test_change_case() {
@@ -72,5 +72,8 @@ mod tests {
         assert_eq!(change_case("test_text", IdentCase::LowerCamelCase), "testText");
         assert_eq!(change_case("test_text", IdentCase::UpperSnakeCase), "TEST_TEXT");
         assert_eq!(change_case("test_text", IdentCase::LowerSnakeCase), "test_text");
+
+        assert_eq!(change_case("ui/button", IdentCase::UpperCamelCase), "UiButton");
+        assert_eq!(change_case("ui/button", IdentCase::UpperSnakeCase), "UI_BUTTON");
     }
 }
@@ -0,0 +1,595 @@
+use std::cmp::Ordering;
+
+use crate::SkyliteModelError;
+
+use super::{guile::{scm_car, scm_cdr, scm_is_false, scm_is_null, scm_is_symbol, scm_is_true, scm_length, scm_list_p, scm_pair_p, scm_to_int64, SCM}, scheme_util::{cxr, eval_asset, form_to_string, iter_list, parse_bool, parse_f32, parse_f64, parse_int, parse_string, parse_symbol}};
+use super::scheme_util::CXROp::*;
+
+/// Type of a Skylite variable or parameter.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Type {
+    U8, U16, U32, U64,
+    I8, I16, I32, I64,
+    F32, F64,
+    Bool,
+    String,
+    Tuple(Vec<Type>),
+    Vec(Box<Type>),
+    Map(Box<Type>, Box<Type>),
+    Optional(Box<Type>),
+    Value(ValueType)
+}
+
+impl Type {
+    /// Returns `true` if the type is `f32` or `f64`, or is composed of one of them.
+    pub fn contains_float(&self) -> bool {
+        match self {
+            Type::F32 | Type::F64 => true,
+            Type::Tuple(members) => members.iter().any(Type::contains_float),
+            Type::Vec(item) => item.contains_float(),
+            Type::Map(key, value) => key.contains_float() || value.contains_float(),
+            Type::Optional(item) => item.contains_float(),
+            Type::Value(value_type) => value_type.fields.iter().any(|f| f.typename.contains_float()),
+            _ => false
+        }
+    }
+}
+
+/// A custom value type, declared in the `value-types` section of the project definition.
+///
+/// Values of this type are written as an alist of fields, e.g. `((x . 4) (y . 8))`,
+/// and are represented in Rust by the struct generated with `skylite_value!`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ValueType {
+    pub name: String,
+    pub fields: Vec<Variable>
+}
+
+impl ValueType {
+    /// Parses a value type declaration of the form `(name (field type [documentation])...)`.
+    ///
+    /// Field types can refer to the value types in `value_types`, which have been declared before.
+    pub(crate) unsafe fn from_scheme(def: SCM, value_types: &[ValueType], prelude: &str) -> Result<ValueType, SkyliteModelError> {
+        if scm_is_false(scm_pair_p(def)) {
+            return Err(SkyliteModelError::DataError(format!("Expected (name fields...) for value type, got {}", form_to_string(def))));
+        }
+        let name = parse_symbol(scm_car(def))?;
+        let fields = iter_list(scm_cdr(def))?
+            .map(|f| parse_variable_definition(f, value_types, prelude))
+            .collect::<Result<Vec<Variable>, SkyliteModelError>>()?;
+        if fields.iter().any(|f| f.default.is_some()) {
+            return Err(SkyliteModelError::DataError(format!("Fields of value type {} cannot have default values", name)));
+        }
+        Ok(ValueType { name, fields })
+    }
+}
+
+/// Converts a type name from Scheme to an instance of `Type`.
+///
+/// `item_type` must be one of the following symbols for primitive types:
+/// - `u8`, `u16`, `u32`, `u64`
+/// - `i8`, `i16`, `i32`, `i64`
+/// - `f32`, `f64`
+/// - `bool`
+/// - `string`
+/// - the name of one of the `value_types`
+///
+/// In addition, `item_type` can use the following forms to construct aggregate types:
+/// - `(<type1> <type2> ... )`: A tuple of the given types.
+/// - `(vec <type>)`: A vector of the given types.
+/// - `(map <key-type> <value-type>)`: A map. The key type must be an integer type, `bool` or `string`.
+/// - `(optional <type>)`: A value of the given type, which may be absent. Absent values are written as `none`.
+pub(crate) unsafe fn parse_type(typename: SCM, value_types: &[ValueType]) -> Result<Type, SkyliteModelError> {
+    if scm_is_symbol(typename) {
+        let type_name = parse_symbol(typename)?;
+        match &type_name[..] {
+            "u8" => Ok(Type::U8),
+            "u16" => Ok(Type::U16),
+            "u32" => Ok(Type::U32),
+            "u64" => Ok(Type::U64),
+            "i8" => Ok(Type::I8),
+            "i16" => Ok(Type::I16),
+            "i32" => Ok(Type::I32),
+            "i64" => Ok(Type::I64),
+            "f32" => Ok(Type::F32),
+            "f64" => Ok(Type::F64),
+            "bool" => Ok(Type::Bool),
+            "string" => Ok(Type::String),
+            _ => value_types.iter()
+                .find(|vt| vt.name == type_name)
+                .map(|vt| Type::Value(vt.clone()))
+                .ok_or_else(|| SkyliteModelError::DataError(format!("Unknown data type: {}", type_name)))
+        }
+    } else if scm_is_true(scm_list_p(typename)) {
+        let car = scm_car(typename);
+        if scm_is_symbol(car) && parse_symbol(car)? == "vec" {
+            let item_type = cxr(typename, &[CDR, CAR])?;
+            Ok(Type::Vec(Box::new(parse_type(item_type, value_types)?)))
+        } else if scm_is_symbol(car) && parse_symbol(car)? == "map" {
+            let key_type = parse_type(cxr(typename, &[CDR, CAR])?, value_types)?;
+            let value_type = parse_type(cxr(typename, &[CDR, CDR, CAR])?, value_types)?;
+            match key_type {
+                Type::U8 | Type::U16 | Type::U32 | Type::U64
+                | Type::I8 | Type::I16 | Type::I32 | Type::I64
+                | Type::Bool | Type::String => Ok(Type::Map(Box::new(key_type), Box::new(value_type))),
+                _ => Err(SkyliteModelError::DataError(format!("Unsupported key type for map: {:?}", key_type)))
+            }
+        } else if scm_is_symbol(car) && parse_symbol(car)? == "optional" {
+            let item_type = cxr(typename, &[CDR, CAR])?;
+            Ok(Type::Optional(Box::new(parse_type(item_type, value_types)?)))
+        } else {
+            iter_list(typename).unwrap()
+                .map(|t| parse_type(t, value_types))
+                .collect::<Result<Vec<Type>, SkyliteModelError>>()
+                .map(|ok| Type::Tuple(ok))
+        }
+    } else {
+        Err(SkyliteModelError::DataError(format!("Unsupported type: {}", form_to_string(typename))))
+    }
+}
+
+/// A data item combined with a type.
+#[derive(PartialEq, Debug, Clone)]
+pub enum TypedValue {
+    U8(u8), U16(u16), U32(u32), U64(u64),
+    I8(i8), I16(i16), I32(i32), I64(i64),
+    F32(f32), F64(f64),
+    Bool(bool),
+    String(String),
+    Tuple(Vec<TypedValue>),
+    Vec(Vec<TypedValue>),
+    /// Entries are sorted by key, and there are no duplicate keys.
+    Map(Vec<(TypedValue, TypedValue)>),
+    /// Value of an optional type. `None` if the value is absent.
+    Optional(Option<Box<TypedValue>>),
+    /// Value of a custom value type. The fields are in the order of their declaration.
+    Value(String, Vec<(String, TypedValue)>)
+}
+
+/// Compares two keys of a map.
+///
+/// Strings are compared by their content, which matches the order of their ids
+/// in the string table.
+fn compare_keys(a: &TypedValue, b: &TypedValue) -> Ordering {
+    match (a, b) {
+        (TypedValue::U8(a), TypedValue::U8(b)) => a.cmp(b),
+        (TypedValue::U16(a), TypedValue::U16(b)) => a.cmp(b),
+        (TypedValue::U32(a), TypedValue::U32(b)) => a.cmp(b),
+        (TypedValue::U64(a), TypedValue::U64(b)) => a.cmp(b),
+        (TypedValue::I8(a), TypedValue::I8(b)) => a.cmp(b),
+        (TypedValue::I16(a), TypedValue::I16(b)) => a.cmp(b),
+        (TypedValue::I32(a), TypedValue::I32(b)) => a.cmp(b),
+        (TypedValue::I64(a), TypedValue::I64(b)) => a.cmp(b),
+        (TypedValue::Bool(a), TypedValue::Bool(b)) => a.cmp(b),
+        (TypedValue::String(a), TypedValue::String(b)) => a.cmp(b),
+        _ => unreachable!("Map keys must have the same, ordered type")
+    }
+}
+
+/// Evaluates `data` if it is an expression instead of a number, e.g. `(+ SCREEN-W 8)`.
+///
+/// The expression is evaluated together with the project's `prelude`, which also binds the project's constants.
+unsafe fn eval_numeric(data: SCM, prelude: &str) -> Result<SCM, SkyliteModelError> {
+    if scm_is_symbol(data) || scm_is_true(scm_pair_p(data)) {
+        eval_asset(&form_to_string(data), prelude)
+    } else {
+        Ok(data)
+    }
+}
+
+/// Constructs a `TypedValue` given a type and a Scheme form for the value.
+///
+/// Values of numeric types can also be given as expressions, which are evaluated at parse time.
+pub(crate) unsafe fn parse_typed_value(typename: &Type, data: SCM, prelude: &str) -> Result<TypedValue, SkyliteModelError> {
+    match typename {
+        Type::U8 => Ok(TypedValue::U8(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::U16 => Ok(TypedValue::U16(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::U32 => Ok(TypedValue::U32(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::U64 => Ok(TypedValue::U64(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::I8 => Ok(TypedValue::I8(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::I16 => Ok(TypedValue::I16(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::I32 => Ok(TypedValue::I32(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::I64 => Ok(TypedValue::I64(parse_int(eval_numeric(data, prelude)?)?)),
+        Type::F32 => Ok(TypedValue::F32(parse_f32(eval_numeric(data, prelude)?)?)),
+        Type::F64 => Ok(TypedValue::F64(parse_f64(eval_numeric(data, prelude)?)?)),
+        Type::Bool => Ok(TypedValue::Bool(parse_bool(data)?)),
+        Type::String => Ok(TypedValue::String(parse_string(data)?)),
+
+        Type::Vec(item_type) => iter_list(data)?
+            .map(|e| parse_typed_value(&item_type, e, prelude))
+            .collect::<Result<Vec<TypedValue>, SkyliteModelError>>()
+            .map(|ok| TypedValue::Vec(ok)),
+
+        Type::Tuple(types) => parse_typed_value_tuple(types, data, prelude),
+
+        Type::Map(key_type, value_type) => parse_typed_value_map(key_type, value_type, data, prelude),
+
+        Type::Optional(item_type) => if scm_is_symbol(data) && parse_symbol(data)? == "none" {
+            Ok(TypedValue::Optional(None))
+        } else {
+            Ok(TypedValue::Optional(Some(Box::new(parse_typed_value(item_type, data, prelude)?))))
+        },
+
+        Type::Value(value_type) => parse_typed_value_value(value_type, data, prelude)
+    }
+}
+
+/// Constructs a `TypedValue` of a numeric type from an integer.
+///
+/// Returns an `Err` if `typename` is not a numeric type, or if `val` is out of range for `typename`.
+pub fn typed_value_from_i64(typename: &Type, val: i64) -> Result<TypedValue, SkyliteModelError> {
+    fn convert<T: TryFrom<i64>>(val: i64, typename: &Type) -> Result<T, SkyliteModelError> {
+        T::try_from(val).map_err(|_| SkyliteModelError::DataError(format!("Value {} out of range for type {:?}", val, typename)))
+    }
+
+    match typename {
+        Type::U8 => Ok(TypedValue::U8(convert(val, typename)?)),
+        Type::U16 => Ok(TypedValue::U16(convert(val, typename)?)),
+        Type::U32 => Ok(TypedValue::U32(convert(val, typename)?)),
+        Type::U64 => Ok(TypedValue::U64(convert(val, typename)?)),
+        Type::I8 => Ok(TypedValue::I8(convert(val, typename)?)),
+        Type::I16 => Ok(TypedValue::I16(convert(val, typename)?)),
+        Type::I32 => Ok(TypedValue::I32(convert(val, typename)?)),
+        Type::I64 => Ok(TypedValue::I64(val)),
+        Type::F32 => Ok(TypedValue::F32(val as f32)),
+        Type::F64 => Ok(TypedValue::F64(val as f64)),
+        _ => Err(SkyliteModelError::DataError(format!("Expected numeric type, found {:?}", typename)))
+    }
+}
+
+/// Parses a map from an alist of the form `((key1 . value1) (key2 . value2) ...)`.
+unsafe fn parse_typed_value_map(key_type: &Type, value_type: &Type, values: SCM, prelude: &str) -> Result<TypedValue, SkyliteModelError> {
+    let mut entries = iter_list(values)?
+        .map(|entry| {
+            if !scm_is_true(scm_pair_p(entry)) {
+                return Err(SkyliteModelError::DataError(format!("Expected (key . value) pair for map entry, found {}", form_to_string(entry))));
+            }
+            Ok((parse_typed_value(key_type, scm_car(entry), prelude)?, parse_typed_value(value_type, scm_cdr(entry), prelude)?))
+        })
+        .collect::<Result<Vec<(TypedValue, TypedValue)>, SkyliteModelError>>()?;
+
+    entries.sort_by(|(a, _), (b, _)| compare_keys(a, b));
+    if let Some(w) = entries.windows(2).find(|w| compare_keys(&w[0].0, &w[1].0) == Ordering::Equal) {
+        return Err(SkyliteModelError::DataError(format!("Duplicate key in map: {:?}", w[0].0)));
+    }
+    Ok(TypedValue::Map(entries))
+}
+
+/// Parses a value of a custom value type from an alist of the form `((field1 . value1) (field2 . value2) ...)`.
+///
+/// The fields can be given in any order, but every field must be present exactly once.
+unsafe fn parse_typed_value_value(value_type: &ValueType, values: SCM, prelude: &str) -> Result<TypedValue, SkyliteModelError> {
+    let mut fields: Vec<Option<TypedValue>> = vec![None; value_type.fields.len()];
+    for entry in iter_list(values)? {
+        if !scm_is_true(scm_pair_p(entry)) || !scm_is_symbol(scm_car(entry)) {
+            return Err(SkyliteModelError::DataError(format!("Expected (field . value) pair for {}, found {}", value_type.name, form_to_string(entry))));
+        }
+        let field_name = parse_symbol(scm_car(entry))?;
+        let idx = value_type.fields.iter()
+            .position(|f| f.name == field_name)
+            .ok_or_else(|| SkyliteModelError::DataError(format!("Value type {} has no field {}", value_type.name, field_name)))?;
+        if fields[idx].is_some() {
+            return Err(SkyliteModelError::DataError(format!("Duplicate field {} for {}", field_name, value_type.name)));
+        }
+        fields[idx] = Some(parse_typed_value(&value_type.fields[idx].typename, scm_cdr(entry), prelude)?);
+    }
+
+    Iterator::zip(value_type.fields.iter(), fields.into_iter())
+        .map(|(f, v)| v
+            .map(|v| (f.name.clone(), v))
+            .ok_or_else(|| SkyliteModelError::DataError(format!("Missing field {} for {}", f.name, value_type.name))))
+        .collect::<Result<Vec<(String, TypedValue)>, SkyliteModelError>>()
+        .map(|ok| TypedValue::Value(value_type.name.clone(), ok))
+}
+
+unsafe fn parse_typed_value_tuple(types: &[Type], values: SCM, prelude: &str) -> Result<TypedValue, SkyliteModelError> {
+    if types.len() as i64 != scm_to_int64(scm_length(values)) {
+        return Err(SkyliteModelError::DataError(format!("Tuple definition has differing number of types and values.")));
+    }
+
+    Iterator::zip(types.iter(), iter_list(values)?)
+        .map(|(t, v)| parse_typed_value(t, v, prelude))
+        .collect::<Result<Vec<TypedValue>, SkyliteModelError>>()
+        .map(|ok| TypedValue::Tuple(ok))
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Variable {
+    pub name: String,
+    pub typename: Type,
+    pub documentation: Option<String>,
+    pub default: Option<TypedValue>
+}
+
+pub(crate) unsafe fn parse_variable_definition(def: SCM, value_types: &[ValueType], prelude: &str) -> Result<Variable, SkyliteModelError> {
+    if scm_is_false(scm_list_p(def)) {
+        return Err(SkyliteModelError::DataError(format!("Expected variable definition, found {}", form_to_string(def))));
+    }
+
+    let mut current_pair = def;
+    if scm_is_null(current_pair) {
+        return Err(SkyliteModelError::DataError(format!("Expected variable name")));
+    }
+    let name = parse_symbol(scm_car(current_pair))?;
+
+    current_pair = scm_cdr(current_pair);
+    if scm_is_null(current_pair) {
+        return Err(SkyliteModelError::DataError(format!("Expected variable type")));
+    }
+    let typename = parse_type(scm_car(current_pair), value_types)?;
+
+    current_pair = scm_cdr(current_pair);
+    let documentation = if scm_is_null(current_pair) {
+        return Ok(Variable { name, typename, documentation: None, default: None })
+    } else {
+        Some(parse_string(scm_car(current_pair))?)
+    };
+
+    current_pair = scm_cdr(current_pair);
+    let default = if scm_is_null(current_pair) {
+        return Ok(Variable { name, typename, documentation, default: None })
+    } else {
+        Some(parse_typed_value(&typename, scm_car(current_pair), prelude)?)
+    };
+
+    Ok(Variable {
+        name, typename, documentation, default
+    })
+}
+
+pub(crate) unsafe fn parse_argument_list(args_raw: SCM, parameters: &[Variable], prelude: &str) -> Result<Vec<TypedValue>, SkyliteModelError> {
+    // Pad with empty values. If there are any empty values left after the argument list
+    // has been parsed, replace with the corresponding default values. If there is no
+    // default value, raise an error.
+    let mut args: Vec<Option<TypedValue>> = vec![None; parameters.len()];
+    let mut next_arg = 0;
+    for arg_raw in iter_list(args_raw)? {
+        let (arg_idx, param, value) = if scm_is_true(scm_pair_p(arg_raw)) && scm_is_symbol(scm_car(arg_raw)) {
+            // Named argument
+            let arg_name = parse_symbol(scm_car(arg_raw)).unwrap();
+            let (idx, p) = parameters.iter()
+                .enumerate()
+                .find(|(_, param)| param.name == arg_name)
+                .ok_or(SkyliteModelError::DataError(format!("No parameter {} found", arg_name)))?;
+
+            (idx, p, scm_cdr(arg_raw))
+        } else {
+            // Positional argument
+            if next_arg >= parameters.len() {
+                return Err(SkyliteModelError::DataError(format!("Too many arguments")));
+            } else {
+                (next_arg, &parameters[next_arg], arg_raw)
+            }
+        };
+        next_arg = arg_idx + 1;
+
+        args[arg_idx] = Some(parse_typed_value(&param.typename, value, prelude)?);
+    }
+
+    let mut out = Vec::with_capacity(parameters.len());
+    for (i, maybe_arg) in args.into_iter().enumerate() {
+        let val = match maybe_arg {
+            Some(arg) => arg,
+            None => if let Some(def) = parameters[i].default.clone() {
+                def
+            } else {
+                return Err(SkyliteModelError::DataError(format!("Missing argument for parameter {}", parameters[i].name)));
+            }
+        };
+        out.push(val);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{guile::{scm_from_bool, scm_from_double, scm_from_int32}, scheme_util::{eval_str, with_guile}, values::{parse_type, parse_typed_value, parse_variable_definition, Type, TypedValue, ValueType, Variable}};
+
+    use super::{parse_argument_list, typed_value_from_i64};
+
+    #[test]
+    fn test_contains_float() {
+        assert!(!Type::Tuple(vec![Type::U8, Type::Vec(Box::new(Type::I16))]).contains_float());
+        assert!(Type::Map(Box::new(Type::U8), Box::new(Type::Tuple(vec![Type::Bool, Type::F32]))).contains_float());
+        assert!(Type::Optional(Box::new(Type::F64)).contains_float());
+        let value_type = ValueType {
+            name: "vec2".to_owned(),
+            fields: vec![Variable { name: "x".to_owned(), typename: Type::F64, documentation: None, default: None }]
+        };
+        assert!(Type::Vec(Box::new(Type::Value(value_type))).contains_float());
+    }
+
+    #[test]
+    fn test_typed_value_from_i64() {
+        assert_eq!(typed_value_from_i64(&Type::U8, 200).unwrap(), TypedValue::U8(200));
+        assert_eq!(typed_value_from_i64(&Type::I16, -32).unwrap(), TypedValue::I16(-32));
+        assert_eq!(typed_value_from_i64(&Type::F32, 16).unwrap(), TypedValue::F32(16.0));
+        assert!(typed_value_from_i64(&Type::U8, 256).is_err());
+        assert!(typed_value_from_i64(&Type::U16, -1).is_err());
+        assert!(typed_value_from_i64(&Type::Bool, 1).is_err());
+    }
+
+    extern "C" fn test_typed_value_impl(_: &()) {
+        unsafe {
+            let type_name = parse_type(eval_str("'u8").unwrap(), &[]).unwrap();
+            assert_eq!(parse_typed_value(&type_name, scm_from_int32(5), "").unwrap(), TypedValue::U8(5));
+            assert!(parse_typed_value(&type_name, scm_from_int32(300), "").is_err());
+
+            let type_name = parse_type(eval_str("'f64").unwrap(), &[]).unwrap();
+            let value = scm_from_double(1.0);
+            assert_eq!(parse_typed_value(&type_name, value, "").unwrap(), TypedValue::F64(1.0));
+
+            let type_name = parse_type(eval_str("'string").unwrap(), &[]).unwrap();
+            let value = eval_str("\"test123\"").unwrap();
+            assert_eq!(parse_typed_value(&type_name, value, "").unwrap(), TypedValue::String("test123".to_owned()));
+
+            let type_name = parse_type(eval_str("'bool").unwrap(), &[]).unwrap();
+            assert_eq!(parse_typed_value(&type_name, scm_from_bool(true), "").unwrap(), TypedValue::Bool(true));
+
+            let type_name = parse_type(eval_str("'(u8 bool (u16 u16))").unwrap(), &[]).unwrap();
+            let value = eval_str("'(1 #t (2 3))").unwrap();
+            assert_eq!(
+                parse_typed_value(&type_name, value, "").unwrap(),
+                TypedValue::Tuple(vec![
+                    TypedValue::U8(1),
+                    TypedValue::Bool(true),
+                    TypedValue::Tuple(vec![
+                        TypedValue::U16(2),
+                        TypedValue::U16(3),
+                    ])
+                ])
+            );
+
+            let type_name = parse_type(eval_str("'(vec i16)").unwrap(), &[]).unwrap();
+            let value = eval_str("'(0 5 10 15 20 25)").unwrap();
+            assert_eq!(
+                parse_typed_value(&type_name, value, "").unwrap(),
+                TypedValue::Vec(vec![
+                    TypedValue::I16(0), TypedValue::I16(5), TypedValue::I16(10), TypedValue::I16(15), TypedValue::I16(20), TypedValue::I16(25)
+                ])
+            );
+
+            let type_name = parse_type(eval_str("'(map string (u8 bool))").unwrap(), &[]).unwrap();
+            assert_eq!(type_name, Type::Map(Box::new(Type::String), Box::new(Type::Tuple(vec![Type::U8, Type::Bool]))));
+            let value = eval_str("'((\"slime\" 5 #t) (\"bat\" 2 #f))").unwrap();
+            assert_eq!(
+                parse_typed_value(&type_name, value, "").unwrap(),
+                TypedValue::Map(vec![
+                    (TypedValue::String("bat".to_owned()), TypedValue::Tuple(vec![TypedValue::U8(2), TypedValue::Bool(false)])),
+                    (TypedValue::String("slime".to_owned()), TypedValue::Tuple(vec![TypedValue::U8(5), TypedValue::Bool(true)]))
+                ])
+            );
+            assert!(parse_typed_value(&type_name, eval_str("'((\"bat\" 1 #t) (\"bat\" 2 #f))").unwrap(), "").is_err());
+            assert!(parse_type(eval_str("'(map f32 u8)").unwrap(), &[]).is_err());
+
+            let type_name = parse_type(eval_str("'(optional (vec u8))").unwrap(), &[]).unwrap();
+            assert_eq!(type_name, Type::Optional(Box::new(Type::Vec(Box::new(Type::U8)))));
+            assert_eq!(parse_typed_value(&type_name, eval_str("'none").unwrap(), "").unwrap(), TypedValue::Optional(None));
+            assert_eq!(
+                parse_typed_value(&type_name, eval_str("'(3)").unwrap(), "").unwrap(),
+                TypedValue::Optional(Some(Box::new(TypedValue::Vec(vec![TypedValue::U8(3)]))))
+            );
+            assert!(parse_typed_value(&type_name, eval_str("'some").unwrap(), "").is_err());
+
+            let prelude = "(define TEST-WIDTH 160)";
+            assert_eq!(parse_typed_value(&Type::U8, eval_str("'(+ TEST-WIDTH 8)").unwrap(), prelude).unwrap(), TypedValue::U8(168));
+            assert_eq!(parse_typed_value(&Type::I16, eval_str("'TEST-WIDTH").unwrap(), prelude).unwrap(), TypedValue::I16(160));
+            assert!(parse_typed_value(&Type::U8, eval_str("'(* TEST-WIDTH 2)").unwrap(), prelude).is_err());
+            assert!(parse_typed_value(&Type::U8, eval_str("'TEST-WIDTH").unwrap(), "").is_err());
+        }
+    }
+
+    #[test]
+    fn test_typed_value() {
+        with_guile(test_typed_value_impl, &());
+    }
+
+    extern "C" fn test_variable_impl(_: &()) {
+        unsafe {
+            let form = eval_str("'(test1 u8)").unwrap();
+            assert_eq!(
+                parse_variable_definition(form, &[], "").unwrap(),
+                Variable { name: String::from("test1"), typename: Type::U8, documentation: None, default: None}
+            );
+
+            let form = eval_str("'(test2 i32 \"Something\")").unwrap();
+            assert_eq!(
+                parse_variable_definition(form, &[], "").unwrap(),
+                Variable { name: String::from("test2"), typename: Type::I32, documentation: Some(String::from("Something")), default: None}
+            );
+
+            let form = eval_str("'(test3 (vec u8) \"Something else\" (0 1 2 3))").unwrap();
+            assert_eq!(
+                parse_variable_definition(form, &[], "").unwrap(),
+                Variable {
+                    name: String::from("test3"),
+                    typename: Type::Vec(Box::new(Type::U8)),
+                    documentation: Some(String::from("Something else")),
+                    default: Some(TypedValue::Vec(vec![
+                        TypedValue::U8(0),
+                        TypedValue::U8(1),
+                        TypedValue::U8(2),
+                        TypedValue::U8(3),
+                    ]))
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_variable() {
+        with_guile(test_variable_impl, &());
+    }
+
+    extern "C" fn test_value_type_impl(_: &()) {
+        unsafe {
+            let point = ValueType::from_scheme(eval_str("'(point (x i16) (y i16 \"Vertical position\"))").unwrap(), &[], "").unwrap();
+            assert_eq!(point, ValueType {
+                name: "point".to_owned(),
+                fields: vec![
+                    Variable { name: "x".to_owned(), typename: Type::I16, documentation: None, default: None },
+                    Variable { name: "y".to_owned(), typename: Type::I16, documentation: Some("Vertical position".to_owned()), default: None }
+                ]
+            });
+            assert!(ValueType::from_scheme(eval_str("'(bad (x i16 \"doc\" 0))").unwrap(), &[], "").is_err());
+
+            let value_types = [point.clone()];
+            let type_name = parse_type(eval_str("'(vec point)").unwrap(), &value_types).unwrap();
+            assert_eq!(type_name, Type::Vec(Box::new(Type::Value(point.clone()))));
+            assert!(parse_type(eval_str("'point").unwrap(), &[]).is_err());
+
+            let value = eval_str("'(((y . 2) (x . -1)))").unwrap();
+            assert_eq!(
+                parse_typed_value(&type_name, value, "").unwrap(),
+                TypedValue::Vec(vec![
+                    TypedValue::Value("point".to_owned(), vec![
+                        ("x".to_owned(), TypedValue::I16(-1)),
+                        ("y".to_owned(), TypedValue::I16(2))
+                    ])
+                ])
+            );
+            let point_type = Type::Value(point);
+            assert!(parse_typed_value(&point_type, eval_str("'((x . 1))").unwrap(), "").is_err());
+            assert!(parse_typed_value(&point_type, eval_str("'((x . 1) (y . 2) (z . 3))").unwrap(), "").is_err());
+            assert!(parse_typed_value(&point_type, eval_str("'((x . 1) (x . 2) (y . 3))").unwrap(), "").is_err());
+        }
+    }
+
+    #[test]
+    fn test_value_type() {
+        with_guile(test_value_type_impl, &());
+    }
+
+    extern "C" fn test_argument_list_impl(_: &()) {
+        let parameters = &[
+            Variable { name: "a".to_owned(), typename: Type::U8, documentation: None, default: None },
+            Variable { name: "b".to_owned(), typename: Type::U8, documentation: None, default: Some(TypedValue::U8(5)) },
+            Variable { name: "c".to_owned(), typename: Type::U8, documentation: None, default: Some(TypedValue::U8(10)) },
+        ];
+
+        unsafe {
+            let args_raw = eval_str("'(1 2 3)").unwrap();
+            let args = parse_argument_list(args_raw, parameters, "").unwrap();
+            assert_eq!(args, vec![TypedValue::U8(1), TypedValue::U8(2), TypedValue::U8(3)]);
+
+            let args_raw = eval_str("'(1)").unwrap();
+            let args = parse_argument_list(args_raw, parameters, "").unwrap();
+            assert_eq!(args, vec![TypedValue::U8(1), TypedValue::U8(5), TypedValue::U8(10)]);
+
+            let args_raw = eval_str("'((c . 3) (a . 1) (b . 2))").unwrap();
+            let args = parse_argument_list(args_raw, parameters, "").unwrap();
+            assert_eq!(args, vec![TypedValue::U8(1), TypedValue::U8(2), TypedValue::U8(3)]);
+
+            let args_raw = eval_str("'((c . 3))").unwrap();
+            assert!(parse_argument_list(args_raw, parameters, "").is_err());
+
+            let args_raw = eval_str("'(1 2 3 4)").unwrap();
+            assert!(parse_argument_list(args_raw, parameters, "").is_err());
+        }
+    }
+
+    #[test]
+    fn test_argument_list() {
+        with_guile(test_argument_list_impl, &())
+    }
+}
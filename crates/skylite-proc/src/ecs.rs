@@ -1,55 +1,196 @@
 use proc_macro2::TokenStream;
 use quote::{quote, format_ident, ToTokens};
-use syn::{parse::Parser, parse2, punctuated::Punctuated, Expr, ExprClosure, Item, ItemEnum, ItemStruct, ItemUnion, Pat, Token};
+use syn::{parse::Parser, parse2, punctuated::Punctuated, Attribute, Expr, ExprClosure, Item, ItemEnum, ItemStruct, ItemUnion, Pat, PatIdent, PatType, PathArguments, Token, Type, TypePath};
 
 use crate::SkyliteProcError;
 
-fn check_closure_args(closure: &ExprClosure) -> Result<(), SkyliteProcError> {
-    if closure.inputs.len() == 0 {
-        return Err(SkyliteProcError::SyntaxError("System must take at least one parameter".to_owned()));
+/// A project-level resource, which a system can request in addition to the components.
+enum Resource {
+    Globals,
+    Controls
+}
+
+impl Resource {
+    fn from_attrs(attrs: &[Attribute]) -> Result<Option<Resource>, SkyliteProcError> {
+        let mut out = None;
+        for attr in attrs {
+            let resource = if attr.path().is_ident("globals") {
+                Resource::Globals
+            } else if attr.path().is_ident("controls") {
+                Resource::Controls
+            } else {
+                return Err(SkyliteProcError::SyntaxError(format!("Unknown attribute on system parameter: {}", attr.to_token_stream())));
+            };
+            if out.is_some() {
+                return Err(SkyliteProcError::SyntaxError("A system parameter can only be a single resource".to_owned()));
+            }
+            out = Some(resource);
+        }
+        Ok(out)
+    }
+
+    fn access(&self, controls: &Expr) -> TokenStream {
+        match self {
+            Resource::Globals => quote!(::skylite_core::prelude::ProjectControls::globals_mut(#controls)),
+            Resource::Controls => quote!(#controls)
+        }
     }
+}
+
+/// Returns the filter for a parameter of type `Changed<T>` or `Added<T>`.
+fn as_filter(ty: &Type) -> Option<TokenStream> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    let segment = path.segments.last()?;
+    let constructor = match segment.ident.to_string().as_str() {
+        "Changed" => format_ident!("changed"),
+        "Added" => format_ident!("added"),
+        _ => return None
+    };
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    let component = args.args.first()?;
+    Some(quote!(::skylite_core::ecs::__private::Filter::#constructor::<#component>()))
+}
+
+/// Returns `true` if `ty` is `ActorHandle`, which requests the handle of the current entity.
+fn is_handle(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { qself: None, path }) if path.segments.last().is_some_and(|s| s.ident == "ActorHandle"))
+}
 
-    if closure.inputs.len() > 8 {
-        return Err(SkyliteProcError::SyntaxError("Too many parameters for system, max 8 are allowed".to_owned()));
+/// Converts a component parameter `pat: &mut T` or `pat: &T` into `pat: Mut<T>`, which is what the
+/// system functions pass to the closure. Mutable parameters are bound mutably, so that writing
+/// to the component through the `Mut` works, and marks the component as changed.
+fn component_param(param: &PatType) -> Result<TokenStream, SkyliteProcError> {
+    let Type::Reference(reference) = &*param.ty else {
+        return Err(SkyliteProcError::SyntaxError(format!("Component parameters of a system must be references: {}", param.to_token_stream())));
+    };
+    let component = &reference.elem;
+    let pat = &param.pat;
+    match &**pat {
+        Pat::Ident(PatIdent { by_ref: None, mutability: None, .. }) if reference.mutability.is_some() => {
+            Ok(quote!(#[allow(unused_mut)] mut #pat: ::skylite_core::prelude::Mut<#component>))
+        },
+        _ => Ok(quote!(#pat: ::skylite_core::prelude::Mut<#component>))
     }
+}
+
+/// The parameters of a system's closure, sorted by what they request.
+struct SystemParams {
+    components: Vec<PatType>,
+    resources: Vec<(PatType, Resource)>,
+    filters: Vec<TokenStream>,
+    handle: Option<PatType>
+}
 
-    let mut types = Vec::new();
-    for i in closure.inputs.iter() {
-        match i {
-            Pat::Type(ty) => {
-                if types.contains(ty) {
+impl SystemParams {
+    fn from_closure(closure: &ExprClosure) -> Result<SystemParams, SkyliteProcError> {
+        let mut out = SystemParams { components: Vec::new(), resources: Vec::new(), filters: Vec::new(), handle: None };
+        for i in closure.inputs.iter() {
+            let Pat::Type(ty) = i else {
+                return Err(SkyliteProcError::SyntaxError("Parameters to a system must always have an explicit type annotation".to_owned()));
+            };
+
+            if let Some(resource) = Resource::from_attrs(&ty.attrs)? {
+                let mut ty = ty.clone();
+                ty.attrs.clear();
+                out.resources.push((ty, resource));
+            } else if let Some(filter) = as_filter(&ty.ty) {
+                out.filters.push(filter);
+            } else if is_handle(&ty.ty) {
+                if out.handle.is_some() {
+                    return Err(SkyliteProcError::SyntaxError("A system can only request the ActorHandle once".to_owned()));
+                }
+                out.handle = Some(ty.clone());
+            } else {
+                if out.components.iter().any(|c| c.ty == ty.ty) {
                     return Err(SkyliteProcError::SyntaxError(format!("Duplicate component type in system: {}", ty.to_token_stream())));
                 }
-                types.push(ty.clone());
-            },
-            _ => {
-                return Err(SkyliteProcError::SyntaxError("Parameters to a system must always have an explicit type annotation".to_owned()));
+                out.components.push(ty.clone());
             }
         }
-    }
 
-    Ok(())
+        if out.components.is_empty() {
+            return Err(SkyliteProcError::SyntaxError("System must take at least one component".to_owned()));
+        }
+
+        if out.components.len() > 8 {
+            return Err(SkyliteProcError::SyntaxError("Too many components for system, max 8 are allowed".to_owned()));
+        }
+
+        // Both resources borrow the ProjectControls mutably.
+        if out.resources.len() > 1 {
+            return Err(SkyliteProcError::SyntaxError("A system can only request one resource, either #[globals] or #[controls]".to_owned()));
+        }
+
+        Ok(out)
+    }
 }
 
 fn system_fallible(args: TokenStream) -> Result<TokenStream, SkyliteProcError> {
     let args = Parser::parse2(Punctuated::<Expr, Token![,]>::parse_separated_nonempty, args.clone())
         .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse arguments: {}", err.to_string())))?;
-    if args.len() != 2 {
-        return Err(SkyliteProcError::SyntaxError("system takes exactly to arguments.".to_owned()));
-    }
-    let receiver = &args[0];
-    let closure = match &args[1] {
-        Expr::Closure(c) => c,
+    let (receiver, controls, closure) = match (args.len(), args.last()) {
+        (2, Some(Expr::Closure(c))) => (&args[0], None, c),
+        (3, Some(Expr::Closure(c))) => (&args[0], Some(&args[1]), c),
+        (2 | 3, _) => {
+            return Err(SkyliteProcError::SyntaxError("Last argument to system must be a closure.".to_owned()));
+        },
         _ => {
-            return Err(SkyliteProcError::SyntaxError("Second argument to system must be a closure.".to_owned()));
+            return Err(SkyliteProcError::SyntaxError("system takes either two or three arguments.".to_owned()));
         }
     };
 
-    check_closure_args(closure)?;
+    let SystemParams { components, resources, filters, handle } = SystemParams::from_closure(closure)?;
+    let num_components = components.len();
+    let inputs = components.iter()
+        .map(component_param)
+        .collect::<Result<Vec<TokenStream>, SkyliteProcError>>()?;
+    let body = &closure.body;
+    let movability = &closure.capture;
 
-    let system_fn = format_ident!("system{}", closure.inputs.len());
+    if resources.is_empty() && filters.is_empty() && handle.is_none() {
+        let system_fn = format_ident!("system{}", num_components);
+        return Ok(quote!(::skylite_core::ecs::__private::#system_fn(#receiver, #movability |#(#inputs),*| #body)));
+    }
+
+    let resource_bindings = if resources.is_empty() {
+        Vec::new()
+    } else {
+        let controls = controls.ok_or_else(|| SkyliteProcError::SyntaxError("Resources can only be requested when the ProjectControls are passed to system".to_owned()))?;
+        resources.iter()
+            .map(|(PatType { pat, ty, .. }, resource)| {
+                let access = resource.access(controls);
+                quote!(let #pat: #ty = #access;)
+            })
+            .collect::<Vec<TokenStream>>()
+    };
 
-    Ok(quote!(::skylite_core::ecs::__private::#system_fn(#receiver, #closure)))
+    let call = if filters.is_empty() && handle.is_none() {
+        let system_fn = format_ident!("system{}", num_components);
+        quote!(::skylite_core::ecs::__private::#system_fn(#receiver, #movability |#(#inputs),*| #body))
+    } else {
+        // The filtered system functions always pass the entity's handle as the first argument.
+        let system_fn = format_ident!("filtered_system{}", num_components);
+        let handle = handle.map(|h| quote!(#h)).unwrap_or(quote!(_: ::skylite_core::prelude::ActorHandle));
+        let entity_closure = quote!(#movability |#handle, #(#inputs),*| #body);
+
+        if filters.is_empty() {
+            quote!(::skylite_core::ecs::__private::#system_fn(#receiver, &[], ::std::option::Option::None, #entity_closure))
+        } else {
+            // Each invocation of system! has its own id, with which the entities remember
+            // which changes the system has already seen.
+            quote! {
+                static SYSTEM: ::skylite_core::ecs::__private::SystemId = ::skylite_core::ecs::__private::SystemId::new();
+                ::skylite_core::ecs::__private::#system_fn(#receiver, &[#(#filters),*], ::std::option::Option::Some(&SYSTEM), #entity_closure)
+            }
+        }
+    };
+
+    Ok(quote! {
+        {
+            #(#resource_bindings)*
+            #call
+        }
+    })
 }
 
 pub(crate) fn system_impl(args: TokenStream) -> TokenStream {
@@ -74,6 +215,53 @@ pub(crate) fn derive_component_impl(item: TokenStream) -> TokenStream {
             }
         }
 
-        impl #typeparams ::skylite_core::ecs::Component  for #typename #typeparams {}
+        impl #typeparams ::skylite_core::prelude::Component  for #typename #typeparams {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::system_fallible;
+
+    #[test]
+    fn test_system() {
+        let actual = system_fallible(quote!(entities, controls, |pos: &mut Position, #[globals] globals: &mut TestGlobals| globals.count += pos.x)).unwrap();
+        let expectation = quote! {
+            {
+                let globals: &mut TestGlobals = ::skylite_core::prelude::ProjectControls::globals_mut(controls);
+                ::skylite_core::ecs::__private::system1(entities, |#[allow(unused_mut)] mut pos: ::skylite_core::prelude::Mut<Position>| globals.count += pos.x)
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+
+        let actual = system_fallible(quote!(entities, |pos: &mut Position, vel: &Velocity| pos.x += vel.x)).unwrap();
+        let expectation = quote! {
+            ::skylite_core::ecs::__private::system2(entities, |#[allow(unused_mut)] mut pos: ::skylite_core::prelude::Mut<Position>, vel: ::skylite_core::prelude::Mut<Velocity>| pos.x += vel.x)
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+
+        let actual = system_fallible(quote!(entities, |pos: &mut Position, _: Changed<Velocity>| pos.x += 1)).unwrap();
+        let expectation = quote! {
+            {
+                static SYSTEM: ::skylite_core::ecs::__private::SystemId = ::skylite_core::ecs::__private::SystemId::new();
+                ::skylite_core::ecs::__private::filtered_system1(entities, &[::skylite_core::ecs::__private::Filter::changed::<Velocity>()], ::std::option::Option::Some(&SYSTEM), |_: ::skylite_core::prelude::ActorHandle, #[allow(unused_mut)] mut pos: ::skylite_core::prelude::Mut<Position>| pos.x += 1)
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+
+        let actual = system_fallible(quote!(entities, |handle: ActorHandle, hp: &mut Health| if hp.0 == 0 { commands.despawn(handle) })).unwrap();
+        let expectation = quote! {
+            {
+                ::skylite_core::ecs::__private::filtered_system1(entities, &[], ::std::option::Option::None, |handle: ActorHandle, #[allow(unused_mut)] mut hp: ::skylite_core::prelude::Mut<Health>| if hp.0 == 0 { commands.despawn(handle) })
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+
+        assert!(system_fallible(quote!(entities, |#[globals] globals: &mut TestGlobals| ())).is_err());
+        assert!(system_fallible(quote!(entities, |pos: &mut Position, #[globals] globals: &mut TestGlobals| ())).is_err());
+        assert!(system_fallible(quote!(entities, controls, |pos: &mut Position, #[globals] g: &mut TestGlobals, #[controls] c: &mut TestControls| ())).is_err());
+        assert!(system_fallible(quote!(entities, |pos: Position| ())).is_err());
     }
 }
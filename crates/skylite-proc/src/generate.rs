@@ -2,4 +2,12 @@ pub(crate) mod project;
 pub(crate) mod scenes;
 pub(crate) mod actors;
 pub(crate) mod encode;
+pub(crate) mod strings;
+pub(crate) mod config;
+pub(crate) mod custom;
+pub(crate) mod embedded;
+pub(crate) mod graphics;
+pub(crate) mod schema;
+pub(crate) mod size_report;
 pub(crate) mod util;
+pub(crate) mod values;
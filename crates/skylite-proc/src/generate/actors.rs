@@ -1,10 +1,10 @@
 use proc_macro2::{Ident, Literal, TokenStream};
-use quote::{format_ident, quote};
-use syn::{parse_str, Item, ItemFn, Meta};
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse::{ParseStream, Parser}, parse2, parse_str, punctuated::Punctuated, Expr, Field, Item, ItemFn, LitFloat, LitStr, Meta, Token};
 
 use crate::{parse::{actors::{Action, Actor}, util::{change_case, IdentCase}, values::Variable}, SkyliteProcError};
 
-use super::{project::{project_ident, project_type_name}, util::{generate_param_list, get_annotated_function, get_macro_item, skylite_type_to_rust, typed_value_to_rust}};
+use super::{project::{project_ident, project_type_name}, util::{check_derive_targets, gen_constructor_docs, gen_properties_init, generate_param_list, get_annotated_function, get_documentation, get_macro_item, skylite_type_to_rust, typed_value_to_rust}};
 
 // region: AnyActor Type for skylite_project CodeGen
 
@@ -12,9 +12,24 @@ pub(super) fn any_actor_type_name(project_name: &str) -> Ident {
     format_ident!("{}Actors", change_case(project_name, IdentCase::UpperCamelCase))
 }
 
-pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor]) -> Result<TokenStream, SkyliteProcError> {
+/// Generates the expression which is evaluated when `_private_decode` encounters an unknown actor id.
+///
+/// If the project has a function marked with `#[skylite_proc::unknown_actor]`, it is called with
+/// the `DecodeError` in `err` and returns a substitute actor. Otherwise, decoding panics.
+fn gen_unknown_actor_fallback(items: &[Item]) -> TokenStream {
+    match get_annotated_function(items, "skylite_proc::unknown_actor") {
+        Some(fun) => {
+            let name = &fun.sig.ident;
+            quote!(#name(err))
+        },
+        None => quote!(::std::panic!("{}", err))
+    }
+}
+
+pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor], items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
     let project_ident = project_ident(project_name);
     let type_name = any_actor_type_name(project_name);
+    let unknown_actor_fallback = gen_unknown_actor_fallback(items);
 
     let actor_names: Vec<Ident> = actors.iter()
         .map(|a| format_ident!("{}", change_case(&a.name, IdentCase::UpperCamelCase)))
@@ -22,13 +37,40 @@ pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor]) -> Resu
     let actor_ids: Vec<Literal> = (0..actors.len())
         .map(|i| Literal::usize_unsuffixed(i))
         .collect();
+    let actor_docs = actors.iter().map(|a| get_documentation(&a.description));
+
+    let reflect_methods = if cfg!(feature = "debug-reflection") {
+        quote! {
+            fn reflect(&self) -> Option<&dyn ::skylite_core::reflect::Reflect> {
+                match *self {
+                    #(
+                        #type_name::#actor_names(ref a) => a.reflect()
+                    ),*
+                }
+            }
+
+            fn reflect_mut(&mut self) -> Option<&mut dyn ::skylite_core::reflect::Reflect> {
+                match *self {
+                    #(
+                        #type_name::#actor_names(ref mut a) => a.reflect_mut()
+                    ),*
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
 
     Ok(quote! {
+        /// Holds any of the project's actors.
         pub enum #type_name {
-            #(#actor_names(::std::boxed::Box::<#actor_names>)),*
+            #(
+                #actor_docs
+                #actor_names(::std::boxed::Box::<#actor_names>)
+            ),*
         }
 
-        impl skylite_core::actors::InstanceId for #type_name {
+        impl ::skylite_core::actors::InstanceId for #type_name {
             fn get_id(&self) -> usize where Self: Sized {
                 // The combination of `*self` and `ref a` is required for an empty `actors` list work,
                 // because there may or may not be a way to construct *something* using an empty actors enum.
@@ -42,27 +84,25 @@ pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor]) -> Resu
             }
         }
 
-        impl skylite_core::actors::ActorBase for #type_name {
+        impl ::skylite_core::prelude::ActorBase for #type_name {
             type P = #project_ident;
 
-            fn _private_decode(decoder: &mut dyn skylite_compress::Decoder) -> Self {
-                match skylite_core::decode::read_varint(decoder) {
-                    #(
-                        #actor_ids => #type_name::#actor_names(::std::boxed::Box::new(#actor_names::_private_decode(decoder))),
-                    )*
-                    _ => ::std::unreachable!()
+            fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> Self {
+                match <Self as ::skylite_core::prelude::AnyActor>::_private_try_decode(decoder) {
+                    Ok(actor) => actor,
+                    Err(err) => #unknown_actor_fallback
                 }
             }
 
-            fn _private_update(&mut self, scene: &mut dyn ::skylite_core::scenes::Scene<P=Self::P>, controls: &mut ::skylite_core::ProjectControls<Self::P>) {
+            fn _private_update(&mut self, scene: &mut dyn ::skylite_core::prelude::Scene<P=Self::P>, controls: &mut ::skylite_core::prelude::ProjectControls<Self::P>, phase: ::skylite_core::prelude::UpdatePhase) {
                 match *self {
                     #(
-                        #type_name::#actor_names(ref mut a) => a._private_update(scene, controls)
+                        #type_name::#actor_names(ref mut a) => a._private_update(scene, controls, phase)
                     ),*
                 }
             }
 
-            fn _private_render(&self, ctx: &skylite_core::DrawContext<Self::P>) {
+            fn _private_render(&self, ctx: &mut ::skylite_core::prelude::DrawContext<Self::P>) {
                 match *self {
                     #(
                         #type_name::#actor_names(ref a) => a._private_render(ctx)
@@ -70,7 +110,39 @@ pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor]) -> Resu
                 }
             }
 
-            fn get_entity(&self) -> &::skylite_core::ecs::Entity {
+            fn z_order(&self) -> i16 {
+                match *self {
+                    #(
+                        #type_name::#actor_names(ref a) => a.z_order()
+                    ),*
+                }
+            }
+
+            fn render_layer(&self) -> ::skylite_core::prelude::RenderLayer {
+                match *self {
+                    #(
+                        #type_name::#actor_names(ref a) => a.render_layer()
+                    ),*
+                }
+            }
+
+            fn parallax(&self) -> (f32, f32) {
+                match *self {
+                    #(
+                        #type_name::#actor_names(ref a) => a.parallax()
+                    ),*
+                }
+            }
+
+            fn update_priority(&self) -> i16 {
+                match *self {
+                    #(
+                        #type_name::#actor_names(ref a) => a.update_priority()
+                    ),*
+                }
+            }
+
+            fn get_entity(&self) -> &::skylite_core::prelude::Entity {
                 match *self {
                     #(
                         #type_name::#actor_names(ref a) => a.get_entity()
@@ -78,17 +150,28 @@ pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor]) -> Resu
                 }
             }
 
-            fn get_entity_mut(&mut self) -> &mut ::skylite_core::ecs::Entity {
+            fn get_entity_mut(&mut self) -> &mut ::skylite_core::prelude::Entity {
                 match *self {
                     #(
                         #type_name::#actor_names(ref mut a) => a.get_entity_mut()
                     ),*
                 }
             }
+
+            #reflect_methods
         }
 
-        impl skylite_core::actors::AnyActor for #type_name {
-            unsafe fn _private_transmute_mut<A: skylite_core::actors::Actor>(&mut self) -> &mut A {
+        impl ::skylite_core::prelude::AnyActor for #type_name {
+            fn _private_try_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> Result<Self, ::skylite_core::prelude::DecodeError> {
+                match ::skylite_core::prelude::read_varint(decoder) {
+                    #(
+                        #actor_ids => Ok(#type_name::#actor_names(::std::boxed::Box::new(#actor_names::_private_decode(decoder)))),
+                    )*
+                    id => Err(::skylite_core::prelude::DecodeError::UnknownId { kind: "actor", id })
+                }
+            }
+
+            unsafe fn _private_transmute_mut<A: ::skylite_core::prelude::Actor>(&mut self) -> &mut A {
                 match self {
                     #(
                         #type_name::#actor_names(a) => {
@@ -101,7 +184,7 @@ pub(crate) fn generate_actors_type(project_name: &str, actors: &[Actor]) -> Resu
                 }
             }
 
-            unsafe fn _private_transmute<A: skylite_core::actors::Actor>(&self) -> &A {
+            unsafe fn _private_transmute<A: ::skylite_core::prelude::Actor>(&self) -> &A {
                 match self {
                     #(
                         #type_name::#actor_names(a) => {
@@ -123,16 +206,6 @@ fn actor_type_name(actor_name: &str) -> Ident { format_ident!("{}", change_case(
 fn action_type_name(actor_name: &str) -> Ident { format_ident!("{}Actions", change_case(actor_name, IdentCase::UpperCamelCase)) }
 fn properties_type_name(actor_name: &str) -> Ident { format_ident!("{}Properties", change_case(actor_name, IdentCase::UpperCamelCase)) }
 
-fn get_documentation(doc: &Option<String>) -> TokenStream {
-    match &doc {
-        Some(v) => {
-            let content = Literal::string(&v);
-            quote!(#[doc = #content])
-        },
-        None => TokenStream::new(),
-    }
-}
-
 fn get_parameter_name(var: &Variable) -> Ident { format_ident!("{}", change_case(&var.name, IdentCase::LowerSnakeCase)) }
 fn get_parameter_type(var: &Variable) -> TokenStream { skylite_type_to_rust(&var.typename) }
 fn get_parameter_docs(var: &Variable) -> TokenStream { get_documentation(&var.documentation) }
@@ -200,9 +273,9 @@ fn gen_actions_type(name: &Ident, actions: &[Action]) -> TokenStream {
             ),*
         }
 
-        impl ::skylite_core::actors::ActorAction for #name {
-            fn _private_decode(decoder: &mut dyn ::skylite_compress::Decoder) -> #name {
-                use skylite_core::decode::Deserialize;
+        impl ::skylite_core::prelude::ActorAction for #name {
+            fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> #name {
+                use ::skylite_core::prelude::Deserialize;
                 match u8::deserialize(decoder) {
                     #(
                         #action_ids => {
@@ -223,28 +296,181 @@ fn gen_actions_type(name: &Ident, actions: &[Action]) -> TokenStream {
 
 fn get_actor_param_list(actor: &Actor) -> TokenStream { generate_param_list(&actor.parameters) }
 
-fn gen_properties_type(actor: &Actor, items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+/// The properties from `skylite_proc::properties!`, with the marker attributes removed.
+struct MarkedProperties {
+    properties: TokenStream,
+    observed: Vec<Field>,
+    interpolated: Vec<Field>,
+    mode: Option<Field>
+}
+
+/// Removes the `#[skylite_proc::observed]`, `#[skylite_proc::interpolated]` and `#[skylite_proc::mode]`
+/// attributes from the fields in `properties`.
+///
+/// Returns the remaining fields, as well as the fields which were marked. At most one field can be the mode.
+fn extract_property_markers(properties: &TokenStream) -> Result<MarkedProperties, SkyliteProcError> {
+    let observed_path = parse_str::<syn::Path>("skylite_proc::observed").unwrap();
+    let interpolated_path = parse_str::<syn::Path>("skylite_proc::interpolated").unwrap();
+    let mode_path = parse_str::<syn::Path>("skylite_proc::mode").unwrap();
+    let mut fields = (|input: ParseStream| Punctuated::<Field, Token![,]>::parse_terminated_with(input, Field::parse_named))
+        .parse2(properties.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse properties: {}", err)))?;
+
+    fn take_marker(field: &mut Field, path: &syn::Path) -> bool {
+        let attr_count = field.attrs.len();
+        field.attrs.retain(|attr| !matches!(&attr.meta, Meta::Path(p) if p == path));
+        field.attrs.len() != attr_count
+    }
+
+    let mut observed = Vec::new();
+    let mut interpolated = Vec::new();
+    let mut mode: Option<Field> = None;
+    for field in fields.iter_mut() {
+        let is_observed = take_marker(field, &observed_path);
+        let is_interpolated = take_marker(field, &interpolated_path);
+        let is_mode = take_marker(field, &mode_path);
+        if is_observed {
+            observed.push(field.clone());
+        }
+        if is_interpolated {
+            interpolated.push(field.clone());
+        }
+        if is_mode {
+            if let Some(other) = &mode {
+                return Err(SkyliteProcError::DataError(format!("Only one property can be marked as mode, found {} and {}", other.ident.as_ref().unwrap(), field.ident.as_ref().unwrap())));
+            }
+            mode = Some(field.clone());
+        }
+    }
+    Ok(MarkedProperties { properties: fields.into_token_stream(), observed, interpolated, mode })
+}
+
+fn get_marked_properties(items: &[Item]) -> Result<MarkedProperties, SkyliteProcError> {
+    match get_macro_item("skylite_proc::properties", items)? {
+        Some(tokens) => extract_property_markers(tokens),
+        None => Ok(MarkedProperties { properties: TokenStream::new(), observed: Vec::new(), interpolated: Vec::new(), mode: None })
+    }
+}
+
+/// Generates a method that stores the current values of the interpolated properties as their previous values.
+fn gen_store_interpolated(interpolated: &[Field]) -> TokenStream {
+    if interpolated.is_empty() {
+        return TokenStream::new();
+    }
+    let names = interpolated.iter().map(|field| field.ident.as_ref().unwrap());
+    quote! {
+        #[doc(hidden)]
+        pub fn _private_store_interpolated(&mut self) {
+            #(self.#names._private_store();)*
+        }
+    }
+}
+
+/// Returns `true` if `ty` is one of the primitive types supported by `ReflectPrimitive`.
+fn is_reflect_primitive(ty: &syn::Type) -> bool {
+    const PRIMITIVES: &[&str] = &["bool", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64"];
+    match ty {
+        syn::Type::Path(p) => p.qself.is_none() && p.path.get_ident().is_some_and(|ident| PRIMITIVES.contains(&ident.to_string().as_str())),
+        _ => false
+    }
+}
+
+/// Generates the implementation of `Reflect` for the properties type, which is used
+/// with the `debug-reflection` feature. Only properties of primitive types are editable.
+fn gen_reflect_impl(properties_type_name: &Ident, properties: &TokenStream) -> Result<TokenStream, SkyliteProcError> {
+    let fields = (|input: ParseStream| Punctuated::<Field, Token![,]>::parse_terminated_with(input, Field::parse_named))
+        .parse2(properties.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse properties: {}", err)))?;
+
+    let infos = fields.iter().map(|field| {
+        let name = field.ident.as_ref().unwrap().to_string();
+        let ty = &field.ty;
+        let type_name = quote!(#ty).to_string();
+        let editable = is_reflect_primitive(ty);
+        quote!(::skylite_core::reflect::PropertyInfo { name: #name, type_name: #type_name, editable: #editable })
+    });
+
+    let editable: Vec<&Ident> = fields.iter()
+        .filter(|field| is_reflect_primitive(&field.ty))
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect();
+    let editable_names: Vec<String> = editable.iter().map(|ident| ident.to_string()).collect();
+
+    Ok(quote! {
+        impl ::skylite_core::reflect::Reflect for #properties_type_name {
+            fn properties(&self) -> &'static [::skylite_core::reflect::PropertyInfo] {
+                &[#(#infos),*]
+            }
+
+            fn get_property(&self, name: &str) -> Option<::skylite_core::reflect::ReflectValue> {
+                use ::skylite_core::reflect::ReflectPrimitive;
+                match name {
+                    #(#editable_names => Some(self.#editable.to_value()),)*
+                    _ => None
+                }
+            }
+
+            fn set_property(&mut self, name: &str, value: ::skylite_core::reflect::ReflectValue) -> bool {
+                use ::skylite_core::reflect::ReflectPrimitive;
+                match name {
+                    #(
+                        #editable_names => match ReflectPrimitive::from_value(value) {
+                            Some(v) => { self.#editable = v; true },
+                            None => false
+                        },
+                    )*
+                    _ => false
+                }
+            }
+        }
+    })
+}
+
+/// Generates setters for the observed properties, which record changes in the `ProjectControls`.
+fn gen_observed_setters(actor_type_name: &Ident, project_type_name: &TokenStream, observed: &[Field]) -> TokenStream {
+    let setters = observed.iter().map(|field| {
+        let name = field.ident.as_ref().unwrap();
+        let name_str = name.to_string();
+        let setter_name = format_ident!("set_{}", name);
+        let ty = &field.ty;
+        quote! {
+            /// Sets the property, and records the change in `controls` if the value is different.
+            pub fn #setter_name(&mut self, value: #ty, controls: &mut ::skylite_core::prelude::ProjectControls<#project_type_name>) {
+                if self.#name != value {
+                    self.#name = value;
+                    controls._private_record_change::<#actor_type_name>(#name_str);
+                }
+            }
+        }
+    });
+    quote!(#(#setters)*)
+}
+
+fn gen_properties_type(actor: &Actor, project_type_name: &TokenStream, items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
     let actor_param_list = get_actor_param_list(actor);
     let actor_param_names: Vec<Ident> = actor.parameters.iter().map(get_parameter_name).collect();
     let properties_type_name = properties_type_name(&actor.name);
 
-    // The properties are copied directly from the `skylite_proc::properties!` function macro.
-    let properties = match get_macro_item("skylite_proc::properties", items)? {
-        Some(tokens) => tokens.clone(),
-        None => TokenStream::new()
+    // The properties are copied directly from the `skylite_proc::properties!` function macro,
+    // except for the `observed`, `interpolated` and `mode` markers.
+    let MarkedProperties { properties, observed, interpolated, .. } = get_marked_properties(items)?;
+    let observed_setters = gen_observed_setters(&actor_type_name(&actor.name), project_type_name, &observed);
+    let store_interpolated = gen_store_interpolated(&interpolated);
+    let reflect_impl = if cfg!(feature = "debug-reflection") {
+        gen_reflect_impl(&properties_type_name, &properties)?
+    } else {
+        TokenStream::new()
     };
 
-    // For an actor that has neither parameters nor properties, the create_properties special function is optional.
-    let create_properties_call = if actor.parameters.len() > 0 || !properties.is_empty() {
-        match get_annotated_function(items, "skylite_proc::create_properties") {
-            Some(fun) => {
-                let ident = &fun.sig.ident;
-                quote! { super::#ident(#(#actor_param_names),*) }
-            },
-            None => return Err(SkyliteProcError::DataError(format!("Missing required special function `create_properties`. Function is required because the actor has parameters or properties.")))
-        }
-    } else {
-        quote!(#properties_type_name {})
+    // If there is no create_properties special function, the properties are initialized
+    // from the parameters with matching names, or from derive_property functions.
+    let create_properties_call = match get_annotated_function(items, "skylite_proc::create_properties") {
+        Some(fun) => {
+            check_derive_targets(items, None)?;
+            let ident = &fun.sig.ident;
+            quote! { super::#ident(#(#actor_param_names),*) }
+        },
+        None => gen_properties_init(&properties_type_name, &properties, &actor.parameters, items)?
     };
 
     Ok(quote! {
@@ -253,10 +479,17 @@ fn gen_properties_type(actor: &Actor, items: &[Item]) -> Result<TokenStream, Sky
         }
 
         impl #properties_type_name {
+            #[allow(unused_variables)]
             fn _private_create_properties(#actor_param_list) -> #properties_type_name {
                 #create_properties_call
             }
+
+            #observed_setters
+
+            #store_interpolated
         }
+
+        #reflect_impl
     })
 }
 
@@ -264,7 +497,7 @@ fn gen_properties_type(actor: &Actor, items: &[Item]) -> Result<TokenStream, Sky
 
 // region: Main Actor Type
 
-fn gen_actor_type(actor: &Actor) -> TokenStream {
+fn gen_actor_type(actor: &Actor, project_name: &str) -> TokenStream {
     let actor_type_name = actor_type_name(&actor.name);
     let action_type_name = action_type_name(&actor.name);
     let properties_type_name = properties_type_name(&actor.name);
@@ -277,24 +510,29 @@ fn gen_actor_type(actor: &Actor) -> TokenStream {
             .find(|action| action.name == actor.initial_action.name).unwrap()
         .params.iter()
             .map(|p| format_ident!("{}", change_case(&p.name, IdentCase::LowerSnakeCase)));
+    let project_type_name = project_type_name(project_name);
     let initial_action_args = actor.initial_action.args.iter()
-        .map(typed_value_to_rust);
+        .map(|arg| typed_value_to_rust(arg, &project_type_name));
+    let actor_docs = get_documentation(&actor.description);
+    let constructor_docs = gen_constructor_docs(&actor_type_name, &actor.parameters);
 
     quote! {
+        #actor_docs
         pub struct #actor_type_name {
             pub properties: #properties_type_name,
-            entity: ::skylite_core::ecs::Entity,
+            entity: ::skylite_core::prelude::Entity,
             current_action: #action_type_name,
             action_changed: bool,
             clear_action_changed: bool
         }
 
         impl #actor_type_name {
+            #constructor_docs
             pub fn new(#actor_param_list) -> #actor_type_name {
                 #actor_type_name {
                     // See `gen_actor_properties_type` for the definition of `create_properties`.
                     properties: #properties_type_name::_private_create_properties(#(#actor_param_names),*),
-                    entity: ::skylite_core::ecs::Entity::new(),
+                    entity: ::skylite_core::prelude::Entity::new(),
                     current_action: #action_type_name::#initial_action_name {
                         #(#initial_action_params: #initial_action_args),*
                     },
@@ -319,8 +557,8 @@ fn gen_actor_decode_fn(actor_type_name: &Ident, params: &[Variable]) -> TokenStr
         });
 
     quote! {
-        fn _private_decode(decoder: &mut dyn ::skylite_compress::Decoder) -> #actor_type_name {
-            use skylite_core::decode::Deserialize;
+        fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> #actor_type_name {
+            use ::skylite_core::prelude::Deserialize;
             #(
                 let #actor_param_names = #actor_args_decoders;
             )*
@@ -330,6 +568,53 @@ fn gen_actor_decode_fn(actor_type_name: &Ident, params: &[Variable]) -> TokenStr
     }
 }
 
+/// Returns the functions annotated with `#[skylite_proc::update("Variant")]`, with the name of the variant.
+fn get_mode_updates(items: &[Item]) -> Result<Vec<(Ident, &ItemFn)>, SkyliteProcError> {
+    let update_path = parse_str::<syn::Path>("skylite_proc::update").unwrap();
+    let mut out: Vec<(Ident, &ItemFn)> = Vec::new();
+    for item in items {
+        let Item::Fn(fun) = item else { continue };
+        for attr in fun.attrs.iter().filter(|attr| *attr.path() == update_path) {
+            let variant = attr.parse_args::<LitStr>()
+                .map_err(|err| SkyliteProcError::SyntaxError(format!("Expected #[skylite_proc::update(\"Variant\")] on {}: {}", fun.sig.ident, err)))?;
+            let variant = parse_str::<Ident>(&variant.value())
+                .map_err(|_| SkyliteProcError::SyntaxError(format!("Invalid variant name {:?} on {}", variant.value(), fun.sig.ident)))?;
+            if out.iter().any(|(v, _)| *v == variant) {
+                return Err(SkyliteProcError::DataError(format!("Multiple functions annotated with #[skylite_proc::update(\"{}\")]", variant)));
+            }
+            out.push((variant, fun));
+        }
+    }
+    Ok(out)
+}
+
+/// Generates the dispatch from the current value of the mode property to the
+/// `#[skylite_proc::update("Variant")]` functions. Variants without a function do nothing.
+fn gen_mode_update(mode: Option<&Field>, items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+    let updates = get_mode_updates(items)?;
+    let Some(mode) = mode else {
+        return match updates.first() {
+            Some((_, fun)) => Err(SkyliteProcError::DataError(format!("{} is annotated with #[skylite_proc::update], but no property is marked with #[skylite_proc::mode]", fun.sig.ident))),
+            None => Ok(TokenStream::new())
+        };
+    };
+
+    let mode_name = mode.ident.as_ref().unwrap();
+    let mode_type = match &mode.ty {
+        syn::Type::Path(p) if p.qself.is_none() => &p.path,
+        _ => return Err(SkyliteProcError::DataError(format!("The type of the mode property {} must be an enum", mode_name)))
+    };
+    let variants = updates.iter().map(|(variant, _)| variant);
+    let functions = updates.iter().map(|(_, fun)| &fun.sig.ident);
+    Ok(quote! {
+        #[allow(unreachable_patterns)]
+        match self.properties.#mode_name {
+            #(#mode_type::#variants { .. } => super::#functions(self, scene, controls),)*
+            _ => {}
+        };
+    })
+}
+
 fn gen_actor_update_fn(actions_type_name: &Ident, actions: &[Action], items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
     fn get_name(fun: &ItemFn) -> Ident { fun.sig.ident.clone() }
 
@@ -339,7 +624,7 @@ fn gen_actor_update_fn(actions_type_name: &Ident, actions: &[Action], items: &[I
         .map(|action| {
             let param_names = action.params.iter().map(get_parameter_name);
             // The arguments to the action implementation must be cloned,
-            // because some of possible types (String, Vec) own memory on the heap.
+            // because some of possible types (Vec) own memory on the heap.
             quote!(#(#param_names.clone()),*)
         });
 
@@ -357,22 +642,96 @@ fn gen_actor_update_fn(actions_type_name: &Ident, actions: &[Action], items: &[I
         .map(|name| quote!(super::#name(self, scene, controls);))
         .unwrap_or(TokenStream::new());
 
+    // The previous values of interpolated properties are stored before anything
+    // else happens in the update, so that they match the state from the last render.
+    let marked_properties = get_marked_properties(items)?;
+    let store_interpolated = if !marked_properties.interpolated.is_empty() {
+        quote!(self.properties._private_store_interpolated();)
+    } else {
+        TokenStream::new()
+    };
+    let mode_update = gen_mode_update(marked_properties.mode.as_ref(), items)?;
+
     Ok(quote! {
-        fn _private_update(&mut self, scene: &mut dyn ::skylite_core::scenes::Scene<P=Self::P>, controls: &mut ::skylite_core::ProjectControls<Self::P>) {
-            #pre_update
-
-            self.clear_action_changed = self.action_changed;
-            match self.current_action {
-                #(
-                    #actions_type_name::#action_names { #action_param_names } => super::#action_implementations(self, scene, controls, #action_args)
-                ),*
-            };
-            if self.clear_action_changed {
-                self.action_changed = false;
-                self.clear_action_changed = false;
+        fn _private_update(&mut self, scene: &mut dyn ::skylite_core::prelude::Scene<P=Self::P>, controls: &mut ::skylite_core::prelude::ProjectControls<Self::P>, phase: ::skylite_core::prelude::UpdatePhase) {
+            use ::skylite_core::prelude::UpdatePhase;
+            match phase {
+                UpdatePhase::Pre => {
+                    #store_interpolated
+                    #pre_update
+                },
+                UpdatePhase::Main => {
+                    self.clear_action_changed = self.action_changed;
+                    match self.current_action {
+                        #(
+                            #actions_type_name::#action_names { #action_param_names } => super::#action_implementations(self, scene, controls, #action_args)
+                        ),*
+                    };
+                    #mode_update
+                    if self.clear_action_changed {
+                        self.action_changed = false;
+                        self.clear_action_changed = false;
+                    }
+                },
+                UpdatePhase::Post => {
+                    #post_update
+                }
             }
+        }
+    })
+}
 
-            #post_update
+/// Generates the `render_layer` method from the `skylite_proc::render_layer!` macro, if present.
+fn gen_render_layer_fn(items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+    let layer_tokens = match get_macro_item("skylite_proc::render_layer", items)? {
+        Some(tokens) => tokens,
+        None => return Ok(TokenStream::new())
+    };
+    let layer = parse2::<Ident>(layer_tokens.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Invalid argument to render_layer!: {}", err)))?;
+    let variant = match layer.to_string().as_str() {
+        "background" => quote!(Background),
+        "main" => quote!(Main),
+        "ui" => quote!(Ui),
+        other => return Err(SkyliteProcError::DataError(format!("Unknown render layer: {}. Expected one of background, main, ui", other)))
+    };
+    Ok(quote! {
+        fn render_layer(&self) -> ::skylite_core::prelude::RenderLayer {
+            ::skylite_core::prelude::RenderLayer::#variant
+        }
+    })
+}
+
+/// Generates the `parallax` method from the `skylite_proc::parallax!` macro, if present.
+fn gen_parallax_fn(items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+    let parallax_tokens = match get_macro_item("skylite_proc::parallax", items)? {
+        Some(tokens) => tokens,
+        None => return Ok(TokenStream::new())
+    };
+    let factors = Parser::parse2(Punctuated::<LitFloat, Token![,]>::parse_separated_nonempty, parallax_tokens.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Invalid arguments to parallax!: {}. Expected (fx, fy)", err)))?;
+    if factors.len() != 2 {
+        return Err(SkyliteProcError::SyntaxError(format!("Wrong number of arguments to parallax!, expected (fx, fy)")));
+    }
+    let (fx, fy) = (&factors[0], &factors[1]);
+    Ok(quote! {
+        fn parallax(&self) -> (f32, f32) {
+            (#fx, #fy)
+        }
+    })
+}
+
+/// Generates the `update_priority` method from the `skylite_proc::update_priority!` macro, if present.
+fn gen_update_priority_fn(items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+    let priority_tokens = match get_macro_item("skylite_proc::update_priority", items)? {
+        Some(tokens) => tokens,
+        None => return Ok(TokenStream::new())
+    };
+    let priority = parse2::<Expr>(priority_tokens.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Invalid argument to update_priority!: {}", err)))?;
+    Ok(quote! {
+        fn update_priority(&self) -> i16 {
+            #priority
         }
     })
 }
@@ -385,27 +744,48 @@ fn gen_actor_base_impl(actor: &Actor, project_type_ident: &TokenStream, items: &
 
     let private_decode = gen_actor_decode_fn(&actor_type_name, &actor.parameters);
     let private_update = gen_actor_update_fn(&actions_type_name, &actor.actions, items)?;
+    let render_layer = gen_render_layer_fn(items)?;
+    let parallax = gen_parallax_fn(items)?;
+    let update_priority = gen_update_priority_fn(items)?;
 
     let render = get_annotated_function(items, "skylite_proc::render")
         .map(get_name)
         .map(|name| quote!(super::#name(self, ctx);))
         .unwrap_or(TokenStream::new());
 
+    let reflect_methods = if cfg!(feature = "debug-reflection") {
+        quote! {
+            fn reflect(&self) -> Option<&dyn ::skylite_core::reflect::Reflect> { Some(&self.properties) }
+
+            fn reflect_mut(&mut self) -> Option<&mut dyn ::skylite_core::reflect::Reflect> { Some(&mut self.properties) }
+        }
+    } else {
+        TokenStream::new()
+    };
+
     Ok(quote! {
-        impl ::skylite_core::actors::ActorBase for #actor_type_name {
+        impl ::skylite_core::prelude::ActorBase for #actor_type_name {
             type P = #project_type_ident;
 
             #private_decode
 
             #private_update
 
-            fn _private_render(&self, ctx: &::skylite_core::DrawContext<Self::P>) {
+            fn _private_render(&self, ctx: &mut ::skylite_core::prelude::DrawContext<Self::P>) {
                 #render
             }
 
-            fn get_entity(&self) -> &::skylite_core::ecs::Entity { &self.entity }
+            fn get_entity(&self) -> &::skylite_core::prelude::Entity { &self.entity }
+
+            fn get_entity_mut(&mut self) -> &mut ::skylite_core::prelude::Entity { &mut self.entity }
+
+            #render_layer
 
-            fn get_entity_mut(&mut self) -> &mut ::skylite_core::ecs::Entity { &mut self.entity }
+            #parallax
+
+            #update_priority
+
+            #reflect_methods
         }
     })
 }
@@ -429,8 +809,8 @@ pub(crate) fn generate_actor_definition(actor: &Actor, actor_id: usize, project_
     let action_type_name = action_type_name(&actor.name);
     let action_type = gen_actions_type(&action_type_name, &actor.actions);
 
-    let properties_type = gen_properties_type(actor, items)?;
-    let actor_type = gen_actor_type(actor);
+    let properties_type = gen_properties_type(actor, &project_type_name, items)?;
+    let actor_type = gen_actor_type(actor, project_name);
     let actor_base_impl = gen_actor_base_impl(actor, &project_type_name, items)?;
 
     Ok(quote! {
@@ -455,7 +835,7 @@ pub(crate) fn generate_actor_definition(actor: &Actor, actor_id: usize, project_
 
             #actor_base_impl
 
-            impl ::skylite_core::actors::Actor for #actor_type_name {
+            impl ::skylite_core::prelude::Actor for #actor_type_name {
                 type Action = #action_type_name;
 
                 fn set_action(&mut self, action: #action_type_name) {
@@ -476,16 +856,73 @@ pub(crate) fn generate_actor_definition(actor: &Actor, actor_id: usize, project_
 
 #[cfg(test)]
 mod tests {
-    use quote::quote;
+    use quote::{format_ident, quote};
     use syn::{parse2, File, Item};
     use crate::parse::actors::{Actor, Action, ActionInstance};
     use crate::parse::values::{Type, TypedValue, Variable};
 
-    use super::{action_type_name, gen_actions_type, gen_actor_base_impl, gen_actor_type, gen_properties_type};
+    use super::{action_type_name, gen_actions_type, gen_actor_base_impl, gen_actor_type, gen_actor_update_fn, gen_mode_update, gen_parallax_fn, gen_properties_type, gen_reflect_impl, gen_render_layer_fn, gen_unknown_actor_fallback, gen_update_priority_fn};
+
+    #[test]
+    fn test_gen_update_priority_fn() {
+        assert!(gen_update_priority_fn(&[]).unwrap().is_empty());
+
+        let items = parse2::<File>(quote!(skylite_proc::update_priority!(-5);)).unwrap().items;
+        let expectation = quote! {
+            fn update_priority(&self) -> i16 {
+                -5
+            }
+        };
+        assert_eq!(gen_update_priority_fn(&items).unwrap().to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_gen_parallax_fn() {
+        assert!(gen_parallax_fn(&[]).unwrap().is_empty());
+
+        let items = parse2::<File>(quote!(skylite_proc::parallax!(0.5, 0.25);)).unwrap().items;
+        let expectation = quote! {
+            fn parallax(&self) -> (f32, f32) {
+                (0.5, 0.25)
+            }
+        };
+        assert_eq!(gen_parallax_fn(&items).unwrap().to_string(), expectation.to_string());
+
+        let items = parse2::<File>(quote!(skylite_proc::parallax!(0.5);)).unwrap().items;
+        assert!(gen_parallax_fn(&items).is_err());
+    }
+
+    #[test]
+    fn test_gen_render_layer_fn() {
+        assert!(gen_render_layer_fn(&[]).unwrap().is_empty());
+
+        let items = parse2::<File>(quote!(skylite_proc::render_layer!(ui);)).unwrap().items;
+        let expectation = quote! {
+            fn render_layer(&self) -> ::skylite_core::prelude::RenderLayer {
+                ::skylite_core::prelude::RenderLayer::Ui
+            }
+        };
+        assert_eq!(gen_render_layer_fn(&items).unwrap().to_string(), expectation.to_string());
+
+        let items = parse2::<File>(quote!(skylite_proc::render_layer!(foreground);)).unwrap().items;
+        assert!(gen_render_layer_fn(&items).is_err());
+    }
+
+    #[test]
+    fn test_gen_unknown_actor_fallback() {
+        assert_eq!(gen_unknown_actor_fallback(&[]).to_string(), quote!(::std::panic!("{}", err)).to_string());
+
+        let body: File = parse2(quote! {
+            #[skylite_proc::unknown_actor]
+            fn placeholder(err: DecodeError) -> TestProjectActors { todo!() }
+        }).unwrap();
+        assert_eq!(gen_unknown_actor_fallback(&body.items).to_string(), quote!(placeholder(err)).to_string());
+    }
 
     fn create_test_actor() -> Actor {
         Actor {
             name: "TestActor".to_owned(),
+            description: Some("An actor for testing".to_owned()),
             parameters: vec![
                 Variable { name: "x".to_owned(), typename: Type::U16, documentation: Some("x-coordinate".to_owned()), default: None },
                 Variable { name: "y".to_owned(), typename: Type::U16, documentation: Some("y-coordinate".to_owned()), default: None },
@@ -531,7 +968,7 @@ mod tests {
             fn pre_update(actor: &mut TestActor, project: &mut TestProject) {}
 
             #[skylite_proc::render]
-            fn render(actor: &TestActor, project: &mut ::skylite_core::DrawContext<TestProject>) {}
+            fn render(actor: &TestActor, project: &mut ::skylite_core::prelude::DrawContext<TestProject>) {}
 
             #[skylite_proc::action("action1")]
             fn action1(actor: &mut TestActor, project: &mut TestProject, dx: u8, dy: u8) {}
@@ -564,9 +1001,9 @@ mod tests {
                 Action3 {}
             }
 
-            impl ::skylite_core::actors::ActorAction for TestActorActions {
-                fn _private_decode(decoder: &mut dyn ::skylite_compress::Decoder) -> TestActorActions {
-                    use skylite_core::decode::Deserialize;
+            impl ::skylite_core::prelude::ActorAction for TestActorActions {
+                fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> TestActorActions {
+                    use ::skylite_core::prelude::Deserialize;
                     match u8::deserialize(decoder) {
                         0 => {
                             let dx = u8::deserialize(decoder);
@@ -592,7 +1029,7 @@ mod tests {
     fn test_gen_properties_type() {
         let actor = create_test_actor();
         let items = create_test_items();
-        let code = gen_properties_type(&actor, &items).unwrap();
+        let code = gen_properties_type(&actor, &quote!(crate::TestProject), &items).unwrap();
         let expectation = quote! {
             pub struct TestActorProperties {
                 val1: u8,
@@ -601,30 +1038,237 @@ mod tests {
             }
 
             impl TestActorProperties {
+                #[allow(unused_variables)]
+                fn _private_create_properties(x: u16, y: u16) -> TestActorProperties {
+                    super::create_properties(x, y)
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_gen_observed_properties() {
+        let actor = create_test_actor();
+        let items = parse2::<File>(quote! {
+            skylite_proc::properties! {
+                #[skylite_proc::observed]
+                pub hp: u8,
+                pub max_hp: u8
+            }
+
+            #[skylite_proc::create_properties]
+            fn create_properties(x: u8, y: u8) -> TestActorProperties { todo!() }
+        }).unwrap().items;
+        let code = gen_properties_type(&actor, &quote!(crate::TestProject), &items).unwrap();
+        let expectation = quote! {
+            pub struct TestActorProperties {
+                pub hp: u8,
+                pub max_hp: u8
+            }
+
+            impl TestActorProperties {
+                #[allow(unused_variables)]
+                fn _private_create_properties(x: u16, y: u16) -> TestActorProperties {
+                    super::create_properties(x, y)
+                }
+
+                /// Sets the property, and records the change in `controls` if the value is different.
+                pub fn set_hp(&mut self, value: u8, controls: &mut ::skylite_core::prelude::ProjectControls<crate::TestProject>) {
+                    if self.hp != value {
+                        self.hp = value;
+                        controls._private_record_change::<TestActor>("hp");
+                    }
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_gen_interpolated_properties() {
+        let actor = create_test_actor();
+        let items = parse2::<File>(quote! {
+            skylite_proc::properties! {
+                #[skylite_proc::interpolated]
+                pub pos: ::skylite_core::prelude::Interpolated<(f32, f32)>,
+                pub hp: u8
+            }
+
+            #[skylite_proc::create_properties]
+            fn create_properties(x: u8, y: u8) -> TestActorProperties { todo!() }
+        }).unwrap().items;
+        let code = gen_properties_type(&actor, &quote!(crate::TestProject), &items).unwrap();
+        let expectation = quote! {
+            pub struct TestActorProperties {
+                pub pos: ::skylite_core::prelude::Interpolated<(f32, f32)>,
+                pub hp: u8
+            }
+
+            impl TestActorProperties {
+                #[allow(unused_variables)]
                 fn _private_create_properties(x: u16, y: u16) -> TestActorProperties {
                     super::create_properties(x, y)
                 }
+
+                #[doc(hidden)]
+                pub fn _private_store_interpolated(&mut self) {
+                    self.pos._private_store();
+                }
             }
         };
         assert_eq!(code.to_string(), expectation.to_string());
+
+        let update = gen_actor_update_fn(&format_ident!("TestActorActions"), &[], &items).unwrap().to_string();
+        assert!(update.contains(&quote!(UpdatePhase::Pre => { self.properties._private_store_interpolated(); }).to_string()));
+    }
+
+    #[test]
+    fn test_gen_mode_update() {
+        let items = parse2::<File>(quote! {
+            skylite_proc::properties! {
+                #[skylite_proc::mode]
+                pub mode: Mode,
+                pub hp: u8
+            }
+
+            #[skylite_proc::update("Walking")]
+            fn walk(actor: &mut TestActor, scene: &mut dyn Scene<P=TestProject>, controls: &mut ProjectControls<TestProject>) {}
+
+            #[skylite_proc::update("Jumping")]
+            fn jump(actor: &mut TestActor, scene: &mut dyn Scene<P=TestProject>, controls: &mut ProjectControls<TestProject>) {}
+
+            #[skylite_proc::create_properties]
+            fn create_properties() -> TestActorProperties { todo!() }
+        }).unwrap().items;
+        let actor = Actor {
+            name: "test-actor".to_owned(),
+            description: None,
+            parameters: vec![],
+            actions: vec![],
+            initial_action: ActionInstance { name: "idle".to_owned(), args: vec![] }
+        };
+        let code = gen_properties_type(&actor, &quote!(crate::TestProject), &items).unwrap().to_string();
+        assert!(code.contains(&quote!(pub struct TestActorProperties { pub mode: Mode, pub hp: u8 }).to_string()));
+
+        let update = gen_actor_update_fn(&format_ident!("TestActorActions"), &[], &items).unwrap().to_string();
+        let expectation = quote! {
+            #[allow(unreachable_patterns)]
+            match self.properties.mode {
+                Mode::Walking { .. } => super::walk(self, scene, controls),
+                Mode::Jumping { .. } => super::jump(self, scene, controls),
+                _ => {}
+            };
+        };
+        assert!(update.contains(&expectation.to_string()));
+
+        // Update functions require a mode property.
+        assert!(gen_mode_update(None, &items[1..3]).is_err());
+        assert!(gen_mode_update(None, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gen_reflect_impl() {
+        let code = gen_reflect_impl(&format_ident!("TestActorProperties"), &quote!(pub hp: u8, pub pos: (i16, i16))).unwrap();
+        let expectation = quote! {
+            impl ::skylite_core::reflect::Reflect for TestActorProperties {
+                fn properties(&self) -> &'static [::skylite_core::reflect::PropertyInfo] {
+                    &[
+                        ::skylite_core::reflect::PropertyInfo { name: "hp", type_name: "u8", editable: true },
+                        ::skylite_core::reflect::PropertyInfo { name: "pos", type_name: "(i16 , i16)", editable: false }
+                    ]
+                }
+
+                fn get_property(&self, name: &str) -> Option<::skylite_core::reflect::ReflectValue> {
+                    use ::skylite_core::reflect::ReflectPrimitive;
+                    match name {
+                        "hp" => Some(self.hp.to_value()),
+                        _ => None
+                    }
+                }
+
+                fn set_property(&mut self, name: &str, value: ::skylite_core::reflect::ReflectValue) -> bool {
+                    use ::skylite_core::reflect::ReflectPrimitive;
+                    match name {
+                        "hp" => match ReflectPrimitive::from_value(value) {
+                            Some(v) => { self.hp = v; true },
+                            None => false
+                        },
+                        _ => false
+                    }
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_gen_derived_properties() {
+        let actor = create_test_actor();
+        let items = parse2::<File>(quote! {
+            skylite_proc::properties! {
+                pub x: u16,
+                pub y: u16,
+                pub sum: u32
+            }
+
+            #[skylite_proc::derive_property("sum")]
+            fn derive_sum(x: u16, y: u16) -> u32 { x as u32 + y as u32 }
+        }).unwrap().items;
+        let code = gen_properties_type(&actor, &quote!(crate::TestProject), &items).unwrap();
+        let expectation = quote! {
+            pub struct TestActorProperties {
+                pub x: u16,
+                pub y: u16,
+                pub sum: u32
+            }
+
+            impl TestActorProperties {
+                #[allow(unused_variables)]
+                fn _private_create_properties(x: u16, y: u16) -> TestActorProperties {
+                    {
+                        let _private_derived_sum = super::derive_sum(x.clone(), y.clone());
+                        TestActorProperties { x, y, sum: _private_derived_sum }
+                    }
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+
+        let items = parse2::<File>(quote! {
+            skylite_proc::properties! {
+                pub x: u16,
+                pub z: u16
+            }
+        }).unwrap().items;
+        assert!(gen_properties_type(&actor, &quote!(crate::TestProject), &items).is_err());
     }
 
     #[test]
     fn test_gen_actor_type() {
         let actor = create_test_actor();
-        let code = gen_actor_type(&actor);
+        let code = gen_actor_type(&actor, "TestProject");
         let expectation = quote! {
+            #[doc = "An actor for testing"]
             pub struct TestActor {
                 pub properties: TestActorProperties,
+                entity: ::skylite_core::prelude::Entity,
                 current_action: TestActorActions,
                 action_changed: bool,
                 clear_action_changed: bool
             }
 
             impl TestActor {
+                #[doc = "Creates a new `TestActor`."]
+                #[doc = ""]
+                #[doc = "# Parameters"]
+                #[doc = ""]
+                #[doc = "- `x`: x-coordinate"]
+                #[doc = "- `y`: y-coordinate"]
                 pub fn new(x: u16, y: u16) -> TestActor {
                     TestActor {
                         properties: TestActorProperties::_private_create_properties(x, y),
+                        entity: ::skylite_core::prelude::Entity::new(),
                         current_action: TestActorActions::Action2 { val: 5u8 },
                         action_changed: true,
                         clear_action_changed: false
@@ -641,33 +1285,40 @@ mod tests {
         let items = create_test_items();
         let code = gen_actor_base_impl(&actor, &quote!(crate::TestProject), &items).unwrap();
         let expectation = quote! {
-            impl ::skylite_core::actors::ActorBase for TestActor {
+            impl ::skylite_core::prelude::ActorBase for TestActor {
                 type P = crate::TestProject;
 
-                fn _private_decode(decoder: &mut dyn ::skylite_compress::Decoder) -> TestActor {
-                    use skylite_core::decode::Deserialize;
+                fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> TestActor {
+                    use ::skylite_core::prelude::Deserialize;
                     let x = u16::deserialize(decoder);
                     let y = u16::deserialize(decoder);
                     TestActor::new(x, y)
                 }
 
-                fn _private_update(&mut self, scene: &mut dyn ::skylite_core::scenes::Scene<P=Self::P>, controls: &mut ::skylite_core::ProjectControls<Self::P>) {
-                    super::pre_update(self, scene, controls);
-
-                    self.clear_action_changed = self.action_changed;
-                    match self.current_action {
-                        TestActorActions::Action1 { dx, dy } => super::action1(self, scene, controls, dx.clone(), dy.clone()),
-                        TestActorActions::Action2 { val } => super::action2(self, scene, controls, val.clone()),
-                        TestActorActions::Action3 {} => super::action3(self, scene, controls,)
-                    };
-
-                    if self.clear_action_changed {
-                        self.action_changed = false;
-                        self.clear_action_changed = false;
+                fn _private_update(&mut self, scene: &mut dyn ::skylite_core::prelude::Scene<P=Self::P>, controls: &mut ::skylite_core::prelude::ProjectControls<Self::P>, phase: ::skylite_core::prelude::UpdatePhase) {
+                    use ::skylite_core::prelude::UpdatePhase;
+                    match phase {
+                        UpdatePhase::Pre => {
+                            super::pre_update(self, scene, controls);
+                        },
+                        UpdatePhase::Main => {
+                            self.clear_action_changed = self.action_changed;
+                            match self.current_action {
+                                TestActorActions::Action1 { dx, dy } => super::action1(self, scene, controls, dx.clone(), dy.clone()),
+                                TestActorActions::Action2 { val } => super::action2(self, scene, controls, val.clone()),
+                                TestActorActions::Action3 {} => super::action3(self, scene, controls,)
+                            };
+
+                            if self.clear_action_changed {
+                                self.action_changed = false;
+                                self.clear_action_changed = false;
+                            }
+                        },
+                        UpdatePhase::Post => {}
                     }
                 }
 
-                fn _private_render(&self, ctx: &mut ::skylite_core::DrawContext<Self::P>) {
+                fn _private_render(&self, ctx: &mut ::skylite_core::prelude::DrawContext<Self::P>) {
                     super::render(self, ctx);
                 }
             }
@@ -0,0 +1,91 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::{parse::{config::Config, util::{change_case, IdentCase}, values::Type}, SkyliteProcError};
+
+use super::{project::project_type_name, util::{skylite_type_to_rust, typed_value_to_rust}};
+
+pub(crate) fn config_type_name(config_name: &str) -> Ident {
+    format_ident!("{}Config", change_case(config_name, IdentCase::UpperCamelCase))
+}
+
+/// Returns `true` if values of type `t` can be written as constants.
+fn is_const_type(t: &Type) -> bool {
+    match t {
+        Type::Vec(_) | Type::Map(_, _) => false,
+        Type::Tuple(members) => members.iter().all(is_const_type),
+        Type::Optional(item) => is_const_type(item),
+        Type::Value(value_type) => value_type.fields.iter().all(|f| is_const_type(&f.typename)),
+        _ => true
+    }
+}
+
+/// Generates a struct with one associated constant for each value of a config asset.
+///
+/// For a config `physics` with a value `gravity`, this generates `PhysicsConfig::GRAVITY`.
+pub(crate) fn generate_config(project_name: &str, config: &Config) -> Result<TokenStream, SkyliteProcError> {
+    let type_name = config_type_name(&config.name);
+    let project_type_name = project_type_name(project_name);
+
+    let consts = config.values.iter()
+        .map(|v| {
+            if !is_const_type(&v.typename) {
+                return Err(SkyliteProcError::DataError(format!("Value {} of config {} has type {:?}, which cannot be used in a constant", v.name, config.name, v.typename)));
+            }
+            let name = format_ident!("{}", change_case(&v.name, IdentCase::UpperSnakeCase));
+            let t = skylite_type_to_rust(&v.typename);
+            // Configs always have a value, see `parse_global`.
+            let value = typed_value_to_rust(v.default.as_ref().unwrap(), &project_type_name);
+            Ok(quote!(pub const #name: #t = #value;))
+        })
+        .collect::<Result<Vec<TokenStream>, SkyliteProcError>>()?;
+
+    let doc = format!("The values from the config asset `{}`.", config.name);
+    Ok(quote! {
+        #[doc = #doc]
+        pub struct #type_name;
+
+        impl #type_name {
+            #(#consts)*
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use crate::parse::{config::Config, values::{Type, TypedValue, Variable}};
+
+    use super::generate_config;
+
+    #[test]
+    fn test_generate_config() {
+        let config = Config {
+            name: "Physics".to_owned(),
+            values: vec![
+                Variable { name: "gravity".to_owned(), typename: Type::F32, documentation: None, default: Some(TypedValue::F32(0.25)) },
+                Variable { name: "spawn-pos".to_owned(), typename: Type::Tuple(vec![Type::I16, Type::I16]), documentation: None, default: Some(TypedValue::Tuple(vec![TypedValue::I16(8), TypedValue::I16(4)])) }
+            ]
+        };
+        let actual = generate_config("test", &config).unwrap();
+        let expectation = quote! {
+            #[doc = "The values from the config asset `Physics`."]
+            pub struct PhysicsConfig;
+
+            impl PhysicsConfig {
+                pub const GRAVITY: f32 = 0.25f32;
+                pub const SPAWN_POS: (i16, i16) = (8i16, 4i16);
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+
+        let invalid = Config {
+            name: "Levels".to_owned(),
+            values: vec![
+                Variable { name: "order".to_owned(), typename: Type::Vec(Box::new(Type::U8)), documentation: None, default: Some(TypedValue::Vec(vec![])) }
+            ]
+        };
+        assert!(generate_config("test", &invalid).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::{parse::{custom::{CustomAsset, CustomAssetKind}, util::{change_case, IdentCase}}, SkyliteProcError};
+
+use super::{encode::{compress_blobs, DEFAULT_COMPRESSION}, project::project_ident, size_report::SizeReport};
+
+/// Generates the static data, the asset enum and the accessors for a custom asset kind.
+///
+/// For a kind `nav-mesh`, this generates:
+/// - `NavMeshAssets`, an enum with one variant per asset, which implements `AssetId`,
+/// - `nav_mesh_decoder(asset)` on the project type, which returns a decoder for the asset's data,
+/// - `load_nav_mesh(asset)` on the project type, if the kind has a Rust type.
+pub(crate) fn generate_custom_assets(project_name: &str, kind: &CustomAssetKind, assets: &[CustomAsset], report: &mut SizeReport) -> Result<TokenStream, SkyliteProcError> {
+    let project_ident = project_ident(project_name);
+    let data_name = format_ident!("{}_DATA", change_case(&kind.name, IdentCase::UpperSnakeCase));
+    let enum_name = format_ident!("{}Assets", change_case(&kind.name, IdentCase::UpperCamelCase));
+    let decoder_fn = format_ident!("{}_decoder", change_case(&kind.name, IdentCase::LowerSnakeCase));
+    let compression = kind.assets.compression.as_deref().unwrap_or(DEFAULT_COMPRESSION);
+
+    let blobs = assets.iter()
+        .map(|asset| {
+            report.add_asset(&asset.name, asset.data.len());
+            (asset.name.as_str(), asset.data.as_slice(), compression)
+        })
+        .collect::<Vec<_>>();
+
+    let mut total_size = 0;
+    let data = compress_blobs(&blobs)?.into_iter()
+        .map(|compressed| {
+            total_size += compressed.len();
+            let bytes = compressed.into_iter().map(Literal::u8_unsuffixed);
+            quote!(&[#(#bytes),*])
+        })
+        .collect::<Vec<TokenStream>>();
+    report.add_static_data(&data_name.to_string(), total_size);
+
+    let variants = assets.iter().map(|a| format_ident!("{}", a.name)).collect::<Vec<_>>();
+    let count = assets.len();
+    // repr(usize) and casts do not work on empty enums.
+    let (repr, index) = if assets.len() > 0 {
+        (quote!(#[repr(usize)]), quote!(self as usize))
+    } else {
+        (TokenStream::new(), quote!(match self {}))
+    };
+
+    let load_fn = match &kind.rust_type {
+        Some(t) => {
+            let rust_type: syn::Type = syn::parse_str(t)
+                .map_err(|err| SkyliteProcError::DataError(format!("Invalid type for custom asset kind {}: {}", kind.name, err)))?;
+            let load_fn_name = format_ident!("load_{}", change_case(&kind.name, IdentCase::LowerSnakeCase));
+            quote! {
+                /// Decodes a custom asset.
+                pub fn #load_fn_name(asset: #enum_name) -> #rust_type {
+                    let mut decoder = Self::#decoder_fn(asset);
+                    <#rust_type as ::skylite_core::prelude::Deserialize>::deserialize(decoder.as_mut())
+                }
+            }
+        },
+        None => TokenStream::new()
+    };
+
+    Ok(quote! {
+        static #data_name: &[&[u8]] = &[#(#data),*];
+
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #repr
+        pub enum #enum_name {
+            #(#variants),*
+        }
+
+        impl ::skylite_core::prelude::AssetId for #enum_name {
+            const COUNT: usize = #count;
+
+            fn index(self) -> usize {
+                #index
+            }
+
+            fn from_index(index: usize) -> ::std::option::Option<#enum_name> {
+                const ALL: &[#enum_name] = &[#(#enum_name::#variants),*];
+                ALL.get(index).copied()
+            }
+        }
+
+        impl #project_ident {
+            /// Returns a decoder for the encoded data of a custom asset.
+            pub fn #decoder_fn(asset: #enum_name) -> ::std::boxed::Box<dyn ::skylite_core::prelude::Decoder> {
+                ::skylite_core::prelude::make_decoder(#data_name[asset as usize])
+            }
+
+            #load_fn
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use quote::quote;
+
+    use crate::{generate::size_report::SizeReport, parse::{custom::{CustomAsset, CustomAssetKind}, project::asset_group_from_single}};
+
+    use super::generate_custom_assets;
+
+    #[test]
+    fn test_generate_custom_assets() {
+        let kind = CustomAssetKind {
+            name: "nav-mesh".to_owned(),
+            assets: asset_group_from_single("./nav-meshes/*.scm", &PathBuf::new()),
+            encoder: PathBuf::from("encoder.scm"),
+            rust_type: Some("crate::NavMesh".to_owned())
+        };
+        let assets = vec![
+            CustomAsset { name: "Level1".to_owned(), data: vec![1, 2] },
+            CustomAsset { name: "Level2".to_owned(), data: vec![3] }
+        ];
+        let mut report = SizeReport::default();
+        let code = generate_custom_assets("TestProject", &kind, &assets, &mut report).unwrap();
+        let expectation = quote! {
+            static NAV_MESH_DATA: &[&[u8]] = &[&[0, 1, 2], &[0, 3]];
+
+            #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+            #[repr(usize)]
+            pub enum NavMeshAssets {
+                Level1,
+                Level2
+            }
+
+            impl ::skylite_core::prelude::AssetId for NavMeshAssets {
+                const COUNT: usize = 2usize;
+
+                fn index(self) -> usize {
+                    self as usize
+                }
+
+                fn from_index(index: usize) -> ::std::option::Option<NavMeshAssets> {
+                    const ALL: &[NavMeshAssets] = &[NavMeshAssets::Level1, NavMeshAssets::Level2];
+                    ALL.get(index).copied()
+                }
+            }
+
+            impl TestProject {
+                /// Returns a decoder for the encoded data of a custom asset.
+                pub fn nav_mesh_decoder(asset: NavMeshAssets) -> ::std::boxed::Box<dyn ::skylite_core::prelude::Decoder> {
+                    ::skylite_core::prelude::make_decoder(NAV_MESH_DATA[asset as usize])
+                }
+
+                /// Decodes a custom asset.
+                pub fn load_nav_mesh(asset: NavMeshAssets) -> crate::NavMesh {
+                    let mut decoder = Self::nav_mesh_decoder(asset);
+                    <crate::NavMesh as ::skylite_core::prelude::Deserialize>::deserialize(decoder.as_mut())
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use skylite_compress::CompressionMethods;
+
+use crate::SkyliteProcError;
+
+use super::encode::{compression_level, verify_compression};
+
+/// Generates the expression for an `embedded_asset!` invocation.
+///
+/// `path` must be absolute. The file is referenced with `include_bytes!`,
+/// so that the crate is rebuilt whenever the file changes, but the embedded
+/// data is the compressed version of `data`.
+pub(crate) fn generate_embedded_asset(path: &Path, data: &[u8], compression: &[CompressionMethods]) -> Result<TokenStream, SkyliteProcError> {
+    let path_str = path.to_string_lossy();
+    let len = Literal::usize_unsuffixed(data.len());
+    let (compressed, _) = skylite_compress::compress(data, compression, compression_level());
+    verify_compression(&path_str, data, &compressed)?;
+    let bytes = compressed.into_iter().map(Literal::u8_unsuffixed);
+
+    Ok(quote! {
+        {
+            const _: &[u8] = include_bytes!(#path_str);
+            ::skylite_core::decode::EmbeddedAsset::_private_new(&[#(#bytes),*], #len)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use quote::quote;
+    use skylite_compress::CompressionMethods;
+
+    use super::generate_embedded_asset;
+
+    #[test]
+    fn test_generate_embedded_asset() {
+        let path = PathBuf::from("/project/music/track1.bin");
+        let code = generate_embedded_asset(&path, &[1, 2, 3], &[CompressionMethods::Raw]).unwrap();
+        let expectation = quote! {
+            {
+                const _: &[u8] = include_bytes!("/project/music/track1.bin");
+                ::skylite_core::decode::EmbeddedAsset::_private_new(&[0, 1, 2, 3], 3)
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+}
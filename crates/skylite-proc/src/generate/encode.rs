@@ -2,23 +2,101 @@
 
 #![allow(non_snake_case)]
 
-use skylite_compress::{compress, CompressionMethods};
+use std::thread;
 
-use crate::parse::values::TypedValue;
+use skylite_compress::{compress, CompressionLevel, CompressionMethods};
+
+use crate::{parse::values::TypedValue, SkyliteProcError};
+
+use super::strings::StringTable;
+
+/// Compression methods used for assets that do not specify their own.
+pub(crate) const DEFAULT_COMPRESSION: &[CompressionMethods] = &[CompressionMethods::LZ77, CompressionMethods::RC];
+
+/// The `CompressionLevel` used for generated assets.
+///
+/// Debug builds use `CompressionLevel::Fast`, so that the expansion of projects with large
+/// assets stays quick during development. Release builds use `CompressionLevel::Default`.
+pub(crate) fn compression_level() -> CompressionLevel {
+    if cfg!(debug_assertions) {
+        CompressionLevel::Fast
+    } else {
+        CompressionLevel::Default
+    }
+}
+
+/// Checks that `compressed` decodes to `original`, if the `verify-compression` feature is enabled.
+/// The `name` of the asset is used in the error message.
+///
+/// This catches bugs in the encoders at compile time, instead of when the corrupted data is decoded at runtime.
+#[cfg_attr(not(feature = "verify-compression"), allow(unused_variables))]
+pub(crate) fn verify_compression(name: &str, original: &[u8], compressed: &[u8]) -> Result<(), SkyliteProcError> {
+    #[cfg(feature = "verify-compression")]
+    skylite_compress::verify(original, compressed)
+        .map_err(|err| SkyliteProcError::DataError(format!("Compressed data of {} does not decode to the original data: {}", name, err)))?;
+    Ok(())
+}
+
+/// Compresses each blob with its own chain of compression methods.
+///
+/// Each blob is given as `(name, data, methods)`. The blobs are independent of each other,
+/// so they are split between several threads. This makes the expansion of projects with many
+/// large assets faster, since compression takes up most of the time. The output has the same
+/// order as `blobs`.
+pub(crate) fn compress_blobs(blobs: &[(&str, &[u8], &[CompressionMethods])]) -> Result<Vec<Vec<u8>>, SkyliteProcError> {
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = blobs.len().div_ceil(threads).max(1);
+    let compressed: Vec<Vec<u8>> = thread::scope(|scope| {
+        let handles: Vec<_> = blobs.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .map(|(_, data, methods)| compress(data, methods, compression_level()).0)
+                    .collect::<Vec<Vec<u8>>>()
+            }))
+            .collect();
+        handles.into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+
+    for ((name, data, _), out) in blobs.iter().zip(&compressed) {
+        verify_compression(name, data, out)?;
+    }
+    Ok(compressed)
+}
 
 pub trait Serialize {
     fn serialize(&self, buffer: &mut CompressionBuffer);
 }
 
 pub struct CompressionBuffer {
-    buffer: Vec<u8>
+    buffer: Vec<u8>,
+    strings: StringTable,
+    max_varint: usize,
+    varint_overflow: Option<usize>
 }
 
 impl CompressionBuffer {
 
+    #[cfg(test)]
     pub fn new() -> CompressionBuffer {
         CompressionBuffer {
-            buffer: Vec::new()
+            buffer: Vec::new(),
+            strings: StringTable::default(),
+            max_varint: u32::MAX as usize,
+            varint_overflow: None
+        }
+    }
+
+    /// Creates a new `CompressionBuffer` which encodes `string`-typed values
+    /// as ids into the given `StringTable`. Varints larger than `max_varint`
+    /// are reported by `varint_overflow`.
+    pub(crate) fn with_strings(strings: StringTable, max_varint: usize) -> CompressionBuffer {
+        CompressionBuffer {
+            buffer: Vec::new(),
+            strings,
+            max_varint,
+            varint_overflow: None
         }
     }
 
@@ -26,26 +104,46 @@ impl CompressionBuffer {
         self.buffer.push(byte);
     }
 
+    #[cfg(test)]
     pub fn write<T: Serialize>(&mut self, val: T) {
         val.serialize(self);
     }
 
+    /// Writes `val` as unsigned LEB128, see `read_varint` in `skylite_core`.
+    ///
+    /// Panics if `val` does not fit into a `u32`.
     pub fn write_varint(&mut self, val: usize) {
-        if val == 0 {
-            self.write_byte(0);
-            return;
+        assert!(val as u64 <= u32::MAX as u64, "Varint {} does not fit into a u32", val);
+        if val > self.max_varint && self.varint_overflow.is_none() {
+            self.varint_overflow = Some(val);
         }
 
-        let mut writes = val.ilog2() / 7;
-        while writes > 1 {
-            self.write_byte(((val >> (writes * 7)) & 0x7f | 0x80) as u8);
-            writes -= 1;
+        let mut val = val;
+        while val >= 0x80 {
+            self.write_byte((val & 0x7f) as u8 | 0x80);
+            val >>= 7;
         }
-        self.write_byte((val & 0x7f) as u8);
+        self.write_byte(val as u8);
+    }
+
+    /// Returns the first varint which was larger than the maximum given in `with_strings`, if any.
+    pub(crate) fn varint_overflow(&self) -> Option<usize> {
+        self.varint_overflow
     }
 
-    pub fn encode(self) -> Vec<u8> {
-        let (out, _reports) = compress(&self.buffer, &[CompressionMethods::LZ77, CompressionMethods::RC]);
+    /// Writes the id of the string `s` from the buffer's `StringTable`.
+    ///
+    /// Panics if `s` is not part of the table.
+    pub fn write_str_id(&mut self, s: &str) {
+        let id = self.strings.get_id(s)
+            .unwrap_or_else(|| panic!("String \"{}\" is not part of the string table", s));
+        self.write_varint(id);
+    }
+
+    #[cfg(test)]
+    /// Compresses the buffer's content with the given chain of compression methods.
+    pub fn encode(self, methods: &[CompressionMethods]) -> Vec<u8> {
+        let (out, _reports) = compress(&self.buffer, methods, compression_level());
         // for r in reports {
         //     println!("{}", r);
         // }
@@ -56,6 +154,11 @@ impl CompressionBuffer {
     pub fn len(&self) -> usize {
         self.buffer.len()
     }
+
+    /// Returns the uncompressed content of the buffer.
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.buffer
+    }
 }
 
 macro_rules! serialize_for_primitive {
@@ -138,16 +241,71 @@ impl Serialize for TypedValue {
             TypedValue::F32(v) => v.serialize(buffer),
             TypedValue::F64(v) => v.serialize(buffer),
             TypedValue::Bool(v) => v.serialize(buffer),
-            TypedValue::String(v) => v.as_str().serialize(buffer),
+            TypedValue::String(v) => buffer.write_str_id(v),
             TypedValue::Tuple(v) => v.iter().for_each(|i| i.serialize(buffer)),
             TypedValue::Vec(v) => (&v[..]).serialize(buffer),
+            TypedValue::Map(entries) => {
+                buffer.write_varint(entries.len());
+                for (k, v) in entries {
+                    k.serialize(buffer);
+                    v.serialize(buffer);
+                }
+            },
+            // Presence flag, followed by the value if it is present.
+            TypedValue::Optional(None) => false.serialize(buffer),
+            TypedValue::Optional(Some(v)) => {
+                true.serialize(buffer);
+                v.serialize(buffer);
+            },
+            TypedValue::Value(_, fields) => fields.iter().for_each(|(_, v)| v.serialize(buffer))
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::CompressionBuffer;
+    use crate::parse::values::TypedValue;
+
+    use skylite_compress::{compress, CompressionLevel, CompressionMethods};
+
+    use super::{compress_blobs, compression_level, CompressionBuffer, StringTable, DEFAULT_COMPRESSION};
+
+    #[test]
+    fn test_write_varint() {
+        let mut buffer = CompressionBuffer::new();
+        for val in [0, 127, 128, 257, 0x12345, u32::MAX as usize] {
+            buffer.write_varint(val);
+        }
+        // Same encoding as `write_varint` in skylite_core.
+        assert_eq!(buffer.data(), [0, 127, 128, 1, 129, 2, 197, 198, 4, 255, 255, 255, 255, 15]);
+        assert_eq!(buffer.varint_overflow(), None);
+
+        let mut buffer = CompressionBuffer::with_strings(StringTable::default(), u16::MAX as usize);
+        buffer.write_varint(u16::MAX as usize);
+        assert_eq!(buffer.varint_overflow(), None);
+        buffer.write_varint(70000);
+        buffer.write_varint(80000);
+        assert_eq!(buffer.varint_overflow(), Some(70000));
+    }
+
+    #[test]
+    fn test_serialize_map() {
+        let mut buffer = CompressionBuffer::new();
+        buffer.write(TypedValue::Map(vec![
+            (TypedValue::U8(1), TypedValue::Bool(true)),
+            (TypedValue::U8(7), TypedValue::Bool(false))
+        ]));
+        // Same encoding as a vec of tuples.
+        assert_eq!(buffer.encode(&[]), [0, 2, 1, 1, 7, 0]);
+    }
+
+    #[test]
+    fn test_serialize_optional() {
+        let mut buffer = CompressionBuffer::new();
+        buffer.write(TypedValue::Optional(None));
+        buffer.write(TypedValue::Optional(Some(Box::new(TypedValue::U16(0x1234)))));
+        assert_eq!(buffer.encode(&[]), [0, 0, 1, 0x12, 0x34]);
+    }
 
     #[test]
     fn test_serialize() {
@@ -173,7 +331,7 @@ mod tests {
         let data = [(5, 10), (15, 20), (25, 30)];
         buffer.write(&data[..]);
 
-        let encoded = buffer.encode();
+        let (encoded, _) = compress(buffer.data(), DEFAULT_COMPRESSION, CompressionLevel::Default);
         let expected = vec![
             3,
             0, 1, 6, 18,
@@ -195,4 +353,31 @@ mod tests {
         ];
         assert_eq!(encoded, expected);
     }
+
+    #[test]
+    fn test_compress_blobs() {
+        let data: Vec<Vec<u8>> = (0..20_u8).map(|i| (0..200).map(|j| j % (i + 1)).collect()).collect();
+        let blobs: Vec<(&str, &[u8], &[CompressionMethods])> = data.iter()
+            .enumerate()
+            .map(|(i, d)| ("test", d.as_slice(), if i % 2 == 0 { DEFAULT_COMPRESSION } else { &[CompressionMethods::LZSS256] as &[CompressionMethods] }))
+            .collect();
+        let expected: Vec<Vec<u8>> = blobs.iter()
+            .map(|(_, d, methods)| compress(d, methods, compression_level()).0)
+            .collect();
+        assert_eq!(compress_blobs(&blobs).unwrap(), expected);
+        assert!(compress_blobs(&[]).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "verify-compression")]
+    #[test]
+    fn test_verify_compression() {
+        let data = [1, 2, 3, 3, 3, 3, 3, 4];
+        let (mut compressed, _) = compress(&data, DEFAULT_COMPRESSION, CompressionLevel::Default);
+        assert!(super::verify_compression("test", &data, &compressed).is_ok());
+
+        let last = compressed.len() - 1;
+        compressed[last] ^= 0xff;
+        let err = super::verify_compression("scene test-scene", &data, &compressed).unwrap_err();
+        assert!(err.to_string().contains("scene test-scene"));
+    }
 }
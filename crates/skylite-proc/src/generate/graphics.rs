@@ -0,0 +1,79 @@
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::parse::{graphics::Graphic, util::{change_case, IdentCase}};
+
+use super::{project::project_data_ident, size_report::SizeReport};
+
+pub(crate) fn graphics_type_name(project_name: &str) -> Ident {
+    format_ident!("{}Graphics", change_case(project_name, IdentCase::UpperCamelCase))
+}
+
+/// Generates the static data and the id enum for the project's graphics.
+///
+/// The graphics are stored uncompressed, since the data is passed to the target directly.
+pub(crate) fn generate_graphics(project_name: &str, graphics: &[Graphic], report: &mut SizeReport) -> TokenStream {
+    let graphics_type_name = graphics_type_name(project_name);
+    let data_ident = project_data_ident(project_name, "GRAPHICS_DATA");
+
+    let mut total_size = 0;
+    let data = graphics.iter()
+        .map(|g| {
+            report.add_asset(&g.name, g.data.len());
+            total_size += g.data.len();
+            let bytes = g.data.iter().map(|b| Literal::u8_unsuffixed(*b));
+            quote!(&[#(#bytes),*])
+        })
+        .collect::<Vec<TokenStream>>();
+    report.add_static_data("GRAPHICS_DATA", total_size);
+
+    let variants = graphics.iter().map(|g| format_ident!("{}", g.name));
+    // repr(usize) does not work on empty enums.
+    let repr = if graphics.len() > 0 {
+        quote!(#[repr(usize)])
+    } else {
+        TokenStream::new()
+    };
+
+    quote! {
+        static #data_ident: &[&[u8]] = &[#(#data),*];
+
+        /// Ids of the project's graphics, used with `DrawContext::draw_graphic`.
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #repr
+        pub enum #graphics_type_name {
+            #(#variants),*
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use crate::{generate::size_report::SizeReport, parse::graphics::Graphic};
+
+    use super::generate_graphics;
+
+    #[test]
+    fn test_generate_graphics() {
+        let graphics = vec![
+            Graphic { name: "Tiles".to_owned(), data: vec![1, 2, 3] },
+            Graphic { name: "Player".to_owned(), data: vec![4] }
+        ];
+        let mut report = SizeReport::default();
+        let code = generate_graphics("TestProject", &graphics, &mut report);
+        let expectation = quote! {
+            static __TEST_PROJECT_GRAPHICS_DATA: &[&[u8]] = &[&[1, 2, 3], &[4]];
+
+            /// Ids of the project's graphics, used with `DrawContext::draw_graphic`.
+            #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+            #[repr(usize)]
+            pub enum TestProjectGraphics {
+                Tiles,
+                Player
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+}
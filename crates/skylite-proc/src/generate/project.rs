@@ -1,10 +1,10 @@
-use proc_macro2::{Ident, Span, TokenStream};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
 use quote::{format_ident, quote};
 use syn::{Item, ItemFn};
 
-use crate::{generate::{scenes::{generate_scene_decode_funs, scene_type_name}, util::{get_annotated_function, typed_value_to_rust}}, parse::{project::SkyliteProject, scenes::SceneInstance, util::{change_case, IdentCase}}, SkyliteProcError};
+use crate::{generate::{scenes::{generate_scene_decode_funs, scene_type_name}, util::{get_annotated_function, get_annotated_macros, skylite_type_to_rust, typed_value_to_rust}}, parse::{project::{SaveSlotsConfig, SkyliteProject, UpdateMode}, scenes::SceneInstance, util::{change_case, IdentCase}, values::Variable}, SkyliteProcError};
 
-use super::{actors::{any_actor_type_name, generate_actors_type}, scenes::generate_scene_data};
+use super::{actors::{any_actor_type_name, generate_actors_type}, config::{config_type_name, generate_config}, custom::generate_custom_assets, graphics::{generate_graphics, graphics_type_name}, scenes::generate_scene_data, schema::emit_schema, size_report::SizeReport, strings::{generate_string_table, string_contributions, StringTable}};
 
 fn tile_type_name(project_name: &str) -> Ident {
     format_ident!("{}Tiles", change_case(project_name, IdentCase::UpperCamelCase))
@@ -22,73 +22,457 @@ fn generate_tile_type_enum<S: AsRef<str>>(project_name: &str, tile_types: &[S])
     }
 }
 
+fn globals_type_name(project_name: &str) -> Ident {
+    format_ident!("{}Globals", change_case(project_name, IdentCase::UpperCamelCase))
+}
+
+fn generate_globals_type(project_name: &str, globals: &[Variable]) -> TokenStream {
+    let globals_type_name = globals_type_name(project_name);
+    let project_type_name = project_type_name(project_name);
+    let names: Vec<Ident> = globals.iter()
+        .map(|g| format_ident!("{}", change_case(&g.name, IdentCase::LowerSnakeCase)))
+        .collect();
+    let types = globals.iter().map(|g| skylite_type_to_rust(&g.typename));
+    // Globals always have a default value, see `parse_global`.
+    let defaults = globals.iter().map(|g| typed_value_to_rust(g.default.as_ref().unwrap(), &project_type_name));
+    quote! {
+        /// The global variables of the project, accessible through `ProjectControls::globals`.
+        pub struct #globals_type_name {
+            #(pub #names: #types),*
+        }
+
+        impl #globals_type_name {
+            #[doc(hidden)]
+            pub fn _private_new() -> #globals_type_name {
+                #globals_type_name {
+                    #(#names: #defaults),*
+                }
+            }
+        }
+    }
+}
+
 pub(crate) fn project_ident(project_name: &str) -> Ident {
     format_ident!("{}", change_case(project_name, IdentCase::UpperCamelCase))
 }
 
+/// Returns the identifier of a static data item of the project, e.g. `__TEST_PROJECT_STRINGS`.
+///
+/// The identifiers contain the name of the project, so that several projects can be defined in the same module.
+pub(crate) fn project_data_ident(project_name: &str, name: &str) -> Ident {
+    format_ident!("__{}_{}", change_case(project_name, IdentCase::UpperSnakeCase), name)
+}
+
 pub(crate) fn project_type_name(project_name: &str) -> TokenStream {
     let project_ident = project_ident(project_name);
     quote!(crate::#project_ident)
 }
 
-fn generate_project_type(project_name: &str, target_type: &TokenStream) -> TokenStream {
+/// Generates the documentation of the project type, which gives an overview of all assets.
+fn generate_assets_overview(project: &SkyliteProject) -> TokenStream {
+    fn section(lines: &mut Vec<String>, title: &str, entries: Vec<(String, Option<&String>)>) {
+        if entries.is_empty() {
+            return;
+        }
+        lines.push(String::new());
+        lines.push(format!("## {}", title));
+        lines.push(String::new());
+        lines.extend(entries.into_iter().map(|(name, doc)| match doc {
+            Some(doc) => format!("- `{}`: {}", name, doc),
+            None => format!("- `{}`", name)
+        }));
+    }
+
+    let mut lines = vec![
+        format!("The main type of the project `{}`.", project.name),
+        String::new(),
+        "# Assets".to_owned()
+    ];
+    section(&mut lines, "Actors", project.actors.iter()
+        .map(|a| (change_case(&a.name, IdentCase::UpperCamelCase), a.description.as_ref()))
+        .collect());
+    section(&mut lines, "Scenes", project.scenes.iter()
+        .map(|s| (change_case(&s.name, IdentCase::UpperCamelCase), s.description.as_ref()))
+        .collect());
+    section(&mut lines, "Graphics", project.graphics.iter()
+        .map(|g| (format!("{}::{}", graphics_type_name(&project.name), g.name), None))
+        .collect());
+    section(&mut lines, "Configs", project.configs.iter()
+        .map(|c| (config_type_name(&c.name).to_string(), None))
+        .collect());
+    for (kind, assets) in &project.custom {
+        let enum_name = format!("{}Assets", change_case(&kind.name, IdentCase::UpperCamelCase));
+        section(&mut lines, &kind.name, assets.iter()
+            .map(|a| (format!("{}::{}", enum_name, a.name), None))
+            .collect());
+    }
+    quote!(#(#[doc = #lines])*)
+}
+
+/// Generates the count, the names and a name to id lookup for one kind of asset.
+///
+/// `names` must be given in the order of the asset ids.
+fn gen_asset_ids(kind: &str, names: &[String]) -> TokenStream {
+    let count_name = format_ident!("{}_COUNT", change_case(kind, IdentCase::UpperSnakeCase));
+    let names_name = format_ident!("{}_NAMES", change_case(kind, IdentCase::UpperSnakeCase));
+    let ids_name = format_ident!("{}_IDS", change_case(kind, IdentCase::UpperSnakeCase));
+    let lookup_name = format_ident!("{}_id", change_case(kind, IdentCase::LowerSnakeCase));
+    let count = names.len();
+    let count_doc = format!("The number of {} assets.", kind);
+    let names_doc = format!("The names of the {} assets, indexed by id.", kind);
+    let lookup_doc = format!("Returns the id of the {} asset with the given name.", kind);
+
+    let mut sorted: Vec<(&String, usize)> = names.iter().zip(0..).collect();
+    sorted.sort();
+    let sorted_names = sorted.iter().map(|(name, _)| name);
+    let sorted_ids = sorted.iter().map(|(_, id)| id);
+
+    quote! {
+        #[doc = #count_doc]
+        pub const #count_name: usize = #count;
+
+        #[doc = #names_doc]
+        pub const #names_name: &[&str] = &[#(#names),*];
+
+        const #ids_name: &[(&str, usize)] = &[#((#sorted_names, #sorted_ids)),*];
+
+        #[doc = #lookup_doc]
+        pub fn #lookup_name(name: &str) -> ::std::option::Option<usize> {
+            #ids_name.binary_search_by(|(n, _)| (*n).cmp(name)).ok().map(|i| #ids_name[i].1)
+        }
+    }
+}
+
+/// Generates the `assets` module, which lists the actors and scenes of the project.
+///
+/// The names are the names of the generated types, e.g. `PlayerActor`, and the ids are the
+/// type ids which are also used by the generated actor and scene types.
+fn generate_assets_module(actor_names: &[String], scene_names: &[String]) -> TokenStream {
+    let actors = gen_asset_ids("actor", actor_names);
+    let scenes = gen_asset_ids("scene", scene_names);
+    quote! {
+        /// Counts, names and ids of the assets of the project, e.g. for tools and debug overlays.
+        pub mod assets {
+            #actors
+            #scenes
+        }
+    }
+}
+
+/// Generates an invocation of each `macro_rules!` macro annotated with `#[skylite_proc::extend_project]`.
+///
+/// The macros receive the names and ids of the project's actors, scenes and custom assets,
+/// in the order of their ids, and expand to additional items next to the generated project code.
+fn generate_project_extensions(project_name: &str, actor_names: &[String], scene_names: &[String], custom: &[(String, Vec<String>)], items: &[Item]) -> TokenStream {
     let project_ident = project_ident(project_name);
+    let entries = |names: &[String]| {
+        let idents = names.iter().map(|name| format_ident!("{}", name));
+        let ids = (0..names.len()).map(Literal::usize_unsuffixed);
+        quote!(#((#idents, #ids)),*)
+    };
+    let actors = entries(actor_names);
+    let scenes = entries(scene_names);
+    let custom = custom.iter().map(|(kind, assets)| {
+        let enum_name = format_ident!("{}Assets", change_case(kind, IdentCase::UpperCamelCase));
+        let assets = entries(assets);
+        quote!((#enum_name, [#assets]))
+    });
+    let custom = quote!(#(#custom),*);
+
+    let invocations = get_annotated_macros(items, "skylite_proc::extend_project").into_iter()
+        .map(|mac| quote! {
+            #mac! {
+                project: #project_ident,
+                actors: [#actors],
+                scenes: [#scenes],
+                custom: [#custom]
+            }
+        });
+    quote!(#(#invocations)*)
+}
+
+fn generate_project_type(project_name: &str, target_type: &TokenStream, save_slots: Option<&SaveSlotsConfig>, docs: &TokenStream) -> TokenStream {
+    let project_ident = project_ident(project_name);
+    let save_slot_field = if save_slots.is_some() {
+        quote! {
+            , save_slot: ::std::option::Option<usize>,
+            storage: ::std::option::Option<::std::boxed::Box<dyn ::skylite_core::prelude::StorageBackend>>
+        }
+    } else {
+        TokenStream::new()
+    };
     quote! {
+        #docs
         pub struct #project_ident {
             target: #target_type,
-            scene: ::std::boxed::Box<dyn ::skylite_core::scenes::Scene<P=Self>>,
-            controls: ::skylite_core::ProjectControls<#project_ident>,
+            scene: ::std::boxed::Box<dyn ::skylite_core::prelude::Scene<P=Self>>,
+            controls: ::skylite_core::prelude::ProjectControls<#project_ident>,
             graphics_cache: ::std::vec::Vec<::std::rc::Weak<u8>>,
             focus_x: i32,
-            focus_y: i32
+            focus_y: i32,
+            draw_stats: ::skylite_core::prelude::DrawStats
+            #save_slot_field
         }
     }
 }
 
-fn generate_project_new_method(project_name: &str, target_type: &TokenStream, init_call: &TokenStream, initial_scene: &SceneInstance) -> TokenStream {
+fn builder_type_name(project_name: &str) -> Ident {
+    format_ident!("{}Builder", change_case(project_name, IdentCase::UpperCamelCase))
+}
+
+/// Generates the builder type, which creates project instances with non-default options.
+///
+/// The constructors of the `SkyliteProject` trait are shortcuts for the builder.
+fn generate_project_builder(project_name: &str, target_type: &TokenStream, initial_scene: &SceneInstance, save_slots: Option<&SaveSlotsConfig>, items: &[Item]) -> TokenStream {
     let project_ident = project_ident(project_name);
+    let builder_type_name = builder_type_name(project_name);
+    let globals_type_name = globals_type_name(project_name);
+    let project_type_name = project_type_name(project_name);
     let initial_scene_name = scene_type_name(&initial_scene.name);
-    let initial_scene_params = initial_scene.args.iter().map(typed_value_to_rust);
+    let initial_scene_params = initial_scene.args.iter().map(|arg| typed_value_to_rust(arg, &project_type_name));
+
+    let init_call = get_annotated_function(items, "skylite_proc::init")
+        .map(|fun| fun.sig.ident.clone())
+        .map(|name| quote!(#name(&mut out);))
+        .unwrap_or(TokenStream::new());
+
+    let (save_slot_field, save_slot_init, save_slot_setter, save_slot_value) = if save_slots.is_some() {
+        (
+            quote! {
+                , save_slot: ::std::option::Option<usize>,
+                storage: ::std::option::Option<::std::boxed::Box<dyn ::skylite_core::prelude::StorageBackend>>
+            },
+            quote!(, save_slot: None, storage: None),
+            quote! {
+                /// Sets the active save slot, which is available through `active_save_slot`
+                /// while the project is initialized, e.g. to load the save data.
+                pub fn save_slot(mut self, slot: usize) -> #builder_type_name {
+                    assert!(slot < #project_ident::SAVE_SLOTS.count(), "Save slot {} out of range", slot);
+                    self.save_slot = Some(slot);
+                    self
+                }
+
+                /// Stores the save slots in `storage` instead of the target's persistent storage.
+                pub fn storage(mut self, storage: impl ::skylite_core::prelude::StorageBackend + 'static) -> #builder_type_name {
+                    self.storage = Some(::std::boxed::Box::new(storage));
+                    self
+                }
+            },
+            quote!(, save_slot: self.save_slot, storage: self.storage)
+        )
+    } else {
+        (TokenStream::new(), TokenStream::new(), TokenStream::new(), TokenStream::new())
+    };
+
+    quote! {
+        /// Creates instances of the project with non-default options.
+        ///
+        /// `SkyliteProject::new` and `SkyliteProject::new_with_scene` are shortcuts for this builder.
+        pub struct #builder_type_name {
+            target: #target_type,
+            scene: ::std::option::Option<::std::boxed::Box<dyn ::skylite_core::prelude::Scene<P=#project_ident>>>
+            #save_slot_field
+        }
+
+        impl #builder_type_name {
+            pub fn new(target: #target_type) -> #builder_type_name {
+                #builder_type_name {
+                    target,
+                    scene: None
+                    #save_slot_init
+                }
+            }
+
+            /// Starts the project with `scene` instead of the initial scene from the project file.
+            pub fn scene(mut self, scene: ::std::boxed::Box<dyn ::skylite_core::prelude::Scene<P=#project_ident>>) -> #builder_type_name {
+                self.scene = Some(scene);
+                self
+            }
+
+            #save_slot_setter
+
+            pub fn build(self) -> #project_ident {
+                let (w, h) = self.target.get_screen_size();
+                let scene = match self.scene {
+                    Some(scene) => scene,
+                    None => ::std::boxed::Box::new(#initial_scene_name::new(#(#initial_scene_params),*))
+                };
+                let mut out = #project_ident {
+                    target: self.target,
+                    scene,
+                    controls: ::skylite_core::prelude::ProjectControls {
+                        pending_scene: None,
+                        globals: #globals_type_name::_private_new(),
+                        changes: ::std::vec::Vec::new(),
+                        pending_changes: ::std::vec::Vec::new(),
+                        events: ::skylite_core::prelude::EventQueue::new(),
+                        shake: ::skylite_core::prelude::ScreenShake::new()
+                    },
+                    graphics_cache: ::std::vec::Vec::new(),
+                    focus_x: w as i32 / 2,
+                    focus_y: h as i32 / 2,
+                    draw_stats: ::skylite_core::prelude::DrawStats::default()
+                    #save_slot_value
+                };
+
+                #init_call
+                out
+            }
+        }
+    }
+}
+
+fn generate_project_new_method(project_name: &str, target_type: &TokenStream) -> TokenStream {
+    let project_ident = project_ident(project_name);
+    let builder_type_name = builder_type_name(project_name);
     quote! {
         fn new(target: #target_type) -> #project_ident {
-            let (w, h) = target.get_screen_size();
-            let mut out = #project_ident {
-                target,
-                scene: ::std::boxed::Box::new(#initial_scene_name::new(#(#initial_scene_params),*)),
-                controls: ::skylite_core::ProjectControls { pending_scene: None },
-                graphics_cache: ::std::vec::Vec::new(),
-                focus_x: w as i32 / 2,
-                focus_y: h as i32 / 2
-            };
+            #builder_type_name::new(target).build()
+        }
+
+        fn new_with_scene(target: #target_type, scene: ::std::boxed::Box<dyn ::skylite_core::prelude::Scene<P=Self>>) -> #project_ident {
+            #builder_type_name::new(target).scene(scene).build()
+        }
+    }
+}
+
+/// Generates the `SAVE_SLOTS` constant and the methods for accessing the save slots,
+/// if the project definition contains a `save-slots` entry.
+///
+/// If the project has a function marked with `#[skylite_proc::save_transform]`, the
+/// save data is passed through the returned `SaveTransform` when reading and writing.
+fn gen_save_slot_methods(save_slots: Option<&SaveSlotsConfig>, items: &[Item]) -> TokenStream {
+    let SaveSlotsConfig { offset, slot_size, count } = match save_slots {
+        Some(s) => *s,
+        None => return TokenStream::new()
+    };
+
+    let (list, read, write) = match get_annotated_function(items, "skylite_proc::save_transform").map(|fun| fun.sig.ident.clone()) {
+        Some(name) => (
+            quote!(Self::SAVE_SLOTS.list_with(self.__skylite_storage(), &#name())),
+            quote!(Self::SAVE_SLOTS.read_with(self.__skylite_storage(), slot, &#name())),
+            quote!(Self::SAVE_SLOTS.write_with(self.__skylite_storage_mut(), slot, play_time, progress, data, &#name()))
+        ),
+        None => (
+            quote!(Self::SAVE_SLOTS.list(self.__skylite_storage())),
+            quote!(Self::SAVE_SLOTS.read(self.__skylite_storage(), slot)),
+            quote!(Self::SAVE_SLOTS.write(self.__skylite_storage_mut(), slot, play_time, progress, data))
+        )
+    };
+
+    quote! {
+        /// The layout of the save slots in persistent storage, from the `save-slots` entry of the project definition.
+        pub const SAVE_SLOTS: ::skylite_core::prelude::SaveSlots = ::skylite_core::prelude::SaveSlots::new(#offset, #slot_size, #count);
+
+        /// Returns the storage set with the builder's `storage` option, or the target.
+        fn __skylite_storage(&self) -> &dyn ::skylite_core::prelude::StorageBackend {
+            match &self.storage {
+                Some(storage) => storage.as_ref(),
+                None => &self.target
+            }
+        }
+
+        fn __skylite_storage_mut(&mut self) -> &mut dyn ::skylite_core::prelude::StorageBackend {
+            match &mut self.storage {
+                Some(storage) => storage.as_mut(),
+                None => &mut self.target
+            }
+        }
 
-            #init_call
-            out
+        /// Returns the metadata of every save slot, or `None` for empty slots.
+        pub fn list_save_slots(&self) -> ::std::vec::Vec<::std::option::Option<::skylite_core::prelude::SlotMetadata>> {
+            #list
+        }
+
+        /// Reads the save data from the slot `slot`, or `None` if the slot is empty.
+        pub fn read_save_slot(&self, slot: usize) -> ::std::option::Option<(::skylite_core::prelude::SlotMetadata, ::std::vec::Vec<u8>)> {
+            #read
+        }
+
+        /// Writes `data` to the slot `slot`, together with the play time in seconds and a progress marker.
+        pub fn write_save_slot(&mut self, slot: usize, play_time: u32, progress: u16, data: &[u8]) {
+            #write
+        }
+
+        /// Marks the slot `slot` as empty.
+        pub fn clear_save_slot(&mut self, slot: usize) {
+            Self::SAVE_SLOTS.clear(self.__skylite_storage_mut(), slot)
+        }
+
+        /// Returns the save slot selected with the builder's `save_slot` option, if any.
+        pub fn active_save_slot(&self) -> ::std::option::Option<usize> {
+            self.save_slot
         }
     }
 }
 
-fn generate_project_impl(project_name: &str) -> TokenStream {
+fn generate_project_impl(project_name: &str, target: Option<&str>, save_slots: Option<&SaveSlotsConfig>, items: &[Item]) -> TokenStream {
     let scene_decode_funs = generate_scene_decode_funs(project_name);
+    let save_slot_methods = gen_save_slot_methods(save_slots, items);
     let project_ident = project_ident(project_name);
+    let target = target.unwrap_or("");
 
     quote! {
         impl #project_ident {
+            /// The target selected with `target_name!`, or an empty string. Checked by the asset macros, see `generate_target_check`.
+            #[doc(hidden)]
+            pub const _PRIVATE_TARGET_NAME: &'static str = #target;
+
             #scene_decode_funs
+
+            /// Returns the active scene.
+            ///
+            /// A scene change requested during an update only takes effect at the start
+            /// of the next update, so until then this returns the previous scene.
+            pub fn scene(&self) -> &dyn ::skylite_core::prelude::Scene<P=Self> {
+                self.scene.as_ref()
+            }
+
+            /// Returns the statistics of the draw calls of the most recent render.
+            ///
+            /// The statistics are only counted with the `debug-draw-stats` feature of `skylite_core`.
+            pub fn draw_stats(&self) -> ::skylite_core::prelude::DrawStats {
+                self.draw_stats
+            }
+
+            #save_slot_methods
+        }
+    }
+}
+
+/// Generates a check that an asset macro selected the same target as the `target_name!` of the project.
+///
+/// The target decides which assets exist, and therefore the ids of the assets. An asset macro
+/// with a different target would compute its ids from a different list of assets than the project.
+pub(crate) fn generate_target_check(project_name: &str, macro_name: &str, target: Option<&str>) -> TokenStream {
+    let project_type_name = project_type_name(project_name);
+    let target = target.unwrap_or("");
+    let msg = format!("The target selected by {}! does not match the target_name! of the project", macro_name);
+    quote! {
+        const _: () = ::std::assert!(::skylite_core::strings::_private::str_eq(#project_type_name::_PRIVATE_TARGET_NAME, #target), #msg);
+    }
+}
+
+/// Generates the `UPDATE_MODE` constant, if the project does not use the default update mode.
+fn gen_update_mode_const(update_mode: UpdateMode) -> TokenStream {
+    match update_mode {
+        UpdateMode::Nested => TokenStream::new(),
+        UpdateMode::Phased => quote! {
+            const UPDATE_MODE: ::skylite_core::prelude::UpdateMode = ::skylite_core::prelude::UpdateMode::Phased;
         }
     }
 }
 
-fn generate_project_trait_impl(project_name: &str, target_type: &TokenStream, initial_scene: &SceneInstance, items: &[Item]) -> TokenStream {
+fn generate_project_trait_impl(project_name: &str, target_type: &TokenStream, update_mode: UpdateMode, items: &[Item]) -> TokenStream {
     fn get_name(fun: &ItemFn) -> Ident { fun.sig.ident.clone() }
 
     let project_ident = project_ident(project_name);
     let tile_type_name = tile_type_name(project_name);
     let actors_type_name = any_actor_type_name(project_name);
-
-    let init = get_annotated_function(items, "skylite_proc::init")
-        .map(get_name)
-        .map(|name| quote!(#name(&mut out);))
-        .unwrap_or(TokenStream::new());
+    let globals_type_name = globals_type_name(project_name);
+    let graphics_type_name = graphics_type_name(project_name);
+    let strings_ident = project_data_ident(project_name, "STRINGS");
+    let graphics_data_ident = project_data_ident(project_name, "GRAPHICS_DATA");
 
     let pre_update = get_annotated_function(items, "skylite_proc::pre_update")
         .map(get_name)
@@ -102,43 +486,59 @@ fn generate_project_trait_impl(project_name: &str, target_type: &TokenStream, in
 
     let pre_render = get_annotated_function(items, "skylite_proc::pre_render")
         .map(get_name)
-        .map(|name| quote!(#name(&mut self.draw_context);))
+        .map(|name| quote!(#name(&mut draw_context);))
         .unwrap_or(TokenStream::new());
 
     let post_render = get_annotated_function(items, "skylite_proc::post_render")
         .map(get_name)
-        .map(|name| quote!(#name(&mut self.draw_context);))
+        .map(|name| quote!(#name(&mut draw_context);))
         .unwrap_or(TokenStream::new());
 
-    let new_method = generate_project_new_method(project_name, target_type, &init, initial_scene);
+    let new_method = generate_project_new_method(project_name, target_type);
+    let update_mode_const = gen_update_mode_const(update_mode);
 
     quote! {
-        impl skylite_core::SkyliteProject for #project_ident {
+        impl ::skylite_core::prelude::SkyliteProject for #project_ident {
             type Target = #target_type;
             type TileType = #tile_type_name;
             type Actors = #actors_type_name;
+            type Globals = #globals_type_name;
+            type Graphics = #graphics_type_name;
+
+            #update_mode_const
 
             #new_method
 
-            fn render(&mut self) {
-                let draw_context = ::skylite_core::DrawContext {
+            fn render_interpolated(&mut self, alpha: f32) {
+                let mut draw_context = ::skylite_core::prelude::DrawContext {
                     target: &mut self.target,
                     graphics_cache: &mut self.graphics_cache,
                     focus_x: self.focus_x,
-                    focus_y: self.focus_y
+                    focus_y: self.focus_y,
+                    layer: ::skylite_core::prelude::RenderLayer::Main,
+                    parallax: (1.0, 1.0),
+                    alpha,
+                    offset: self.controls.shake.offset(),
+                    offset_stack: ::std::vec::Vec::new(),
+                    clip: None,
+                    clip_stack: ::std::vec::Vec::new(),
+                    shape_fallback: None,
+                    stats: ::skylite_core::prelude::DrawStats::default()
                 };
                 #pre_render
 
                 // Main rendering
-                ::skylite_core::scenes::_private::render_scene(self.scene.as_ref(), &draw_context);
+                ::skylite_core::scenes::_private::render_scene(self.scene.as_ref(), &mut draw_context);
 
                 #post_render
+                self.draw_stats = draw_context.stats;
             }
 
             fn update(&mut self) {
                 if let Some(scene) = self.controls.pending_scene.take() {
                     self.scene = scene;
                 }
+                self.controls._private_next_update();
 
                 #pre_update
 
@@ -147,23 +547,61 @@ fn generate_project_trait_impl(project_name: &str, target_type: &TokenStream, in
 
                 #post_update
             }
+
+            fn get_str(id: ::skylite_core::prelude::StrId) -> &'static str {
+                #strings_ident[id._private_index()]
+            }
+
+            fn get_graphics(id: #graphics_type_name) -> &'static [u8] {
+                #graphics_data_ident[id as usize]
+            }
         }
     }
 }
 
-
-impl SkyliteProject {
-
-    pub(crate) fn generate(&self, target_type: &TokenStream, items: &[Item]) -> Result<Vec<Item>, SkyliteProcError> {
-        Ok(vec![
-            Item::Verbatim(generate_tile_type_enum(&self.name, &self.tile_types)),
-            Item::Verbatim(generate_actors_type(&self.name, &self.actors)?),
-            Item::Verbatim(generate_scene_data(&self.scenes, &self.actors)),
-            Item::Verbatim(generate_project_type(&self.name, &target_type)),
-            Item::Verbatim(generate_project_impl(&self.name)),
-            Item::Verbatim(generate_project_trait_impl(&self.name, &target_type, &self.initial_scene, items))
-        ])
+/// Generates the items of the `skylite_project!` macro for `project`.
+pub(crate) fn generate_project(project: &SkyliteProject, target: Option<&str>, target_type: &TokenStream, items: &[Item]) -> Result<Vec<Item>, SkyliteProcError> {
+    let strings = StringTable::from_project(project)?;
+    let mut report = SizeReport::default();
+    for (name, size) in string_contributions(project) {
+        report.add_strings(&name, size);
     }
+    report.add_static_data("STRINGS", strings.data_len());
+    let scene_data = generate_scene_data(&project.name, &project.scenes, &project.actors, &strings, project.count_width, &mut report)?;
+    let graphics = generate_graphics(&project.name, &project.graphics, &mut report);
+    let custom_assets = project.custom.iter()
+        .map(|(kind, assets)| Ok(Item::Verbatim(generate_custom_assets(&project.name, kind, assets, &mut report)?)))
+        .collect::<Result<Vec<Item>, SkyliteProcError>>()?;
+    let configs = project.configs.iter()
+        .map(|config| Ok(Item::Verbatim(generate_config(&project.name, config)?)))
+        .collect::<Result<Vec<Item>, SkyliteProcError>>()?;
+    report.emit(&project.name)?;
+    emit_schema(project)?;
+
+    let actor_names: Vec<String> = project.actors.iter().map(|a| change_case(&a.name, IdentCase::UpperCamelCase)).collect();
+    let scene_names: Vec<String> = project.scenes.iter().map(|s| change_case(&s.name, IdentCase::UpperCamelCase)).collect();
+    let custom_names: Vec<(String, Vec<String>)> = project.custom.iter()
+        .map(|(kind, assets)| (kind.name.clone(), assets.iter().map(|a| a.name.clone()).collect()))
+        .collect();
+
+    let mut out = vec![
+        Item::Verbatim(generate_tile_type_enum(&project.name, &project.tile_types)),
+        Item::Verbatim(generate_globals_type(&project.name, &project.globals)),
+        Item::Verbatim(generate_actors_type(&project.name, &project.actors, items)?),
+        Item::Verbatim(generate_string_table(&project.name, &strings)),
+        Item::Verbatim(scene_data),
+        Item::Verbatim(graphics),
+        Item::Verbatim(generate_project_type(&project.name, &target_type, project.save_slots.as_ref(), &generate_assets_overview(project))),
+        Item::Verbatim(generate_project_builder(&project.name, &target_type, &project.initial_scene, project.save_slots.as_ref(), items)),
+        Item::Verbatim(generate_project_impl(&project.name, target, project.save_slots.as_ref(), items)),
+        Item::Verbatim(report.generate_size_report_fn(&project.name, &project.actors, &project.scenes)),
+        Item::Verbatim(generate_project_trait_impl(&project.name, &target_type, project.update_mode, items)),
+        Item::Verbatim(generate_assets_module(&actor_names, &scene_names)),
+        Item::Verbatim(generate_project_extensions(&project.name, &actor_names, &scene_names, &custom_names, items))
+    ];
+    out.extend(configs);
+    out.extend(custom_assets);
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -171,9 +609,77 @@ mod tests {
     use quote::quote;
     use syn::parse_quote;
 
-    use crate::parse::{scenes::SceneInstance, values::TypedValue};
+    use crate::parse::{actors::{Action, ActionInstance, Actor}, graphics::Graphic, project::{CountWidth, SaveSlotsConfig, SkyliteProject, UpdateMode}, scenes::SceneInstance, values::{Type, TypedValue, Variable}};
 
-    use super::generate_project_trait_impl;
+    use super::{gen_asset_ids, gen_save_slot_methods, generate_project_extensions, gen_update_mode_const, generate_assets_overview, generate_globals_type, generate_project_builder, generate_project_trait_impl, generate_target_check};
+
+    #[test]
+    fn test_generate_assets_overview() {
+        let project = SkyliteProject {
+            name: "Test1".to_owned(),
+            actors: vec![
+                Actor {
+                    name: "player-actor".to_owned(),
+                    description: Some("The player character".to_owned()),
+                    parameters: vec![],
+                    actions: vec![Action { name: "idle".to_owned(), params: vec![], description: None }],
+                    initial_action: ActionInstance { name: "idle".to_owned(), args: vec![] }
+                }
+            ],
+            scenes: vec![],
+            save_data: vec![],
+            globals: vec![],
+            initial_scene: SceneInstance { name: "test-scene".to_owned(), args: vec![] },
+            tile_types: vec![],
+            graphics: vec![Graphic { name: "Tiles".to_owned(), data: vec![] }],
+            configs: vec![],
+            custom: vec![],
+            update_mode: UpdateMode::Nested,
+            save_slots: None,
+            count_width: CountWidth::U32
+        };
+        let expectation = quote! {
+            #[doc = "The main type of the project `Test1`."]
+            #[doc = ""]
+            #[doc = "# Assets"]
+            #[doc = ""]
+            #[doc = "## Actors"]
+            #[doc = ""]
+            #[doc = "- `PlayerActor`: The player character"]
+            #[doc = ""]
+            #[doc = "## Graphics"]
+            #[doc = ""]
+            #[doc = "- `Test1Graphics::Tiles`"]
+        };
+        assert_eq!(generate_assets_overview(&project).to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_generate_globals_type() {
+        let globals = vec![
+            Variable { name: "coins".to_owned(), typename: Type::U16, documentation: None, default: Some(TypedValue::U16(0)) },
+            Variable { name: "has-key".to_owned(), typename: Type::Bool, documentation: None, default: Some(TypedValue::Bool(true)) }
+        ];
+        let actual = generate_globals_type("Test1", &globals);
+        let expectation = quote! {
+            /// The global variables of the project, accessible through `ProjectControls::globals`.
+            pub struct Test1Globals {
+                pub coins: u16,
+                pub has_key: bool
+            }
+
+            impl Test1Globals {
+                #[doc(hidden)]
+                pub fn _private_new() -> Test1Globals {
+                    Test1Globals {
+                        coins: 0u16,
+                        has_key: true
+                    }
+                }
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+    }
 
     #[test]
     fn test_generate_project_implementation() {
@@ -185,46 +691,41 @@ mod tests {
             fn pre_update(project: &mut Test1) {}
 
             #[skylite_proc::post_render]
-            fn post_render(project: &mut skylite_core::DrawContext<'static, Test1>) {}
+            fn post_render(project: &mut ::skylite_core::prelude::DrawContext<'static, Test1>) {}
         };
 
         let actual = generate_project_trait_impl(
             "Test1",
             &quote!(MockTarget),
-            &SceneInstance { name: "TestScene".to_owned(), args: vec![TypedValue::Bool(false), TypedValue::U8(5)]},
+            UpdateMode::Nested,
             &body_parsed.items
         );
         let expectation = quote! {
-            impl skylite_core::SkyliteProject for Test1 {
+            impl ::skylite_core::prelude::SkyliteProject for Test1 {
                 type Target = MockTarget;
                 type TileType = Test1Tiles;
                 type Actors = Test1Actors;
+                type Globals = Test1Globals;
+                type Graphics = Test1Graphics;
 
                 fn new(target: MockTarget) -> Test1 {
-                    let (w, h) = target.get_screen_size();
-                    let mut out = Test1 {
-                        draw_context: skylite_core::DrawContext {
-                            target,
-                            graphics_cache: Vec::new(),
-                            focus_x: w as i32 / 2,
-                            focus_y: h as i32 / 2
-                        },
-                        scene: ::std::boxed::Box::new(TestScene::new(false, 5u8)),
-                        controls: ::skylite_core::ProjectControls { pending_scene: None }
-                    };
-                    init(&mut out);
-                    out
+                    Test1Builder::new(target).build()
                 }
 
-                fn render(&mut self) {
+                fn new_with_scene(target: MockTarget, scene: ::std::boxed::Box<dyn ::skylite_core::prelude::Scene<P=Self>>) -> Test1 {
+                    Test1Builder::new(target).scene(scene).build()
+                }
+
+                fn render_interpolated(&mut self, alpha: f32) {
                     ::skylite_core::scenes::_private::render_scene(self.scene.as_ref(), &mut self.draw_context);
-                    post_render(&mut self.draw_context);
+                    post_render(&mut draw_context);
                 }
 
                 fn update(&mut self) {
                     if let Some(scene) = self.controls.pending_scene.take() {
                         self.scene = scene;
                     }
+                    self.controls._private_next_update();
 
                     pre_update(self);
                     self.scene._private_update(&mut self.controls);
@@ -233,4 +734,120 @@ mod tests {
         };
         assert_eq!(actual.to_string(), expectation.to_string());
     }
+
+    #[test]
+    fn test_gen_asset_ids() {
+        let actual = gen_asset_ids("actor", &["Player".to_owned(), "Bat".to_owned()]);
+        let expectation = quote! {
+            #[doc = "The number of actor assets."]
+            pub const ACTOR_COUNT: usize = 2usize;
+
+            #[doc = "The names of the actor assets, indexed by id."]
+            pub const ACTOR_NAMES: &[&str] = &["Player", "Bat"];
+
+            const ACTOR_IDS: &[(&str, usize)] = &[("Bat", 1usize), ("Player", 0usize)];
+
+            #[doc = "Returns the id of the actor asset with the given name."]
+            pub fn actor_id(name: &str) -> ::std::option::Option<usize> {
+                ACTOR_IDS.binary_search_by(|(n, _)| (*n).cmp(name)).ok().map(|i| ACTOR_IDS[i].1)
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_generate_project_extensions() {
+        assert!(generate_project_extensions("Test1", &[], &[], &[], &[]).is_empty());
+
+        let body_parsed: syn::File = parse_quote! {
+            #[skylite_proc::extend_project]
+            macro_rules! tables { ($($t:tt)*) => {}; }
+        };
+        let actual = generate_project_extensions(
+            "Test1",
+            &["Player".to_owned(), "Bat".to_owned()],
+            &["Level1".to_owned()],
+            &[("item".to_owned(), vec!["Potion".to_owned()])],
+            &body_parsed.items
+        );
+        let expectation = quote! {
+            tables! {
+                project: Test1,
+                actors: [(Player, 0), (Bat, 1)],
+                scenes: [(Level1, 0)],
+                custom: [(ItemAssets, [(Potion, 0)])]
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_gen_update_mode_const() {
+        assert!(gen_update_mode_const(UpdateMode::Nested).is_empty());
+        let expectation = quote! {
+            const UPDATE_MODE: ::skylite_core::prelude::UpdateMode = ::skylite_core::prelude::UpdateMode::Phased;
+        };
+        assert_eq!(gen_update_mode_const(UpdateMode::Phased).to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_gen_save_slot_methods() {
+        assert!(gen_save_slot_methods(None, &[]).is_empty());
+        let save_slots = SaveSlotsConfig { offset: 16, slot_size: 128, count: 3 };
+        let code = gen_save_slot_methods(Some(&save_slots), &[]).to_string();
+        let expected_const = quote! {
+            pub const SAVE_SLOTS: ::skylite_core::prelude::SaveSlots = ::skylite_core::prelude::SaveSlots::new(16usize, 128usize, 3usize);
+        };
+        assert!(code.contains(&expected_const.to_string()));
+        assert!(code.contains("pub fn list_save_slots"));
+        assert!(code.contains(&quote!(Self::SAVE_SLOTS.read(self.__skylite_storage(), slot)).to_string()));
+
+        let body_parsed: syn::File = parse_quote! {
+            #[skylite_proc::save_transform]
+            fn obfuscate() -> XorTransform { XorTransform::new(0x5eed) }
+        };
+        let code = gen_save_slot_methods(Some(&save_slots), &body_parsed.items).to_string();
+        assert!(code.contains(&quote!(Self::SAVE_SLOTS.list_with(self.__skylite_storage(), &obfuscate())).to_string()));
+        assert!(code.contains(&quote!(Self::SAVE_SLOTS.read_with(self.__skylite_storage(), slot, &obfuscate())).to_string()));
+        assert!(code.contains(&quote!(Self::SAVE_SLOTS.write_with(self.__skylite_storage_mut(), slot, play_time, progress, data, &obfuscate())).to_string()));
+    }
+
+    #[test]
+    fn test_generate_project_builder() {
+        let body_parsed: syn::File = parse_quote! {
+            #[skylite_proc::init]
+            fn init(project: &mut Test1) {}
+        };
+        let initial_scene = SceneInstance { name: "test-scene".to_owned(), args: vec![TypedValue::U8(5)] };
+
+        let code = generate_project_builder("Test1", &quote!(MockTarget), &initial_scene, None, &body_parsed.items).to_string();
+        let expected_scene = quote! {
+            let scene = match self.scene {
+                Some(scene) => scene,
+                None => ::std::boxed::Box::new(TestScene::new(5u8))
+            };
+        };
+        assert!(code.contains(&expected_scene.to_string()));
+        assert!(code.contains(&quote!(init(&mut out);).to_string()));
+        assert!(!code.contains("save_slot"));
+
+        let save_slots = SaveSlotsConfig { offset: 0, slot_size: 64, count: 2 };
+        let code = generate_project_builder("Test1", &quote!(MockTarget), &initial_scene, Some(&save_slots), &body_parsed.items).to_string();
+        assert!(code.contains("pub fn save_slot"));
+    }
+
+    #[test]
+    fn test_generate_target_check() {
+        let code = generate_target_check("test1", "asset_file", Some("wasm4"));
+        let expectation = quote! {
+            const _: () = ::std::assert!(
+                ::skylite_core::strings::_private::str_eq(crate::Test1::_PRIVATE_TARGET_NAME, "wasm4"),
+                "The target selected by asset_file! does not match the target_name! of the project"
+            );
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+
+        let code = generate_target_check("test1", "embedded_asset", None).to_string();
+        assert!(code.contains(&quote!(str_eq(crate::Test1::_PRIVATE_TARGET_NAME, "")).to_string()));
+    }
 }
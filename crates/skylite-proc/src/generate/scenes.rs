@@ -4,70 +4,101 @@ use std::collections::HashMap;
 
 use proc_macro2::{Literal, TokenStream, Ident};
 
-use crate::{parse::{actors::Actor, scenes::{Scene, SceneStub}, util::{change_case, IdentCase}, values::Variable}, SkyliteProcError};
+use crate::{parse::{actors::Actor, project::CountWidth, scenes::{ActorInstance, Scene, SceneStub}, util::{change_case, IdentCase}, values::Variable}, SkyliteProcError};
 
-use super::{actors::any_actor_type_name, encode::{CompressionBuffer, Serialize}, project::project_type_name, util::{generate_param_list, get_annotated_function, get_macro_item, skylite_type_to_rust}};
+use super::{actors::any_actor_type_name, encode::{compress_blobs, CompressionBuffer, Serialize, DEFAULT_COMPRESSION}, project::{project_data_ident, project_type_name}, size_report::SizeReport, strings::StringTable, util::{check_derive_targets, gen_constructor_docs, gen_properties_init, generate_param_list, get_annotated_function, get_documentation, get_macro_item, skylite_type_to_rust}};
 
 // region: skylite_project stuff
 
 pub(crate) fn scene_type_name(name: &str) -> Ident { format_ident!("{}", change_case(name, IdentCase::UpperCamelCase)) }
 
-fn encode_scene(scene: &Scene, actor_ids: &HashMap<String, usize>, buffer: &mut CompressionBuffer) {
+/// Encodes the actors and extras of `scene` into `buffer`.
+///
+/// The number of bytes written for each actor instance is added to the entry
+/// of its actor in `actor_sizes`, which is indexed by actor id.
+fn encode_scene(scene: &Scene, actor_ids: &HashMap<String, usize>, buffer: &mut CompressionBuffer, actor_sizes: &mut [usize]) {
     buffer.write_varint(scene.actors.len());
     for a in &scene.actors {
-        buffer.write_varint(*actor_ids.get(&a.1.actor_name).unwrap());
-        for p in &a.1.args {
-            p.serialize(buffer);
-        }
+        encode_actor_instance(&a.1, actor_ids, buffer, actor_sizes);
     }
 
     buffer.write_varint(scene.extras.len());
     for e in &scene.extras {
-        buffer.write_varint(*actor_ids.get(&e.actor_name).unwrap());
-        for p in &e.args {
-            p.serialize(buffer);
-        }
+        encode_actor_instance(e, actor_ids, buffer, actor_sizes);
+    }
+}
+
+fn encode_actor_instance(instance: &ActorInstance, actor_ids: &HashMap<String, usize>, buffer: &mut CompressionBuffer, actor_sizes: &mut [usize]) {
+    let start = buffer.len();
+    let actor_id = *actor_ids.get(&instance.actor_name).unwrap();
+    buffer.write_varint(actor_id);
+    for p in &instance.args {
+        p.serialize(buffer);
     }
+    actor_sizes[actor_id] += buffer.len() - start;
 }
 
-pub(crate) fn generate_scene_data(scenes: &[Scene], actors: &[Actor]) -> TokenStream {
+pub(crate) fn generate_scene_data(project_name: &str, scenes: &[Scene], actors: &[Actor], strings: &StringTable, count_width: CountWidth, report: &mut SizeReport) -> Result<TokenStream, SkyliteProcError> {
     let actor_ids = actors.iter()
         .enumerate()
         .map(|(i, actor)| (actor.name.clone(), i))
         .collect::<HashMap<String, usize>>();
-    let mut scene_buffer = CompressionBuffer::new();
-    let offsets = scenes.iter()
-        .map(|s| {
-            let out = scene_buffer.len();
-            encode_scene(s, &actor_ids, &mut scene_buffer);
-            out
-        })
-        .map(|offset| Literal::usize_unsuffixed(offset))
-        .collect::<Vec<Literal>>();
 
-    let scene_data = scene_buffer.encode()
-        .into_iter()
-        .map(|b| Literal::u8_unsuffixed(b));
+    // The instances of each actor are part of the scene data, so their size is recorded per actor as well.
+    let mut actor_sizes = vec![0; actors.len()];
 
-    quote! {
-        static SCENE_DATA: &[u8] = &[#(#scene_data),*];
-        static SCENE_OFFSETS: &[usize] = &[#(#offsets),*];
+    // Each scene is compressed separately, so that every scene can use its own compression methods.
+    let buffers = scenes.iter()
+        .map(|s| {
+            let mut buffer = CompressionBuffer::with_strings(strings.clone(), count_width.max());
+            encode_scene(s, &actor_ids, &mut buffer, &mut actor_sizes);
+            if let Some(val) = buffer.varint_overflow() {
+                return Err(SkyliteProcError::DataError(format!("Scene {} contains the count or id {}, which exceeds the count-width of the project", s.name, val)));
+            }
+            report.add_asset(&s.name, buffer.len());
+            Ok(buffer)
+        })
+        .collect::<Result<Vec<CompressionBuffer>, SkyliteProcError>>()?;
+    for (actor, size) in actors.iter().zip(actor_sizes) {
+        report.add_asset(&actor.name, size);
     }
+    let names = scenes.iter()
+        .map(|s| format!("scene {}", s.name))
+        .collect::<Vec<String>>();
+    let blobs = buffers.iter()
+        .zip(scenes)
+        .zip(&names)
+        .map(|((buffer, s), name)| (name.as_str(), buffer.data(), s.compression.as_deref().unwrap_or(DEFAULT_COMPRESSION)))
+        .collect::<Vec<_>>();
+
+    let mut total_size = 0;
+    let scene_data = compress_blobs(&blobs)?.into_iter()
+        .map(|data| {
+            total_size += data.len();
+            let bytes = data.into_iter().map(|b| Literal::u8_unsuffixed(b));
+            quote!(&[#(#bytes),*])
+        })
+        .collect::<Vec<TokenStream>>();
+    report.add_static_data("SCENE_DATA", total_size);
+
+    let data_ident = project_data_ident(project_name, "SCENE_DATA");
+    Ok(quote! {
+        static #data_ident: &[&[u8]] = &[#(#scene_data),*];
+    })
 }
 
 pub(crate) fn generate_scene_decode_funs(project_name: &str) -> TokenStream {
     let any_actor_type_name = any_actor_type_name(project_name);
+    let data_ident = project_data_ident(project_name, "SCENE_DATA");
 
     quote! {
-        pub fn _private_get_decoder_for_scene(id: u32) -> ::std::boxed::Box<dyn ::skylite_compress::Decoder> {
-            let mut out = ::skylite_compress::make_decoder(SCENE_DATA);
-            for _ in 0..SCENE_OFFSETS[id as usize] { out.decode_u8(); }
-            out
+        pub fn _private_get_decoder_for_scene(id: u32) -> ::std::boxed::Box<dyn ::skylite_core::prelude::Decoder> {
+            ::skylite_core::prelude::make_decoder(#data_ident[id as usize])
         }
 
-        pub fn _private_decode_actor_list(decoder: &mut dyn ::skylite_compress::Decoder) -> Vec<#any_actor_type_name> {
-            use ::skylite_core::actors::ActorBase;
-            let len = ::skylite_core::decode::read_varint(decoder);
+        pub fn _private_decode_actor_list(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> Vec<#any_actor_type_name> {
+            use ::skylite_core::prelude::ActorBase;
+            let len = ::skylite_core::prelude::read_varint(decoder);
             (0..len).map(|_| #any_actor_type_name::_private_decode(decoder)).collect()
         }
     }
@@ -110,17 +141,15 @@ fn gen_properties_type(scene: &SceneStub, items: &[Item]) -> Result<TokenStream,
         None => TokenStream::new()
     };
 
-    // For an actor that has neither parameters nor properties, the create_properties special function is optional.
-    let create_properties_call = if scene.parameters.len() > 0 || !properties.is_empty() {
-        match get_annotated_function(items, "skylite_proc::create_properties") {
-            Some(fun) => {
-                let ident = &fun.sig.ident;
-                quote! { super::#ident(#(#scene_param_names),*) }
-            },
-            None => return Err(SkyliteProcError::DataError(format!("Missing required special function `create_properties`. Function is required because the actor has parameters or properties.")))
-        }
-    } else {
-        quote!(#properties_type_name {})
+    // If there is no create_properties special function, the properties are initialized
+    // from the parameters with matching names, or from derive_property functions.
+    let create_properties_call = match get_annotated_function(items, "skylite_proc::create_properties") {
+        Some(fun) => {
+            check_derive_targets(items, None)?;
+            let ident = &fun.sig.ident;
+            quote! { super::#ident(#(#scene_param_names),*) }
+        },
+        None => gen_properties_init(&properties_type_name, &properties, &scene.parameters, items)?
     };
 
     Ok(quote! {
@@ -129,6 +158,7 @@ fn gen_properties_type(scene: &SceneStub, items: &[Item]) -> Result<TokenStream,
         }
 
         impl #properties_type_name {
+            #[allow(unused_variables)]
             fn _private_create_properties(#scene_param_list) -> #properties_type_name {
                 #create_properties_call
             }
@@ -140,15 +170,18 @@ fn gen_scene_type(scene: &SceneStub, type_id: u32, project_name: &str, items: &[
     let type_name = scene_type_name(&scene.name);
     let properties_type_name = properties_type_name(&scene.name);
     let project_type_name = project_type_name(project_name);
-    let any_actor_type = quote!(<#project_type_name as ::skylite_core::SkyliteProject>::Actors);
+    let any_actor_type = quote!(<#project_type_name as ::skylite_core::prelude::SkyliteProject>::Actors);
     let scene_param_list = generate_param_list(&scene.parameters);
     let scene_param_names: Vec<Ident> = scene.parameters.iter().map(get_parameter_name).collect();
     let init_call = match get_annotated_function(items, "skylite_proc::init") {
         Some(ident) => quote!(#ident(&mut out, #(#scene_param_names),*);),
         None => TokenStream::new()
     };
+    let scene_docs = get_documentation(&scene.description);
+    let constructor_docs = gen_constructor_docs(&type_name, &scene.parameters);
 
     Ok(quote! {
+        #scene_docs
         pub struct #type_name {
             pub properties: #properties_type_name,
             actors: Vec<#any_actor_type>,
@@ -157,6 +190,7 @@ fn gen_scene_type(scene: &SceneStub, type_id: u32, project_name: &str, items: &[
         }
 
         impl #type_name {
+            #constructor_docs
             pub fn new(#scene_param_list) -> #type_name {
                 let mut decoder = #project_type_name::_private_get_decoder_for_scene(#type_id);
                 let actors = #project_type_name::_private_decode_actor_list(decoder.as_mut());
@@ -184,8 +218,8 @@ fn gen_scene_decode_fn(scene_type_name: &Ident, params: &[Variable]) -> TokenStr
         });
 
     quote! {
-        fn _private_decode(decoder: &mut dyn ::skylite_compress::Decoder) -> #scene_type_name {
-            use ::skylite_core::decode::Deserialize;
+        fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> #scene_type_name {
+            use ::skylite_core::prelude::Deserialize;
             #(
                 let #scene_param_names = #scene_args_decoders;
             )*
@@ -194,12 +228,14 @@ fn gen_scene_decode_fn(scene_type_name: &Ident, params: &[Variable]) -> TokenStr
     }
 }
 
-fn gen_scene_trait_impl(scene: &SceneStub, project_type_name: &TokenStream, items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+fn gen_scene_trait_impl(scene: &SceneStub, type_id: u32, project_type_name: &TokenStream, items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
     fn get_name(fun: &ItemFn) -> Ident { fun.sig.ident.clone() }
 
     let scene_type_name = scene_type_name(&scene.name);
 
     let decode_fn = gen_scene_decode_fn(&scene_type_name, &scene.parameters);
+    let scene_id = type_id as usize;
+    let actor_names = &scene.actor_names;
 
     let pre_update = get_annotated_function(items, "skylite_proc::pre_update")
         .map(get_name)
@@ -222,74 +258,92 @@ fn gen_scene_trait_impl(scene: &SceneStub, project_type_name: &TokenStream, item
         .unwrap_or(TokenStream::new());
 
     Ok(quote! {
-        impl ::skylite_core::scenes::Scene for #scene_type_name {
+        impl ::skylite_core::prelude::Scene for #scene_type_name {
             type P = #project_type_name;
 
             #decode_fn
 
-            fn _private_update(&mut self, controls: &mut ::skylite_core::ProjectControls<Self::P>) {
-                use ::skylite_core::actors::ActorBase;
+            fn _private_update(&mut self, controls: &mut ::skylite_core::prelude::ProjectControls<Self::P>) {
+                use ::skylite_core::prelude::ActorBase;
 
                 #pre_update
 
-                // We need to take the lists of actors and scenes out of the scene here,
-                // to pass the borrow checks. After each actor and extra is updated, the
-                // lists are restored.
-                let mut actors = ::std::mem::take(&mut self.actors);
-                let mut extras = ::std::mem::take(&mut self.extras);
+                // Depending on the project's update mode, the actors are either updated
+                // in a single pass, or in a separate pass for each phase.
+                for phases in <Self::P as ::skylite_core::prelude::SkyliteProject>::UPDATE_MODE.passes() {
+                    // We need to take the lists of actors and scenes out of the scene here,
+                    // to pass the borrow checks. After each actor and extra is updated, the
+                    // lists are restored.
+                    let mut actors = ::std::mem::take(&mut self.actors);
+                    let mut extras = ::std::mem::take(&mut self.extras);
 
-                actors.iter_mut().for_each(|a| a._private_update(self, controls));
-                self.actors = actors;
+                    for idx in ::skylite_core::scenes::_private::update_order(&actors) {
+                        for &phase in *phases {
+                            actors[idx]._private_update(self, controls, phase);
+                        }
+                    }
+                    self.actors = actors;
 
-                extras = extras.into_iter().filter_map(|mut e| {
+                    let mut removed = ::std::vec![false; extras.len()];
+                    for idx in ::skylite_core::scenes::_private::update_order(&extras) {
                         self.remove_extra = false;
-                        e._private_update(self, controls);
-                        if !self.remove_extra {
-                            Some(e)
-                        } else {
-                            None
+                        for &phase in *phases {
+                            extras[idx]._private_update(self, controls, phase);
                         }
-                    })
-                    .collect();
-
-                // Between taking the extras at the beginning of the update
-                // and putting them back here, any of the update calls may
-                // have added new extras. These have to go at the end of the list.
-                ::std::mem::swap(&mut self.extras, &mut extras);
-                self.extras.append(&mut extras);
+                        removed[idx] = self.remove_extra;
+                    }
+                    let mut removed_iter = removed.into_iter();
+                    extras.retain(|_| !removed_iter.next().unwrap());
+
+                    // Between taking the extras at the beginning of the update
+                    // and putting them back here, any of the update calls may
+                    // have added new extras. These have to go at the end of the list.
+                    ::std::mem::swap(&mut self.extras, &mut extras);
+                    self.extras.append(&mut extras);
+                }
 
                 #post_update
             }
 
-            fn _private_render(&self, ctx: &::skylite_core::DrawContext<Self::P>) {
+            fn _private_render(&self, ctx: &mut ::skylite_core::prelude::DrawContext<Self::P>) {
                 #pre_render
                 ::skylite_core::scenes::_private::render_scene(self, ctx);
                 #post_render
             }
 
-            fn iter_actors(&self, which: ::skylite_core::scenes::IterActors) -> ::skylite_core::scenes::ActorIterator<<Self::P as ::skylite_core::SkyliteProject>::Actors> {
-                use ::skylite_core::scenes::IterActors;
+            fn iter_actors(&self, which: ::skylite_core::prelude::IterActors) -> ::skylite_core::prelude::ActorIterator<<Self::P as ::skylite_core::prelude::SkyliteProject>::Actors> {
+                use ::skylite_core::prelude::IterActors;
                 match which {
-                    IterActors::Named => ::skylite_core::scenes::ActorIterator::_private_new(&self.actors, &[]),
-                    IterActors::Extra => ::skylite_core::scenes::ActorIterator::_private_new(&[], &self.extras),
-                    IterActors::All => ::skylite_core::scenes::ActorIterator::_private_new(&self.actors, &self.extras)
+                    IterActors::Named => ::skylite_core::prelude::ActorIterator::new(&self.actors, &[]),
+                    IterActors::Extra => ::skylite_core::prelude::ActorIterator::new(&[], &self.extras),
+                    IterActors::All => ::skylite_core::prelude::ActorIterator::new(&self.actors, &self.extras)
                 }
             }
 
-            fn iter_actors_mut(&mut self, which: ::skylite_core::scenes::IterActors) -> ::skylite_core::scenes::ActorIteratorMut<<Self::P as ::skylite_core::SkyliteProject>::Actors> {
-                use ::skylite_core::scenes::IterActors;
+            fn iter_actors_mut(&mut self, which: ::skylite_core::prelude::IterActors) -> ::skylite_core::prelude::ActorIteratorMut<<Self::P as ::skylite_core::prelude::SkyliteProject>::Actors> {
+                use ::skylite_core::prelude::IterActors;
                 match which {
-                    IterActors::Named => ::skylite_core::scenes::ActorIteratorMut::_private_new(self.actors.as_mut_slice(), &mut []),
-                    IterActors::Extra => ::skylite_core::scenes::ActorIteratorMut::_private_new(&mut [], self.extras.as_mut_slice()),
-                    IterActors::All => ::skylite_core::scenes::ActorIteratorMut::_private_new(self.actors.as_mut_slice(), self.extras.as_mut_slice())
+                    IterActors::Named => ::skylite_core::prelude::ActorIteratorMut::new(self.actors.as_mut_slice(), &mut []),
+                    IterActors::Extra => ::skylite_core::prelude::ActorIteratorMut::new(&mut [], self.extras.as_mut_slice()),
+                    IterActors::All => ::skylite_core::prelude::ActorIteratorMut::new(self.actors.as_mut_slice(), self.extras.as_mut_slice())
                 }
             }
 
-            fn add_extra(&mut self, extra: <Self::P as ::skylite_core::SkyliteProject>::Actors) {
+            fn add_extra(&mut self, extra: <Self::P as ::skylite_core::prelude::SkyliteProject>::Actors) {
                 self.extras.push(extra);
             }
 
             fn remove_current_extra(&mut self) { self.remove_extra = true; }
+
+            fn remove_extra_by_handle(&mut self, handle: ::skylite_core::prelude::ActorHandle) -> bool {
+                let len = self.extras.len();
+                self.extras.retain(|e| ::skylite_core::prelude::ActorBase::handle(e) != handle);
+                self.extras.len() != len
+            }
+
+            fn actor_names(&self) -> &'static [&'static str] { &[#(#actor_names),*] }
+
+            fn scene_id(&self) -> usize { #scene_id }
         }
     })
 }
@@ -300,7 +354,7 @@ pub(crate) fn generate_scene_definition(scene: &SceneStub, type_id: u32, items:
     let named_actors_type = gen_named_actors_type(scene);
     let properties_type = gen_properties_type(scene, items)?;
     let scene_type = gen_scene_type(scene, type_id, project_name, items)?;
-    let scene_trait_impl = gen_scene_trait_impl(scene, &project_type_name, items)?;
+    let scene_trait_impl = gen_scene_trait_impl(scene, type_id, &project_type_name, items)?;
 
     let imports = items.iter().filter_map(|item| if let Item::Use(import) = item {
         Some(import.to_owned())
@@ -343,6 +397,7 @@ mod tests {
     fn create_test_scene() -> SceneStub {
         SceneStub {
             name: "TestScene".to_owned(),
+            description: None,
             actor_names: vec![
                 "actor1".to_owned(),
                 "actor2".to_owned(),
@@ -393,57 +448,73 @@ mod tests {
         let scene = create_test_scene();
         let items = create_test_items();
 
-        let code = gen_scene_trait_impl(&scene, &quote!(TestProject), &items).unwrap();
+        let code = gen_scene_trait_impl(&scene, 0, &quote!(TestProject), &items).unwrap();
         let expected = quote! {
-            impl ::skylite_core::scenes::Scene for TestScene {
+            impl ::skylite_core::prelude::Scene for TestScene {
                 type P = TestProject;
 
-                fn _private_decode(decoder: &mut dyn ::skylite_compress::Decoder) -> TestScene {
-                    use ::skylite_core::decode::Deserialize;
+                fn _private_decode(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> TestScene {
+                    use ::skylite_core::prelude::Deserialize;
                     let val1 = u8::deserialize(decoder);
                     let val2 = bool::deserialize(decoder);
                     TestScene::new(val1, val2)
                 }
 
-                fn _private_actors(&mut self) -> &mut [<Self::P as ::skylite_core::SkyliteProject>::Actors] { self.actors.as_mut_slice() }
-                fn _private_extras(&mut self) -> &mut Vec<<Self::P as ::skylite_core::SkyliteProject>::Actors> { &mut self.extras }
+                fn _private_actors(&mut self) -> &mut [<Self::P as ::skylite_core::prelude::SkyliteProject>::Actors] { self.actors.as_mut_slice() }
+                fn _private_extras(&mut self) -> &mut Vec<<Self::P as ::skylite_core::prelude::SkyliteProject>::Actors> { &mut self.extras }
 
-                fn _private_update(&mut self, controls: &mut ::skylite_core::ProjectControls<Self::P>) {
-                    use ::skylite_core::actors::ActorBase;
+                fn _private_update(&mut self, controls: &mut ::skylite_core::prelude::ProjectControls<Self::P>) {
+                    use ::skylite_core::prelude::ActorBase;
 
                     super::pre_update(self, controls);
 
-                    let mut actors = ::std::mem::take(&mut self.actors);
-                    let mut extras = ::std::mem::take(&mut self.extras);
+                    for phases in <Self::P as ::skylite_core::prelude::SkyliteProject>::UPDATE_MODE.passes() {
+                        let mut actors = ::std::mem::take(&mut self.actors);
+                        let mut extras = ::std::mem::take(&mut self.extras);
 
-                    actors.iter_mut().for_each(|a| a._private_update(self, controls));
-                    self.actors = actors;
+                        for idx in ::skylite_core::scenes::_private::update_order(&actors) {
+                            for &phase in *phases {
+                                actors[idx]._private_update(self, controls, phase);
+                            }
+                        }
+                        self.actors = actors;
 
-                    extras = extras.into_iter().filter_map(|mut e| {
+                        let mut removed = ::std::vec![false; extras.len()];
+                        for idx in ::skylite_core::scenes::_private::update_order(&extras) {
                             self.remove_extra = false;
-                            e._private_update(self, controls);
-                            if !self.remove_extra {
-                                Some(e)
-                            } else {
-                                None
+                            for &phase in *phases {
+                                extras[idx]._private_update(self, controls, phase);
                             }
-                        })
-                        .collect();
+                            removed[idx] = self.remove_extra;
+                        }
+                        let mut removed_iter = removed.into_iter();
+                        extras.retain(|_| !removed_iter.next().unwrap());
 
-                    ::std::mem::swap(&mut self.extras, &mut extras);
-                    self.extras.append(&mut extras);
+                        ::std::mem::swap(&mut self.extras, &mut extras);
+                        self.extras.append(&mut extras);
+                    }
                 }
 
-                fn _private_render(&self, ctx: & ::skylite_core::DrawContext<Self::P>) {
+                fn _private_render(&self, ctx: &mut ::skylite_core::prelude::DrawContext<Self::P>) {
                     ::skylite_core::scenes::_private::render_scene(self, ctx);
                     super::post_render(self, ctx);
                 }
 
-                fn get_actors(&self) -> &[<Self::P as ::skylite_core::SkyliteProject>::Actors] { &self.actors }
+                fn get_actors(&self) -> &[<Self::P as ::skylite_core::prelude::SkyliteProject>::Actors] { &self.actors }
 
-                fn get_extras(&self) -> &[<Self::P as ::skylite_core::SkyliteProject>::Actors] { &self.extras }
+                fn get_extras(&self) -> &[<Self::P as ::skylite_core::prelude::SkyliteProject>::Actors] { &self.extras }
 
                 fn remove_current_extra(&mut self) { self.remove_extra = true; }
+
+                fn remove_extra_by_handle(&mut self, handle: ::skylite_core::prelude::ActorHandle) -> bool {
+                    let len = self.extras.len();
+                    self.extras.retain(|e| ::skylite_core::prelude::ActorBase::handle(e) != handle);
+                    self.extras.len() != len
+                }
+
+                fn actor_names(&self) -> &'static [&'static str] { &["actor1", "actor2", "actor3"] }
+
+                fn scene_id(&self) -> usize { 0usize }
             }
         };
         assert_eq!(code.to_string(), expected.to_string());
@@ -0,0 +1,186 @@
+use std::{env, fmt::Write, path::PathBuf, str::FromStr};
+
+use crate::{parse::{actors::Actor, project::SkyliteProject, scenes::Scene, values::{Type, TypedValue, Variable}}, SkyliteProcError};
+
+/// Reads the `SKYLITE_SCHEMA` environment variable.
+///
+/// If the variable is set, it is interpreted as a path, relative to the crate's
+/// manifest directory, to which the schema is written.
+fn get_schema_path() -> Option<PathBuf> {
+    match env::var("SKYLITE_SCHEMA") {
+        Ok(s) if !s.is_empty() => {
+            let base_dir = PathBuf::from_str(&env::var("CARGO_MANIFEST_DIR").unwrap()).unwrap();
+            Some(base_dir.join(s))
+        },
+        _ => None
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_list<T>(items: &[T], to_json: impl Fn(&T) -> String) -> String {
+    format!("[{}]", items.iter().map(to_json).collect::<Vec<String>>().join(", "))
+}
+
+/// Describes a type. Primitive types are strings with the type's name, composed types
+/// are objects with a single key for the kind of the type, e.g. `{"vec": "u8"}`.
+fn type_to_json(typename: &Type) -> String {
+    match typename {
+        Type::U8 => json_string("u8"),
+        Type::U16 => json_string("u16"),
+        Type::U32 => json_string("u32"),
+        Type::U64 => json_string("u64"),
+        Type::I8 => json_string("i8"),
+        Type::I16 => json_string("i16"),
+        Type::I32 => json_string("i32"),
+        Type::I64 => json_string("i64"),
+        Type::F32 => json_string("f32"),
+        Type::F64 => json_string("f64"),
+        Type::Bool => json_string("bool"),
+        Type::String => json_string("string"),
+        Type::Tuple(members) => format!("{{\"tuple\": {}}}", json_list(members, type_to_json)),
+        Type::Vec(item) => format!("{{\"vec\": {}}}", type_to_json(item)),
+        Type::Map(key, value) => format!("{{\"map\": [{}, {}]}}", type_to_json(key), type_to_json(value)),
+        Type::Optional(item) => format!("{{\"optional\": {}}}", type_to_json(item)),
+        Type::Value(value_type) => format!("{{\"value\": {{\"name\": {}, \"fields\": {}}}}}", json_string(&value_type.name), json_list(&value_type.fields, variable_to_json))
+    }
+}
+
+/// Converts a value to JSON. Maps become lists of `[key, value]` pairs, since their keys
+/// are not always strings, and absent optional values become `null`.
+fn value_to_json(value: &TypedValue) -> String {
+    match value {
+        TypedValue::U8(v) => v.to_string(),
+        TypedValue::U16(v) => v.to_string(),
+        TypedValue::U32(v) => v.to_string(),
+        TypedValue::U64(v) => v.to_string(),
+        TypedValue::I8(v) => v.to_string(),
+        TypedValue::I16(v) => v.to_string(),
+        TypedValue::I32(v) => v.to_string(),
+        TypedValue::I64(v) => v.to_string(),
+        TypedValue::F32(v) => v.to_string(),
+        TypedValue::F64(v) => v.to_string(),
+        TypedValue::Bool(v) => v.to_string(),
+        TypedValue::String(s) => json_string(s),
+        TypedValue::Tuple(items) | TypedValue::Vec(items) => json_list(items, value_to_json),
+        TypedValue::Map(entries) => json_list(entries, |(k, v)| format!("[{}, {}]", value_to_json(k), value_to_json(v))),
+        TypedValue::Optional(None) => "null".to_owned(),
+        TypedValue::Optional(Some(v)) => value_to_json(v),
+        TypedValue::Value(_, fields) => format!("{{{}}}", fields.iter()
+            .map(|(name, v)| format!("{}: {}", json_string(name), value_to_json(v)))
+            .collect::<Vec<String>>()
+            .join(", "))
+    }
+}
+
+fn optional_string(s: &Option<String>) -> String {
+    s.as_deref().map(json_string).unwrap_or("null".to_owned())
+}
+
+fn variable_to_json(var: &Variable) -> String {
+    format!("{{\"name\": {}, \"type\": {}, \"description\": {}, \"default\": {}}}",
+        json_string(&var.name),
+        type_to_json(&var.typename),
+        optional_string(&var.documentation),
+        var.default.as_ref().map(value_to_json).unwrap_or("null".to_owned()))
+}
+
+fn actor_to_json(actor: &Actor) -> String {
+    let actions = json_list(&actor.actions, |action| format!("{{\"name\": {}, \"description\": {}, \"parameters\": {}}}",
+        json_string(&action.name),
+        optional_string(&action.description),
+        json_list(&action.params, variable_to_json)));
+    format!("{{\"name\": {}, \"description\": {}, \"parameters\": {}, \"actions\": {}}}",
+        json_string(&actor.name),
+        optional_string(&actor.description),
+        json_list(&actor.parameters, variable_to_json),
+        actions)
+}
+
+fn scene_to_json(scene: &Scene) -> String {
+    format!("{{\"name\": {}, \"description\": {}, \"parameters\": {}}}",
+        json_string(&scene.name),
+        optional_string(&scene.description),
+        json_list(&scene.parameters, variable_to_json))
+}
+
+/// Formats the schema of the project's assets as JSON.
+///
+/// The schema lists the actors with their parameters and actions, and the scenes with their
+/// parameters, so that external editors can create valid asset data without parsing the
+/// Scheme files themselves.
+fn format_schema(project: &SkyliteProject) -> String {
+    format!("{{\"project\": {}, \"actors\": {}, \"scenes\": {}}}\n",
+        json_string(&project.name),
+        json_list(&project.actors, actor_to_json),
+        json_list(&project.scenes, scene_to_json))
+}
+
+/// Writes the schema to the path given by `SKYLITE_SCHEMA`, if it is set.
+pub(crate) fn emit_schema(project: &SkyliteProject) -> Result<(), SkyliteProcError> {
+    if let Some(path) = get_schema_path() {
+        std::fs::write(&path, format_schema(project))
+            .map_err(|err| SkyliteProcError::OtherError(format!("Failed to write schema to {}: {}", path.display(), err)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{actors::{Action, ActionInstance, Actor}, values::{Type, TypedValue, ValueType, Variable}};
+
+    use super::{actor_to_json, json_string, type_to_json, value_to_json};
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("say \"hi\"\n"), "\"say \\\"hi\\\"\\n\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_schema_types() {
+        let point = ValueType {
+            name: "point".to_owned(),
+            fields: vec![Variable { name: "x".to_owned(), typename: Type::I16, documentation: None, default: None }]
+        };
+        assert_eq!(type_to_json(&Type::Map(Box::new(Type::String), Box::new(Type::Optional(Box::new(Type::U8))))), "{\"map\": [\"string\", {\"optional\": \"u8\"}]}");
+        assert_eq!(type_to_json(&Type::Vec(Box::new(Type::Value(point)))),
+            "{\"vec\": {\"value\": {\"name\": \"point\", \"fields\": [{\"name\": \"x\", \"type\": \"i16\", \"description\": null, \"default\": null}]}}}");
+
+        let value = TypedValue::Tuple(vec![
+            TypedValue::Map(vec![(TypedValue::U8(1), TypedValue::Bool(true))]),
+            TypedValue::Optional(None),
+            TypedValue::Value("point".to_owned(), vec![("x".to_owned(), TypedValue::I16(-3))])
+        ]);
+        assert_eq!(value_to_json(&value), "[[[1, true]], null, {\"x\": -3}]");
+    }
+
+    #[test]
+    fn test_actor_schema() {
+        let actor = Actor {
+            name: "slime".to_owned(),
+            description: Some("A slime.".to_owned()),
+            parameters: vec![Variable { name: "hp".to_owned(), typename: Type::U8, documentation: Some("Health".to_owned()), default: Some(TypedValue::U8(3)) }],
+            actions: vec![Action { name: "idle".to_owned(), params: vec![], description: None }],
+            initial_action: ActionInstance { name: "idle".to_owned(), args: vec![] }
+        };
+        assert_eq!(actor_to_json(&actor), "{\"name\": \"slime\", \"description\": \"A slime.\", \
+            \"parameters\": [{\"name\": \"hp\", \"type\": \"u8\", \"description\": \"Health\", \"default\": 3}], \
+            \"actions\": [{\"name\": \"idle\", \"description\": null, \"parameters\": []}]}");
+    }
+}
@@ -0,0 +1,155 @@
+use std::{env, fmt::Write, path::PathBuf, str::FromStr};
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use crate::{parse::{actors::Actor, scenes::Scene, util::{change_case, IdentCase}}, SkyliteProcError};
+
+use super::project::project_ident;
+
+enum ReportTarget {
+    Stdout,
+    File(PathBuf)
+}
+
+/// Reads the `SKYLITE_SIZE_REPORT` environment variable.
+///
+/// If the variable is unset or `none`, no report is generated. If it is set to `stdout`,
+/// the report is printed while compiling. Any other value is interpreted as a path,
+/// relative to the crate's manifest directory, to which the report is written.
+fn get_report_target() -> Option<ReportTarget> {
+    match env::var("SKYLITE_SIZE_REPORT") {
+        Ok(s) => match s.as_str() {
+            "" | "none" => None,
+            "stdout" => Some(ReportTarget::Stdout),
+            path => {
+                let base_dir = PathBuf::from_str(&env::var("CARGO_MANIFEST_DIR").unwrap()).unwrap();
+                Some(ReportTarget::File(base_dir.join(path)))
+            }
+        },
+        Err(_) => None
+    }
+}
+
+/// Collects the sizes of the static data generated by `skylite_project!`.
+#[derive(Default)]
+pub(crate) struct SizeReport {
+    assets: Vec<(String, usize)>,
+    strings: Vec<(String, usize)>,
+    static_data: Vec<(String, usize)>
+}
+
+impl SizeReport {
+
+    /// Records the size of a single asset, before compression.
+    pub(crate) fn add_asset(&mut self, name: &str, size: usize) {
+        self.assets.push((name.to_owned(), size));
+    }
+
+    /// Records the number of bytes of the strings used by a single asset.
+    pub(crate) fn add_strings(&mut self, name: &str, size: usize) {
+        self.strings.push((name.to_owned(), size));
+    }
+
+    /// Records the size of an item of static data, as it is stored in the final binary.
+    pub(crate) fn add_static_data(&mut self, name: &str, size: usize) {
+        self.static_data.push((name.to_owned(), size));
+    }
+
+    fn format(&self, project_name: &str) -> String {
+        let mut out = String::new();
+        writeln!(out, "Size report for {}:", project_name).unwrap();
+
+        writeln!(out, "Encoded assets (uncompressed):").unwrap();
+        for (name, size) in &self.assets {
+            writeln!(out, "\t{}: {} bytes", name, size).unwrap();
+        }
+
+        writeln!(out, "Strings by asset:").unwrap();
+        for (name, size) in &self.strings {
+            writeln!(out, "\t{}: {} bytes", name, size).unwrap();
+        }
+
+        writeln!(out, "Static data:").unwrap();
+        for (name, size) in &self.static_data {
+            writeln!(out, "\t{}: {} bytes", name, size).unwrap();
+        }
+        let total: usize = self.static_data.iter().map(|(_, size)| size).sum();
+        writeln!(out, "\tTotal: {} bytes", total).unwrap();
+        out
+    }
+
+    /// Writes the compile-time part of the report to the target given by `SKYLITE_SIZE_REPORT`.
+    pub(crate) fn emit(&self, project_name: &str) -> Result<(), SkyliteProcError> {
+        match get_report_target() {
+            Some(ReportTarget::Stdout) => print!("{}", self.format(project_name)),
+            Some(ReportTarget::File(path)) => std::fs::write(&path, self.format(project_name))
+                .map_err(|err| SkyliteProcError::OtherError(format!("Failed to write size report to {}: {}", path.display(), err)))?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Generates the `size_report` function for the project type.
+    ///
+    /// The sizes of the generated types are only known once the crate using `skylite_project!`
+    /// is compiled, so they cannot be included in the compile-time report. Instead, they are
+    /// reported by this function at runtime, together with the sizes of the static data.
+    pub(crate) fn generate_size_report_fn(&self, project_name: &str, actors: &[Actor], scenes: &[Scene]) -> TokenStream {
+        let project_ident = project_ident(project_name);
+        let static_part = self.format(project_name);
+        let actor_names = actors.iter().map(|a| change_case(&a.name, IdentCase::UpperCamelCase));
+        let actor_idents = actors.iter().map(|a| format_ident!("{}", change_case(&a.name, IdentCase::UpperCamelCase)));
+        let scene_names = scenes.iter().map(|s| change_case(&s.name, IdentCase::UpperCamelCase));
+        let scene_idents = scenes.iter().map(|s| format_ident!("{}", change_case(&s.name, IdentCase::UpperCamelCase)));
+        quote! {
+            impl #project_ident {
+                /// Returns a report of the memory used by the project. This includes the sizes of the
+                /// static data generated from the assets, as well as the sizes of the generated actor
+                /// and scene types.
+                pub fn size_report() -> ::std::string::String {
+                    let mut out = ::std::string::String::from(#static_part);
+                    out.push_str("Actor types:\n");
+                    #(
+                        out.push_str(&::std::format!("\t{}: {} bytes\n", #actor_names, ::std::mem::size_of::<#actor_idents>()));
+                    )*
+                    out.push_str("Scene types:\n");
+                    #(
+                        out.push_str(&::std::format!("\t{}: {} bytes\n", #scene_names, ::std::mem::size_of::<#scene_idents>()));
+                    )*
+                    out.push_str(&::std::format!("Project type: {} bytes\n", ::std::mem::size_of::<#project_ident>()));
+                    out
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SizeReport;
+
+    #[test]
+    fn test_format_size_report() {
+        let mut report = SizeReport::default();
+        report.add_asset("scene-1", 12);
+        report.add_asset("scene-2", 30);
+        report.add_asset("actor-1", 8);
+        report.add_strings("scene-1", 5);
+        report.add_static_data("SCENE_DATA", 35);
+        report.add_static_data("STRINGS", 5);
+
+        let expectation = "Size report for Test:\n\
+            Encoded assets (uncompressed):\n\
+            \tscene-1: 12 bytes\n\
+            \tscene-2: 30 bytes\n\
+            \tactor-1: 8 bytes\n\
+            Strings by asset:\n\
+            \tscene-1: 5 bytes\n\
+            Static data:\n\
+            \tSCENE_DATA: 35 bytes\n\
+            \tSTRINGS: 5 bytes\n\
+            \tTotal: 40 bytes\n";
+        assert_eq!(report.format("Test"), expectation);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeSet;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+use crate::{parse::{project::SkyliteProject, values::{TypedValue, Variable}}, SkyliteProcError};
+
+use super::project::{project_data_ident, project_ident};
+
+/// Table of all strings that are used in the assets of a project.
+///
+/// Each unique string is stored exactly once, and the strings are kept
+/// in sorted order, so that the table is independent of the order in which
+/// assets are parsed. The index of a string within the table is its id.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct StringTable {
+    strings: Vec<String>
+}
+
+fn collect_strings<'a>(value: &'a TypedValue, out: &mut BTreeSet<&'a str>) {
+    match value {
+        TypedValue::String(s) => { out.insert(s); },
+        TypedValue::Tuple(members) | TypedValue::Vec(members) => members.iter().for_each(|m| collect_strings(m, out)),
+        TypedValue::Map(entries) => entries.iter().for_each(|(k, v)| {
+            collect_strings(k, out);
+            collect_strings(v, out);
+        }),
+        TypedValue::Optional(Some(v)) => collect_strings(v, out),
+        TypedValue::Value(_, fields) => fields.iter().for_each(|(_, v)| collect_strings(v, out)),
+        _ => {}
+    }
+}
+
+fn collect_default_strings<'a>(vars: &'a [Variable], out: &mut BTreeSet<&'a str>) {
+    vars.iter()
+        .filter_map(|v| v.default.as_ref())
+        .for_each(|d| collect_strings(d, out));
+}
+
+/// Collects the strings used by each part of the project, together with the name of that part.
+fn strings_by_asset(project: &SkyliteProject) -> Vec<(&str, BTreeSet<&str>)> {
+    let mut out = Vec::new();
+
+    let mut strings = BTreeSet::new();
+    project.initial_scene.args.iter().for_each(|a| collect_strings(a, &mut strings));
+    out.push(("initial-scene", strings));
+
+    let mut strings = BTreeSet::new();
+    collect_default_strings(&project.globals, &mut strings);
+    out.push(("globals", strings));
+
+    for config in &project.configs {
+        let mut strings = BTreeSet::new();
+        collect_default_strings(&config.values, &mut strings);
+        out.push((config.name.as_str(), strings));
+    }
+
+    for actor in &project.actors {
+        let mut strings = BTreeSet::new();
+        collect_default_strings(&actor.parameters, &mut strings);
+        actor.actions.iter().for_each(|action| collect_default_strings(&action.params, &mut strings));
+        actor.initial_action.args.iter().for_each(|a| collect_strings(a, &mut strings));
+        out.push((actor.name.as_str(), strings));
+    }
+
+    for scene in &project.scenes {
+        let mut strings = BTreeSet::new();
+        collect_default_strings(&scene.parameters, &mut strings);
+        scene.actors.iter()
+            .map(|(_, instance)| instance)
+            .chain(scene.extras.iter())
+            .flat_map(|instance| instance.args.iter())
+            .for_each(|a| collect_strings(a, &mut strings));
+        out.push((scene.name.as_str(), strings));
+    }
+
+    out
+}
+
+/// Returns the number of bytes of the unique strings used by each part of the project.
+///
+/// A string which is used by several parts is counted for each of them, even though it is
+/// stored only once in the string table. Parts which do not use any strings are left out.
+pub(crate) fn string_contributions(project: &SkyliteProject) -> Vec<(String, usize)> {
+    strings_by_asset(project).into_iter()
+        .filter(|(_, strings)| !strings.is_empty())
+        .map(|(name, strings)| (name.to_owned(), strings.iter().map(|s| s.len()).sum()))
+        .collect()
+}
+
+impl StringTable {
+
+    pub(crate) fn from_project(project: &SkyliteProject) -> Result<StringTable, SkyliteProcError> {
+        let strings = strings_by_asset(project).into_iter()
+            .flat_map(|(_, strings)| strings)
+            .collect::<BTreeSet<&str>>();
+
+        if strings.len() > u16::MAX as usize + 1 {
+            return Err(SkyliteProcError::DataError(format!("Too many unique strings in project: {}, maximum is {}", strings.len(), u16::MAX as usize + 1)));
+        }
+
+        Ok(StringTable {
+            strings: strings.into_iter().map(str::to_owned).collect()
+        })
+    }
+
+    /// Returns the id of the string `s`, or `None` if the string is not part of the table.
+    pub(crate) fn get_id(&self, s: &str) -> Option<usize> {
+        self.strings.binary_search_by(|probe| probe.as_str().cmp(s)).ok()
+    }
+
+    /// Returns the total number of bytes of all strings in the table.
+    pub(crate) fn data_len(&self) -> usize {
+        self.strings.iter().map(String::len).sum()
+    }
+}
+
+/// Generates the static string table for a project, as well as the `_private_str_id`
+/// function, which is used by generated code to turn string literals into `StrId`s.
+pub(crate) fn generate_string_table(project_name: &str, table: &StringTable) -> TokenStream {
+    let project_ident = project_ident(project_name);
+    let strings_ident = project_data_ident(project_name, "STRINGS");
+    let strings = table.strings.iter().map(|s| Literal::string(s));
+    quote! {
+        const #strings_ident: &[&str] = &[#(#strings),*];
+
+        impl #project_ident {
+            pub const fn _private_str_id(s: &str) -> ::skylite_core::prelude::StrId {
+                let mut i = 0;
+                while i < #strings_ident.len() {
+                    if ::skylite_core::strings::_private::str_eq(#strings_ident[i], s) {
+                        return ::skylite_core::strings::StrId::_private_new(i as u16);
+                    }
+                    i += 1;
+                }
+                panic!("String is not part of the project's string table.");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{actors::{Action, ActionInstance, Actor}, project::{CountWidth, SkyliteProject, UpdateMode}, scenes::{ActorInstance, Scene, SceneInstance}, values::{Type, TypedValue, Variable}};
+
+    use super::{string_contributions, StringTable};
+
+    #[test]
+    fn test_string_table() {
+        let actor = Actor {
+            name: "test-actor".to_owned(),
+            description: None,
+            parameters: vec![
+                Variable { name: "name".to_owned(), typename: Type::String, documentation: None, default: Some(TypedValue::String("default".to_owned())) }
+            ],
+            actions: vec![
+                Action { name: "idle".to_owned(), params: vec![], description: None }
+            ],
+            initial_action: ActionInstance { name: "idle".to_owned(), args: vec![] }
+        };
+        let scene = Scene {
+            name: "test-scene".to_owned(),
+            description: None,
+            actors: vec![
+                ("actor-1".to_owned(), ActorInstance { actor_name: "test-actor".to_owned(), args: vec![TypedValue::String("zeta".to_owned())] })
+            ],
+            extras: vec![
+                ActorInstance { actor_name: "test-actor".to_owned(), args: vec![TypedValue::String("alpha".to_owned())] },
+                ActorInstance { actor_name: "test-actor".to_owned(), args: vec![TypedValue::String("zeta".to_owned())] }
+            ],
+            parameters: vec![],
+            compression: None
+        };
+        let project = SkyliteProject {
+            name: "test".to_owned(),
+            actors: vec![actor],
+            scenes: vec![scene],
+            save_data: vec![],
+            globals: vec![],
+            initial_scene: SceneInstance {
+                name: "test-scene".to_owned(),
+                args: vec![TypedValue::Vec(vec![TypedValue::String("beta".to_owned())])]
+            },
+            tile_types: vec![],
+            graphics: vec![],
+            configs: vec![],
+            custom: vec![],
+            update_mode: UpdateMode::Nested,
+            save_slots: None,
+            count_width: CountWidth::U32
+        };
+
+        let table = StringTable::from_project(&project).unwrap();
+        assert_eq!(table.strings, vec!["alpha", "beta", "default", "zeta"]);
+        assert_eq!(table.get_id("default"), Some(2));
+        assert_eq!(table.get_id("zeta"), Some(3));
+        assert_eq!(table.get_id("gamma"), None);
+
+        assert_eq!(string_contributions(&project), vec![
+            ("initial-scene".to_owned(), 4),
+            ("test-actor".to_owned(), 7),
+            ("test-scene".to_owned(), 9)
+        ]);
+    }
+}
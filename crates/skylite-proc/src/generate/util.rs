@@ -1,9 +1,11 @@
-use proc_macro2::{Literal, TokenStream};
+use proc_macro2::{Ident, Literal, TokenStream};
 use quote::{format_ident, quote, ToTokens};
-use syn::{parse_str, Item, ItemFn, Macro, Meta, Path};
+use syn::{parse::{ParseStream, Parser}, parse_str, punctuated::Punctuated, Field, Item, ItemFn, LitStr, Macro, Meta, Path, Token};
 
 use crate::{parse::{util::{change_case, IdentCase}, values::{Type, TypedValue, Variable}}, SkyliteProcError};
 
+use super::values::value_type_name;
+
 /// Returns the function item annotated with the given `attribute` from the list of `items`.
 ///
 /// The attribute must be of the form `#[attribute-name]`.
@@ -25,6 +27,21 @@ pub(crate) fn get_annotated_function<'a>(items: &'a [Item], attribute: &str) ->
         })
 }
 
+/// Returns the names of all `macro_rules!` definitions annotated with the given `attribute` from the list of `items`.
+///
+/// The attribute must be of the form `#[attribute-name]`.
+pub(crate) fn get_annotated_macros<'a>(items: &'a [Item], attribute: &str) -> Vec<&'a Ident> {
+    let attribute_path = syn::parse_str::<Path>(attribute).unwrap();
+    items.iter()
+        .filter_map(|item| if let Item::Macro(m) = item {
+            m.ident.as_ref().filter(|_| m.attrs.iter()
+                .any(|attr| if let Meta::Path(ref p) = attr.meta { *p == attribute_path } else { false }))
+        } else {
+            None
+        })
+        .collect()
+}
+
 /// Returns a function macro invocation with the given `name` from the list of `items`.
 ///
 /// If no invocation with the given `name` is found, `Ok(None)` is returned. If multiple
@@ -49,6 +66,99 @@ pub(crate) fn get_macro_item<'tok>(name: &str, items: &'tok [Item]) -> Result<Op
     }
 }
 
+/// Returns the function item annotated with `#[attribute(arg)]` from the list of `items`.
+///
+/// Returns an `Err` if more than one function has the annotation.
+pub(crate) fn get_annotated_function_with_arg<'a>(items: &'a [Item], attribute: &str, arg: &str) -> Result<Option<&'a ItemFn>, SkyliteProcError> {
+    let meta = parse_str::<Meta>(&format!("{}(\"{}\")", attribute, arg)).unwrap();
+    let mut res = items.iter()
+        .filter_map(|item| if let Item::Fn(fun) = item {
+            Some(fun)
+        } else {
+            None
+        })
+        .filter(|fun| fun.attrs.iter().any(|attr| attr.meta == meta));
+
+    let out = res.next();
+    match res.next() {
+        Some(_) => Err(SkyliteProcError::DataError(format!("Multiple functions annotated with #[{}(\"{}\")]", attribute, arg))),
+        None => Ok(out)
+    }
+}
+
+/// Checks that every function annotated with `#[skylite_proc::derive_property("name")]` derives one of `properties`.
+///
+/// A derive function for a name that is not a property is never called, e.g. because the name is misspelled,
+/// so it is reported as an error instead of being ignored. `properties` is `None` if the properties are
+/// initialized by a `create_properties` function, in which case no derive functions are used at all.
+pub(crate) fn check_derive_targets(items: &[Item], properties: Option<&[String]>) -> Result<(), SkyliteProcError> {
+    let derive_path = parse_str::<Path>("skylite_proc::derive_property").unwrap();
+    let derive_attrs = items.iter()
+        .filter_map(|item| if let Item::Fn(fun) = item { Some(fun) } else { None })
+        .flat_map(|fun| fun.attrs.iter().map(move |attr| (fun, attr)))
+        .filter(|(_, attr)| *attr.path() == derive_path);
+
+    for (fun, attr) in derive_attrs {
+        let target = attr.parse_args::<LitStr>()
+            .map_err(|err| SkyliteProcError::SyntaxError(format!("Invalid #[skylite_proc::derive_property] on function `{}`: {}", fun.sig.ident, err)))?
+            .value();
+        match properties {
+            None => return Err(SkyliteProcError::DataError(format!("Function `{}` derives property `{}`, but derive functions are not used when there is a `create_properties` function.", fun.sig.ident, target))),
+            Some(properties) if !properties.contains(&target) => return Err(SkyliteProcError::DataError(format!("Function `{}` derives property `{}`, which does not exist. Available properties: {}", fun.sig.ident, target, properties.join(", ")))),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Generates the initialization of a properties type without a `create_properties` function.
+///
+/// Each property is either initialized from the parameter with the same name, or from the
+/// result of the function annotated with `#[skylite_proc::derive_property("property-name")]`.
+/// Derive functions take the same arguments as `create_properties` would. Since the parameters
+/// may be used by multiple derive functions, they are cloned for each call. The derive functions
+/// are called before the struct is built, because that moves the parameters into their properties.
+pub(crate) fn gen_properties_init(properties_type_name: &Ident, properties: &TokenStream, params: &[Variable], items: &[Item]) -> Result<TokenStream, SkyliteProcError> {
+    let fields = (|input: ParseStream| Punctuated::<Field, Token![,]>::parse_terminated_with(input, Field::parse_named))
+        .parse2(properties.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse properties: {}", err)))?;
+
+    let field_names: Vec<String> = fields.iter().map(|field| field.ident.as_ref().unwrap().to_string()).collect();
+    check_derive_targets(items, Some(&field_names))?;
+
+    let param_names: Vec<Ident> = params.iter()
+        .map(|p| format_ident!("{}", change_case(&p.name, IdentCase::LowerSnakeCase)))
+        .collect();
+
+    let mut derived = Vec::new();
+    let initializers = fields.iter()
+        .map(|field| {
+            let field_name = field.ident.as_ref().unwrap();
+            let field_name_str = field_name.to_string();
+            if let Some(fun) = get_annotated_function_with_arg(items, "skylite_proc::derive_property", &field_name_str)? {
+                let ident = &fun.sig.ident;
+                let var = format_ident!("_private_derived_{}", field_name);
+                derived.push(quote!(let #var = super::#ident(#(#param_names.clone()),*);));
+                Ok(quote!(#field_name: #var))
+            } else if param_names.iter().any(|name| *name == field_name_str) {
+                Ok(quote!(#field_name))
+            } else {
+                Err(SkyliteProcError::DataError(format!("Property `{}` does not match any parameter and has no #[skylite_proc::derive_property(\"{}\")] function. Either add one, or initialize all properties with a `create_properties` function.", field_name_str, field_name_str)))
+            }
+        })
+        .collect::<Result<Vec<TokenStream>, SkyliteProcError>>()?;
+
+    if derived.is_empty() {
+        return Ok(quote!(#properties_type_name { #(#initializers),* }));
+    }
+    Ok(quote! {
+        {
+            #(#derived)*
+            #properties_type_name { #(#initializers),* }
+        }
+    })
+}
+
 /// Converts a `Type` to the corresponding Rust type.
 pub(crate) fn skylite_type_to_rust(t: &Type) -> TokenStream {
     match t {
@@ -63,7 +173,7 @@ pub(crate) fn skylite_type_to_rust(t: &Type) -> TokenStream {
         Type::F32 => quote!(f32),
         Type::F64 => quote!(f64),
         Type::Bool => quote!(bool),
-        Type::String => quote!(String),
+        Type::String => quote!(::skylite_core::prelude::StrId),
         Type::Tuple(member_types) => {
             let member_types_tokens = member_types.iter().map(skylite_type_to_rust);
             quote!((#(#member_types_tokens),*))
@@ -71,6 +181,19 @@ pub(crate) fn skylite_type_to_rust(t: &Type) -> TokenStream {
         Type::Vec(item_type) => {
             let item_type_tokens = skylite_type_to_rust(&item_type);
             quote!(Vec<#item_type_tokens>)
+        },
+        Type::Map(key_type, value_type) => {
+            let key_type_tokens = skylite_type_to_rust(&key_type);
+            let value_type_tokens = skylite_type_to_rust(&value_type);
+            quote!(::skylite_core::prelude::SortedMap<#key_type_tokens, #value_type_tokens>)
+        },
+        Type::Optional(item_type) => {
+            let item_type_tokens = skylite_type_to_rust(&item_type);
+            quote!(::std::option::Option<#item_type_tokens>)
+        },
+        Type::Value(value_type) => {
+            let type_name = value_type_name(&value_type.name);
+            quote!(crate::#type_name)
         }
     }
 }
@@ -84,7 +207,41 @@ pub(crate) fn generate_param_list(params: &[Variable]) -> TokenStream {
     }
 }
 
-pub(crate) fn typed_value_to_rust(val: &TypedValue) -> TokenStream {
+/// Generates a `#[doc]` attribute from an optional documentation string.
+pub(crate) fn get_documentation(doc: &Option<String>) -> TokenStream {
+    match &doc {
+        Some(v) => {
+            let content = Literal::string(&v);
+            quote!(#[doc = #content])
+        },
+        None => TokenStream::new(),
+    }
+}
+
+/// Generates the documentation for the `new` function of an actor or scene,
+/// which lists the parameters together with their documentation strings.
+pub(crate) fn gen_constructor_docs(type_name: &Ident, params: &[Variable]) -> TokenStream {
+    let mut lines = vec![format!("Creates a new `{}`.", type_name)];
+    if !params.is_empty() {
+        lines.push(String::new());
+        lines.push("# Parameters".to_owned());
+        lines.push(String::new());
+        lines.extend(params.iter().map(|p| {
+            let name = change_case(&p.name, IdentCase::LowerSnakeCase);
+            match &p.documentation {
+                Some(doc) => format!("- `{}`: {}", name, doc),
+                None => format!("- `{}`", name)
+            }
+        }));
+    }
+    quote!(#(#[doc = #lines])*)
+}
+
+/// Converts a `TypedValue` to a Rust expression.
+///
+/// Strings are converted to a `StrId` into the string table of the project
+/// given by `project_type_name`. The lookup happens at compile time.
+pub(crate) fn typed_value_to_rust(val: &TypedValue, project_type_name: &TokenStream) -> TokenStream {
     match val {
         TypedValue::U8(v) => Literal::u8_suffixed(*v).into_token_stream(),
         TypedValue::U16(v) => Literal::u16_suffixed(*v).into_token_stream(),
@@ -99,15 +256,87 @@ pub(crate) fn typed_value_to_rust(val: &TypedValue) -> TokenStream {
         TypedValue::Bool(v) => if *v { quote!(true) } else { quote!(false) }
         TypedValue::String(v) => {
             let lit = Literal::string(v);
-            quote!(String::from(#lit))
+            quote! {
+                {
+                    const ID: ::skylite_core::prelude::StrId = #project_type_name::_private_str_id(#lit);
+                    ID
+                }
+            }
         },
         TypedValue::Tuple(vec) => {
-            let members = vec.iter().map(typed_value_to_rust);
+            let members = vec.iter().map(|v| typed_value_to_rust(v, project_type_name));
             quote!((#(#members),*))
         },
         TypedValue::Vec(vec) => {
-            let members = vec.iter().map(typed_value_to_rust);
+            let members = vec.iter().map(|v| typed_value_to_rust(v, project_type_name));
             quote!(vec![#(#members),*])
         },
+        TypedValue::Map(entries) => {
+            let keys = entries.iter().map(|(k, _)| typed_value_to_rust(k, project_type_name));
+            let values = entries.iter().map(|(_, v)| typed_value_to_rust(v, project_type_name));
+            quote!(::skylite_core::prelude::SortedMap::_private_from_sorted(vec![#((#keys, #values)),*]))
+        },
+        TypedValue::Optional(None) => quote!(::std::option::Option::None),
+        TypedValue::Optional(Some(v)) => {
+            let value = typed_value_to_rust(v, project_type_name);
+            quote!(::std::option::Option::Some(#value))
+        },
+        TypedValue::Value(name, fields) => {
+            let type_name = value_type_name(name);
+            let field_names = fields.iter().map(|(f, _)| format_ident!("{}", change_case(f, IdentCase::LowerSnakeCase)));
+            let values = fields.iter().map(|(_, v)| typed_value_to_rust(v, project_type_name));
+            quote!(crate::#type_name { #(#field_names: #values),* })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::{format_ident, quote};
+    use syn::{parse_quote, Item};
+
+    use crate::{parse::values::{Type, Variable}, SkyliteProcError};
+
+    use super::{check_derive_targets, gen_properties_init};
+
+    #[test]
+    fn test_gen_properties_init() {
+        let params = vec![
+            Variable { name: "x".to_owned(), typename: Type::I16, documentation: None, default: None }
+        ];
+        let properties = quote!(x: i16, speed: u8);
+        let items: Vec<Item> = vec![parse_quote! {
+            #[skylite_proc::derive_property("speed")]
+            fn speed(x: i16) -> u8 { 1 }
+        }];
+        let init = gen_properties_init(&format_ident!("TestProperties"), &properties, &params, &items).unwrap();
+        let expectation = quote! {
+            {
+                let _private_derived_speed = super::speed(x.clone());
+                TestProperties { x, speed: _private_derived_speed }
+            }
+        };
+        assert_eq!(init.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_unknown_derive_target() {
+        let params = vec![
+            Variable { name: "speed".to_owned(), typename: Type::U8, documentation: None, default: None }
+        ];
+        let properties = quote!(speed: u8);
+        // The property is initialized from the parameter, so the misspelled derive function would never be called.
+        let items: Vec<Item> = vec![parse_quote! {
+            #[skylite_proc::derive_property("sped")]
+            fn speed() -> u8 { 1 }
+        }];
+        assert!(matches!(
+            gen_properties_init(&format_ident!("TestProperties"), &properties, &params, &items),
+            Err(SkyliteProcError::DataError(_))
+        ));
+
+        // Derive functions are not used together with `create_properties`.
+        assert!(matches!(check_derive_targets(&items, None), Err(SkyliteProcError::DataError(_))));
+        assert!(check_derive_targets(&[], None).is_ok());
     }
 }
@@ -0,0 +1,77 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::Item;
+
+use crate::parse::{util::{change_case, IdentCase}, values::ValueType};
+
+use super::util::{get_documentation, skylite_type_to_rust};
+
+/// Returns the name of the Rust struct for a value type.
+pub(crate) fn value_type_name(name: &str) -> Ident { format_ident!("{}", change_case(name, IdentCase::UpperCamelCase)) }
+
+/// Generates the struct for a value type, together with the implementations of
+/// `Serialize` and `Deserialize`. The fields are encoded in the order of their declaration,
+/// which matches the order in which the asset data is written by `skylite_project!`.
+///
+/// All other items from the body of `skylite_value!` are copied after the struct.
+pub(crate) fn generate_value_type(value_type: &ValueType, items: &[Item]) -> TokenStream {
+    let type_name = value_type_name(&value_type.name);
+    let field_docs = value_type.fields.iter().map(|f| get_documentation(&f.documentation));
+    let field_names = value_type.fields.iter().map(|f| format_ident!("{}", change_case(&f.name, IdentCase::LowerSnakeCase)));
+    let field_types = value_type.fields.iter().map(|f| skylite_type_to_rust(&f.typename));
+    let other_items = items.iter().filter(|item| !matches!(item, Item::Macro(_)));
+
+    quote! {
+        #[derive(Clone, Debug, PartialEq, ::skylite_proc::SkyliteSerialize, ::skylite_proc::SkyliteDeserialize)]
+        pub struct #type_name {
+            #(
+                #field_docs
+                pub #field_names: #field_types
+            ),*
+        }
+
+        #(#other_items)*
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{parse_quote, File};
+
+    use crate::parse::values::{Type, ValueType, Variable};
+
+    use super::generate_value_type;
+
+    #[test]
+    fn test_generate_value_type() {
+        let value_type = ValueType {
+            name: "spawn-point".to_owned(),
+            fields: vec![
+                Variable { name: "pos".to_owned(), typename: Type::Tuple(vec![Type::I16, Type::I16]), documentation: Some("Position in the scene".to_owned()), default: None },
+                Variable { name: "label".to_owned(), typename: Type::String, documentation: None, default: None }
+            ]
+        };
+        let body: File = parse_quote! {
+            skylite_proc::asset_file!("./project.scm", "spawn-point");
+
+            impl SpawnPoint {
+                pub fn x(&self) -> i16 { self.pos.0 }
+            }
+        };
+        let actual = generate_value_type(&value_type, &body.items);
+        let expectation = quote! {
+            #[derive(Clone, Debug, PartialEq, ::skylite_proc::SkyliteSerialize, ::skylite_proc::SkyliteDeserialize)]
+            pub struct SpawnPoint {
+                #[doc = "Position in the scene"]
+                pub pos: (i16, i16),
+                pub label: ::skylite_core::prelude::StrId
+            }
+
+            impl SpawnPoint {
+                pub fn x(&self) -> i16 { self.pos.0 }
+            }
+        };
+        assert_eq!(actual.to_string(), expectation.to_string());
+    }
+}
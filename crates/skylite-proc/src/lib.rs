@@ -1,27 +1,37 @@
 use std::{path::PathBuf, str::FromStr};
 
 use generate::actors::generate_actor_definition;
+use generate::embedded::generate_embedded_asset;
+use generate::encode::DEFAULT_COMPRESSION;
+use generate::project::{generate_project, generate_target_check};
 use generate::scenes::generate_scene_definition;
+use generate::values::generate_value_type;
 use generate::util::get_macro_item;
 use parse::actors::Actor;
 use parse::scenes::SceneStub;
 use parse::util::{change_case, IdentCase};
 use quote::{format_ident, quote};
-use parse::{guile::SCM, project::SkyliteProjectStub};
-use parse::scheme_util::form_to_string;
+use parse::project::SkyliteProjectStub;
 use proc_macro2::{TokenStream, TokenTree};
 use parse::project::SkyliteProject;
 use syn::{parse::Parser, parse2, punctuated::Punctuated, Item, Token, File, LitStr};
 
-mod parse;
+use skylite_model as parse;
+use skylite_model::SkyliteModelError;
+
 mod generate;
 mod ecs;
+mod serialize;
+#[cfg(feature = "snapshot")]
+mod snapshot;
 
 use ecs::{derive_component_impl, system_impl};
+use serialize::{derive_deserialize_impl, derive_serialize_impl};
 
 #[derive(Debug, Clone)]
-enum SkyliteProcError {
-    GuileException(SCM),
+pub(crate) enum SkyliteProcError {
+    /// An error from reading the project definition or one of the asset files.
+    ModelError(SkyliteModelError),
     DataError(String),
     SyntaxError(String),
     OtherError(String)
@@ -30,7 +40,7 @@ enum SkyliteProcError {
 impl std::fmt::Display for SkyliteProcError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::GuileException(scm) => write!(f, "Scheme Exception: {}", form_to_string(*scm)),
+            Self::ModelError(err) => err.fmt(f),
             Self::DataError(str) => write!(f, "Data Error: {}", str),
             Self::SyntaxError(str) => write!(f, "Syntax Error: {}", str),
             Self::OtherError(str) => write!(f, "Error: {}", str)
@@ -38,6 +48,12 @@ impl std::fmt::Display for SkyliteProcError {
     }
 }
 
+impl From<SkyliteModelError> for SkyliteProcError {
+    fn from(err: SkyliteModelError) -> SkyliteProcError {
+        SkyliteProcError::ModelError(err)
+    }
+}
+
 impl Into<TokenStream> for SkyliteProcError {
     fn into(self) -> TokenStream {
         let msg = self.to_string();
@@ -75,7 +91,7 @@ fn get_crate_root_check() -> TokenStream {
             let mut i = 0;
             while i < max {
                 if i >= expected.len() || i >= actual.len() || expected[i] != actual[i] {
-                    panic!("skylite_project! can only be called at the crate root.");
+                    panic!("skylite_project! can only be called at the crate root. Use skylite_proc::allow_non_root! to disable this check.");
                 }
                 i += 1;
             }
@@ -83,28 +99,44 @@ fn get_crate_root_check() -> TokenStream {
     }
 }
 
-fn skylite_project_impl_fallible(body_raw: TokenStream) -> Result<TokenStream, SkyliteProcError> {
-    let items = parse2::<File>(body_raw)
-        .map_err(|err| SkyliteProcError::SyntaxError(err.to_string()))?
-        .items;
-
-    let project_file_mac = get_macro_item("skylite_proc::project_file", &items)?
+/// Loads the project selected by the `project_file!` and `target_name!` items in the body of
+/// `skylite_project!`, and generates its items. Returns the project and the generated items.
+fn generate_project_items(items: &[Item]) -> Result<(SkyliteProject, Vec<Item>), SkyliteProcError> {
+    let project_file_mac = get_macro_item("skylite_proc::project_file", items)?
         .ok_or(SkyliteProcError::DataError(format!("Missing required macro skylite_proc::project_file!")))?;
     let path = parse_project_file(project_file_mac)?;
 
-    let target_type_mac = get_macro_item("skylite_proc::target_type", &items)?
+    let target_type_mac = get_macro_item("skylite_proc::target_type", items)?
         .ok_or(SkyliteProcError::DataError(format!("Missing required macro skylite_proc::target_type!")))?;
     // Verify that the content of target_type is actually a valid path.
     parse2::<syn::Path>(target_type_mac.clone())
         .map_err(|err| SkyliteProcError::SyntaxError(err.to_string()))?;
 
-    let project_stub = SkyliteProjectStub::from_file(&path)?;
+    let target = match get_macro_item("skylite_proc::target_name", items)? {
+        Some(tokens) => Some(parse2::<LitStr>(tokens.clone())
+            .map_err(|err| SkyliteProcError::SyntaxError(format!("Illegal arguments to target_name!: {}", err)))?
+            .value()),
+        None => None
+    };
+
+    let project_stub = SkyliteProjectStub::from_file(&path, target.as_deref())?;
     let project = SkyliteProject::from_stub(project_stub)?;
+    let project_items = generate_project(&project, target.as_deref(), target_type_mac, items)?;
+    Ok((project, project_items))
+}
+
+fn skylite_project_impl_fallible(body_raw: TokenStream) -> Result<TokenStream, SkyliteProcError> {
+    let items = parse2::<File>(body_raw)
+        .map_err(|err| SkyliteProcError::SyntaxError(err.to_string()))?
+        .items;
 
+    let (project, project_items) = generate_project_items(&items)?;
     let module_name = format_ident!("{}", change_case(&project.name, IdentCase::LowerSnakeCase));
-    let project_items = project.generate(&target_type_mac, &items)?;
 
-    let crate_root_check = get_crate_root_check();
+    let crate_root_check = match get_macro_item("skylite_proc::allow_non_root", &items)? {
+        Some(_) => TokenStream::new(),
+        None => get_crate_root_check()
+    };
 
     let out = quote! {
         #crate_root_check
@@ -130,21 +162,30 @@ fn skylite_project_impl_fallible(body_raw: TokenStream) -> Result<TokenStream, S
     Ok(out)
 }
 
-fn extract_asset_file(definition_file: &TokenStream) -> Result<(SkyliteProjectStub, String), SkyliteProcError> {
-    let args = Parser::parse2(Punctuated::<LitStr, Token![,]>::parse_separated_nonempty, definition_file.clone())
-        .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse definition_file! macro: {}. Expected (\"project-path\", \"asset-name\")", err.to_string())))?;
+/// Parses the arguments `("project-path", "name")` or `("project-path", "name", "target")`
+/// of `asset_file!` and `embedded_asset!`.
+fn parse_asset_args(tokens: &TokenStream, macro_name: &str, second_arg: &str) -> Result<(PathBuf, String, Option<String>), SkyliteProcError> {
+    let args = Parser::parse2(Punctuated::<LitStr, Token![,]>::parse_separated_nonempty, tokens.clone())
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse {}! macro: {}. Expected (\"project-path\", \"{}\")", macro_name, err.to_string(), second_arg)))?;
 
-    if args.len() != 2 {
-        return Err(SkyliteProcError::SyntaxError(format!("Wrong number of arguments to definition_file!, expected (\"project-path\", \"asset-name\")")));
+    if args.len() != 2 && args.len() != 3 {
+        return Err(SkyliteProcError::SyntaxError(format!("Wrong number of arguments to {}!, expected (\"project-path\", \"{}\") or (\"project-path\", \"{}\", \"target\")", macro_name, second_arg, second_arg)));
     }
 
     let relative_path = PathBuf::try_from(args[0].value())
         .map_err(|_| SkyliteProcError::DataError(format!("Not a valid project path: {}", args[0].value())))?;
 
     let base_dir = PathBuf::from_str(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).unwrap();
-    let stub = SkyliteProjectStub::from_file(&base_dir.join(relative_path))?;
+    Ok((base_dir.join(relative_path), args[1].value(), args.iter().nth(2).map(LitStr::value)))
+}
 
-    return Ok((stub, args[1].value()));
+/// Loads the project stub for the arguments of `asset_file!`. Returns the stub, the name of the asset,
+/// and the check that the target of `asset_file!` matches the one of the project.
+fn extract_asset_file(definition_file: &TokenStream) -> Result<(SkyliteProjectStub, String, TokenStream), SkyliteProcError> {
+    let (project_path, name, target) = parse_asset_args(definition_file, "asset_file", "asset-name")?;
+    let stub = SkyliteProjectStub::from_file(&project_path, target.as_deref())?;
+    let target_check = generate_target_check(&stub.name, "asset_file", target.as_deref());
+    Ok((stub, name, target_check))
 }
 
 #[cfg(debug_assertions)]
@@ -176,12 +217,13 @@ fn actor_definition_fallible(body_raw: TokenStream) -> Result<TokenStream, Skyli
 
     let args = get_macro_item("skylite_proc::asset_file", &items)?
         .ok_or(SkyliteProcError::DataError(format!("Missing required macro asset_file!")))?;
-    let (project_stub, name) = extract_asset_file(args)?;
+    let (project_stub, name, target_check) = extract_asset_file(args)?;
 
     let (id, path) = project_stub.assets.actors.find_asset(&name)?;
-    let actor = Actor::from_file(&path)?;
+    let actor = Actor::from_file(&path, &project_stub.assets.actors, &project_stub.value_types, &project_stub.prelude)?;
 
-    let out = generate_actor_definition(&actor, id, &project_stub.name, &items, &body_raw)?;
+    let definition = generate_actor_definition(&actor, id, &project_stub.name, &items, &body_raw)?;
+    let out = quote!(#target_check #definition);
 
     #[cfg(debug_assertions)]
     process_debug_output(&out, &items)?;
@@ -196,12 +238,13 @@ fn scene_definition_fallible(body_raw: TokenStream) -> Result<TokenStream, Skyli
 
     let mac = get_macro_item("skylite_proc::asset_file", &items)?
         .ok_or(SkyliteProcError::DataError(format!("Missing required macro asset_file!")))?;
-    let (project_stub, name) = extract_asset_file(mac)?;
+    let (project_stub, name, target_check) = extract_asset_file(mac)?;
 
     let (id, path) = project_stub.assets.scenes.find_asset(&name)?;
-    let scene = SceneStub::from_file(&path)?;
+    let scene = SceneStub::from_file(&path, &project_stub.assets.scenes.asset_name(&path), &project_stub.value_types, &project_stub.prelude)?;
 
-    let out = generate_scene_definition(&scene, id as u32, &items, &project_stub.name, &body_raw)?;
+    let definition = generate_scene_definition(&scene, id as u32, &items, &project_stub.name, &body_raw)?;
+    let out = quote!(#target_check #definition);
 
     #[cfg(debug_assertions)]
     process_debug_output(&out, &items)?;
@@ -209,7 +252,51 @@ fn scene_definition_fallible(body_raw: TokenStream) -> Result<TokenStream, Skyli
     Ok(out)
 }
 
+fn value_definition_fallible(body_raw: TokenStream) -> Result<TokenStream, SkyliteProcError> {
+    let items = parse2::<File>(body_raw)
+        .map_err(|err| SkyliteProcError::SyntaxError(err.to_string()))?
+        .items;
+
+    let mac = get_macro_item("skylite_proc::asset_file", &items)?
+        .ok_or(SkyliteProcError::DataError(format!("Missing required macro asset_file!")))?;
+    let (project_stub, name, target_check) = extract_asset_file(mac)?;
+
+    let value_type = project_stub.value_types.iter()
+        .find(|vt| vt.name == name)
+        .ok_or(SkyliteProcError::DataError(format!("Value type {} is not declared in the project definition", name)))?;
+
+    let definition = generate_value_type(value_type, &items);
+    let out = quote!(#target_check #definition);
+
+    #[cfg(debug_assertions)]
+    process_debug_output(&out, &items)?;
 
+    Ok(out)
+}
+
+fn embedded_asset_fallible(args: TokenStream) -> Result<TokenStream, SkyliteProcError> {
+    let (project_path, file, target) = parse_asset_args(&args, "embedded_asset", "file-path")?;
+    let project_stub = SkyliteProjectStub::from_file(&project_path, target.as_deref())?;
+
+    let project_dir = project_path.canonicalize()
+        .map_err(|e| SkyliteProcError::OtherError(format!("Error resolving project path: {}", e)))?
+        .parent().unwrap()
+        .to_owned();
+    let path = project_dir.join(&file).canonicalize()
+        .map_err(|e| SkyliteProcError::OtherError(format!("Error resolving embedded asset {}: {}", file, e)))?;
+    let data = std::fs::read(&path)
+        .map_err(|e| SkyliteProcError::OtherError(format!("Error reading embedded asset {}: {}", path.display(), e)))?;
+
+    let compression = project_stub.compression.as_deref().unwrap_or(DEFAULT_COMPRESSION);
+    let asset = generate_embedded_asset(&path, &data, compression)?;
+    let target_check = generate_target_check(&project_stub.name, "embedded_asset", target.as_deref());
+    Ok(quote! {
+        {
+            #target_check
+            #asset
+        }
+    })
+}
 
 fn skylite_project_impl(body_raw: TokenStream) -> TokenStream {
     match skylite_project_impl_fallible(body_raw) {
@@ -232,11 +319,60 @@ fn scene_definition_impl(body_raw: TokenStream) -> TokenStream {
     }
 }
 
+/// Generates the main project type from a project definition file.
+///
+/// ```ignore
+/// skylite_proc::skylite_project! {
+///     skylite_proc::project_file!("./project/project.scm");
+///     skylite_proc::target_type!(MyTarget);
+/// }
+/// ```
+///
+/// The items of the body, the entries of the project definition and the generated items are
+/// described in `docs/project_definition.md`.
 #[proc_macro]
 pub fn skylite_project(body: proc_macro::TokenStream) -> proc_macro::TokenStream {
     skylite_project_impl(body.into()).into()
 }
 
+#[cfg(feature = "snapshot")]
+fn project_snapshot_impl(body_raw: TokenStream) -> TokenStream {
+    let res = parse2::<File>(body_raw)
+        .map_err(|err| SkyliteProcError::SyntaxError(err.to_string()))
+        .and_then(|file| generate_project_items(&file.items))
+        .and_then(|(_, project_items)| snapshot::format_snapshot(&project_items));
+    match res {
+        Ok(code) => quote!(#code),
+        Err(err) => err.into()
+    }
+}
+
+/// Evaluates to the formatted code which `skylite_project!` generates for the same body, as a `&'static str`.
+///
+/// Only available with the `snapshot` feature. The snapshot only contains the generated items, not the
+/// items from the body. Committing the snapshot and comparing it in a test shows how the generated API
+/// changes after edits to the assets or after upgrading Skylite:
+///
+/// ```ignore
+/// #[test]
+/// fn test_generated_api() {
+///     let code = skylite_proc::project_snapshot! {
+///         skylite_proc::project_file!("./project/project.scm");
+///         skylite_proc::target_type!(MockTarget);
+///     };
+///     let path = concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots/project.rs");
+///     if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+///         std::fs::write(path, code).unwrap();
+///     }
+///     assert_eq!(code, std::fs::read_to_string(path).unwrap());
+/// }
+/// ```
+#[cfg(feature = "snapshot")]
+#[proc_macro]
+pub fn project_snapshot(body: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    project_snapshot_impl(body.into()).into()
+}
+
 #[doc = include_str!("../../../docs/actor_definition.md")]
 #[proc_macro]
 pub fn actor_definition(body: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -249,6 +385,117 @@ pub fn scene_definition(body: proc_macro::TokenStream) -> proc_macro::TokenStrea
     scene_definition_impl(body.into()).into()
 }
 
+fn skylite_value_impl(body_raw: TokenStream) -> TokenStream {
+    match value_definition_fallible(body_raw) {
+        Ok(stream) => stream,
+        Err(err) => err.into()
+    }
+}
+
+/// Defines the Rust struct for a custom value type.
+///
+/// Value types are declared in the `value-types` section of the project definition, with one
+/// `(name (field type [documentation])...)` entry per type. Fields can use all built-in types,
+/// as well as the value types declared before them:
+///
+/// ```scheme
+/// (value-types .
+///   ((point (x i16) (y i16))
+///    (spawn-point (pos point "Position in the scene") (label string))))
+/// ```
+///
+/// Once declared, a value type can be used like any built-in type for parameters, globals and
+/// save data. Values are written as an alist of fields, e.g. `((x . 4) (y . 8))`, in any order.
+///
+/// `skylite_value!` generates a struct with one public field per declared field, which implements
+/// `Serialize` and `Deserialize`. The value type is selected with `skylite_proc::asset_file!`,
+/// and all other items in the body are copied after the struct. The generated code for actors and
+/// scenes refers to the struct as `crate::<TypeName>`, so it must be defined or re-exported at the
+/// crate root.
+///
+/// ## Example
+/// ```ignore
+/// skylite_value! {
+///     skylite_proc::asset_file!("./project/project.scm", "point");
+///
+///     impl Point {
+///         pub fn manhattan_len(&self) -> u16 { self.x.unsigned_abs() + self.y.unsigned_abs() }
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn skylite_value(body: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    skylite_value_impl(body.into()).into()
+}
+
+fn embedded_asset_impl(args: TokenStream) -> TokenStream {
+    match embedded_asset_fallible(args) {
+        Ok(stream) => stream,
+        Err(err) => err.into()
+    }
+}
+
+/// Embeds an arbitrary file into the binary, using the project's compression settings.
+///
+/// The first argument is the path to the project root file, relative to the crate's
+/// `Cargo.toml`. The second argument is the path to the embedded file, relative to the
+/// project root file. The optional third argument is the target name, see `docs/project_definition.md`.
+/// The file is compressed with the methods given by the top-level `compression` entry of the
+/// project definition, or with the default methods if the entry is not present.
+///
+/// The macro evaluates to a `skylite_core::prelude::EmbeddedAsset`.
+///
+/// ## Example
+/// ```rust
+/// static TRACK1: EmbeddedAsset = skylite_proc::embedded_asset!("./project/project.scm", "./music/track1.bin");
+///
+/// let mut decoder = TRACK1.decoder();
+/// ```
+#[proc_macro]
+pub fn embedded_asset(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    embedded_asset_impl(args.into()).into()
+}
+
+/// Runs a closure for every entity that has all of the components requested by the closure's parameters.
+///
+/// The first argument is an iterator over `&mut Entity`. Each parameter of the closure must have an
+/// explicit type, which is a reference to a component type. Up to 8 components can be requested.
+/// The components are passed as `skylite_core::prelude::Mut`, which dereferences to the component,
+/// and records when a component requested by `&mut` is modified.
+///
+/// When the `ProjectControls` are passed as an additional second argument, the closure can also request
+/// one project-level resource, by marking a parameter with `#[globals]` (for the project's globals) or
+/// `#[controls]` (for the `ProjectControls` themselves):
+///
+/// ```ignore
+/// skylite_proc::system!(entities, controls, |pos: &mut Position, #[globals] globals: &mut MyProjectGlobals| {
+///     globals.max_x = globals.max_x.max(pos.x);
+/// });
+/// ```
+///
+/// Parameters of type `Changed<T>` or `Added<T>` are filters, which restrict the system to the entities
+/// whose component `T` was modified or added since the same `system!` invocation last ran on the entity.
+/// Components are considered changed when they are written to through a `Mut`, either in a system or
+/// from `Entity::get_component_mut`, but not when they are only read. Changes made by the system itself
+/// are not reported to its next run.
+///
+/// ```ignore
+/// skylite_proc::system!(entities, |path: &mut Path, pos: &mut Position, _: Changed<Target>| {
+///     path.recompute(pos);
+/// });
+/// ```
+///
+/// A parameter of type `ActorHandle` receives the handle of the current entity. Since the entities cannot
+/// be modified structurally while the system runs, the handle is typically used to record changes in a
+/// `Commands` buffer, which is applied to the scene afterwards:
+///
+/// ```ignore
+/// let mut commands = Commands::new();
+/// skylite_proc::system!(entities, |handle: ActorHandle, hp: &mut Health| {
+///     if hp.0 == 0 { commands.despawn(handle) }
+/// });
+/// commands.apply(scene);
+/// ```
 #[proc_macro]
 pub fn system(args: proc_macro::TokenStream) -> proc_macro::TokenStream {
     system_impl(args.into()).into()
@@ -259,18 +506,199 @@ pub fn derive_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
     derive_component_impl(item.into()).into()
 }
 
+/// Implements `skylite_core::prelude::Serialize` for a struct.
+///
+/// The fields are serialized in the order of their declaration, using the same
+/// format as the data generated from asset files. This makes it possible to store
+/// user types in save data. All fields must implement `Serialize`.
+#[proc_macro_derive(SkyliteSerialize)]
+pub fn derive_skylite_serialize(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive_serialize_impl(item.into()).into()
+}
+
+/// Implements `skylite_core::prelude::Deserialize` for a struct.
+///
+/// This is the counterpart to [`SkyliteSerialize`], and reads the fields in the order
+/// of their declaration. All fields must implement `Deserialize`.
+#[proc_macro_derive(SkyliteDeserialize)]
+pub fn derive_skylite_deserialize(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    derive_deserialize_impl(item.into()).into()
+}
+
 #[proc_macro]
 pub fn project_file(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
 
 #[proc_macro]
 pub fn target_type(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
 
+/// Selects the target for the conditional sections of the project definition, see `docs/project_definition.md`.
+///
+/// **This macro must always be used with an absolute path: `skylite_proc::target_name!`.**
+#[proc_macro]
+pub fn target_name(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
+
 /// Marks a function to be called to initialize an instance of `SkyliteProject` or `Scene.`
 ///
 /// **This macro must always be used with an absolute path: `#[skylite_proc::init]`.**
 #[proc_macro_attribute]
 pub fn init(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
 
+/// Marks an actor property as observed.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::observed]`.**
+///
+/// For each observed property, a setter `set_<property>(value, controls)` is generated on
+/// the actor's properties type. When the setter changes the value of the property, the change
+/// is recorded in the `ProjectControls`. The changes made during an update can be queried
+/// during the following update with `ProjectControls::changes` or `ProjectControls::has_changed`.
+/// Changes made by assigning to the property directly are not recorded.
+///
+/// ## Example
+/// ```rust
+/// skylite_proc::properties! {
+///     #[skylite_proc::observed]
+///     pub hp: u8
+/// }
+///
+/// // In a different actor:
+/// if controls.has_changed::<Player>("hp") { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn observed(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
+/// Marks an actor property as interpolated.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::interpolated]`.**
+///
+/// The property must have the type `Interpolated<T>`. At the start of every update, before the
+/// `pre_update` hook runs, the current value of the property is stored as its previous value.
+/// During rendering, `lerp_value(ctx.alpha())` then returns a value between the previous and the
+/// current one, so that movement looks smooth when the project renders more often than it updates.
+///
+/// Interpolated properties cannot be initialized directly from a parameter. Use a
+/// `derive_property` or `create_properties` function instead.
+///
+/// ## Example
+/// ```rust
+/// skylite_proc::properties! {
+///     #[skylite_proc::interpolated]
+///     pub pos: Interpolated<(f32, f32)>
+/// }
+///
+/// #[skylite_proc::derive_property("pos")]
+/// fn derive_pos(x: f32, y: f32) -> Interpolated<(f32, f32)> { Interpolated::new((x, y)) }
+///
+/// #[skylite_proc::render]
+/// fn render(actor: &Player, ctx: &mut DrawContext<MyProject>) {
+///     let (x, y) = actor.properties.pos.lerp_value(ctx.alpha());
+///     ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn interpolated(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
+/// Marks an actor property as the mode of the actor.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::mode]`.**
+///
+/// The property must have an enum type. During the main phase of each update, after the current
+/// action, the function annotated with `#[skylite_proc::update("Variant")]` for the current variant
+/// of the mode is called. This is a lighter alternative to actions for actors whose behavior is a
+/// simple state machine. At most one property of an actor can be the mode.
+///
+/// ## Example
+/// ```rust
+/// enum Mode { Walking, Jumping { height: u8 } }
+///
+/// skylite_proc::properties! {
+///     #[skylite_proc::mode]
+///     pub mode: Mode
+/// }
+///
+/// #[skylite_proc::update("Jumping")]
+/// fn jump(actor: &mut Player, scene: &mut dyn Scene<P=MyProject>, controls: &mut ProjectControls<MyProject>) {
+///     ...
+///     actor.properties.mode = Mode::Walking;
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn mode(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
+/// Marks a function to be called during the update while the actor's mode property has the given variant.
+///
+/// The function takes the same parameters as a `pre_update` function. Variants without a function
+/// do nothing. See `skylite_proc::mode` for details.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::update("Variant")]`.**
+#[proc_macro_attribute]
+pub fn update(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
+/// Marks a function which provides a substitute actor when decoding encounters an unknown actor id.
+///
+/// This can happen when data is decoded that was written by a different version of the project,
+/// for example an old save state. Without this function, decoding an unknown actor id panics.
+/// The function is called with the `DecodeError` and must return an instance of the project's
+/// actors type. Note that the data of the unknown actor can not be skipped, so the substitute
+/// should usually not depend on any data decoded afterwards.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::unknown_actor]`.**
+///
+/// ## Example
+/// ```rust
+/// #[skylite_proc::unknown_actor]
+/// fn placeholder(err: DecodeError) -> MyProjectActors {
+///     MyProjectActors::Placeholder(Box::new(Placeholder::new()))
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn unknown_actor(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
+/// Marks a function which provides the `SaveTransform` for the project's save slots.
+///
+/// The transform is applied to the save data before it is written to persistent storage and
+/// reversed after it is read, e.g. to prevent players from editing their save files. Without
+/// this function, the save data is stored as-is. The function takes no parameters.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::save_transform]`.**
+///
+/// ## Example
+/// ```rust
+/// #[skylite_proc::save_transform]
+/// fn save_transform() -> XorTransform {
+///     XorTransform::new(0x5eed_1234)
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn save_transform(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
+/// Marks a `macro_rules!` macro in `skylite_project!` which extends the generated project code.
+///
+/// The macro is invoked after the project code is generated, with the names of the generated types
+/// for the project's actors, scenes and custom assets and their ids. This allows a project to generate
+/// its own code from the assets, e.g. lookup tables, without changes to `skylite_proc`. The input of
+/// the macro has the following form:
+///
+/// ```text
+/// project: MyProject,
+/// actors: [(Player, 0), (Slime, 1)],
+/// scenes: [(Level1, 0)],
+/// custom: [(ItemAssets, [(Potion, 0), (Key, 1)])]
+/// ```
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::extend_project]`.**
+///
+/// ## Example
+/// ```rust
+/// #[skylite_proc::extend_project]
+/// macro_rules! actor_names {
+///     (project: $project:ident, actors: [$(($actor:ident, $id:literal)),*], $($rest:tt)*) => {
+///         pub const ACTOR_LABELS: &[&str] = &[$(stringify!($actor)),*];
+///     };
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn extend_project(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
 /// Marks a function to be called at the beginning of an update.
 ///
 /// **This macro must always be used with an absolute path: `#[skylite_proc::pre_update]`.**
@@ -309,6 +737,20 @@ pub fn post_render(_args: proc_macro::TokenStream, body: proc_macro::TokenStream
 #[proc_macro_attribute]
 pub fn create_properties(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
 
+/// Marks a function to compute the initial value of a single property from the parameters defined in the asset file.
+///
+/// **This macro must always be used with an absolute path: `#[skylite_proc::derive_property("name")]`.**
+///
+/// Derive functions are only used when there is no `create_properties` function. The marked function must take
+/// the same parameters as `create_properties` would and return the value of the property:
+///
+/// ```rust
+/// #[skylite_proc::derive_property("speed")]
+/// fn derive_speed(x: i16, y: i16) -> i16 { (x.abs() + y.abs()) / 8 }
+/// ```
+#[proc_macro_attribute]
+pub fn derive_property(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
+
 /// Marks an action for an actor.
 ///
 /// The name of the corresponding action in the actor asset file should be given like this:
@@ -320,14 +762,19 @@ pub fn create_properties(_args: proc_macro::TokenStream, body: proc_macro::Token
 #[proc_macro_attribute]
 pub fn action(_args: proc_macro::TokenStream, body: proc_macro::TokenStream) -> proc_macro::TokenStream { body }
 
-/// Sets the backing asset file for an `actor_definition` or `scene_definition`.
+/// Sets the backing asset file for an `actor_definition`, `scene_definition` or `skylite_value`.
 ///
 /// **This macro must always be used with an absolute path: `skylite_proc::asset_file!`.**
 ///
 /// The definition file consists of the path to the project root file and the name of the asset.
 /// The name of the asset does *not* include the file extension. The specific file will be searched
 /// within the files for the respective asset defined in the project definition, i.e. actors files for
-/// `actor_definition!`, scenes for `scene_definition!`, etc.
+/// `actor_definition!`, scenes for `scene_definition!`, etc. For `skylite_value!`, the name refers
+/// to a value type declared in the project definition.
+///
+/// An optional third argument selects the target for the conditional sections of the project
+/// definition. It must be the same as in `skylite_proc::target_name!` of the `skylite_project!`,
+/// otherwise the definition fails to compile, since the asset ids would not match those of the project.
 ///
 /// ## Example
 /// ```rust
@@ -357,6 +804,83 @@ pub fn asset_file(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { p
 #[proc_macro]
 pub fn properties(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
 
+/// Sets the layer in which an actor is rendered.
+///
+/// **This macro must always be used with an absolute path: `skylite_proc::render_layer!`.**
+///
+/// The argument is one of `background`, `main` or `ui`. See `skylite_core::prelude::RenderLayer`.
+///
+/// ## Example
+///
+/// ```rust
+/// actor_definition! {
+///     skylite_proc::render_layer!(ui);
+/// }
+/// ```
+#[proc_macro]
+pub fn render_layer(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
+
+/// Sets the priority with which an actor is updated.
+///
+/// **This macro must always be used with an absolute path: `skylite_proc::update_priority!`.**
+///
+/// The argument is an `i16`. Actors with a lower priority are updated first.
+/// See `ActorBase::update_priority` for the exact ordering guarantees.
+///
+/// ## Example
+///
+/// ```rust
+/// actor_definition! {
+///     // Update after the actors with the default priority of 0.
+///     skylite_proc::update_priority!(10);
+/// }
+/// ```
+#[proc_macro]
+pub fn update_priority(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
+
+/// Sets the parallax factors of an actor.
+///
+/// **This macro must always be used with an absolute path: `skylite_proc::parallax!`.**
+///
+/// The arguments are the factors by which the horizontal and vertical focus are scaled
+/// when the actor is rendered, as float literals. See `DrawContext::get_focus_scaled`.
+///
+/// ## Example
+///
+/// ```rust
+/// actor_definition! {
+///     // Scrolls at half the speed of the main layer.
+///     skylite_proc::parallax!(0.5, 0.5);
+/// }
+/// ```
+#[proc_macro]
+pub fn parallax(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
+
+/// Disables the check that `skylite_project!` is called at the crate root.
+///
+/// **This macro must always be used with an absolute path: `skylite_proc::allow_non_root!`.**
+///
+/// The code generated by `actor_definition!` and `scene_definition!` refers to the project
+/// type as `crate::ProjectName`. When the project is defined in a submodule, it must therefore
+/// be re-exported at the crate root. Likewise, all actor and scene types must be visible from
+/// the module in which `skylite_project!` is called.
+///
+/// ## Example
+/// ```rust
+/// // lib.rs
+/// pub mod game {
+///     skylite_project! {
+///         skylite_proc::project_file!("./project/project.scm");
+///         skylite_proc::target_type!(MyTarget);
+///         skylite_proc::allow_non_root!();
+///     }
+/// }
+///
+/// pub use game::*;
+/// ```
+#[proc_macro]
+pub fn allow_non_root(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
+
 #[cfg(debug_assertions)]
 #[proc_macro]
 pub fn debug_output(_body: proc_macro::TokenStream) -> proc_macro::TokenStream { proc_macro::TokenStream::new() }
@@ -1,7 +0,0 @@
-pub(crate) mod guile;
-pub(crate) mod project;
-pub(crate) mod actors;
-pub(crate) mod scenes;
-pub(crate) mod scheme_util;
-pub(crate) mod util;
-pub(crate) mod values;
@@ -0,0 +1,146 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, parse_quote, Data, DeriveInput, Fields, Generics, Ident};
+
+use crate::SkyliteProcError;
+
+fn parse_struct(item: TokenStream, derive_name: &str) -> Result<(Ident, Generics, Fields), SkyliteProcError> {
+    let input = parse2::<DeriveInput>(item)
+        .map_err(|err| SkyliteProcError::SyntaxError(format!("Failed to parse item: {}", err)))?;
+    match input.data {
+        Data::Struct(data) => Ok((input.ident, input.generics, data.fields)),
+        _ => Err(SkyliteProcError::SyntaxError(format!("{} can only be derived for structs", derive_name)))
+    }
+}
+
+/// Adds `bound` to every type parameter in `generics`.
+fn add_trait_bounds(mut generics: Generics, bound: TokenStream) -> Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+fn derive_serialize_fallible(item: TokenStream) -> Result<TokenStream, SkyliteProcError> {
+    let (ident, generics, fields) = parse_struct(item, "SkyliteSerialize")?;
+    let generics = add_trait_bounds(generics, quote!(::skylite_core::prelude::Serialize));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let members: Vec<TokenStream> = match &fields {
+        Fields::Named(named) => named.named.iter()
+            .map(|f| { let name = f.ident.as_ref().unwrap(); quote!(#name) })
+            .collect(),
+        Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len())
+            .map(|i| { let idx = Literal::usize_unsuffixed(i); quote!(#idx) })
+            .collect(),
+        Fields::Unit => Vec::new()
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::skylite_core::prelude::Serialize for #ident #type_generics #where_clause {
+            #[allow(unused_variables)]
+            fn serialize(&self, out: &mut ::std::vec::Vec<u8>) {
+                #(
+                    ::skylite_core::prelude::Serialize::serialize(&self.#members, out);
+                )*
+            }
+        }
+    })
+}
+
+fn derive_deserialize_fallible(item: TokenStream) -> Result<TokenStream, SkyliteProcError> {
+    let (ident, generics, fields) = parse_struct(item, "SkyliteDeserialize")?;
+    let generics = add_trait_bounds(generics, quote!(::skylite_core::prelude::Deserialize));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    // The fields are decoded into local variables first, to guarantee
+    // that they are decoded in the order of their declaration.
+    let locals: Vec<Ident> = (0..fields.len()).map(|i| format_ident!("field{}", i)).collect();
+    let types = fields.iter().map(|f| &f.ty);
+    let construct = match &fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote!(#ident { #(#names: #locals),* })
+        },
+        Fields::Unnamed(_) => quote!(#ident(#(#locals),*)),
+        Fields::Unit => quote!(#ident)
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::skylite_core::prelude::Deserialize for #ident #type_generics #where_clause {
+            #[allow(unused_variables)]
+            fn deserialize(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> Self {
+                #(
+                    let #locals = <#types as ::skylite_core::prelude::Deserialize>::deserialize(decoder);
+                )*
+                #construct
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_serialize_impl(item: TokenStream) -> TokenStream {
+    match derive_serialize_fallible(item) {
+        Ok(stream) => stream,
+        Err(err) => err.into()
+    }
+}
+
+pub(crate) fn derive_deserialize_impl(item: TokenStream) -> TokenStream {
+    match derive_deserialize_fallible(item) {
+        Ok(stream) => stream,
+        Err(err) => err.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::{derive_deserialize_fallible, derive_serialize_fallible};
+
+    #[test]
+    fn test_derive_serialize() {
+        let code = derive_serialize_fallible(quote! {
+            struct Point<T> {
+                x: T,
+                y: T,
+                visible: bool
+            }
+        }).unwrap();
+        let expectation = quote! {
+            impl<T: ::skylite_core::prelude::Serialize> ::skylite_core::prelude::Serialize for Point<T> {
+                #[allow(unused_variables)]
+                fn serialize(&self, out: &mut ::std::vec::Vec<u8>) {
+                    ::skylite_core::prelude::Serialize::serialize(&self.x, out);
+                    ::skylite_core::prelude::Serialize::serialize(&self.y, out);
+                    ::skylite_core::prelude::Serialize::serialize(&self.visible, out);
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_derive_deserialize() {
+        let code = derive_deserialize_fallible(quote! {
+            struct Entry(u8, Vec<(u16, bool)>);
+        }).unwrap();
+        let expectation = quote! {
+            impl ::skylite_core::prelude::Deserialize for Entry {
+                #[allow(unused_variables)]
+                fn deserialize(decoder: &mut dyn ::skylite_core::prelude::Decoder) -> Self {
+                    let field0 = <u8 as ::skylite_core::prelude::Deserialize>::deserialize(decoder);
+                    let field1 = <Vec<(u16, bool)> as ::skylite_core::prelude::Deserialize>::deserialize(decoder);
+                    Entry(field0, field1)
+                }
+            }
+        };
+        assert_eq!(code.to_string(), expectation.to_string());
+    }
+
+    #[test]
+    fn test_derive_for_enum_fails() {
+        assert!(derive_serialize_fallible(quote!(enum Test { A, B })).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use quote::quote;
+use syn::{parse2, File, Item};
+
+use crate::SkyliteProcError;
+
+/// Formats the items generated for a project as Rust source code.
+pub(crate) fn format_snapshot(project_items: &[Item]) -> Result<String, SkyliteProcError> {
+    // Most of the generated items are `Item::Verbatim`, which prettyplease does not
+    // format, so the items are parsed again before formatting.
+    let file = parse2::<File>(quote!(#(#project_items)*))
+        .map_err(|err| SkyliteProcError::OtherError(format!("Failed to parse generated code for snapshot: {}", err)))?;
+    Ok(prettyplease::unparse(&file))
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, Item};
+
+    use super::format_snapshot;
+
+    #[test]
+    fn test_format_snapshot() {
+        let items: Vec<Item> = vec![
+            Item::Verbatim(quote::quote!(pub struct Test1 { pub a : u8 } impl Test1 { fn f ( & self ) -> u8 { self . a } })),
+            parse_quote!(pub enum Test1Tiles { Solid })
+        ];
+        let expected = "\
+pub struct Test1 {
+    pub a: u8,
+}
+impl Test1 {
+    fn f(&self) -> u8 {
+        self.a
+    }
+}
+pub enum Test1Tiles {
+    Solid,
+}
+";
+        assert_eq!(format_snapshot(&items).unwrap(), expected);
+    }
+}
@@ -0,0 +1,181 @@
+//! A target for Skylite which turns a project into a libretro core, so that it can be played
+//! in RetroArch and other libretro frontends.
+//!
+//! The game crate must have the crate type `cdylib`, and exports the functions of the
+//! libretro API with the [`libretro_core!`] macro:
+//!
+//! ```ignore
+//! libretro_target::libretro_core!(MyGame, CoreOptions {
+//!     name: c"My Game",
+//!     version: c"1.0",
+//!     width: 160,
+//!     height: 144,
+//!     fps: 60.0,
+//!     save_ram_size: 1024
+//! });
+//! ```
+//!
+//! The core does not need any content, the game starts directly when the core is loaded.
+//! The persistent storage of the target is the save RAM of the core, which the frontend
+//! stores in its save file. The frontend loads the save file after the project was created,
+//! so save data should not be read from the project's `init` function.
+//!
+//! Save states, rewind and netplay require the state of the project to be serialized. Since
+//! Skylite cannot encode the state of scenes and actors generically, the project provides the
+//! encoding by implementing [`SaveState`], and the core is exported with
+//! `libretro_core!(MyGame, options, save_state)`. Without `save_state`, the frontend is told
+//! that the core cannot be serialized.
+//!
+//! The graphics of the project must be in the format described at [`LibretroTarget`].
+pub mod ffi;
+mod runtime;
+
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use skylite_core::SkyliteTarget;
+use software_target::{Raster, XrgbPixels};
+
+pub use runtime::{Core, CoreOptions, SaveState};
+pub use software_target::{Input, BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_UP};
+
+/// The save RAM of the core, shared between the target and the frontend.
+///
+/// The frontend reads and writes the memory directly through the pointer returned by
+/// `retro_get_memory_data`, so the memory is never reallocated.
+#[derive(Clone)]
+pub(crate) struct SaveRam(Rc<[Cell<u8>]>);
+
+impl SaveRam {
+    pub fn new(size: usize) -> SaveRam {
+        SaveRam((0..size).map(|_| Cell::new(0)).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn as_mut_ptr(&self) -> *mut u8 {
+        // Writing through the pointer is fine, since each byte is a `Cell`.
+        self.0.as_ptr() as *mut u8
+    }
+}
+
+/// A `SkyliteTarget` that draws into the framebuffer of a libretro core.
+///
+/// The graphics of the project must be in the format described in the `software-target` crate.
+///
+/// Reads from the persistent storage outside of the save RAM return `0`, and writes
+/// outside of the save RAM are dropped.
+pub struct LibretroTarget {
+    width: u16,
+    height: u16,
+    pixels: Rc<RefCell<Vec<u32>>>,
+    input: Input,
+    save_ram: SaveRam
+}
+
+impl LibretroTarget {
+    pub(crate) fn new(width: u16, height: u16, save_ram: SaveRam) -> LibretroTarget {
+        LibretroTarget {
+            width,
+            height,
+            pixels: Rc::new(RefCell::new(vec![0; width as usize * height as usize])),
+            input: Input::default(),
+            save_ram
+        }
+    }
+
+    /// Returns the state of the buttons of the first joypad, which is updated at the start of each frame.
+    ///
+    /// The A and B buttons of the frontend's joypad are mapped to `BUTTON_1` and `BUTTON_2`.
+    pub fn input(&self) -> Input {
+        self.input.clone()
+    }
+
+    fn with_raster<R>(&self, f: impl FnOnce(&mut XrgbPixels) -> R) -> R {
+        let mut pixels = self.pixels.borrow_mut();
+        f(&mut XrgbPixels { width: self.width, height: self.height, pixels: &mut pixels })
+    }
+}
+
+/// Returns the buttons held down on a joypad, where `pressed` returns the
+/// state of a button given by its libretro id.
+pub(crate) fn buttons_from_joypad(pressed: impl Fn(std::ffi::c_uint) -> bool) -> u8 {
+    [
+        (ffi::RETRO_DEVICE_ID_JOYPAD_A, BUTTON_1),
+        (ffi::RETRO_DEVICE_ID_JOYPAD_B, BUTTON_2),
+        (ffi::RETRO_DEVICE_ID_JOYPAD_LEFT, BUTTON_LEFT),
+        (ffi::RETRO_DEVICE_ID_JOYPAD_RIGHT, BUTTON_RIGHT),
+        (ffi::RETRO_DEVICE_ID_JOYPAD_UP, BUTTON_UP),
+        (ffi::RETRO_DEVICE_ID_JOYPAD_DOWN, BUTTON_DOWN)
+    ].into_iter()
+        .filter(|(id, _)| pressed(*id))
+        .fold(0, |buttons, (_, button)| buttons | button)
+}
+
+impl SkyliteTarget for LibretroTarget {
+    fn draw_sub(&mut self, data: &[u8], x: i16, y: i16, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
+        self.with_raster(|raster| raster.draw_sub(data, x, y, src_x, src_y, src_w, src_h, flip_h, flip_v, rotate));
+    }
+
+    fn get_screen_size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        for (byte, value) in self.save_ram.0.iter().skip(offset).zip(data) {
+            byte.set(*value);
+        }
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        (offset..offset + len)
+            .map(|i| self.save_ram.0.get(i).map_or(0, Cell::get))
+            .collect()
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn fill_rect(&mut self, x: i16, y: i16, w: u16, h: u16, color: u32) -> bool {
+        self.with_raster(|raster| raster.fill_rect(x, y, w, h, color));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skylite_core::SkyliteTarget;
+
+    use crate::{ffi::{RETRO_DEVICE_ID_JOYPAD_A, RETRO_DEVICE_ID_JOYPAD_LEFT}, BUTTON_1, BUTTON_LEFT};
+
+    use super::{buttons_from_joypad, LibretroTarget, SaveRam};
+
+    #[test]
+    fn test_save_ram() {
+        let save_ram = SaveRam::new(4);
+        let mut target = LibretroTarget::new(2, 2, save_ram.clone());
+        target.write_storage(2, &[1, 2, 3]);
+        assert_eq!(target.read_storage(1, 5), [0, 1, 2, 0, 0]);
+
+        // The frontend writes the save file directly into the memory.
+        unsafe { *save_ram.as_mut_ptr() = 9 };
+        assert_eq!(target.read_storage(0, 1), [9]);
+    }
+
+    #[test]
+    fn test_draw_sub() {
+        let mut target = LibretroTarget::new(2, 1, SaveRam::new(0));
+        target.fill_rect(0, 0, 2, 1, 0x123456);
+        let data = [0x03, 0x02, 0x01, 0xff, 0x06, 0x05, 0x04, 0x7f, 2, 0];
+        target.draw_sub(&data, 0, 0, 0, 0, 2, 1, true, false, false);
+        assert_eq!(*target.pixels.borrow(), [0x123456, 0x010203]);
+    }
+
+    #[test]
+    fn test_buttons_from_joypad() {
+        let buttons = buttons_from_joypad(|id| id == RETRO_DEVICE_ID_JOYPAD_A || id == RETRO_DEVICE_ID_JOYPAD_LEFT);
+        assert_eq!(buttons, BUTTON_1 | BUTTON_LEFT);
+    }
+}
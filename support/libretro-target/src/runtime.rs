@@ -0,0 +1,456 @@
+use std::{cell::{RefCell, UnsafeCell}, ffi::{c_uint, c_void, CStr}, ptr::null, rc::Rc};
+
+use skylite_core::SkyliteProject;
+
+use crate::{buttons_from_joypad, ffi::*, Input, LibretroTarget, SaveRam};
+
+/// The sample rate of the audio sent to the frontend. Skylite has no audio yet,
+/// but some frontends expect audio samples to pace the frames.
+const SAMPLE_RATE: f64 = 44100.0;
+
+/// Size of the header in front of a serialized state, which holds the length of the state as `u32` in little endian.
+const STATE_HEADER_LEN: usize = 4;
+
+/// The information about a core which is reported to the frontend.
+pub struct CoreOptions {
+    pub name: &'static CStr,
+    pub version: &'static CStr,
+    /// The screen size of the project in pixels.
+    pub width: u16,
+    pub height: u16,
+    /// The number of frames per second. Each frame runs exactly one update.
+    pub fps: f64,
+    /// The size of the save RAM, which is the persistent storage of the target.
+    pub save_ram_size: usize
+}
+
+/// A project whose state can be stored by the frontend, which enables save states, rewind and netplay.
+///
+/// The state is encoded by the project itself, e.g. with the same `Serialize` and `Deserialize`
+/// implementations that are used for the data of the save slots. Restoring a state must reproduce
+/// the project exactly, otherwise rewind and netplay will diverge.
+pub trait SaveState {
+    /// The maximum size of an encoded state in bytes.
+    ///
+    /// The frontend allocates buffers of this size, so it must not depend on the state of the project.
+    const STATE_SIZE: usize;
+
+    /// Appends the encoded state of the project to `out`.
+    fn save_state(&self, out: &mut Vec<u8>);
+
+    /// Restores a state encoded by `save_state`. Returns `false` if `data` is not a valid state.
+    fn load_state(&mut self, data: &[u8]) -> bool;
+}
+
+/// Writes `state` with its header into `out`, and fills the rest of `out` with zeros.
+///
+/// Returns `false` if `out` is too small.
+fn write_state(state: &[u8], out: &mut [u8]) -> bool {
+    let Ok(len) = u32::try_from(state.len()) else {
+        return false;
+    };
+    if out.len() < STATE_HEADER_LEN + state.len() {
+        return false;
+    }
+    let (header, body) = out.split_at_mut(STATE_HEADER_LEN);
+    header.copy_from_slice(&len.to_le_bytes());
+    body[..state.len()].copy_from_slice(state);
+    body[state.len()..].fill(0);
+    true
+}
+
+/// Returns the state written by `write_state` into `data`, or `None` if the header is invalid.
+fn read_state(data: &[u8]) -> Option<&[u8]> {
+    let header = data.get(..STATE_HEADER_LEN)?;
+    let len = u32::from_le_bytes(header.try_into().unwrap()) as usize;
+    data.get(STATE_HEADER_LEN..STATE_HEADER_LEN.checked_add(len)?)
+}
+
+#[derive(Default)]
+struct Callbacks {
+    environment: Option<EnvironmentFn>,
+    video_refresh: Option<VideoRefreshFn>,
+    audio_sample_batch: Option<AudioSampleBatchFn>,
+    input_poll: Option<InputPollFn>,
+    input_state: Option<InputStateFn>
+}
+
+/// The state of a core while a game is loaded.
+struct Running<P: SkyliteProject<Target = LibretroTarget>> {
+    project: P,
+    pixels: Rc<RefCell<Vec<u32>>>,
+    input: Input,
+    save_ram: SaveRam
+}
+
+impl<P: SkyliteProject<Target = LibretroTarget>> Running<P> {
+    fn new(options: &CoreOptions, save_ram: SaveRam) -> Running<P> {
+        let target = LibretroTarget::new(options.width, options.height, save_ram.clone());
+        let pixels = target.pixels.clone();
+        let input = target.input();
+        Running { project: P::new(target), pixels, input, save_ram }
+    }
+}
+
+/// The state of a libretro core, which is stored in a static by [`libretro_core!`](crate::libretro_core).
+///
+/// The methods correspond to the functions of the libretro API, and are only meant
+/// to be called from the functions generated by `libretro_core!`.
+///
+/// # Safety
+///
+/// Like the functions of the libretro API, the methods must only be called from the
+/// frontend's thread, and pointers must be valid as described in `libretro.h`.
+pub struct Core<P: SkyliteProject<Target = LibretroTarget>> {
+    options: CoreOptions,
+    callbacks: UnsafeCell<Option<Callbacks>>,
+    running: UnsafeCell<Option<Running<P>>>
+}
+
+// The libretro API requires the frontend to call all functions of the core from the same thread.
+unsafe impl<P: SkyliteProject<Target = LibretroTarget>> Sync for Core<P> {}
+
+// The safety requirements are the same for all methods, see the documentation of `Core`.
+#[allow(clippy::missing_safety_doc)]
+impl<P: SkyliteProject<Target = LibretroTarget>> Core<P> {
+
+    pub const fn new(options: CoreOptions) -> Core<P> {
+        Core { options, callbacks: UnsafeCell::new(None), running: UnsafeCell::new(None) }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn callbacks(&self) -> &mut Callbacks {
+        (*self.callbacks.get()).get_or_insert_with(Callbacks::default)
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn running(&self) -> Option<&mut Running<P>> {
+        (*self.running.get()).as_mut()
+    }
+
+    pub unsafe fn set_environment(&self, cb: EnvironmentFn) {
+        self.callbacks().environment = Some(cb);
+        let mut no_game = true;
+        cb(RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME, &mut no_game as *mut bool as *mut c_void);
+    }
+
+    pub unsafe fn set_video_refresh(&self, cb: VideoRefreshFn) {
+        self.callbacks().video_refresh = Some(cb);
+    }
+
+    pub unsafe fn set_audio_sample_batch(&self, cb: AudioSampleBatchFn) {
+        self.callbacks().audio_sample_batch = Some(cb);
+    }
+
+    pub unsafe fn set_input_poll(&self, cb: InputPollFn) {
+        self.callbacks().input_poll = Some(cb);
+    }
+
+    pub unsafe fn set_input_state(&self, cb: InputStateFn) {
+        self.callbacks().input_state = Some(cb);
+    }
+
+    pub unsafe fn get_system_info(&self, info: *mut SystemInfo) {
+        *info = SystemInfo {
+            library_name: self.options.name.as_ptr(),
+            library_version: self.options.version.as_ptr(),
+            valid_extensions: null(),
+            need_fullpath: false,
+            block_extract: false
+        };
+    }
+
+    pub unsafe fn get_system_av_info(&self, info: *mut SystemAvInfo) {
+        let (width, height) = (self.options.width as c_uint, self.options.height as c_uint);
+        *info = SystemAvInfo {
+            geometry: GameGeometry {
+                base_width: width,
+                base_height: height,
+                max_width: width,
+                max_height: height,
+                aspect_ratio: width as f32 / height as f32
+            },
+            timing: SystemTiming { fps: self.options.fps, sample_rate: SAMPLE_RATE }
+        };
+    }
+
+    /// Creates the project. The game info is ignored, since the game is part of the core.
+    pub unsafe fn load_game(&self, _game: *const GameInfo) -> bool {
+        let Some(environment) = self.callbacks().environment else {
+            return false;
+        };
+        let mut format = RETRO_PIXEL_FORMAT_XRGB8888;
+        if !environment(RETRO_ENVIRONMENT_SET_PIXEL_FORMAT, &mut format as *mut c_uint as *mut c_void) {
+            return false;
+        }
+        *self.running.get() = Some(Running::new(&self.options, SaveRam::new(self.options.save_ram_size)));
+        true
+    }
+
+    pub unsafe fn unload_game(&self) {
+        *self.running.get() = None;
+    }
+
+    /// Restarts the project, keeping the save RAM.
+    pub unsafe fn reset(&self) {
+        if let Some(running) = self.running() {
+            *running = Running::new(&self.options, running.save_ram.clone());
+        }
+    }
+
+    /// Runs one update, renders the project and sends the frame to the frontend.
+    pub unsafe fn run(&self) {
+        let callbacks = self.callbacks();
+        let Some(running) = self.running() else {
+            return;
+        };
+
+        if let (Some(input_poll), Some(input_state)) = (callbacks.input_poll, callbacks.input_state) {
+            input_poll();
+            running.input.set_buttons(buttons_from_joypad(|id| input_state(0, RETRO_DEVICE_JOYPAD, 0, id) != 0));
+        }
+
+        running.project.update();
+        running.project.render();
+
+        if let Some(video_refresh) = callbacks.video_refresh {
+            let pixels = running.pixels.borrow();
+            let pitch = self.options.width as usize * 4;
+            video_refresh(pixels.as_ptr() as *const c_void, self.options.width as c_uint, self.options.height as c_uint, pitch);
+        }
+        if let Some(audio_sample_batch) = callbacks.audio_sample_batch {
+            let frames = (SAMPLE_RATE / self.options.fps) as usize;
+            let silence = vec![0i16; frames * 2];
+            audio_sample_batch(silence.as_ptr(), frames);
+        }
+    }
+
+    pub unsafe fn memory_data(&self, id: c_uint) -> *mut c_void {
+        match self.running() {
+            Some(running) if id == RETRO_MEMORY_SAVE_RAM => running.save_ram.as_mut_ptr() as *mut c_void,
+            _ => std::ptr::null_mut()
+        }
+    }
+
+    pub unsafe fn memory_size(&self, id: c_uint) -> usize {
+        match self.running() {
+            Some(running) if id == RETRO_MEMORY_SAVE_RAM => running.save_ram.len(),
+            _ => 0
+        }
+    }
+}
+
+// The serialization methods are only available for projects which implement `SaveState`.
+#[allow(clippy::missing_safety_doc)]
+impl<P: SkyliteProject<Target = LibretroTarget> + SaveState> Core<P> {
+
+    /// Returns the size of the buffers for serialized states, or 0 if no game is loaded.
+    pub unsafe fn serialize_size(&self) -> usize {
+        match self.running() {
+            Some(_) => STATE_HEADER_LEN + P::STATE_SIZE,
+            None => 0
+        }
+    }
+
+    pub unsafe fn serialize(&self, data: *mut c_void, size: usize) -> bool {
+        let Some(running) = self.running() else {
+            return false;
+        };
+        if data.is_null() {
+            return false;
+        }
+        let mut state = Vec::new();
+        running.project.save_state(&mut state);
+        if state.len() > P::STATE_SIZE {
+            return false;
+        }
+        write_state(&state, std::slice::from_raw_parts_mut(data as *mut u8, size))
+    }
+
+    pub unsafe fn unserialize(&self, data: *const c_void, size: usize) -> bool {
+        let Some(running) = self.running() else {
+            return false;
+        };
+        if data.is_null() {
+            return false;
+        }
+        match read_state(std::slice::from_raw_parts(data as *const u8, size)) {
+            Some(state) => running.project.load_state(state),
+            None => false
+        }
+    }
+}
+
+/// Exports the functions of the libretro API for the project type `$project`,
+/// with the [`CoreOptions`] given by `$options`.
+///
+/// If `save_state` is passed as the third argument, the project must implement [`SaveState`],
+/// and the core supports save states, rewind and netplay. Otherwise, the frontend is told
+/// that the core cannot be serialized.
+///
+/// This must be called exactly once in the game crate.
+#[macro_export]
+macro_rules! libretro_core {
+    (@core $project:ty, $options:expr) => {
+        static _SKYLITE_LIBRETRO_CORE: $crate::Core<$project> = $crate::Core::new($options);
+
+        #[no_mangle]
+        pub extern "C" fn retro_api_version() -> ::std::ffi::c_uint {
+            $crate::ffi::RETRO_API_VERSION
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_set_environment(cb: $crate::ffi::EnvironmentFn) {
+            _SKYLITE_LIBRETRO_CORE.set_environment(cb)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_set_video_refresh(cb: $crate::ffi::VideoRefreshFn) {
+            _SKYLITE_LIBRETRO_CORE.set_video_refresh(cb)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_set_audio_sample(_cb: $crate::ffi::AudioSampleFn) {}
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_set_audio_sample_batch(cb: $crate::ffi::AudioSampleBatchFn) {
+            _SKYLITE_LIBRETRO_CORE.set_audio_sample_batch(cb)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_set_input_poll(cb: $crate::ffi::InputPollFn) {
+            _SKYLITE_LIBRETRO_CORE.set_input_poll(cb)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_set_input_state(cb: $crate::ffi::InputStateFn) {
+            _SKYLITE_LIBRETRO_CORE.set_input_state(cb)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_init() {}
+
+        #[no_mangle]
+        pub extern "C" fn retro_deinit() {}
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_get_system_info(info: *mut $crate::ffi::SystemInfo) {
+            _SKYLITE_LIBRETRO_CORE.get_system_info(info)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_get_system_av_info(info: *mut $crate::ffi::SystemAvInfo) {
+            _SKYLITE_LIBRETRO_CORE.get_system_av_info(info)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_set_controller_port_device(_port: ::std::ffi::c_uint, _device: ::std::ffi::c_uint) {}
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_reset() {
+            _SKYLITE_LIBRETRO_CORE.reset()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_run() {
+            _SKYLITE_LIBRETRO_CORE.run()
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_cheat_reset() {}
+
+        #[no_mangle]
+        pub extern "C" fn retro_cheat_set(_index: ::std::ffi::c_uint, _enabled: bool, _code: *const ::std::ffi::c_char) {}
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_load_game(game: *const $crate::ffi::GameInfo) -> bool {
+            _SKYLITE_LIBRETRO_CORE.load_game(game)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_load_game_special(_game_type: ::std::ffi::c_uint, _info: *const $crate::ffi::GameInfo, _num_info: usize) -> bool {
+            false
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_unload_game() {
+            _SKYLITE_LIBRETRO_CORE.unload_game()
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_get_region() -> ::std::ffi::c_uint {
+            $crate::ffi::RETRO_REGION_NTSC
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_get_memory_data(id: ::std::ffi::c_uint) -> *mut ::std::ffi::c_void {
+            _SKYLITE_LIBRETRO_CORE.memory_data(id)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_get_memory_size(id: ::std::ffi::c_uint) -> usize {
+            _SKYLITE_LIBRETRO_CORE.memory_size(id)
+        }
+    };
+
+    ($project:ty, $options:expr) => {
+        $crate::libretro_core!(@core $project, $options);
+
+        #[no_mangle]
+        pub extern "C" fn retro_serialize_size() -> usize {
+            0
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_serialize(_data: *mut ::std::ffi::c_void, _size: usize) -> bool {
+            false
+        }
+
+        #[no_mangle]
+        pub extern "C" fn retro_unserialize(_data: *const ::std::ffi::c_void, _size: usize) -> bool {
+            false
+        }
+    };
+
+    ($project:ty, $options:expr, save_state) => {
+        $crate::libretro_core!(@core $project, $options);
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_serialize_size() -> usize {
+            _SKYLITE_LIBRETRO_CORE.serialize_size()
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_serialize(data: *mut ::std::ffi::c_void, size: usize) -> bool {
+            _SKYLITE_LIBRETRO_CORE.serialize(data, size)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn retro_unserialize(data: *const ::std::ffi::c_void, size: usize) -> bool {
+            _SKYLITE_LIBRETRO_CORE.unserialize(data, size)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_state, write_state, STATE_HEADER_LEN};
+
+    #[test]
+    fn test_write_read_state() {
+        let mut buffer = [0xff; STATE_HEADER_LEN + 5];
+        assert!(write_state(&[1, 2, 3], &mut buffer));
+        assert_eq!(buffer, [3, 0, 0, 0, 1, 2, 3, 0, 0]);
+        assert_eq!(read_state(&buffer), Some(&[1, 2, 3][..]));
+
+        assert!(!write_state(&[1, 2, 3], &mut buffer[..STATE_HEADER_LEN + 2]));
+    }
+
+    #[test]
+    fn test_read_invalid_state() {
+        assert_eq!(read_state(&[1, 0]), None);
+        assert_eq!(read_state(&[4, 0, 0, 0, 1, 2, 3]), None);
+        assert_eq!(read_state(&[0xff, 0xff, 0xff, 0xff]), None);
+        assert_eq!(read_state(&[0, 0, 0, 0]), Some(&[][..]));
+    }
+}
@@ -0,0 +1,56 @@
+use std::{cell::Cell, rc::Rc};
+
+// The buttons use the same bits as the gamepads of WASM-4, so that
+// input code can be shared between all targets.
+pub const BUTTON_1: u8 = 1;
+pub const BUTTON_2: u8 = 2;
+pub const BUTTON_LEFT: u8 = 16;
+pub const BUTTON_RIGHT: u8 = 32;
+pub const BUTTON_UP: u8 = 64;
+pub const BUTTON_DOWN: u8 = 128;
+
+/// The state of the buttons of a target.
+///
+/// `Input` is a handle to state shared with the target, so a clone obtained
+/// before the project is created stays up to date.
+#[derive(Clone, Default, Debug)]
+pub struct Input {
+    buttons: Rc<Cell<u8>>
+}
+
+impl Input {
+
+    /// Returns the bits of all buttons which are currently held down.
+    pub fn buttons(&self) -> u8 {
+        self.buttons.get()
+    }
+
+    /// Returns `true` if all buttons in `mask` are currently held down.
+    pub fn is_down(&self, mask: u8) -> bool {
+        self.buttons.get() & mask == mask
+    }
+
+    /// Sets the bits of all buttons at once, replacing the previous state.
+    ///
+    /// This is called by the target whenever the state of its input devices changes.
+    pub fn set_buttons(&self, buttons: u8) {
+        self.buttons.set(buttons);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Input, BUTTON_1, BUTTON_LEFT};
+
+    #[test]
+    fn test_input() {
+        let input = Input::default();
+        let handle = input.clone();
+        input.set_buttons(BUTTON_LEFT | BUTTON_1);
+        assert_eq!(handle.buttons(), BUTTON_LEFT | BUTTON_1);
+        assert!(handle.is_down(BUTTON_1));
+
+        input.set_buttons(BUTTON_1);
+        assert!(!handle.is_down(BUTTON_LEFT | BUTTON_1));
+    }
+}
@@ -0,0 +1,16 @@
+//! Building blocks shared by the Skylite targets which draw in software, i.e. `winit-target`,
+//! `web-target` and `libretro-target`.
+//!
+//! The data of a graphics asset for these targets is a texture atlas with 4 bytes per pixel,
+//! each pixel being a `u32` of the form `0xAARRGGBB` in little endian. Pixels with an alpha
+//! value below `0x80` are transparent. The atlas is followed by its width in pixels as a little
+//! endian `u16`. Colors passed to `fill_rect` have the form `0x00RRGGBB`.
+//!
+//! [`Raster`] implements `draw_sub` and `fill_rect` of `SkyliteTarget` for a framebuffer in
+//! memory, and [`Input`] holds the state of the buttons, which the targets update from their
+//! respective input devices.
+mod input;
+mod raster;
+
+pub use input::{Input, BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_UP};
+pub use raster::{Raster, RgbaPixels, XrgbPixels};
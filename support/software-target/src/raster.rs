@@ -0,0 +1,205 @@
+/// Returns the position of the pixel (`x`, `y`) of a region of size `w` by `h`,
+/// after the region was flipped and rotated as described for `SkyliteTarget::draw_sub`.
+fn apply_transform(x: u16, y: u16, w: u16, h: u16, flip_h: bool, flip_v: bool, rotate: bool) -> (u16, u16) {
+    let x = if flip_h { w - x - 1 } else { x };
+    let y = if flip_v { h - y - 1 } else { y };
+    if rotate {
+        (h - y - 1, x)
+    } else {
+        (x, y)
+    }
+}
+
+/// A framebuffer in memory, which is drawn to in software.
+///
+/// Implementors only store single pixels. The drawing functions of `SkyliteTarget` are provided
+/// on top of that, for graphics in the format described in the crate documentation.
+pub trait Raster {
+
+    /// Returns the width and height of the framebuffer in pixels.
+    fn size(&self) -> (u16, u16);
+
+    /// Sets the pixel at `idx` to `color`, which has the form `0x00RRGGBB`.
+    ///
+    /// Pixels are indexed row by row, from the top left of the screen.
+    fn set_pixel(&mut self, idx: usize, color: u32);
+
+    /// Draws a region of a texture atlas, see `SkyliteTarget::draw_sub`.
+    ///
+    /// Pixels outside of the framebuffer or the atlas are skipped.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_sub(&mut self, data: &[u8], x: i16, y: i16, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
+        if data.len() < 2 {
+            return;
+        }
+        let atlas_width = u16::from_le_bytes([data[data.len() - 2], data[data.len() - 1]]) as i32;
+        let atlas = &data[..data.len() - 2];
+        let (screen_w, screen_h) = self.size();
+        let (screen_w, screen_h) = (screen_w as i32, screen_h as i32);
+
+        for py in 0..src_h {
+            for px in 0..src_w {
+                let (dx, dy) = apply_transform(px, py, src_w, src_h, flip_h, flip_v, rotate);
+                let (screen_x, screen_y) = (x as i32 + dx as i32, y as i32 + dy as i32);
+                if screen_x < 0 || screen_y < 0 || screen_x >= screen_w || screen_y >= screen_h {
+                    continue;
+                }
+
+                let (atlas_x, atlas_y) = (src_x as i32 + px as i32, src_y as i32 + py as i32);
+                if atlas_x < 0 || atlas_y < 0 || atlas_x >= atlas_width {
+                    continue;
+                }
+                let idx = (atlas_y as usize * atlas_width as usize + atlas_x as usize) * 4;
+                let Some(color) = atlas.get(idx..idx + 4) else {
+                    continue;
+                };
+                let color = u32::from_le_bytes(color.try_into().unwrap());
+                if color >> 24 >= 0x80 {
+                    self.set_pixel((screen_y * screen_w + screen_x) as usize, color & 0xffffff);
+                }
+            }
+        }
+    }
+
+    /// Fills a rectangle with `color`, see `SkyliteTarget::fill_rect`.
+    ///
+    /// The rectangle is clipped to the framebuffer.
+    fn fill_rect(&mut self, x: i16, y: i16, w: u16, h: u16, color: u32) {
+        let (screen_w, screen_h) = self.size();
+        let (x_start, x_end) = ((x as i32).max(0), (x as i32 + w as i32).min(screen_w as i32));
+        let (y_start, y_end) = ((y as i32).max(0), (y as i32 + h as i32).min(screen_h as i32));
+        for row in y_start..y_end {
+            for col in x_start..x_end {
+                self.set_pixel((row * screen_w as i32 + col) as usize, color & 0xffffff);
+            }
+        }
+    }
+}
+
+/// A framebuffer with one `u32` of the form `0x00RRGGBB` per pixel.
+pub struct XrgbPixels<'a> {
+    pub width: u16,
+    pub height: u16,
+    pub pixels: &'a mut [u32]
+}
+
+impl Raster for XrgbPixels<'_> {
+    fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn set_pixel(&mut self, idx: usize, color: u32) {
+        self.pixels[idx] = color;
+    }
+}
+
+/// A framebuffer with 4 bytes per pixel in the order red, green, blue and alpha,
+/// which is the format of a canvas' `ImageData`. All pixels are opaque.
+pub struct RgbaPixels<'a> {
+    pub width: u16,
+    pub height: u16,
+    pub pixels: &'a mut [u8]
+}
+
+impl Raster for RgbaPixels<'_> {
+    fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn set_pixel(&mut self, idx: usize, color: u32) {
+        let [b, g, r, _] = color.to_le_bytes();
+        self.pixels[idx * 4..idx * 4 + 4].copy_from_slice(&[r, g, b, 0xff]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Raster, RgbaPixels, XrgbPixels};
+
+    /// Creates an atlas of `w` by `h` pixels, where each pixel is opaque and has its index as color.
+    fn atlas(w: u16, h: u16) -> Vec<u8> {
+        let mut out: Vec<u8> = (0..w as u32 * h as u32)
+            .flat_map(|i| (0xff000000 | i).to_le_bytes())
+            .collect();
+        out.extend_from_slice(&w.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn test_draw_sub() {
+        let mut pixels = [0; 16];
+        let mut raster = XrgbPixels { width: 4, height: 4, pixels: &mut pixels };
+        let data = atlas(3, 2);
+        raster.draw_sub(&data, 1, 1, 1, 0, 2, 2, false, false, false);
+        raster.draw_sub(&data, -1, 3, 1, 0, 2, 1, true, false, false);
+        assert_eq!(pixels, [
+            0, 0, 0, 0,
+            0, 1, 2, 0,
+            0, 4, 5, 0,
+            1, 0, 0, 0
+        ]);
+
+        let mut raster = XrgbPixels { width: 4, height: 4, pixels: &mut pixels };
+        raster.draw_sub(&data, 0, 0, 0, 0, 3, 2, false, false, true);
+        assert_eq!(pixels[..8], [3, 0, 0, 0, 4, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_draw_sub_outside_of_atlas() {
+        let mut pixels = [0; 9];
+        let mut raster = XrgbPixels { width: 3, height: 3, pixels: &mut pixels };
+        raster.fill_rect(0, 0, 3, 3, 7);
+        let data = atlas(2, 2);
+        // Only the pixels 0 and 1 in the top row of the atlas are inside of the source rectangle.
+        raster.draw_sub(&data, 0, 0, -1, -1, 3, 2, false, false, false);
+        // The second column is past the right edge of the atlas, and the second row past the bottom edge.
+        raster.draw_sub(&data, 0, 1, 1, 1, 2, 2, false, false, false);
+        assert_eq!(pixels, [
+            7, 7, 7,
+            3, 0, 1,
+            7, 7, 7
+        ]);
+
+        let mut raster = XrgbPixels { width: 3, height: 3, pixels: &mut pixels };
+        raster.draw_sub(&[], 0, 0, 0, 0, 1, 1, false, false, false);
+        raster.draw_sub(&data, 0, 0, i16::MIN, i16::MAX, 2, 2, false, false, false);
+        assert_eq!(pixels, [7, 7, 7, 3, 0, 1, 7, 7, 7]);
+    }
+
+    #[test]
+    fn test_transparency() {
+        let mut pixels = [0; 2];
+        let mut raster = XrgbPixels { width: 2, height: 1, pixels: &mut pixels };
+        raster.fill_rect(0, 0, 2, 1, 0x123456);
+        let mut data = atlas(2, 1);
+        data[7] = 0x7f;
+        raster.draw_sub(&data, 0, 0, 0, 0, 2, 1, false, false, false);
+        assert_eq!(pixels, [0, 0x123456]);
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut pixels = [0; 9];
+        XrgbPixels { width: 3, height: 3, pixels: &mut pixels }.fill_rect(-1, 1, 3, 5, 0xff000007);
+        assert_eq!(pixels, [
+            0, 0, 0,
+            7, 7, 0,
+            7, 7, 0
+        ]);
+    }
+
+    #[test]
+    fn test_rgba_pixels() {
+        let mut pixels = [0; 16];
+        let mut raster = RgbaPixels { width: 2, height: 2, pixels: &mut pixels };
+        raster.fill_rect(0, 0, 2, 2, 0x102030);
+
+        // A 2x1 atlas with an opaque and a transparent pixel.
+        let data = [0x03, 0x02, 0x01, 0xff, 0x06, 0x05, 0x04, 0x7f, 2, 0];
+        raster.draw_sub(&data, 1, 0, 0, 0, 2, 1, false, false, true);
+        assert_eq!(pixels, [
+            0x10, 0x20, 0x30, 0xff,  0x01, 0x02, 0x03, 0xff,
+            0x10, 0x20, 0x30, 0xff,  0x10, 0x20, 0x30, 0xff
+        ]);
+    }
+}
@@ -2,7 +2,7 @@ mod wasm4;
 pub mod w4alloc;
 
 use skylite_core::SkyliteTarget;
-use wasm4::{blit_sub, diskr, diskw, BLIT_FLIP_X, BLIT_FLIP_Y, BLIT_ROTATE, SCREEN_SIZE};
+use wasm4::{blit_sub, diskr, diskw, BLIT_FLIP_X, BLIT_FLIP_Y, BLIT_ROTATE, FRAMEBUFFER, SCREEN_SIZE};
 
 pub const NUM_LAYERS: u8 = 8;
 pub const LAYER_CFG_TILE_SIZE: u32 = 0;
@@ -21,6 +21,48 @@ impl Wasm4Target {
     }
 }
 
+/// Direct access to the WASM-4 framebuffer.
+///
+/// This is an alternative to `draw_sub` for graphics which are not stored in a 2bpp atlas,
+/// such as procedural effects or dithering. Render code gets access to it through
+/// `DrawContext::target_ext::<Wasm4Target>()`.
+///
+/// The framebuffer stores 2 bits per pixel, with 4 pixels per byte and the leftmost pixel in
+/// the least significant bits. Colors are indices into the palette (0-3), not `DRAW_COLORS`.
+pub trait Wasm4Framebuffer {
+
+    /// Returns the raw framebuffer.
+    fn framebuffer(&mut self) -> &mut [u8; 6400];
+
+    /// Sets the pixel at the screen position (`x`, `y`) to `color`. Positions outside of the screen are ignored.
+    fn set_pixel(&mut self, x: i32, y: i32, color: u8) {
+        if let Some((idx, shift)) = pixel_offset(x, y) {
+            let byte = &mut self.framebuffer()[idx];
+            *byte = (*byte & !(0b11 << shift)) | ((color & 0b11) << shift);
+        }
+    }
+
+    /// Returns the color of the pixel at the screen position (`x`, `y`), or `None` if the position is outside of the screen.
+    fn get_pixel(&mut self, x: i32, y: i32) -> Option<u8> {
+        pixel_offset(x, y).map(|(idx, shift)| (self.framebuffer()[idx] >> shift) & 0b11)
+    }
+}
+
+/// Returns the byte index and bit shift of a pixel in the framebuffer.
+fn pixel_offset(x: i32, y: i32) -> Option<(usize, u32)> {
+    if x < 0 || y < 0 || x >= SCREEN_SIZE as i32 || y >= SCREEN_SIZE as i32 {
+        return None;
+    }
+    let idx = (y as u32 * SCREEN_SIZE + x as u32) as usize;
+    Some((idx / 4, (idx as u32 % 4) * 2))
+}
+
+impl Wasm4Framebuffer for Wasm4Target {
+    fn framebuffer(&mut self) -> &mut [u8; 6400] {
+        unsafe { &mut *FRAMEBUFFER }
+    }
+}
+
 impl SkyliteTarget for Wasm4Target {
     fn draw_sub(&mut self, data: &[u8], x: i16, y: i16, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
         let atlas_width = u16::from_le_bytes([data[data.len() - 2], data[data.len() - 1]]) as u32;
@@ -59,4 +101,8 @@ impl SkyliteTarget for Wasm4Target {
         }
         out
     }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
 }
@@ -0,0 +1,125 @@
+use std::{cell::RefCell, rc::Rc};
+
+use skylite_core::{prelude::FramePacer, SkyliteProject};
+use wasm_bindgen::{closure::Closure, convert::FromWasmAbi, Clamped, JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, EventTarget, HtmlCanvasElement, HtmlElement, ImageData, KeyboardEvent, TouchEvent, Window};
+
+use crate::{input::{button_for_code, HeldButtons}, Framebuffer, Input, WebTarget};
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+/// Registers `callback` as a listener for `event` on `target`. The listener is never removed.
+fn listen<E: FromWasmAbi + 'static>(target: &EventTarget, event: &str, callback: impl FnMut(E) + 'static) -> Result<(), JsValue> {
+    let closure = Closure::<dyn FnMut(E)>::new(callback);
+    target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+/// Returns `true` if `target` is an element which takes text input, like a text field.
+fn is_editable(target: Option<EventTarget>) -> bool {
+    let Some(element) = target.and_then(|target| target.dyn_into::<HtmlElement>().ok()) else {
+        return false;
+    };
+    element.is_content_editable() || matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+}
+
+fn listen_input(window: &Window, canvas: &HtmlCanvasElement, input: &Input) -> Result<(), JsValue> {
+    let held = Rc::new(HeldButtons::new(input.clone()));
+
+    for (event, down) in [("keydown", true), ("keyup", false)] {
+        let held = held.clone();
+        listen(window, event, move |event: KeyboardEvent| {
+            let Some(button) = button_for_code(&event.code()) else {
+                return;
+            };
+            if is_editable(event.target()) {
+                // Keys typed into other elements of the page are not meant for the game.
+                // Releasing them still releases the button, so it does not get stuck.
+                if !down {
+                    held.set_key(button, false);
+                }
+                return;
+            }
+            // Keeps the arrow keys and space from scrolling the page.
+            event.prevent_default();
+            held.set_key(button, down);
+        })?;
+    }
+
+    let blur_held = held.clone();
+    listen(window, "blur", move |_: web_sys::Event| blur_held.clear())?;
+
+    for event in ["touchstart", "touchmove", "touchend", "touchcancel"] {
+        let held = held.clone();
+        let touch_canvas = canvas.clone();
+        listen(canvas, event, move |event: TouchEvent| {
+            event.prevent_default();
+            let rect = touch_canvas.get_bounding_client_rect();
+            let touches = event.touches();
+            held.set_touches((0..touches.length())
+                .filter_map(|i| touches.get(i))
+                .map(|touch| (
+                    (touch.client_x() as f64 - rect.left()) / rect.width(),
+                    (touch.client_y() as f64 - rect.top()) / rect.height()
+                )));
+        })?;
+    }
+    Ok(())
+}
+
+/// Copies the framebuffer to the canvas.
+fn present(context: &CanvasRenderingContext2d, framebuffer: &Framebuffer) -> Result<(), JsValue> {
+    let pixels = framebuffer.pixels.borrow();
+    let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), framebuffer.width as u32, framebuffer.height as u32)?;
+    context.put_image_data(&image, 0.0, 0.0)
+}
+
+/// Runs the project returned by `create` on the canvas with the id `canvas_id`.
+///
+/// The screen size of the project is taken from the size of the canvas. The project is updated
+/// `updates_per_second` times per second and rendered on every animation frame, so `run`
+/// returns immediately, and the game keeps running in the background.
+pub fn run<P: SkyliteProject<Target = WebTarget> + 'static>(canvas_id: &str, updates_per_second: u32, create: impl FnOnce(WebTarget) -> P) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("No window")?;
+    let canvas: HtmlCanvasElement = window.document()
+        .ok_or("No document")?
+        .get_element_by_id(canvas_id)
+        .ok_or_else(|| format!("No element with id {}", canvas_id))?
+        .dyn_into()?;
+    let context: CanvasRenderingContext2d = canvas.get_context("2d")?
+        .ok_or("Canvas does not support 2d rendering")?
+        .dyn_into()?;
+
+    let target = WebTarget::new(canvas.width() as u16, canvas.height() as u16);
+    let framebuffer = target.framebuffer();
+    listen_input(&window, &canvas, &target.input())?;
+    let mut project = create(target);
+    let mut pacer = FramePacer::new(updates_per_second);
+    let mut last_timestamp: Option<f64> = None;
+
+    // The callback requests the next frame with itself, so it has to hold a reference to its own closure.
+    let frame_callback: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
+    let next_frame = frame_callback.clone();
+    let frame_window = window.clone();
+    *frame_callback.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+        let elapsed = last_timestamp.map_or(0.0, |last| (timestamp - last) / 1000.0);
+        last_timestamp = Some(timestamp);
+
+        for _ in 0..pacer.advance(elapsed as f32) {
+            project.update();
+        }
+        project.render_interpolated(pacer.alpha());
+        if let Err(err) = present(&context, &framebuffer) {
+            web_sys::console::error_2(&"Error presenting frame:".into(), &err);
+            return;
+        }
+
+        if let Some(callback) = next_frame.borrow().as_ref() {
+            let _ = frame_window.request_animation_frame(callback.as_ref().unchecked_ref());
+        }
+    }));
+
+    window.request_animation_frame(frame_callback.borrow().as_ref().unwrap().as_ref().unchecked_ref())?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::cell::Cell;
+
+use crate::{Input, BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_UP};
+
+/// Touches closer than this to the center of the virtual d-pad, relative to the size of the canvas, are ignored.
+const DPAD_DEAD_ZONE: f64 = 0.05;
+
+/// The buttons held down by the keyboard and by touches, which are tracked separately
+/// and combined into the state of the [`Input`].
+pub(crate) struct HeldButtons {
+    input: Input,
+    keys: Cell<u8>,
+    touches: Cell<u8>
+}
+
+impl HeldButtons {
+
+    pub fn new(input: Input) -> HeldButtons {
+        HeldButtons { input, keys: Cell::new(0), touches: Cell::new(0) }
+    }
+
+    pub fn set_key(&self, mask: u8, down: bool) {
+        let keys = self.keys.get();
+        self.keys.set(if down { keys | mask } else { keys & !mask });
+        self.update();
+    }
+
+    /// Sets the buttons held down by the current touches. The positions are relative
+    /// to the canvas, from (0.0, 0.0) at the top left to (1.0, 1.0) at the bottom right.
+    pub fn set_touches(&self, touches: impl Iterator<Item = (f64, f64)>) {
+        self.touches.set(touches.fold(0, |buttons, (x, y)| buttons | button_for_touch(x, y)));
+        self.update();
+    }
+
+    /// Releases all buttons, e.g. when the page loses focus.
+    pub fn clear(&self) {
+        self.keys.set(0);
+        self.touches.set(0);
+        self.update();
+    }
+
+    fn update(&self) {
+        self.input.set_buttons(self.keys.get() | self.touches.get());
+    }
+}
+
+/// Returns the button for the `code` of a `KeyboardEvent`, or `None` if the key is not mapped.
+pub(crate) fn button_for_code(code: &str) -> Option<u8> {
+    match code {
+        "ArrowLeft" | "KeyA" => Some(BUTTON_LEFT),
+        "ArrowRight" | "KeyD" => Some(BUTTON_RIGHT),
+        "ArrowUp" | "KeyW" => Some(BUTTON_UP),
+        "ArrowDown" | "KeyS" => Some(BUTTON_DOWN),
+        "KeyX" | "Space" | "Enter" => Some(BUTTON_1),
+        "KeyZ" | "KeyC" | "Backspace" => Some(BUTTON_2),
+        _ => None
+    }
+}
+
+/// Returns the buttons for a touch at the relative position (`x`, `y`) on the canvas.
+fn button_for_touch(x: f64, y: f64) -> u8 {
+    if x >= 0.75 {
+        return BUTTON_1;
+    }
+    if x >= 0.5 {
+        return BUTTON_2;
+    }
+
+    let (dx, dy) = (x - 0.25, y - 0.5);
+    if dx.abs() < DPAD_DEAD_ZONE && dy.abs() < DPAD_DEAD_ZONE {
+        0
+    } else if dx.abs() > dy.abs() {
+        if dx < 0.0 { BUTTON_LEFT } else { BUTTON_RIGHT }
+    } else if dy < 0.0 {
+        BUTTON_UP
+    } else {
+        BUTTON_DOWN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{button_for_code, HeldButtons, BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_UP};
+    use crate::Input;
+
+    #[test]
+    fn test_held_buttons() {
+        let input = Input::default();
+        let held = HeldButtons::new(input.clone());
+        held.set_key(button_for_code("ArrowUp").unwrap(), true);
+        held.set_touches([(0.1, 0.5), (0.8, 0.9)].into_iter());
+        assert_eq!(input.buttons(), BUTTON_UP | BUTTON_LEFT | BUTTON_1);
+
+        held.set_touches([(0.26, 0.51), (0.3, 0.9), (0.6, 0.1)].into_iter());
+        assert_eq!(input.buttons(), BUTTON_UP | BUTTON_DOWN | BUTTON_2);
+
+        held.set_key(BUTTON_UP, false);
+        held.set_touches(std::iter::empty());
+        assert_eq!(input.buttons(), 0);
+        assert_eq!(button_for_code("KeyQ"), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! A browser target for Skylite, which draws to an HTML canvas.
+//!
+//! The canvas sets the screen size of the project through its `width` and `height` attributes.
+//! It can be scaled with CSS, preferably with `image-rendering: pixelated`:
+//!
+//! ```html
+//! <canvas id="game" width="160" height="144" style="width: 480px; image-rendering: pixelated"></canvas>
+//! ```
+//!
+//! The project is started from the `#[wasm_bindgen(start)]` function of the game:
+//!
+//! ```ignore
+//! #[wasm_bindgen(start)]
+//! pub fn start() -> Result<(), JsValue> {
+//!     let storage = LocalStorage::open("my-game")?;
+//!     web_target::run("game", 60, |target| MyGame::new(target.with_storage(storage)))
+//! }
+//! ```
+//!
+//! The graphics of the project must be in the format described at [`WebTarget`].
+mod app;
+mod input;
+mod storage;
+
+use std::{cell::RefCell, rc::Rc};
+
+use skylite_core::{prelude::{MemoryStorage, StorageBackend}, SkyliteTarget};
+use software_target::{Raster, RgbaPixels};
+
+pub use app::run;
+pub use software_target::{Input, BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_UP};
+pub use storage::LocalStorage;
+
+/// The pixels of the screen, shared between the target and the animation frame
+/// callback which copies them to the canvas.
+///
+/// The pixels are stored as RGBA with 4 bytes per pixel, which is the format of `ImageData`.
+#[derive(Clone)]
+pub(crate) struct Framebuffer {
+    pub width: u16,
+    pub height: u16,
+    pub pixels: Rc<RefCell<Vec<u8>>>
+}
+
+/// A `SkyliteTarget` that draws into a framebuffer, which is copied to the canvas after each frame.
+///
+/// The graphics of the project must be in the format described in the `software-target` crate.
+///
+/// The persistent storage is kept in memory by default. Use [`WebTarget::with_storage`] with
+/// a [`LocalStorage`] to keep it in the browser's `localStorage` instead.
+pub struct WebTarget {
+    framebuffer: Framebuffer,
+    input: Input,
+    storage: Box<dyn StorageBackend>
+}
+
+impl WebTarget {
+
+    /// Creates a target with a screen of `width` by `height` pixels.
+    pub fn new(width: u16, height: u16) -> WebTarget {
+        WebTarget {
+            framebuffer: Framebuffer {
+                width,
+                height,
+                pixels: Rc::new(RefCell::new(vec![0; width as usize * height as usize * 4]))
+            },
+            input: Input::default(),
+            storage: Box::new(MemoryStorage::new())
+        }
+    }
+
+    /// Uses `storage` as the persistent storage of the target.
+    pub fn with_storage(mut self, storage: impl StorageBackend + 'static) -> WebTarget {
+        self.storage = Box::new(storage);
+        self
+    }
+
+    /// Returns the state of the buttons, which is updated from keyboard and touch events by [`run`].
+    ///
+    /// The keyboard is mapped to the buttons as follows:
+    ///
+    /// - Arrow keys and WASD: directions
+    /// - X, Space and Enter: `BUTTON_1`
+    /// - Z, C and Backspace: `BUTTON_2`
+    ///
+    /// Keys typed into text fields of the page are ignored. On touch screens, the left half of
+    /// the canvas is a virtual d-pad centered in that half. The right half is split into
+    /// `BUTTON_2` on the left and `BUTTON_1` on the right.
+    pub fn input(&self) -> Input {
+        self.input.clone()
+    }
+
+    pub(crate) fn framebuffer(&self) -> Framebuffer {
+        self.framebuffer.clone()
+    }
+
+    fn with_raster<R>(&self, f: impl FnOnce(&mut RgbaPixels) -> R) -> R {
+        let mut pixels = self.framebuffer.pixels.borrow_mut();
+        f(&mut RgbaPixels { width: self.framebuffer.width, height: self.framebuffer.height, pixels: &mut pixels })
+    }
+}
+
+impl SkyliteTarget for WebTarget {
+    fn draw_sub(&mut self, data: &[u8], x: i16, y: i16, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
+        self.with_raster(|raster| raster.draw_sub(data, x, y, src_x, src_y, src_w, src_h, flip_h, flip_v, rotate));
+    }
+
+    fn get_screen_size(&self) -> (u16, u16) {
+        (self.framebuffer.width, self.framebuffer.height)
+    }
+
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        self.storage.write_storage(offset, data);
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        self.storage.read_storage(offset, len)
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn fill_rect(&mut self, x: i16, y: i16, w: u16, h: u16, color: u32) -> bool {
+        self.with_raster(|raster| raster.fill_rect(x, y, w, h, color));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skylite_core::SkyliteTarget;
+
+    use super::WebTarget;
+
+    #[test]
+    fn test_draw_sub() {
+        let mut target = WebTarget::new(2, 2);
+        target.fill_rect(0, 0, 2, 2, 0x102030);
+
+        // A 2x1 atlas with an opaque and a transparent pixel.
+        let data = [0x03, 0x02, 0x01, 0xff, 0x06, 0x05, 0x04, 0x7f, 2, 0];
+        target.draw_sub(&data, 1, 0, 0, 0, 2, 1, false, false, true);
+        assert_eq!(*target.framebuffer.pixels.borrow(), [
+            0x10, 0x20, 0x30, 0xff,  0x01, 0x02, 0x03, 0xff,
+            0x10, 0x20, 0x30, 0xff,  0x10, 0x20, 0x30, 0xff
+        ]);
+    }
+}
@@ -0,0 +1,72 @@
+use skylite_core::prelude::{MemoryStorage, StorageBackend};
+use wasm_bindgen::JsValue;
+use web_sys::{console, Storage};
+
+/// A `StorageBackend` that keeps the persistent storage in the browser's `localStorage`.
+///
+/// The data is stored as a hex string under a key, which should be unique for each game
+/// on the same origin. It is read once when the storage is opened, and written
+/// completely on every write.
+pub struct LocalStorage {
+    storage: Storage,
+    key: String,
+    data: MemoryStorage
+}
+
+impl LocalStorage {
+
+    /// Opens the storage under `key`. Returns an `Err` if `localStorage` is not available,
+    /// e.g. because it was disabled by the user.
+    pub fn open(key: &str) -> Result<LocalStorage, JsValue> {
+        let storage = web_sys::window()
+            .ok_or("No window")?
+            .local_storage()?
+            .ok_or("localStorage is not available")?;
+        let data = storage.get_item(key)?
+            .and_then(|hex| decode_hex(&hex))
+            .unwrap_or_default();
+        Ok(LocalStorage { storage, key: key.to_owned(), data: MemoryStorage::from_data(data) })
+    }
+}
+
+impl StorageBackend for LocalStorage {
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        self.data.write_storage(offset, data);
+        // `StorageBackend` has no way to report errors, and losing a save
+        // should not crash the game.
+        if let Err(err) = self.storage.set_item(&self.key, &encode_hex(self.data.data())) {
+            console::error_2(&"Error writing storage:".into(), &err);
+        }
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        self.data.read_storage(offset, len)
+    }
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes a string created by `encode_hex`, or returns `None` if it is not valid.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_hex, encode_hex};
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode_hex(&[0, 0x5a, 0xff]), "005aff");
+        assert_eq!(decode_hex("005aff").unwrap(), [0, 0x5a, 0xff]);
+        assert_eq!(decode_hex("5"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+}
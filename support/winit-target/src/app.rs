@@ -0,0 +1,234 @@
+use std::{collections::HashSet, num::NonZeroU32, rc::Rc, time::{Duration, Instant}};
+
+use skylite_core::{prelude::FramePacer, SkyliteProject};
+use softbuffer::{Context, Surface};
+use winit::{application::ApplicationHandler, dpi::LogicalSize, error::EventLoopError, event::{ElementState, WindowEvent}, event_loop::{ActiveEventLoop, ControlFlow, EventLoop}, keyboard::{KeyCode, PhysicalKey}, window::{Window, WindowId}};
+
+use crate::{input::buttons_for_keys, Framebuffer, Input, WinitTarget};
+
+/// The options for the window of a [`SkyliteApp`].
+#[derive(Clone, Debug)]
+pub struct WindowOptions {
+    pub title: String,
+    /// The screen size of the project in pixels.
+    pub width: u16,
+    pub height: u16,
+    /// The initial size of the window as a multiple of the screen size, in logical pixels.
+    pub scale: u32,
+    pub updates_per_second: u32
+}
+
+impl WindowOptions {
+
+    /// Creates options for a project with a screen of `width` by `height` pixels,
+    /// with an initial scale of 3 and 60 updates per second.
+    pub fn new(title: &str, width: u16, height: u16) -> WindowOptions {
+        WindowOptions { title: title.to_owned(), width, height, scale: 3, updates_per_second: 60 }
+    }
+}
+
+type WindowSurface = Surface<Rc<Window>, Rc<Window>>;
+
+/// Runs a project with a `WinitTarget` in a window, implemented as a winit `ApplicationHandler`.
+///
+/// [`run`] creates an event loop for the app and runs it until the window is closed. To drive
+/// the app from an existing event loop instead, e.g. with winit's `pump_app_events`, create
+/// it with [`SkyliteApp::new`] and pass it to the event loop.
+///
+/// The app never blocks while waiting for the next frame, it only tells the event loop
+/// when to wake up again.
+pub struct SkyliteApp<P: SkyliteProject<Target = WinitTarget>> {
+    options: WindowOptions,
+    project: P,
+    framebuffer: Framebuffer,
+    input: Input,
+    /// The keys which are currently held down, see [`buttons_for_keys`].
+    held_keys: HashSet<KeyCode>,
+    pacer: FramePacer,
+    last_frame: Option<Instant>,
+    window: Option<(Rc<Window>, WindowSurface)>
+}
+
+impl<P: SkyliteProject<Target = WinitTarget>> SkyliteApp<P> {
+
+    /// Creates the app. `create` is called with the target, and must return the project, e.g. `MyGame::new`.
+    pub fn new(options: WindowOptions, create: impl FnOnce(WinitTarget) -> P) -> SkyliteApp<P> {
+        let target = WinitTarget::new(options.width, options.height);
+        let framebuffer = target.framebuffer();
+        let input = target.input();
+        let pacer = FramePacer::new(options.updates_per_second);
+        SkyliteApp { options, project: create(target), framebuffer, input, held_keys: HashSet::new(), pacer, last_frame: None, window: None }
+    }
+
+    pub fn project(&self) -> &P {
+        &self.project
+    }
+
+    pub fn project_mut(&mut self) -> &mut P {
+        &mut self.project
+    }
+
+    fn create_window(&self, event_loop: &ActiveEventLoop) -> Result<(Rc<Window>, WindowSurface), String> {
+        let size = LogicalSize::new(
+            self.options.width as u32 * self.options.scale,
+            self.options.height as u32 * self.options.scale
+        );
+        let attributes = Window::default_attributes()
+            .with_title(&self.options.title)
+            .with_inner_size(size)
+            .with_min_inner_size(LogicalSize::new(self.options.width as u32, self.options.height as u32));
+        let window = Rc::new(event_loop.create_window(attributes).map_err(|e| e.to_string())?);
+        let context = Context::new(window.clone()).map_err(|e| e.to_string())?;
+        let surface = Surface::new(&context, window.clone()).map_err(|e| e.to_string())?;
+        Ok((window, surface))
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        let Some((window, surface)) = &mut self.window else {
+            return Ok(());
+        };
+        let size = window.inner_size();
+        let (Some(width), Some(height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) else {
+            // The window is minimized.
+            return Ok(());
+        };
+
+        self.project.render_interpolated(self.pacer.alpha());
+
+        surface.resize(width, height).map_err(|e| e.to_string())?;
+        let mut buffer = surface.buffer_mut().map_err(|e| e.to_string())?;
+        scale_into(
+            &self.framebuffer.pixels.borrow(),
+            (self.framebuffer.width, self.framebuffer.height),
+            &mut buffer,
+            (size.width, size.height)
+        );
+        buffer.present().map_err(|e| e.to_string())
+    }
+}
+
+impl<P: SkyliteProject<Target = WinitTarget>> ApplicationHandler for SkyliteApp<P> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        match self.create_window(event_loop) {
+            Ok(window) => self.window = Some(window),
+            Err(err) => {
+                eprintln!("Error creating window: {}", err);
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        self.window = None;
+        self.last_frame = None;
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _window_id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Focused(false) => {
+                self.held_keys.clear();
+                self.input.set_buttons(0);
+            },
+            WindowEvent::KeyboardInput { event, .. } => {
+                if let PhysicalKey::Code(code) = event.physical_key {
+                    if event.state == ElementState::Pressed {
+                        self.held_keys.insert(code);
+                    } else {
+                        self.held_keys.remove(&code);
+                    }
+                    self.input.set_buttons(buttons_for_keys(&self.held_keys));
+                }
+            },
+            WindowEvent::RedrawRequested => {
+                if let Err(err) = self.present() {
+                    eprintln!("Error presenting frame: {}", err);
+                    event_loop.exit();
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        let elapsed = self.last_frame.map_or(0.0, |last| (now - last).as_secs_f32());
+        self.last_frame = Some(now);
+
+        let updates = self.pacer.advance(elapsed);
+        for _ in 0..updates {
+            self.project.update();
+        }
+        if let Some((window, _)) = self.window.as_ref().filter(|_| updates > 0) {
+            window.request_redraw();
+        }
+
+        let interval = Duration::from_secs_f32(1.0 / self.options.updates_per_second.max(1) as f32);
+        event_loop.set_control_flow(ControlFlow::WaitUntil(now + interval));
+    }
+}
+
+/// Creates a window for the project returned by `create`, and runs it until the window is closed.
+pub fn run<P: SkyliteProject<Target = WinitTarget>>(options: WindowOptions, create: impl FnOnce(WinitTarget) -> P) -> Result<(), EventLoopError> {
+    let event_loop = EventLoop::new()?;
+    let mut app = SkyliteApp::new(options, create);
+    event_loop.run_app(&mut app)
+}
+
+/// Returns the largest integer scale at which a screen of size `screen` fits into a
+/// window of size `window`, and the position of the scaled screen when it is centered.
+///
+/// The scale is at least 1, so the screen is cut off if the window is too small.
+fn fit_screen(window: (u32, u32), screen: (u16, u16)) -> (u32, u32, u32) {
+    let scale = (window.0 / screen.0.max(1) as u32)
+        .min(window.1 / screen.1.max(1) as u32)
+        .max(1);
+    let x = window.0.saturating_sub(screen.0 as u32 * scale) / 2;
+    let y = window.1.saturating_sub(screen.1 as u32 * scale) / 2;
+    (scale, x, y)
+}
+
+/// Copies the pixels of the screen into the buffer of the window, scaled and centered
+/// as returned by [`fit_screen`]. The rest of the window is filled with black.
+fn scale_into(src: &[u32], screen: (u16, u16), dst: &mut [u32], window: (u32, u32)) {
+    let (scale, offset_x, offset_y) = fit_screen(window, screen);
+    let (screen_w, window_w) = (screen.0 as usize, window.0 as usize);
+    for (y, row) in dst.chunks_exact_mut(window_w).enumerate() {
+        let src_y = (y as u32).checked_sub(offset_y).map(|y| (y / scale) as usize);
+        let Some(src_row) = src_y.filter(|y| *y < screen.1 as usize).map(|y| &src[y * screen_w..(y + 1) * screen_w]) else {
+            row.fill(0);
+            continue;
+        };
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let src_x = (x as u32).checked_sub(offset_x).map(|x| (x / scale) as usize);
+            *pixel = src_x.and_then(|x| src_row.get(x)).copied().unwrap_or(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fit_screen, scale_into};
+
+    #[test]
+    fn test_fit_screen() {
+        assert_eq!(fit_screen((480, 432), (160, 144)), (3, 0, 0));
+        assert_eq!(fit_screen((500, 300), (160, 144)), (2, 90, 6));
+        assert_eq!(fit_screen((100, 100), (160, 144)), (1, 0, 0));
+    }
+
+    #[test]
+    fn test_scale_into() {
+        let mut dst = vec![7; 5 * 4];
+        scale_into(&[1, 2], (2, 1), &mut dst, (5, 4));
+        assert_eq!(dst, [
+            0, 0, 0, 0, 0,
+            1, 1, 2, 2, 0,
+            1, 1, 2, 2, 0,
+            0, 0, 0, 0, 0
+        ]);
+    }
+}
@@ -0,0 +1,48 @@
+use winit::keyboard::KeyCode;
+
+use crate::{BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_UP};
+
+/// Returns the button for a key, or `None` if the key is not mapped.
+pub(crate) fn button_for_key(key: KeyCode) -> Option<u8> {
+    match key {
+        KeyCode::ArrowLeft | KeyCode::KeyA => Some(BUTTON_LEFT),
+        KeyCode::ArrowRight | KeyCode::KeyD => Some(BUTTON_RIGHT),
+        KeyCode::ArrowUp | KeyCode::KeyW => Some(BUTTON_UP),
+        KeyCode::ArrowDown | KeyCode::KeyS => Some(BUTTON_DOWN),
+        KeyCode::KeyX | KeyCode::Space | KeyCode::Enter => Some(BUTTON_1),
+        KeyCode::KeyZ | KeyCode::KeyC | KeyCode::Backspace => Some(BUTTON_2),
+        _ => None
+    }
+}
+
+/// Returns the bits of the buttons which are held down by any of the held `keys`.
+///
+/// Several keys map to the same button, so the button stays down until all of them are released.
+pub(crate) fn buttons_for_keys<'a>(keys: impl IntoIterator<Item = &'a KeyCode>) -> u8 {
+    keys.into_iter()
+        .filter_map(|key| button_for_key(*key))
+        .fold(0, |buttons, button| buttons | button)
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::KeyCode;
+
+    use super::{button_for_key, buttons_for_keys};
+    use crate::{BUTTON_1, BUTTON_LEFT, BUTTON_UP};
+
+    #[test]
+    fn test_button_for_key() {
+        assert_eq!(button_for_key(KeyCode::KeyA), Some(BUTTON_LEFT));
+        assert_eq!(button_for_key(KeyCode::Space), Some(BUTTON_1));
+        assert_eq!(button_for_key(KeyCode::KeyQ), None);
+    }
+
+    #[test]
+    fn test_buttons_for_keys() {
+        assert_eq!(buttons_for_keys(&[KeyCode::ArrowLeft, KeyCode::KeyA, KeyCode::KeyW, KeyCode::KeyQ]), BUTTON_LEFT | BUTTON_UP);
+        // Releasing A does not release the left button while the left arrow is still held.
+        assert_eq!(buttons_for_keys(&[KeyCode::ArrowLeft, KeyCode::KeyW]), BUTTON_LEFT | BUTTON_UP);
+        assert_eq!(buttons_for_keys(&[]), 0);
+    }
+}
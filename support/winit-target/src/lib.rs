@@ -0,0 +1,134 @@
+//! A desktop target for Skylite, based on `winit` for windowing and input, and `softbuffer`
+//! for presenting the frames. It does not depend on any C libraries.
+//!
+//! Graphics are drawn in software into a framebuffer with the screen size of the project, which
+//! is scaled by an integer factor to fit the window, taking the DPI of the display into account.
+//!
+//! ```ignore
+//! fn main() {
+//!     let options = WindowOptions::new("My Game", 160, 144);
+//!     winit_target::run(options, MyGame::new).unwrap();
+//! }
+//! ```
+//!
+//! The graphics of the project must be in the format described at [`WinitTarget`].
+mod app;
+mod input;
+mod storage;
+
+use std::{cell::RefCell, rc::Rc};
+
+use skylite_core::{prelude::{MemoryStorage, StorageBackend}, SkyliteTarget};
+use software_target::{Raster, XrgbPixels};
+
+pub use app::{run, SkyliteApp, WindowOptions};
+pub use software_target::{Input, BUTTON_1, BUTTON_2, BUTTON_DOWN, BUTTON_LEFT, BUTTON_RIGHT, BUTTON_UP};
+pub use storage::FileStorage;
+
+/// The pixels of the screen, shared between the target and the [`SkyliteApp`] which presents them.
+///
+/// Each pixel is stored as `0x00RRGGBB`, rows from top to bottom.
+#[derive(Clone)]
+pub(crate) struct Framebuffer {
+    pub width: u16,
+    pub height: u16,
+    pub pixels: Rc<RefCell<Vec<u32>>>
+}
+
+/// A `SkyliteTarget` that draws into a framebuffer in memory.
+///
+/// The graphics of the project must be in the format described in the `software-target` crate.
+///
+/// The persistent storage is kept in memory by default. Use [`WinitTarget::with_storage`] with
+/// a [`FileStorage`] to store it in a file instead.
+pub struct WinitTarget {
+    framebuffer: Framebuffer,
+    input: Input,
+    storage: Box<dyn StorageBackend>
+}
+
+impl WinitTarget {
+
+    /// Creates a target with a screen of `width` by `height` pixels.
+    pub fn new(width: u16, height: u16) -> WinitTarget {
+        WinitTarget {
+            framebuffer: Framebuffer {
+                width,
+                height,
+                pixels: Rc::new(RefCell::new(vec![0; width as usize * height as usize]))
+            },
+            input: Input::default(),
+            storage: Box::new(MemoryStorage::new())
+        }
+    }
+
+    /// Uses `storage` as the persistent storage of the target.
+    pub fn with_storage(mut self, storage: impl StorageBackend + 'static) -> WinitTarget {
+        self.storage = Box::new(storage);
+        self
+    }
+
+    /// Returns the state of the buttons, which is updated from the keyboard by the [`SkyliteApp`].
+    ///
+    /// The keyboard is mapped to the buttons as follows:
+    ///
+    /// - Arrow keys and WASD: directions
+    /// - X, Space and Enter: `BUTTON_1`
+    /// - Z, C and Backspace: `BUTTON_2`
+    pub fn input(&self) -> Input {
+        self.input.clone()
+    }
+
+    pub(crate) fn framebuffer(&self) -> Framebuffer {
+        self.framebuffer.clone()
+    }
+
+    fn with_raster<R>(&self, f: impl FnOnce(&mut XrgbPixels) -> R) -> R {
+        let mut pixels = self.framebuffer.pixels.borrow_mut();
+        f(&mut XrgbPixels { width: self.framebuffer.width, height: self.framebuffer.height, pixels: &mut pixels })
+    }
+}
+
+impl SkyliteTarget for WinitTarget {
+    fn draw_sub(&mut self, data: &[u8], x: i16, y: i16, src_x: i16, src_y: i16, src_w: u16, src_h: u16, flip_h: bool, flip_v: bool, rotate: bool) {
+        self.with_raster(|raster| raster.draw_sub(data, x, y, src_x, src_y, src_w, src_h, flip_h, flip_v, rotate));
+    }
+
+    fn get_screen_size(&self) -> (u16, u16) {
+        (self.framebuffer.width, self.framebuffer.height)
+    }
+
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        self.storage.write_storage(offset, data);
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        self.storage.read_storage(offset, len)
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn std::any::Any> {
+        Some(self)
+    }
+
+    fn fill_rect(&mut self, x: i16, y: i16, w: u16, h: u16, color: u32) -> bool {
+        self.with_raster(|raster| raster.fill_rect(x, y, w, h, color));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skylite_core::SkyliteTarget;
+
+    use super::WinitTarget;
+
+    #[test]
+    fn test_draw_sub() {
+        let mut target = WinitTarget::new(2, 2);
+        target.fill_rect(-1, 1, 2, 2, 0x123456);
+        // A 1x1 atlas with a single opaque pixel.
+        let data = [0x01, 0x02, 0x03, 0xff, 1, 0];
+        target.draw_sub(&data, 1, 0, 0, 0, 1, 1, false, false, false);
+        assert_eq!(*target.framebuffer.pixels.borrow(), [0, 0x030201, 0x123456, 0]);
+    }
+}
@@ -0,0 +1,75 @@
+use std::{fs::{read, rename, write}, io, path::{Path, PathBuf}};
+
+use skylite_core::prelude::{MemoryStorage, StorageBackend};
+
+/// A `StorageBackend` that keeps the persistent storage in a file.
+///
+/// The file is read once when the storage is opened, and written completely on every write.
+/// This is fine for save data, which is small and written rarely. The data is written to a
+/// temporary file first, which then replaces the file, so a crash during a write does not
+/// leave a partially written file behind.
+pub struct FileStorage {
+    path: PathBuf,
+    data: MemoryStorage
+}
+
+impl FileStorage {
+
+    /// Opens the storage at `path`. If the file does not exist, the storage starts out empty,
+    /// and the file is created on the first write.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<FileStorage> {
+        let path = path.into();
+        let data = match read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err)
+        };
+        Ok(FileStorage { path, data: MemoryStorage::from_data(data) })
+    }
+}
+
+impl StorageBackend for FileStorage {
+    fn write_storage(&mut self, offset: usize, data: &[u8]) {
+        self.data.write_storage(offset, data);
+        // `StorageBackend` has no way to report errors, and losing a save
+        // should not crash the game.
+        if let Err(err) = replace_file(&self.path, self.data.data()) {
+            eprintln!("Error writing storage to {}: {}", self.path.display(), err);
+        }
+    }
+
+    fn read_storage(&self, offset: usize, len: usize) -> Vec<u8> {
+        self.data.read_storage(offset, len)
+    }
+}
+
+/// Writes `data` to a temporary file next to `path`, and then renames it to `path`.
+fn replace_file(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    write(&tmp_path, data)?;
+    rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read, remove_file};
+
+    use skylite_core::prelude::StorageBackend;
+
+    use super::FileStorage;
+
+    #[test]
+    fn test_file_storage() {
+        let path = std::env::temp_dir().join(format!("skylite_file_storage_{}.sav", std::process::id()));
+        let mut storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.read_storage(0, 2), [0, 0]);
+        storage.write_storage(1, &[5, 6]);
+        assert_eq!(read(&path).unwrap(), [0, 5, 6]);
+        assert!(!path.with_extension("sav.tmp").exists());
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.read_storage(0, 3), [0, 5, 6]);
+        remove_file(&path).unwrap();
+    }
+}